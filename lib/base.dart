@@ -1,6 +1,14 @@
+import 'dart:async';
+import 'dart:typed_data';
+
+import 'src/message_pack.dart';
 import 'src/rust/api/tracer.dart';
 
 class RevmTracer {
+  /// Traces a transaction and returns the result as a JSON string.
+  ///
+  /// Fees may be given either as `gasPrice`/`gasPriorityFee` or with their
+  /// EIP-1559 names `maxFeePerGas`/`maxPriorityFeePerGas`, but not both.
   static revmTrace({
     required BigInt chainId,
     required String from,
@@ -8,22 +16,186 @@ class RevmTracer {
     required String to,
     required String data,
     required BigInt gasLimit,
-    required BigInt gasPrice,
-    required BigInt gasPriorityFee,
+    BigInt? gasPrice,
+    BigInt? gasPriorityFee,
+    BigInt? maxFeePerGas,
+    BigInt? maxPriorityFeePerGas,
+    required String latestBlockEnv,
+    required String prestateTracerResult,
+    required bool isOpStack,
+    String? accessList
+  }) {
+    final (maxFee, priorityFee) = _resolveFees(
+        gasPrice, gasPriorityFee, maxFeePerGas, maxPriorityFeePerGas);
+    return formatAndTraceTransaction(
+      chainId: chainId,
+      from: from,
+      fromNonce: fromNonce,
+      to: to,
+      data: data,
+      gasLimit: gasLimit,
+      gasPrice: maxFee,
+      gasPriorityFee: priorityFee,
+      latestBlockEnv: latestBlockEnv,
+      prestateTracerResult: prestateTracerResult,
+      isOpStack: isOpStack,
+      accessList: accessList,
+    );
+  }
+
+  /// Traces a transaction like [revmTrace], returning typed results.
+  ///
+  /// Throws [FfiTraceError] if the transaction cannot be traced.
+  static FfiTraceResult revmTraceTyped({
+    required BigInt chainId,
+    required String from,
+    required BigInt fromNonce,
+    required String to,
+    required String data,
+    required BigInt gasLimit,
+    BigInt? gasPrice,
+    BigInt? gasPriorityFee,
+    BigInt? maxFeePerGas,
+    BigInt? maxPriorityFeePerGas,
+    required String latestBlockEnv,
+    required String prestateTracerResult,
+    required bool isOpStack,
+    String? accessList
+  }) {
+    final (maxFee, priorityFee) = _resolveFees(
+        gasPrice, gasPriorityFee, maxFeePerGas, maxPriorityFeePerGas);
+    return traceTransactionTyped(
+      chainId: chainId,
+      from: from,
+      fromNonce: fromNonce,
+      to: to,
+      data: data,
+      gasLimit: gasLimit,
+      gasPrice: maxFee,
+      gasPriorityFee: priorityFee,
+      latestBlockEnv: latestBlockEnv,
+      prestateTracerResult: prestateTracerResult,
+      isOpStack: isOpStack,
+      accessList: accessList,
+    );
+  }
+
+  /// Traces a transaction like [revmTrace], returning the result as
+  /// MessagePack bytes in the same shape as the JSON result.
+  ///
+  /// Large traces cross the FFI boundary and parse much faster this way; decode
+  /// the bytes with [decodeMessagePack]. Throws [FfiTraceError] if the
+  /// transaction cannot be traced.
+  static Uint8List revmTraceBinary({
+    required BigInt chainId,
+    required String from,
+    required BigInt fromNonce,
+    required String to,
+    required String data,
+    required BigInt gasLimit,
+    BigInt? gasPrice,
+    BigInt? gasPriorityFee,
+    BigInt? maxFeePerGas,
+    BigInt? maxPriorityFeePerGas,
     required String latestBlockEnv,
     required String prestateTracerResult,
-    required bool isOpStack
-  }) => formatAndTraceTransaction(
-    chainId: chainId,
-    from: from,
-    fromNonce: fromNonce,
-    to: to,
-    data: data,
-    gasLimit: gasLimit,
-    gasPrice: gasPrice,
-    gasPriorityFee: gasPriorityFee,
-    latestBlockEnv: latestBlockEnv,
-    prestateTracerResult: prestateTracerResult,
-    isOpStack: isOpStack,
-  );
-}
\ No newline at end of file
+    required bool isOpStack,
+    String? accessList
+  }) {
+    final (maxFee, priorityFee) = _resolveFees(
+        gasPrice, gasPriorityFee, maxFeePerGas, maxPriorityFeePerGas);
+    return formatAndTraceTransactionBinary(
+      chainId: chainId,
+      from: from,
+      fromNonce: fromNonce,
+      to: to,
+      data: data,
+      gasLimit: gasLimit,
+      gasPrice: maxFee,
+      gasPriorityFee: priorityFee,
+      latestBlockEnv: latestBlockEnv,
+      prestateTracerResult: prestateTracerResult,
+      isOpStack: isOpStack,
+      accessList: accessList,
+    );
+  }
+
+  /// Traces a transaction like [revmTraceTyped] on a worker thread, so long
+  /// traces do not block the isolate.
+  ///
+  /// [onProgress] is called periodically with the number of frames completed
+  /// and the execution gas consumed so far. Completes with [FfiTraceError] if
+  /// the transaction cannot be traced.
+  static Future<FfiTraceResult> revmTraceAsync({
+    required BigInt chainId,
+    required String from,
+    required BigInt fromNonce,
+    required String to,
+    required String data,
+    required BigInt gasLimit,
+    BigInt? gasPrice,
+    BigInt? gasPriorityFee,
+    BigInt? maxFeePerGas,
+    BigInt? maxPriorityFeePerGas,
+    required String latestBlockEnv,
+    required String prestateTracerResult,
+    required bool isOpStack,
+    String? accessList,
+    void Function(BigInt frames, BigInt gasUsed)? onProgress
+  }) async {
+    final (maxFee, priorityFee) = _resolveFees(
+        gasPrice, gasPriorityFee, maxFeePerGas, maxPriorityFeePerGas);
+    final updates = traceTransactionStream(
+      chainId: chainId,
+      from: from,
+      fromNonce: fromNonce,
+      to: to,
+      data: data,
+      gasLimit: gasLimit,
+      gasPrice: maxFee,
+      gasPriorityFee: priorityFee,
+      latestBlockEnv: latestBlockEnv,
+      prestateTracerResult: prestateTracerResult,
+      isOpStack: isOpStack,
+      accessList: accessList,
+    );
+    await for (final update in updates) {
+      final result = update.result;
+      final error = update.error;
+      if (result != null) return result;
+      if (error != null) throw error;
+      onProgress?.call(update.frames, update.gasUsed);
+    }
+    throw StateError('Trace stream closed without a result');
+  }
+
+  /// Traces a transaction on the chain described by [chainKind] and returns
+  /// the result as a JSON string.
+  ///
+  /// [chainKind] is a JSON object such as `{"kind": "opStack"}` and [request]
+  /// holds the transaction, block and prestate; see the README for both formats.
+  static String revmTraceWithChain({
+    required String chainKind,
+    required String request,
+  }) {
+    return traceWithChain(chainKind: chainKind, request: request);
+  }
+
+  static (BigInt, BigInt) _resolveFees(BigInt? gasPrice, BigInt? gasPriorityFee,
+      BigInt? maxFeePerGas, BigInt? maxPriorityFeePerGas) {
+    if (gasPrice != null && maxFeePerGas != null) {
+      throw ArgumentError('Only one of gasPrice and maxFeePerGas may be provided');
+    }
+    if (gasPriorityFee != null && maxPriorityFeePerGas != null) {
+      throw ArgumentError(
+          'Only one of gasPriorityFee and maxPriorityFeePerGas may be provided');
+    }
+    final maxFee = maxFeePerGas ?? gasPrice;
+    final priorityFee = maxPriorityFeePerGas ?? gasPriorityFee;
+    if (maxFee == null || priorityFee == null) {
+      throw ArgumentError(
+          'Either gasPrice/gasPriorityFee or maxFeePerGas/maxPriorityFeePerGas must be provided');
+    }
+    return (maxFee, priorityFee);
+  }
+}
@@ -1,4 +1,7 @@
 library revm_tracer;
 
 export 'base.dart';
+export 'src/message_pack.dart' show decodeMessagePack;
+export 'src/rust/api/tracer.dart'
+    show FfiTraceResult, FfiCallFrame, FfiLogEntry, FfiTraceError, FfiTraceUpdate;
 export 'src/rust/frb_generated.dart' show RustLib;
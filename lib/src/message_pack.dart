@@ -0,0 +1,154 @@
+import 'dart:convert';
+import 'dart:typed_data';
+
+/// Decodes MessagePack bytes, such as the result of
+/// [RevmTracer.revmTraceBinary], into the same values `jsonDecode` returns
+/// for the equivalent JSON: maps, lists, strings, numbers, booleans and null.
+///
+/// Binary values are returned as [Uint8List] and extension values as their
+/// raw payload. Unsigned integers above the range of [int] are returned as
+/// [BigInt].
+Object? decodeMessagePack(Uint8List bytes) {
+  final reader = _MessagePackReader(bytes);
+  final value = reader.read();
+  if (reader.offset != bytes.length) {
+    throw FormatException(
+        'Trailing bytes after MessagePack value', bytes, reader.offset);
+  }
+  return value;
+}
+
+class _MessagePackReader {
+  _MessagePackReader(this.bytes) : data = ByteData.sublistView(bytes);
+
+  final Uint8List bytes;
+  final ByteData data;
+  int offset = 0;
+
+  Object? read() {
+    final type = _uint8();
+    if (type <= 0x7f) return type;
+    if (type <= 0x8f) return _map(type & 0x0f);
+    if (type <= 0x9f) return _list(type & 0x0f);
+    if (type <= 0xbf) return _string(type & 0x1f);
+    if (type >= 0xe0) return type - 0x100;
+
+    switch (type) {
+      case 0xc0:
+        return null;
+      case 0xc2:
+        return false;
+      case 0xc3:
+        return true;
+      case 0xc4:
+        return _bytes(_uint8());
+      case 0xc5:
+        return _bytes(_uint16());
+      case 0xc6:
+        return _bytes(_uint32());
+      case 0xc7:
+        return _extension(_uint8());
+      case 0xc8:
+        return _extension(_uint16());
+      case 0xc9:
+        return _extension(_uint32());
+      case 0xca:
+        return data.getFloat32(_advance(4));
+      case 0xcb:
+        return data.getFloat64(_advance(8));
+      case 0xcc:
+        return _uint8();
+      case 0xcd:
+        return _uint16();
+      case 0xce:
+        return _uint32();
+      case 0xcf:
+        return _uint64();
+      case 0xd0:
+        return data.getInt8(_advance(1));
+      case 0xd1:
+        return data.getInt16(_advance(2));
+      case 0xd2:
+        return data.getInt32(_advance(4));
+      case 0xd3:
+        final high = data.getInt32(_advance(4));
+        final low = _uint32();
+        return high * 0x100000000 + low;
+      case 0xd4:
+        return _extension(1);
+      case 0xd5:
+        return _extension(2);
+      case 0xd6:
+        return _extension(4);
+      case 0xd7:
+        return _extension(8);
+      case 0xd8:
+        return _extension(16);
+      case 0xd9:
+        return _string(_uint8());
+      case 0xda:
+        return _string(_uint16());
+      case 0xdb:
+        return _string(_uint32());
+      case 0xdc:
+        return _list(_uint16());
+      case 0xdd:
+        return _list(_uint32());
+      case 0xde:
+        return _map(_uint16());
+      case 0xdf:
+        return _map(_uint32());
+    }
+    throw FormatException(
+        'Invalid MessagePack type byte 0x${type.toRadixString(16)}',
+        bytes,
+        offset - 1);
+  }
+
+  /// Moves past [length] bytes and returns where they start
+  int _advance(int length) {
+    if (offset + length > bytes.length) {
+      throw FormatException('Truncated MessagePack value', bytes, offset);
+    }
+    final start = offset;
+    offset += length;
+    return start;
+  }
+
+  int _uint8() => data.getUint8(_advance(1));
+
+  int _uint16() => data.getUint16(_advance(2));
+
+  int _uint32() => data.getUint32(_advance(4));
+
+  Object _uint64() {
+    final high = _uint32();
+    final low = _uint32();
+    if (high < 0x80000000) return high * 0x100000000 + low;
+    return (BigInt.from(high) << 32) + BigInt.from(low);
+  }
+
+  Uint8List _bytes(int length) {
+    final start = _advance(length);
+    return Uint8List.sublistView(bytes, start, start + length);
+  }
+
+  String _string(int length) => utf8.decode(_bytes(length));
+
+  Uint8List _extension(int length) {
+    _advance(1); // extension type
+    return _bytes(length);
+  }
+
+  List<Object?> _list(int length) =>
+      List<Object?>.generate(length, (_) => read(), growable: false);
+
+  Map<String, Object?> _map(int length) {
+    final map = <String, Object?>{};
+    for (var i = 0; i < length; i++) {
+      final key = read();
+      map[key is String ? key : '$key'] = read();
+    }
+    return map;
+  }
+}
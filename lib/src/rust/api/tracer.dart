@@ -6,7 +6,9 @@
 import '../frb_generated.dart';
 import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 
-// These functions are ignored because they are not marked as `pub`: `format_and_trace_transaction_internal`
+// These types are ignored because they are neither used by any `pub` functions nor (for structs and enums) marked `#[frb(unignore)]`: `ChainTraceResult`
+// These functions are ignored because they are not marked as `pub`: `format_and_trace_transaction_internal`, `from_result`, `run_trace`
+// These function are ignored because they are on traits that is not defined in current crate (put an empty `#[frb]` on it to unignore): `from`, `from`, `from`
 
 /// Formats and traces a transaction, returning the result as a JSON string
 ///
@@ -26,6 +28,7 @@ import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 /// * `latest_block_env` - Block environment as JSON string
 /// * `prestate_tracer_result` - Prestate as JSON string
 /// * `is_op_stack` - If true, use Optimism tracer; if false, use standard Ethereum tracer
+/// * `access_list` - Optional EIP-2930 access list as JSON string
 ///
 /// # Returns
 ///
@@ -43,7 +46,8 @@ String formatAndTraceTransaction(
         required BigInt gasPriorityFee,
         required String latestBlockEnv,
         required String prestateTracerResult,
-        required bool isOpStack}) =>
+        required bool isOpStack,
+        String? accessList}) =>
     RustLib.instance.api.crateApiTracerFormatAndTraceTransaction(
         chainId: chainId,
         from: from,
@@ -55,4 +59,329 @@ String formatAndTraceTransaction(
         gasPriorityFee: gasPriorityFee,
         latestBlockEnv: latestBlockEnv,
         prestateTracerResult: prestateTracerResult,
-        isOpStack: isOpStack);
+        isOpStack: isOpStack,
+        accessList: accessList);
+
+/// Traces a transaction like `format_and_trace_transaction`, returning the result as
+/// MessagePack instead of a JSON string
+///
+/// The result has the same shape as the JSON one, see `trace::binary`; it is smaller
+/// and much cheaper to produce and parse for large traces. Errors are returned as
+/// `FfiTraceError` and surface as exceptions on the Dart side.
+Uint8List formatAndTraceTransactionBinary(
+        {required BigInt chainId,
+        required String from,
+        required BigInt fromNonce,
+        required String to,
+        required String data,
+        required BigInt gasLimit,
+        required BigInt gasPrice,
+        required BigInt gasPriorityFee,
+        required String latestBlockEnv,
+        required String prestateTracerResult,
+        required bool isOpStack,
+        String? accessList}) =>
+    RustLib.instance.api.crateApiTracerFormatAndTraceTransactionBinary(
+        chainId: chainId,
+        from: from,
+        fromNonce: fromNonce,
+        to: to,
+        data: data,
+        gasLimit: gasLimit,
+        gasPrice: gasPrice,
+        gasPriorityFee: gasPriorityFee,
+        latestBlockEnv: latestBlockEnv,
+        prestateTracerResult: prestateTracerResult,
+        isOpStack: isOpStack,
+        accessList: accessList);
+
+/// Traces a transaction off the calling isolate, streaming progress as it goes
+///
+/// Takes the same arguments as `format_and_trace_transaction`. Unlike the other entry
+/// points it is not synchronous: the trace runs on a worker thread, so long traces do
+/// not block Dart. Progress updates are sent every 100 completed frames; the last
+/// update holds the result or the error, after which the stream closes.
+Stream<FfiTraceUpdate> traceTransactionStream(
+        {required BigInt chainId,
+        required String from,
+        required BigInt fromNonce,
+        required String to,
+        required String data,
+        required BigInt gasLimit,
+        required BigInt gasPrice,
+        required BigInt gasPriorityFee,
+        required String latestBlockEnv,
+        required String prestateTracerResult,
+        required bool isOpStack,
+        String? accessList}) =>
+    RustLib.instance.api.crateApiTracerTraceTransactionStream(
+        chainId: chainId,
+        from: from,
+        fromNonce: fromNonce,
+        to: to,
+        data: data,
+        gasLimit: gasLimit,
+        gasPrice: gasPrice,
+        gasPriorityFee: gasPriorityFee,
+        latestBlockEnv: latestBlockEnv,
+        prestateTracerResult: prestateTracerResult,
+        isOpStack: isOpStack,
+        accessList: accessList);
+
+/// Traces a transaction, returning typed results instead of a JSON string
+///
+/// Takes the same arguments as `format_and_trace_transaction`. Errors are returned as
+/// `FfiTraceError` and surface as exceptions on the Dart side.
+FfiTraceResult traceTransactionTyped(
+        {required BigInt chainId,
+        required String from,
+        required BigInt fromNonce,
+        required String to,
+        required String data,
+        required BigInt gasLimit,
+        required BigInt gasPrice,
+        required BigInt gasPriorityFee,
+        required String latestBlockEnv,
+        required String prestateTracerResult,
+        required bool isOpStack,
+        String? accessList}) =>
+    RustLib.instance.api.crateApiTracerTraceTransactionTyped(
+        chainId: chainId,
+        from: from,
+        fromNonce: fromNonce,
+        to: to,
+        data: data,
+        gasLimit: gasLimit,
+        gasPrice: gasPrice,
+        gasPriorityFee: gasPriorityFee,
+        latestBlockEnv: latestBlockEnv,
+        prestateTracerResult: prestateTracerResult,
+        isOpStack: isOpStack,
+        accessList: accessList);
+
+/// Traces a transaction on the chain described by `chain_kind`, returning the result as
+/// a JSON string
+///
+/// Unlike `format_and_trace_transaction`, chain-specific settings travel in `chain_kind`
+/// rather than in the signature, so supporting a new chain does not change this function.
+///
+/// # Arguments
+///
+/// * `chain_kind` - A `ChainKind` as JSON, e.g. `{"kind": "ethereum"}`,
+///   `{"kind": "opStack"}` or `{"kind": "arbitrumNitro", "l1PricePerUnit": "0x..."}`
+/// * `request` - A `TraceRequest` as JSON: `tx` in the `TxParams` format,
+///   `latestBlockEnv`, `prestateTracerResult` and optionally `stateOverrides` and
+///   `tracerConfig`
+///
+/// # Returns
+///
+/// JSON string containing either the trace result or an error object, in the same
+/// format as `format_and_trace_transaction`
+String traceWithChain({required String chainKind, required String request}) =>
+    RustLib.instance.api
+        .crateApiTracerTraceWithChain(chainKind: chainKind, request: request);
+
+/// A call frame of the trace, with hex-encoded addresses, values and data
+class FfiCallFrame {
+  final String callType;
+  final String from;
+  final String? to;
+  final String value;
+  final BigInt gas;
+  final BigInt gasUsed;
+  final String input;
+  final String? output;
+  final String? error;
+  final String? revertReason;
+  final List<FfiLogEntry> logs;
+  final List<FfiCallFrame> calls;
+
+  const FfiCallFrame({
+    required this.callType,
+    required this.from,
+    this.to,
+    required this.value,
+    required this.gas,
+    required this.gasUsed,
+    required this.input,
+    this.output,
+    this.error,
+    this.revertReason,
+    required this.logs,
+    required this.calls,
+  });
+
+  @override
+  int get hashCode =>
+      callType.hashCode ^
+      from.hashCode ^
+      to.hashCode ^
+      value.hashCode ^
+      gas.hashCode ^
+      gasUsed.hashCode ^
+      input.hashCode ^
+      output.hashCode ^
+      error.hashCode ^
+      revertReason.hashCode ^
+      logs.hashCode ^
+      calls.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is FfiCallFrame &&
+          runtimeType == other.runtimeType &&
+          callType == other.callType &&
+          from == other.from &&
+          to == other.to &&
+          value == other.value &&
+          gas == other.gas &&
+          gasUsed == other.gasUsed &&
+          input == other.input &&
+          output == other.output &&
+          error == other.error &&
+          revertReason == other.revertReason &&
+          logs == other.logs &&
+          calls == other.calls;
+}
+
+/// A log entry, with hex-encoded fields
+class FfiLogEntry {
+  final String address;
+  final List<String> topics;
+  final String data;
+
+  const FfiLogEntry({
+    required this.address,
+    required this.topics,
+    required this.data,
+  });
+
+  @override
+  int get hashCode =>
+      address.hashCode ^
+      topics.hashCode ^
+      data.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is FfiLogEntry &&
+          runtimeType == other.runtimeType &&
+          address == other.address &&
+          topics == other.topics &&
+          data == other.data;
+}
+
+/// Error returned by `trace_transaction_typed`
+class FfiTraceError implements FrbException {
+  /// Name of the `TraceError` variant
+  final String kind;
+  final String message;
+
+  const FfiTraceError({
+    required this.kind,
+    required this.message,
+  });
+
+  @override
+  int get hashCode =>
+      kind.hashCode ^
+      message.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is FfiTraceError &&
+          runtimeType == other.runtimeType &&
+          kind == other.kind &&
+          message == other.message;
+}
+
+/// Result of `trace_transaction_typed`
+class FfiTraceResult {
+  /// `success`, `revert` or `halt`
+  final String status;
+  final BigInt gasUsed;
+  /// Return data on success, revert data on revert, empty on halt
+  final String output;
+  /// Halt reason when the transaction halted
+  final String? haltReason;
+  final BigInt blobGasUsed;
+  /// Fee paid in wei, as a hex string
+  final String feePaid;
+  final FfiCallFrame calls;
+  /// Remaining fields of the trace result (state diff, balance changes, ...) as JSON
+  final String details;
+
+  const FfiTraceResult({
+    required this.status,
+    required this.gasUsed,
+    required this.output,
+    this.haltReason,
+    required this.blobGasUsed,
+    required this.feePaid,
+    required this.calls,
+    required this.details,
+  });
+
+  @override
+  int get hashCode =>
+      status.hashCode ^
+      gasUsed.hashCode ^
+      output.hashCode ^
+      haltReason.hashCode ^
+      blobGasUsed.hashCode ^
+      feePaid.hashCode ^
+      calls.hashCode ^
+      details.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is FfiTraceResult &&
+          runtimeType == other.runtimeType &&
+          status == other.status &&
+          gasUsed == other.gasUsed &&
+          output == other.output &&
+          haltReason == other.haltReason &&
+          blobGasUsed == other.blobGasUsed &&
+          feePaid == other.feePaid &&
+          calls == other.calls &&
+          details == other.details;
+}
+
+/// Update sent by `trace_transaction_stream`: progress while tracing, then a final
+/// update carrying either the result or the error
+class FfiTraceUpdate {
+  /// Frames completed so far
+  final BigInt frames;
+  /// Execution gas consumed so far
+  final BigInt gasUsed;
+  final FfiTraceResult? result;
+  final FfiTraceError? error;
+
+  const FfiTraceUpdate({
+    required this.frames,
+    required this.gasUsed,
+    this.result,
+    this.error,
+  });
+
+  @override
+  int get hashCode =>
+      frames.hashCode ^
+      gasUsed.hashCode ^
+      result.hashCode ^
+      error.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is FfiTraceUpdate &&
+          runtimeType == other.runtimeType &&
+          frames == other.frames &&
+          gasUsed == other.gasUsed &&
+          result == other.result &&
+          error == other.error;
+}
@@ -70,7 +70,7 @@ class RustLib extends BaseEntrypoint<RustLibApi, RustLibApiImpl, RustLibWire> {
   String get codegenVersion => '2.11.1';
 
   @override
-  int get rustContentHash => -357441788;
+  int get rustContentHash => -1203476562;
 
   static const kDefaultExternalLibraryLoaderConfig =
       ExternalLibraryLoaderConfig(
@@ -92,9 +92,55 @@ abstract class RustLibApi extends BaseApi {
       required BigInt gasPriorityFee,
       required String latestBlockEnv,
       required String prestateTracerResult,
-      required bool isOpStack});
+      required bool isOpStack,
+      String? accessList});
+
+  Uint8List crateApiTracerFormatAndTraceTransactionBinary(
+      {required BigInt chainId,
+      required String from,
+      required BigInt fromNonce,
+      required String to,
+      required String data,
+      required BigInt gasLimit,
+      required BigInt gasPrice,
+      required BigInt gasPriorityFee,
+      required String latestBlockEnv,
+      required String prestateTracerResult,
+      required bool isOpStack,
+      String? accessList});
 
   Future<void> crateApiTracerInitApp();
+
+  Stream<FfiTraceUpdate> crateApiTracerTraceTransactionStream(
+      {required BigInt chainId,
+      required String from,
+      required BigInt fromNonce,
+      required String to,
+      required String data,
+      required BigInt gasLimit,
+      required BigInt gasPrice,
+      required BigInt gasPriorityFee,
+      required String latestBlockEnv,
+      required String prestateTracerResult,
+      required bool isOpStack,
+      String? accessList});
+
+  FfiTraceResult crateApiTracerTraceTransactionTyped(
+      {required BigInt chainId,
+      required String from,
+      required BigInt fromNonce,
+      required String to,
+      required String data,
+      required BigInt gasLimit,
+      required BigInt gasPrice,
+      required BigInt gasPriorityFee,
+      required String latestBlockEnv,
+      required String prestateTracerResult,
+      required bool isOpStack,
+      String? accessList});
+
+  String crateApiTracerTraceWithChain(
+      {required String chainKind, required String request});
 }
 
 class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
@@ -117,7 +163,8 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
       required BigInt gasPriorityFee,
       required String latestBlockEnv,
       required String prestateTracerResult,
-      required bool isOpStack}) {
+      required bool isOpStack,
+      String? accessList}) {
     return handler.executeSync(SyncTask(
       callFfi: () {
         final serializer = SseSerializer(generalizedFrbRustBinding);
@@ -132,6 +179,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         sse_encode_String(latestBlockEnv, serializer);
         sse_encode_String(prestateTracerResult, serializer);
         sse_encode_bool(isOpStack, serializer);
+        sse_encode_opt_String(accessList, serializer);
         return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 1)!;
       },
       codec: SseCodec(
@@ -150,12 +198,33 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         gasPriorityFee,
         latestBlockEnv,
         prestateTracerResult,
-        isOpStack
+        isOpStack,
+        accessList
       ],
       apiImpl: this,
     ));
   }
 
+  @protected
+  void sse_encode_opt_box_autoadd_ffi_trace_error(FfiTraceError? self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_box_autoadd_ffi_trace_error(self, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_opt_box_autoadd_ffi_trace_result(FfiTraceResult? self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_box_autoadd_ffi_trace_result(self, serializer);
+    }
+  }
+
   TaskConstMeta get kCrateApiTracerFormatAndTraceTransactionConstMeta =>
       const TaskConstMeta(
         debugName: "format_and_trace_transaction",
@@ -170,7 +239,81 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
           "gasPriorityFee",
           "latestBlockEnv",
           "prestateTracerResult",
-          "isOpStack"
+          "isOpStack",
+          "accessList"
+        ],
+      );
+
+  @override
+  Uint8List crateApiTracerFormatAndTraceTransactionBinary(
+      {required BigInt chainId,
+      required String from,
+      required BigInt fromNonce,
+      required String to,
+      required String data,
+      required BigInt gasLimit,
+      required BigInt gasPrice,
+      required BigInt gasPriorityFee,
+      required String latestBlockEnv,
+      required String prestateTracerResult,
+      required bool isOpStack,
+      String? accessList}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_u_64(chainId, serializer);
+        sse_encode_String(from, serializer);
+        sse_encode_u_64(fromNonce, serializer);
+        sse_encode_String(to, serializer);
+        sse_encode_String(data, serializer);
+        sse_encode_u_64(gasLimit, serializer);
+        sse_encode_U128(gasPrice, serializer);
+        sse_encode_U128(gasPriorityFee, serializer);
+        sse_encode_String(latestBlockEnv, serializer);
+        sse_encode_String(prestateTracerResult, serializer);
+        sse_encode_bool(isOpStack, serializer);
+        sse_encode_opt_String(accessList, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 2)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_prim_u_8_strict,
+        decodeErrorData: sse_decode_ffi_trace_error,
+      ),
+      constMeta: kCrateApiTracerFormatAndTraceTransactionBinaryConstMeta,
+      argValues: [
+        chainId,
+        from,
+        fromNonce,
+        to,
+        data,
+        gasLimit,
+        gasPrice,
+        gasPriorityFee,
+        latestBlockEnv,
+        prestateTracerResult,
+        isOpStack,
+        accessList
+      ],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiTracerFormatAndTraceTransactionBinaryConstMeta =>
+      const TaskConstMeta(
+        debugName: "format_and_trace_transaction_binary",
+        argNames: [
+          "chainId",
+          "from",
+          "fromNonce",
+          "to",
+          "data",
+          "gasLimit",
+          "gasPrice",
+          "gasPriorityFee",
+          "latestBlockEnv",
+          "prestateTracerResult",
+          "isOpStack",
+          "accessList"
         ],
       );
 
@@ -180,7 +323,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 2, port: port_);
+            funcId: 3, port: port_);
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
@@ -197,6 +340,196 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         argNames: [],
       );
 
+  @override
+  Stream<FfiTraceUpdate> crateApiTracerTraceTransactionStream(
+      {required BigInt chainId,
+      required String from,
+      required BigInt fromNonce,
+      required String to,
+      required String data,
+      required BigInt gasLimit,
+      required BigInt gasPrice,
+      required BigInt gasPriorityFee,
+      required String latestBlockEnv,
+      required String prestateTracerResult,
+      required bool isOpStack,
+      String? accessList}) {
+    final sink = RustStreamSink<FfiTraceUpdate>();
+    unawaited(handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_StreamSink_ffi_trace_update_Sse(sink, serializer);
+        sse_encode_u_64(chainId, serializer);
+        sse_encode_String(from, serializer);
+        sse_encode_u_64(fromNonce, serializer);
+        sse_encode_String(to, serializer);
+        sse_encode_String(data, serializer);
+        sse_encode_u_64(gasLimit, serializer);
+        sse_encode_U128(gasPrice, serializer);
+        sse_encode_U128(gasPriorityFee, serializer);
+        sse_encode_String(latestBlockEnv, serializer);
+        sse_encode_String(prestateTracerResult, serializer);
+        sse_encode_bool(isOpStack, serializer);
+        sse_encode_opt_String(accessList, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 4, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kCrateApiTracerTraceTransactionStreamConstMeta,
+      argValues: [
+        sink,
+        chainId,
+        from,
+        fromNonce,
+        to,
+        data,
+        gasLimit,
+        gasPrice,
+        gasPriorityFee,
+        latestBlockEnv,
+        prestateTracerResult,
+        isOpStack,
+        accessList
+      ],
+      apiImpl: this,
+    )));
+    return sink.stream;
+  }
+
+  TaskConstMeta get kCrateApiTracerTraceTransactionStreamConstMeta =>
+      const TaskConstMeta(
+        debugName: "trace_transaction_stream",
+        argNames: [
+          "sink",
+          "chainId",
+          "from",
+          "fromNonce",
+          "to",
+          "data",
+          "gasLimit",
+          "gasPrice",
+          "gasPriorityFee",
+          "latestBlockEnv",
+          "prestateTracerResult",
+          "isOpStack",
+          "accessList"
+        ],
+      );
+
+  @override
+  FfiTraceResult crateApiTracerTraceTransactionTyped(
+      {required BigInt chainId,
+      required String from,
+      required BigInt fromNonce,
+      required String to,
+      required String data,
+      required BigInt gasLimit,
+      required BigInt gasPrice,
+      required BigInt gasPriorityFee,
+      required String latestBlockEnv,
+      required String prestateTracerResult,
+      required bool isOpStack,
+      String? accessList}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_u_64(chainId, serializer);
+        sse_encode_String(from, serializer);
+        sse_encode_u_64(fromNonce, serializer);
+        sse_encode_String(to, serializer);
+        sse_encode_String(data, serializer);
+        sse_encode_u_64(gasLimit, serializer);
+        sse_encode_U128(gasPrice, serializer);
+        sse_encode_U128(gasPriorityFee, serializer);
+        sse_encode_String(latestBlockEnv, serializer);
+        sse_encode_String(prestateTracerResult, serializer);
+        sse_encode_bool(isOpStack, serializer);
+        sse_encode_opt_String(accessList, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 5)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_ffi_trace_result,
+        decodeErrorData: sse_decode_ffi_trace_error,
+      ),
+      constMeta: kCrateApiTracerTraceTransactionTypedConstMeta,
+      argValues: [
+        chainId,
+        from,
+        fromNonce,
+        to,
+        data,
+        gasLimit,
+        gasPrice,
+        gasPriorityFee,
+        latestBlockEnv,
+        prestateTracerResult,
+        isOpStack,
+        accessList
+      ],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiTracerTraceTransactionTypedConstMeta =>
+      const TaskConstMeta(
+        debugName: "trace_transaction_typed",
+        argNames: [
+          "chainId",
+          "from",
+          "fromNonce",
+          "to",
+          "data",
+          "gasLimit",
+          "gasPrice",
+          "gasPriorityFee",
+          "latestBlockEnv",
+          "prestateTracerResult",
+          "isOpStack",
+          "accessList"
+        ],
+      );
+
+  @override
+  String crateApiTracerTraceWithChain(
+      {required String chainKind, required String request}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_String(chainKind, serializer);
+        sse_encode_String(request, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 6)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_String,
+        decodeErrorData: null,
+      ),
+      constMeta: kCrateApiTracerTraceWithChainConstMeta,
+      argValues: [chainKind, request],
+      apiImpl: this,
+    ));
+  }
+
+  TaskConstMeta get kCrateApiTracerTraceWithChainConstMeta =>
+      const TaskConstMeta(
+        debugName: "trace_with_chain",
+        argNames: ["chainKind", "request"],
+      );
+
+  @protected
+  AnyhowException dco_decode_AnyhowException(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return AnyhowException(raw as String);
+  }
+
+  @protected
+  RustStreamSink<FfiTraceUpdate> dco_decode_StreamSink_ffi_trace_update_Sse(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    throw UnimplementedError();
+  }
+
   @protected
   String dco_decode_String(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -215,12 +548,139 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return raw as bool;
   }
 
+  @protected
+  FfiTraceError dco_decode_box_autoadd_ffi_trace_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_ffi_trace_error(raw);
+  }
+
+  @protected
+  FfiTraceResult dco_decode_box_autoadd_ffi_trace_result(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_ffi_trace_result(raw);
+  }
+
+  @protected
+  FfiTraceUpdate dco_decode_ffi_trace_update(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 4)
+      throw Exception('unexpected arr length: expect 4 but see ${arr.length}');
+    return FfiTraceUpdate(
+      frames: dco_decode_u_64(arr[0]),
+      gasUsed: dco_decode_u_64(arr[1]),
+      result: dco_decode_opt_box_autoadd_ffi_trace_result(arr[2]),
+      error: dco_decode_opt_box_autoadd_ffi_trace_error(arr[3]),
+    );
+  }
+
+  @protected
+  FfiCallFrame dco_decode_ffi_call_frame(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 12)
+      throw Exception('unexpected arr length: expect 12 but see ${arr.length}');
+    return FfiCallFrame(
+      callType: dco_decode_String(arr[0]),
+      from: dco_decode_String(arr[1]),
+      to: dco_decode_opt_String(arr[2]),
+      value: dco_decode_String(arr[3]),
+      gas: dco_decode_u_64(arr[4]),
+      gasUsed: dco_decode_u_64(arr[5]),
+      input: dco_decode_String(arr[6]),
+      output: dco_decode_opt_String(arr[7]),
+      error: dco_decode_opt_String(arr[8]),
+      revertReason: dco_decode_opt_String(arr[9]),
+      logs: dco_decode_list_ffi_log_entry(arr[10]),
+      calls: dco_decode_list_ffi_call_frame(arr[11]),
+    );
+  }
+
+  @protected
+  FfiTraceError? dco_decode_opt_box_autoadd_ffi_trace_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_box_autoadd_ffi_trace_error(raw);
+  }
+
+  @protected
+  FfiTraceResult? dco_decode_opt_box_autoadd_ffi_trace_result(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_box_autoadd_ffi_trace_result(raw);
+  }
+
+  @protected
+  FfiLogEntry dco_decode_ffi_log_entry(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 3)
+      throw Exception('unexpected arr length: expect 3 but see ${arr.length}');
+    return FfiLogEntry(
+      address: dco_decode_String(arr[0]),
+      topics: dco_decode_list_String(arr[1]),
+      data: dco_decode_String(arr[2]),
+    );
+  }
+
+  @protected
+  FfiTraceError dco_decode_ffi_trace_error(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 2)
+      throw Exception('unexpected arr length: expect 2 but see ${arr.length}');
+    return FfiTraceError(
+      kind: dco_decode_String(arr[0]),
+      message: dco_decode_String(arr[1]),
+    );
+  }
+
+  @protected
+  FfiTraceResult dco_decode_ffi_trace_result(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 8)
+      throw Exception('unexpected arr length: expect 8 but see ${arr.length}');
+    return FfiTraceResult(
+      status: dco_decode_String(arr[0]),
+      gasUsed: dco_decode_u_64(arr[1]),
+      output: dco_decode_String(arr[2]),
+      haltReason: dco_decode_opt_String(arr[3]),
+      blobGasUsed: dco_decode_u_64(arr[4]),
+      feePaid: dco_decode_String(arr[5]),
+      calls: dco_decode_ffi_call_frame(arr[6]),
+      details: dco_decode_String(arr[7]),
+    );
+  }
+
+  @protected
+  List<String> dco_decode_list_String(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_String).toList();
+  }
+
+  @protected
+  List<FfiCallFrame> dco_decode_list_ffi_call_frame(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_ffi_call_frame).toList();
+  }
+
+  @protected
+  List<FfiLogEntry> dco_decode_list_ffi_log_entry(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_ffi_log_entry).toList();
+  }
+
   @protected
   Uint8List dco_decode_list_prim_u_8_strict(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
     return raw as Uint8List;
   }
 
+  @protected
+  String? dco_decode_opt_String(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw == null ? null : dco_decode_String(raw);
+  }
+
   @protected
   BigInt dco_decode_u_64(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -239,6 +699,19 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return;
   }
 
+  @protected
+  AnyhowException sse_decode_AnyhowException(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var inner = sse_decode_String(deserializer);
+    return AnyhowException(inner);
+  }
+
+  @protected
+  RustStreamSink<FfiTraceUpdate> sse_decode_StreamSink_ffi_trace_update_Sse(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    throw UnimplementedError('Unreachable ()');
+  }
+
   @protected
   String sse_decode_String(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -259,6 +732,169 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return deserializer.buffer.getUint8() != 0;
   }
 
+  @protected
+  FfiTraceError sse_decode_box_autoadd_ffi_trace_error(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_ffi_trace_error(deserializer));
+  }
+
+  @protected
+  FfiTraceResult sse_decode_box_autoadd_ffi_trace_result(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_ffi_trace_result(deserializer));
+  }
+
+  @protected
+  FfiCallFrame sse_decode_ffi_call_frame(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_callType = sse_decode_String(deserializer);
+    var var_from = sse_decode_String(deserializer);
+    var var_to = sse_decode_opt_String(deserializer);
+    var var_value = sse_decode_String(deserializer);
+    var var_gas = sse_decode_u_64(deserializer);
+    var var_gasUsed = sse_decode_u_64(deserializer);
+    var var_input = sse_decode_String(deserializer);
+    var var_output = sse_decode_opt_String(deserializer);
+    var var_error = sse_decode_opt_String(deserializer);
+    var var_revertReason = sse_decode_opt_String(deserializer);
+    var var_logs = sse_decode_list_ffi_log_entry(deserializer);
+    var var_calls = sse_decode_list_ffi_call_frame(deserializer);
+    return FfiCallFrame(
+        callType: var_callType,
+        from: var_from,
+        to: var_to,
+        value: var_value,
+        gas: var_gas,
+        gasUsed: var_gasUsed,
+        input: var_input,
+        output: var_output,
+        error: var_error,
+        revertReason: var_revertReason,
+        logs: var_logs,
+        calls: var_calls,
+    );
+  }
+
+  @protected
+  FfiTraceError? sse_decode_opt_box_autoadd_ffi_trace_error(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_box_autoadd_ffi_trace_error(deserializer));
+    } else {
+      return null;
+    }
+  }
+
+  @protected
+  FfiTraceResult? sse_decode_opt_box_autoadd_ffi_trace_result(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_box_autoadd_ffi_trace_result(deserializer));
+    } else {
+      return null;
+    }
+  }
+
+  @protected
+  FfiLogEntry sse_decode_ffi_log_entry(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_address = sse_decode_String(deserializer);
+    var var_topics = sse_decode_list_String(deserializer);
+    var var_data = sse_decode_String(deserializer);
+    return FfiLogEntry(
+        address: var_address,
+        topics: var_topics,
+        data: var_data,
+    );
+  }
+
+  @protected
+  FfiTraceError sse_decode_ffi_trace_error(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_kind = sse_decode_String(deserializer);
+    var var_message = sse_decode_String(deserializer);
+    return FfiTraceError(
+        kind: var_kind,
+        message: var_message,
+    );
+  }
+
+  @protected
+  FfiTraceResult sse_decode_ffi_trace_result(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_status = sse_decode_String(deserializer);
+    var var_gasUsed = sse_decode_u_64(deserializer);
+    var var_output = sse_decode_String(deserializer);
+    var var_haltReason = sse_decode_opt_String(deserializer);
+    var var_blobGasUsed = sse_decode_u_64(deserializer);
+    var var_feePaid = sse_decode_String(deserializer);
+    var var_calls = sse_decode_ffi_call_frame(deserializer);
+    var var_details = sse_decode_String(deserializer);
+    return FfiTraceResult(
+        status: var_status,
+        gasUsed: var_gasUsed,
+        output: var_output,
+        haltReason: var_haltReason,
+        blobGasUsed: var_blobGasUsed,
+        feePaid: var_feePaid,
+        calls: var_calls,
+        details: var_details,
+    );
+  }
+
+  @protected
+  FfiTraceUpdate sse_decode_ffi_trace_update(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_frames = sse_decode_u_64(deserializer);
+    var var_gasUsed = sse_decode_u_64(deserializer);
+    var var_result = sse_decode_opt_box_autoadd_ffi_trace_result(deserializer);
+    var var_error = sse_decode_opt_box_autoadd_ffi_trace_error(deserializer);
+    return FfiTraceUpdate(
+        frames: var_frames,
+        gasUsed: var_gasUsed,
+        result: var_result,
+        error: var_error,
+    );
+  }
+
+  @protected
+  List<String> sse_decode_list_String(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <String>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_String(deserializer));
+    }
+    return ans_;
+  }
+
+  @protected
+  List<FfiCallFrame> sse_decode_list_ffi_call_frame(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <FfiCallFrame>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_ffi_call_frame(deserializer));
+    }
+    return ans_;
+  }
+
+  @protected
+  List<FfiLogEntry> sse_decode_list_ffi_log_entry(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <FfiLogEntry>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_ffi_log_entry(deserializer));
+    }
+    return ans_;
+  }
+
   @protected
   Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -266,6 +902,17 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return deserializer.buffer.getUint8List(len_);
   }
 
+  @protected
+  String? sse_decode_opt_String(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    if (sse_decode_bool(deserializer)) {
+      return (sse_decode_String(deserializer));
+    } else {
+      return null;
+    }
+  }
+
   @protected
   BigInt sse_decode_u_64(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -289,6 +936,24 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return deserializer.buffer.getInt32();
   }
 
+  @protected
+  void sse_encode_AnyhowException(AnyhowException self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.message, serializer);
+  }
+
+  @protected
+  void sse_encode_StreamSink_ffi_trace_update_Sse(RustStreamSink<FfiTraceUpdate> self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(
+        self.setupAndSerialize(
+            codec: SseCodec(
+          decodeSuccessData: sse_decode_ffi_trace_update,
+          decodeErrorData: sse_decode_AnyhowException,
+        )),
+        serializer);
+  }
+
   @protected
   void sse_encode_String(String self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -307,6 +972,99 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     serializer.buffer.putUint8(self ? 1 : 0);
   }
 
+  @protected
+  void sse_encode_box_autoadd_ffi_trace_error(FfiTraceError self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_ffi_trace_error(self, serializer);
+  }
+
+  @protected
+  void sse_encode_box_autoadd_ffi_trace_result(FfiTraceResult self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_ffi_trace_result(self, serializer);
+  }
+
+  @protected
+  void sse_encode_ffi_trace_update(FfiTraceUpdate self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_u_64(self.frames, serializer);
+    sse_encode_u_64(self.gasUsed, serializer);
+    sse_encode_opt_box_autoadd_ffi_trace_result(self.result, serializer);
+    sse_encode_opt_box_autoadd_ffi_trace_error(self.error, serializer);
+  }
+
+  @protected
+  void sse_encode_ffi_call_frame(FfiCallFrame self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.callType, serializer);
+    sse_encode_String(self.from, serializer);
+    sse_encode_opt_String(self.to, serializer);
+    sse_encode_String(self.value, serializer);
+    sse_encode_u_64(self.gas, serializer);
+    sse_encode_u_64(self.gasUsed, serializer);
+    sse_encode_String(self.input, serializer);
+    sse_encode_opt_String(self.output, serializer);
+    sse_encode_opt_String(self.error, serializer);
+    sse_encode_opt_String(self.revertReason, serializer);
+    sse_encode_list_ffi_log_entry(self.logs, serializer);
+    sse_encode_list_ffi_call_frame(self.calls, serializer);
+  }
+
+  @protected
+  void sse_encode_ffi_log_entry(FfiLogEntry self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.address, serializer);
+    sse_encode_list_String(self.topics, serializer);
+    sse_encode_String(self.data, serializer);
+  }
+
+  @protected
+  void sse_encode_ffi_trace_error(FfiTraceError self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.kind, serializer);
+    sse_encode_String(self.message, serializer);
+  }
+
+  @protected
+  void sse_encode_ffi_trace_result(FfiTraceResult self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_String(self.status, serializer);
+    sse_encode_u_64(self.gasUsed, serializer);
+    sse_encode_String(self.output, serializer);
+    sse_encode_opt_String(self.haltReason, serializer);
+    sse_encode_u_64(self.blobGasUsed, serializer);
+    sse_encode_String(self.feePaid, serializer);
+    sse_encode_ffi_call_frame(self.calls, serializer);
+    sse_encode_String(self.details, serializer);
+  }
+
+  @protected
+  void sse_encode_list_String(List<String> self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_String(item, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_list_ffi_call_frame(List<FfiCallFrame> self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_ffi_call_frame(item, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_list_ffi_log_entry(List<FfiLogEntry> self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_ffi_log_entry(item, serializer);
+    }
+  }
+
   @protected
   void sse_encode_list_prim_u_8_strict(
       Uint8List self, SseSerializer serializer) {
@@ -315,6 +1073,16 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     serializer.buffer.putUint8List(self);
   }
 
+  @protected
+  void sse_encode_opt_String(String? self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    sse_encode_bool(self != null, serializer);
+    if (self != null) {
+      sse_encode_String(self, serializer);
+    }
+  }
+
   @protected
   void sse_encode_u_64(BigInt self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -18,6 +18,12 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
     required super.portManager,
   });
 
+  @protected
+  AnyhowException dco_decode_AnyhowException(dynamic raw);
+
+  @protected
+  RustStreamSink<FfiTraceUpdate> dco_decode_StreamSink_ffi_trace_update_Sse(dynamic raw);
+
   @protected
   String dco_decode_String(dynamic raw);
 
@@ -27,9 +33,48 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   bool dco_decode_bool(dynamic raw);
 
+  @protected
+  FfiTraceError dco_decode_box_autoadd_ffi_trace_error(dynamic raw);
+
+  @protected
+  FfiTraceResult dco_decode_box_autoadd_ffi_trace_result(dynamic raw);
+
+  @protected
+  FfiCallFrame dco_decode_ffi_call_frame(dynamic raw);
+
+  @protected
+  FfiLogEntry dco_decode_ffi_log_entry(dynamic raw);
+
+  @protected
+  FfiTraceError dco_decode_ffi_trace_error(dynamic raw);
+
+  @protected
+  FfiTraceResult dco_decode_ffi_trace_result(dynamic raw);
+
+  @protected
+  FfiTraceUpdate dco_decode_ffi_trace_update(dynamic raw);
+
+  @protected
+  List<String> dco_decode_list_String(dynamic raw);
+
+  @protected
+  List<FfiCallFrame> dco_decode_list_ffi_call_frame(dynamic raw);
+
+  @protected
+  List<FfiLogEntry> dco_decode_list_ffi_log_entry(dynamic raw);
+
   @protected
   Uint8List dco_decode_list_prim_u_8_strict(dynamic raw);
 
+  @protected
+  String? dco_decode_opt_String(dynamic raw);
+
+  @protected
+  FfiTraceError? dco_decode_opt_box_autoadd_ffi_trace_error(dynamic raw);
+
+  @protected
+  FfiTraceResult? dco_decode_opt_box_autoadd_ffi_trace_result(dynamic raw);
+
   @protected
   BigInt dco_decode_u_64(dynamic raw);
 
@@ -39,6 +84,12 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void dco_decode_unit(dynamic raw);
 
+  @protected
+  AnyhowException sse_decode_AnyhowException(SseDeserializer deserializer);
+
+  @protected
+  RustStreamSink<FfiTraceUpdate> sse_decode_StreamSink_ffi_trace_update_Sse(SseDeserializer deserializer);
+
   @protected
   String sse_decode_String(SseDeserializer deserializer);
 
@@ -48,9 +99,48 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   bool sse_decode_bool(SseDeserializer deserializer);
 
+  @protected
+  FfiTraceError sse_decode_box_autoadd_ffi_trace_error(SseDeserializer deserializer);
+
+  @protected
+  FfiTraceResult sse_decode_box_autoadd_ffi_trace_result(SseDeserializer deserializer);
+
+  @protected
+  FfiCallFrame sse_decode_ffi_call_frame(SseDeserializer deserializer);
+
+  @protected
+  FfiLogEntry sse_decode_ffi_log_entry(SseDeserializer deserializer);
+
+  @protected
+  FfiTraceError sse_decode_ffi_trace_error(SseDeserializer deserializer);
+
+  @protected
+  FfiTraceResult sse_decode_ffi_trace_result(SseDeserializer deserializer);
+
+  @protected
+  FfiTraceUpdate sse_decode_ffi_trace_update(SseDeserializer deserializer);
+
+  @protected
+  List<String> sse_decode_list_String(SseDeserializer deserializer);
+
+  @protected
+  List<FfiCallFrame> sse_decode_list_ffi_call_frame(SseDeserializer deserializer);
+
+  @protected
+  List<FfiLogEntry> sse_decode_list_ffi_log_entry(SseDeserializer deserializer);
+
   @protected
   Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer);
 
+  @protected
+  String? sse_decode_opt_String(SseDeserializer deserializer);
+
+  @protected
+  FfiTraceError? sse_decode_opt_box_autoadd_ffi_trace_error(SseDeserializer deserializer);
+
+  @protected
+  FfiTraceResult? sse_decode_opt_box_autoadd_ffi_trace_result(SseDeserializer deserializer);
+
   @protected
   BigInt sse_decode_u_64(SseDeserializer deserializer);
 
@@ -63,6 +153,12 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   int sse_decode_i_32(SseDeserializer deserializer);
 
+  @protected
+  void sse_encode_AnyhowException(AnyhowException self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_StreamSink_ffi_trace_update_Sse(RustStreamSink<FfiTraceUpdate> self, SseSerializer serializer);
+
   @protected
   void sse_encode_String(String self, SseSerializer serializer);
 
@@ -72,10 +168,49 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_bool(bool self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_box_autoadd_ffi_trace_error(FfiTraceError self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_box_autoadd_ffi_trace_result(FfiTraceResult self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_ffi_call_frame(FfiCallFrame self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_ffi_log_entry(FfiLogEntry self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_ffi_trace_error(FfiTraceError self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_ffi_trace_result(FfiTraceResult self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_ffi_trace_update(FfiTraceUpdate self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_list_String(List<String> self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_list_ffi_call_frame(List<FfiCallFrame> self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_list_ffi_log_entry(List<FfiLogEntry> self, SseSerializer serializer);
+
   @protected
   void sse_encode_list_prim_u_8_strict(
       Uint8List self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_opt_String(String? self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_opt_box_autoadd_ffi_trace_error(FfiTraceError? self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_opt_box_autoadd_ffi_trace_result(FfiTraceResult? self, SseSerializer serializer);
+
   @protected
   void sse_encode_u_64(BigInt self, SseSerializer serializer);
 
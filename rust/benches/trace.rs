@@ -0,0 +1,48 @@
+//! Trace throughput on the workloads of `trace::workloads`
+//!
+//! Run with `cargo bench --features bench`. Throughput is reported in gas per second.
+//! The `handle_ops` benchmark needs the runtime code of EntryPoint v0.7 as hex, in the
+//! file named by `REVM_TRACER_BENCH_ENTRY_POINT`, and is skipped without it.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use revm::primitives::Bytes;
+
+use revm_tracer::trace::inspector::CallTracerConfig;
+use revm_tracer::trace::workloads::{erc20_transfer, handle_ops, nested_calls, simple_transfer, Workload};
+
+/// Call depth of the `nested_calls` workload
+const NESTED_DEPTH: usize = 32;
+
+fn bench_workload(c: &mut Criterion, workload: Workload) {
+    let result = workload.trace().expect("workload traces");
+    assert!(result.execution_result.is_success(), "{} failed: {:?}", workload.name, result.execution_result);
+
+    let mut group = c.benchmark_group(workload.name);
+    group.throughput(Throughput::Elements(result.execution_result.gas_used()));
+    group.bench_function("call_tracer", |b| b.iter(|| black_box(workload.trace())));
+    let with_storage = CallTracerConfig { with_storage: true, ..CallTracerConfig::default() };
+    group.bench_function("call_tracer_with_storage", |b| {
+        b.iter(|| black_box(workload.trace_with_config(&with_storage)))
+    });
+    group.finish();
+}
+
+fn traces(c: &mut Criterion) {
+    bench_workload(c, simple_transfer());
+    bench_workload(c, erc20_transfer());
+    bench_workload(c, nested_calls(NESTED_DEPTH));
+
+    match std::env::var("REVM_TRACER_BENCH_ENTRY_POINT") {
+        Ok(path) => {
+            let hex = std::fs::read_to_string(&path).expect("EntryPoint code file is readable");
+            let code: Bytes = hex.trim().parse().expect("EntryPoint code is hex");
+            bench_workload(c, handle_ops(code));
+        }
+        Err(_) => eprintln!("REVM_TRACER_BENCH_ENTRY_POINT not set, skipping handle_ops"),
+    }
+}
+
+criterion_group!(benches, traces);
+criterion_main!(benches);
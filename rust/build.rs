@@ -0,0 +1,8 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    // Rust types and service of the `grpc` feature
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/revm_tracer.proto")
+        .expect("failed to compile proto/revm_tracer.proto");
+}
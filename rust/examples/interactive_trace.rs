@@ -1,14 +1,13 @@
-use revm::primitives::{Address, Bytes, U256, HashMap};
-use revm::context::BlockEnv;
+use revm::primitives::{Address, Bytes, U256};
 use std::str::FromStr;
 use std::io::{self, Write};
-use serde_json::json;
 
 // Import from the library
 use revm_tracer::trace::{
-    database::AccountDetails,
-    trace::trace_transaction,
-    block::BlockDetails,
+    rpc::TraceRpcClient,
+    trace::{trace_transaction, TraceTransactionOptions, TxParams, TxSpec},
+    block::create_block_env_from_block_details,
+    display::DisplayOptions,
 };
 
 fn main() {
@@ -25,10 +24,44 @@ fn main() {
     let calldata = prompt_hex("Enter calldata (e.g., 0x for empty, 0xa9059cbb... for data): ");
     let block_number = prompt_input("Enter block number (or 'latest'): ");
 
+    // Get additional transaction parameters
+    let chain_id = prompt_u64("\nEnter chain ID (1 for mainnet, 11155111 for sepolia): ");
+    let from_nonce = prompt_u64("Enter sender nonce: ");
+    let gas_limit = prompt_u64("Enter gas limit: ");
+    let gas_price = prompt_u128("Enter gas price in gwei: ") * 1_000_000_000;
+    let gas_priority_fee = prompt_u128("Enter priority fee in gwei: ") * 1_000_000_000;
+
+    let params = TxParams {
+        chain_id,
+        from: from_address,
+        from_nonce,
+        to: to_address,
+        value,
+        data: calldata,
+        gas_limit,
+        gas_price,
+        gas_priority_fee,
+        tx_spec: TxSpec::Eip1559,
+        access_list: None,
+        blob_versioned_hashes: Vec::new(),
+        max_fee_per_blob_gas: 0,
+        authorization_list: Vec::new(),
+        deposit: None,
+        spec: None,
+    };
+
     println!("\n=== Fetching data from RPC... ===\n");
 
+    let client = match TraceRpcClient::new(rpc_url) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("✗ Failed to create RPC client: {}", e);
+            return;
+        }
+    };
+
     // Fetch block details
-    let block_details = match fetch_block_details(&rpc_url, &block_number) {
+    let block_details = match client.block_details(&block_number) {
         Ok(details) => {
             println!("✓ Block details fetched successfully");
             println!("  Block Number: {}", details.number);
@@ -43,14 +76,7 @@ fn main() {
     };
 
     // Fetch prestate using debug_traceCall
-    let prestate = match fetch_prestate(
-        &rpc_url,
-        &from_address,
-        &to_address,
-        &value,
-        &calldata,
-        &block_number,
-    ) {
+    let prestate = match client.prestate(&params, &block_number) {
         Ok(state) => {
             println!("✓ Prestate fetched successfully ({} accounts)", state.len());
             state
@@ -62,41 +88,23 @@ fn main() {
         }
     };
 
-    // Get additional transaction parameters
-    let chain_id = prompt_u64("\nEnter chain ID (1 for mainnet, 11155111 for sepolia): ");
-    let from_nonce = prompt_u64("Enter sender nonce: ");
-    let gas_limit = prompt_u64("Enter gas limit: ");
-    let gas_price = prompt_u128("Enter gas price in gwei: ") * 1_000_000_000;
-    let gas_priority_fee = prompt_u128("Enter priority fee in gwei: ") * 1_000_000_000;
-
     println!("\n=== Executing transaction trace... ===\n");
 
     // Create block environment
-    let block_env = BlockEnv {
-        number: block_details.number,
-        beneficiary: block_details.miner,
-        timestamp: block_details.timestamp,
-        gas_limit: block_details.gas_limit.try_into().unwrap_or(30_000_000u64),
-        basefee: block_details.base_fee_per_gas.try_into().unwrap_or(20_000_000_000u64),
-        difficulty: block_details.difficulty,
-        prevrandao: Some(revm::primitives::B256::from(block_details.difficulty)),
-        blob_excess_gas_and_price: Some(
-            revm::context_interface::block::BlobExcessGasAndPrice::new(1, 1)
-        ),
+    let block_env = match create_block_env_from_block_details(block_details) {
+        Ok(block_env) => block_env,
+        Err(e) => {
+            eprintln!("✗ Invalid block details: {}", e);
+            return;
+        }
     };
 
     // Execute the trace
     match trace_transaction(
-        chain_id,
-        from_address,
-        from_nonce,
-        to_address,
-        calldata,
-        gas_limit,
-        gas_price,
-        gas_priority_fee,
+        &params,
         block_env,
         prestate,
+        TraceTransactionOptions::default(),
     ) {
         Ok(result) => {
             println!("=== Trace Result ===\n");
@@ -121,10 +129,8 @@ fn main() {
 
             println!("Number of Subcalls: {}", result.calls.calls.len());
 
-            if !result.calls.calls.is_empty() {
-                println!("\nSubcalls:");
-                print_subcalls(&result.calls.calls, 1);
-            }
+            println!("\nCall Tree:");
+            print!("{}", DisplayOptions::colored().render(&result.calls));
         }
         Err(e) => {
             eprintln!("✗ Error tracing transaction: {}", e);
@@ -132,22 +138,6 @@ fn main() {
     }
 }
 
-fn print_subcalls(calls: &[revm_tracer::trace::inspector::CallFrame], depth: usize) {
-    let indent = "  ".repeat(depth);
-    for (i, call) in calls.iter().enumerate() {
-        println!("{}{}. {} from {:?} to {:?}", indent, i + 1, call.call_type, call.from, call.to);
-        println!("{}   Gas Used: {}", indent, call.gas_used);
-        if call.error.is_some() {
-            println!("{}   Status: FAILED", indent);
-        } else {
-            println!("{}   Status: SUCCESS", indent);
-        }
-        if !call.calls.is_empty() {
-            print_subcalls(&call.calls, depth + 1);
-        }
-    }
-}
-
 fn prompt_input(prompt: &str) -> String {
     print!("{}", prompt);
     io::stdout().flush().unwrap();
@@ -206,94 +196,3 @@ fn prompt_hex(prompt: &str) -> Bytes {
         }
     }
 }
-
-fn fetch_block_details(rpc_url: &str, block_number: &str) -> Result<BlockDetails, String> {
-    let client = reqwest::blocking::Client::new();
-
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "method": "eth_getBlockByNumber",
-        "params": [block_number, false],
-        "id": 1
-    });
-
-    let response = client
-        .post(rpc_url)
-        .json(&request_body)
-        .send()
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-
-    let response_text = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
-
-    let json_response: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-    if let Some(error) = json_response.get("error") {
-        return Err(format!("RPC error: {}", error));
-    }
-
-    let result = json_response
-        .get("result")
-        .ok_or("No result in response")?;
-
-    serde_json::from_value(result.clone())
-        .map_err(|e| format!("Failed to deserialize block details: {}", e))
-}
-
-fn fetch_prestate(
-    rpc_url: &str,
-    from: &Address,
-    to: &Address,
-    value: &U256,
-    data: &Bytes,
-    block_number: &str,
-) -> Result<HashMap<Address, AccountDetails>, String> {
-    let client = reqwest::blocking::Client::new();
-
-    // Construct the transaction object
-    let tx_object = json!({
-        "from": format!("{:?}", from),
-        "to": format!("{:?}", to),
-        "value": format!("0x{:x}", value),
-        "data": format!("0x{}", hex::encode(data)),
-    });
-
-    println!("{tx_object}");
-
-    // Request with prestateTracer
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "method": "debug_traceCall",
-        "params": [
-            tx_object,
-            block_number,
-            {
-                "tracer": "prestateTracer"
-            }
-        ],
-        "id": 1
-    });
-
-    let response = client
-        .post(rpc_url)
-        .json(&request_body)
-        .send()
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-
-    let response_text = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
-
-    let json_response: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
-
-    if let Some(error) = json_response.get("error") {
-        return Err(format!("RPC error: {}", error));
-    }
-
-    let result = json_response
-        .get("result")
-        .ok_or("No result in response")?;
-
-    // The prestate tracer returns the accounts directly
-    serde_json::from_value(result.clone())
-        .map_err(|e| format!("Failed to deserialize prestate: {}. Response: {}", e, result))
-}
@@ -3,17 +3,16 @@
 /// This example fetches real blockchain data from an RPC node and traces a transaction.
 /// Configure the parameters below before running.
 ///
-/// Usage: cargo run --example rpc_trace
+/// Usage: cargo run --example rpc_trace --features rpc
 
-use revm::primitives::{Address, Bytes, U256, HashMap};
-use revm::context::BlockEnv;
+use revm::primitives::{Address, Bytes, U256};
 use std::str::FromStr;
-use serde_json::json;
 
 use revm_tracer::trace::{
-    database::AccountDetails,
-    trace::trace_transaction,
-    block::BlockDetails,
+    rpc::TraceRpcClient,
+    trace::{trace_transaction, TraceTransactionOptions, TxParams, TxSpec},
+    block::create_block_env_from_block_details,
+    display::DisplayOptions,
 };
 
 // ============================================================================
@@ -93,10 +92,41 @@ fn main() {
     println!("  Gas Price: {} gwei", GAS_PRICE_GWEI);
     println!("  Priority Fee: {} gwei\n", PRIORITY_FEE_GWEI);
 
+    // Convert gas prices from gwei to wei
+    let gas_price = GAS_PRICE_GWEI * 1_000_000_000;
+    let gas_priority_fee = PRIORITY_FEE_GWEI * 1_000_000_000;
+
+    let params = TxParams {
+        chain_id: CHAIN_ID,
+        from: from_address,
+        from_nonce: FROM_NONCE,
+        to: to_address,
+        value,
+        data: calldata,
+        gas_limit: GAS_LIMIT,
+        gas_price,
+        gas_priority_fee,
+        tx_spec: TxSpec::Eip1559,
+        access_list: None,
+        blob_versioned_hashes: Vec::new(),
+        max_fee_per_blob_gas: 0,
+        authorization_list: Vec::new(),
+        deposit: None,
+        spec: None,
+    };
+
     println!("=== Fetching data from RPC... ===\n");
 
+    let client = match TraceRpcClient::new(RPC_URL) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("✗ Failed to create RPC client: {}", e);
+            return;
+        }
+    };
+
     // Fetch block details
-    let block_details = match fetch_block_details(RPC_URL, BLOCK_NUMBER) {
+    let block_details = match client.block_details(BLOCK_NUMBER) {
         Ok(details) => {
             println!("✓ Block details fetched successfully");
             println!("  Block Number: {}", details.number);
@@ -114,14 +144,7 @@ fn main() {
     };
 
     // Fetch prestate using debug_traceCall
-    let prestate = match fetch_prestate(
-        RPC_URL,
-        &from_address,
-        &to_address,
-        &value,
-        &calldata,
-        BLOCK_NUMBER,
-    ) {
+    let prestate = match client.prestate(&params, BLOCK_NUMBER) {
         Ok(state) => {
             println!("✓ Prestate fetched successfully ({} accounts)\n", state.len());
             state
@@ -139,35 +162,20 @@ fn main() {
     println!("=== Executing transaction trace... ===\n");
 
     // Create block environment
-    let block_env = BlockEnv {
-        number: block_details.number,
-        beneficiary: block_details.miner,
-        timestamp: block_details.timestamp,
-        gas_limit: block_details.gas_limit.try_into().unwrap_or(30_000_000u64),
-        basefee: block_details.base_fee_per_gas.try_into().unwrap_or(20_000_000_000u64),
-        difficulty: block_details.difficulty,
-        prevrandao: Some(revm::primitives::B256::from(block_details.difficulty)),
-        blob_excess_gas_and_price: Some(
-            revm::context_interface::block::BlobExcessGasAndPrice::new(1, 1)
-        ),
+    let block_env = match create_block_env_from_block_details(block_details) {
+        Ok(block_env) => block_env,
+        Err(e) => {
+            eprintln!("✗ Invalid block details: {}", e);
+            return;
+        }
     };
 
-    // Convert gas prices from gwei to wei
-    let gas_price = GAS_PRICE_GWEI * 1_000_000_000;
-    let gas_priority_fee = PRIORITY_FEE_GWEI * 1_000_000_000;
-
     // Execute the trace
     match trace_transaction(
-        CHAIN_ID,
-        from_address,
-        FROM_NONCE,
-        to_address,
-        calldata,
-        GAS_LIMIT,
-        gas_price,
-        gas_priority_fee,
+        &params,
         block_env,
         prestate,
+        TraceTransactionOptions::default(),
     ) {
         Ok(result) => {
             println!("✓ Trace completed successfully!\n");
@@ -201,10 +209,8 @@ fn main() {
 
             println!("\nNumber of Subcalls: {}", result.calls.calls.len());
 
-            if !result.calls.calls.is_empty() {
-                println!("\n=== Subcalls ===");
-                print_subcalls(&result.calls.calls, 1);
-            }
+            println!("\n=== Call Tree ===");
+            print!("{}", DisplayOptions::colored().render(&result.calls));
 
             println!("\n✓ Trace analysis complete!");
         }
@@ -218,149 +224,3 @@ fn main() {
         }
     }
 }
-
-fn print_subcalls(calls: &[revm_tracer::trace::inspector::CallFrame], depth: usize) {
-    let indent = "  ".repeat(depth);
-    for (i, call) in calls.iter().enumerate() {
-        let status = if call.error.is_none() { "✓" } else { "✗" };
-        println!("{}{} {}. {} from {:?} to {:?}",
-            indent, status, i + 1, call.call_type, call.from, call.to);
-        println!("{}   Gas Used: {}", indent, call.gas_used);
-
-        if let Some(error) = &call.error {
-            println!("{}   Error: {}", indent, error);
-        }
-
-        if !call.calls.is_empty() {
-            print_subcalls(&call.calls, depth + 1);
-        }
-    }
-}
-
-// ============================================================================
-// RPC HELPER FUNCTIONS
-// ============================================================================
-
-fn fetch_block_details(rpc_url: &str, block_number: &str) -> Result<BlockDetails, String> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "method": "eth_getBlockByNumber",
-        "params": [block_number, false],
-        "id": 1
-    });
-
-    let response = client
-        .post(rpc_url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
-    }
-
-    let response_text = response.text()
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-
-    let json_response: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse JSON: {}. Response: {}", e, response_text))?;
-
-    if let Some(error) = json_response.get("error") {
-        return Err(format!("RPC error: {}", error));
-    }
-
-    let result = json_response
-        .get("result")
-        .ok_or_else(|| format!("No result in response: {}", response_text))?;
-
-    if result.is_null() {
-        return Err(format!("Block not found: {}", block_number));
-    }
-
-    serde_json::from_value(result.clone())
-        .map_err(|e| format!("Failed to deserialize block details: {}. Result: {}", e, result))
-}
-
-fn fetch_prestate(
-    rpc_url: &str,
-    from: &Address,
-    to: &Address,
-    value: &U256,
-    data: &Bytes,
-    block_number: &str,
-) -> Result<HashMap<Address, AccountDetails>, String> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(60))
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    // Construct the transaction object
-    let tx_object = json!({
-        "from": format!("{:?}", from),
-        "to": format!("{:?}", to),
-        "value": format!("0x{:x}", value),
-        "data": format!("0x{}", hex::encode(data)),
-    });
-
-    println!("Debug - Transaction object: {}", tx_object);
-
-    // Request with prestateTracer
-    let request_body = json!({
-        "jsonrpc": "2.0",
-        "method": "debug_traceCall",
-        "params": [
-            tx_object,
-            block_number,
-            {
-                "tracer": "prestateTracer",
-                "stateOverrides": {
-                "0x123c09e092faa42ac4b3322661345ff7956a350e": {
-                    "stateDiff": {
-                        "0x6b0c432833943740bc6e9ef81debbd2891bfc95a9bf62994fbd90cdae4b251f9": "0x0000000000000000000000000000000000000000000000000000000000000001",
-                        "0xbe282059c802d59471d7d8082e29c73d62e56f511b3cafb30ef1cddf3567dbec": "0x0000000000000000000000000000000000000000000000000000000000000001"
-                    }
-                },
-                "0x671376e3434c64b56fd943d5240ba6d1ae24e56a": {
-                    "balance": "0x3635c9adc5dea00000"
-                }
-            }
-            }
-        ],
-        "id": 1
-    });
-
-    let response = client
-        .post(rpc_url)
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .map_err(|e| format!("HTTP request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
-    }
-
-    let response_text = response.text()
-        .map_err(|e| format!("Failed to read response: {}", e))?;
-
-    let json_response: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("Failed to parse JSON: {}. Response: {}", e, response_text))?;
-
-    if let Some(error) = json_response.get("error") {
-        return Err(format!("RPC error: {}. Make sure debug_traceCall is supported.", error));
-    }
-
-    let result = json_response
-        .get("result")
-        .ok_or_else(|| format!("No result in response: {}", response_text))?;
-
-    // The prestate tracer returns the accounts directly
-    serde_json::from_value(result.clone())
-        .map_err(|e| format!("Failed to deserialize prestate: {}. Response: {}", e, result))
-}
@@ -1,12 +1,12 @@
 use revm::primitives::{Address, Bytes, U256};
 use std::str::FromStr;
-use revm::context::BlockEnv;
 use revm::primitives::HashMap;
 
 // Import from the library
 use revm_tracer::trace::{
+    block::BlockEnvBuilder,
     database::AccountDetails,
-    trace::trace_transaction,
+    trace::{trace_transaction, TraceTransactionOptions, TxParams, TxSpec},
 };
 
 fn main() {
@@ -42,18 +42,14 @@ fn main() {
 
 
     // Setup: Create block environment
-    let block_env = BlockEnv {
-        number: U256::from(18_000_000),
-        beneficiary: Address::from_str("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa").unwrap(),
-        timestamp: U256::from(1_700_000_000u64),
-        gas_limit: 30_000_000u64,
-        basefee: 20_000_000_000u64, // 20 gwei
-        difficulty: U256::ZERO,
-        prevrandao: Some(revm::primitives::B256::from([0u8; 32])),
-        blob_excess_gas_and_price: Some(
-            revm::context_interface::block::BlobExcessGasAndPrice::new(1, 1)
-        ),
-    };
+    let block_env = BlockEnvBuilder::new(1)
+        .number(18_000_000)
+        .timestamp(1_700_000_000)
+        .beneficiary(Address::from_str("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa").unwrap())
+        .gas_limit(30_000_000)
+        .basefee(20_000_000_000) // 20 gwei
+        .build()
+        .expect("valid block environment");
 
     // Setup: Transaction parameters
     let chain_id = 1u64; // Ethereum mainnet
@@ -82,17 +78,30 @@ fn main() {
     // Execute the trace
     println!("Executing transaction trace...\n");
 
-    match trace_transaction(
+    let params = TxParams {
         chain_id,
-        from_address,
+        from: from_address,
         from_nonce,
-        to_address,
+        to: to_address,
+        value: U256::ZERO,
         data,
         gas_limit,
         gas_price,
         gas_priority_fee,
+        tx_spec: TxSpec::Eip1559,
+        access_list: None,
+        blob_versioned_hashes: Vec::new(),
+        max_fee_per_blob_gas: 0,
+        authorization_list: Vec::new(),
+        deposit: None,
+        spec: None,
+    };
+
+    match trace_transaction(
+        &params,
         block_env,
         prestate,
+        TraceTransactionOptions::default(),
     ) {
         Ok(result) => {
             println!("=== Trace Result ===\n");
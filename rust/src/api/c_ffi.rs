@@ -0,0 +1,112 @@
+//! C ABI for consumers that do not go through flutter_rust_bridge (Swift, Kotlin, Go, ...)
+//!
+//! Requests and results are exchanged as NUL-terminated JSON strings. Strings returned by
+//! this module are owned by the library and must be released with `revm_tracer_free_string`.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+
+use serde::Deserialize;
+
+use crate::api::tracer::format_and_trace_transaction;
+use crate::trace::error::TraceErrorCode;
+
+/// Trace request accepted by `revm_tracer_trace`, mirroring the arguments of
+/// `format_and_trace_transaction`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceRequest {
+    chain_id: u64,
+    from: String,
+    from_nonce: u64,
+    to: String,
+    data: String,
+    gas_limit: u64,
+    gas_price: u128,
+    gas_priority_fee: u128,
+    /// Block details object, in the `latestBlockEnv` format
+    latest_block_env: serde_json::Value,
+    /// Prestate tracer result object
+    prestate_tracer_result: serde_json::Value,
+    #[serde(default)]
+    is_op_stack: bool,
+    #[serde(default)]
+    access_list: Option<serde_json::Value>,
+}
+
+/// Traces a transaction described by a JSON request and returns the result as JSON
+///
+/// The request holds the arguments of `format_and_trace_transaction` in camelCase, with
+/// `latestBlockEnv`, `prestateTracerResult` and `accessList` given as JSON values rather
+/// than strings. The result has the same format as `format_and_trace_transaction`,
+/// including the `{"error": true, ...}` object on failure; a malformed request or a panic
+/// is reported the same way.
+///
+/// # Safety
+///
+/// `json_in` must be null or point to a NUL-terminated string that stays valid for the
+/// duration of the call. The returned pointer is never null and must be released with
+/// `revm_tracer_free_string`.
+#[flutter_rust_bridge::frb(ignore)]
+#[no_mangle]
+pub unsafe extern "C" fn revm_tracer_trace(json_in: *const c_char) -> *mut c_char {
+    let result = if json_in.is_null() {
+        error_json(TraceErrorCode::InvalidRequest, "null request")
+    } else {
+        let request = CStr::from_ptr(json_in);
+        match panic::catch_unwind(AssertUnwindSafe(|| trace_request(request))) {
+            Ok(result) => result,
+            Err(_) => error_json(TraceErrorCode::Internal, "tracer panicked"),
+        }
+    };
+    // JSON output never contains interior NUL bytes
+    CString::new(result).unwrap_or_default().into_raw()
+}
+
+/// Releases a string returned by `revm_tracer_trace`
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by this library that has not been freed yet.
+#[flutter_rust_bridge::frb(ignore)]
+#[no_mangle]
+pub unsafe extern "C" fn revm_tracer_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+fn trace_request(request: &CStr) -> String {
+    let request = match request.to_str() {
+        Ok(request) => request,
+        Err(e) => return error_json(TraceErrorCode::InvalidRequest, &format!("request is not valid UTF-8: {}", e)),
+    };
+    let request: TraceRequest = match serde_json::from_str(request) {
+        Ok(request) => request,
+        Err(e) => return error_json(TraceErrorCode::InvalidRequest, &format!("invalid request: {}", e)),
+    };
+
+    format_and_trace_transaction(
+        request.chain_id,
+        &request.from,
+        request.from_nonce,
+        &request.to,
+        &request.data,
+        request.gas_limit,
+        request.gas_price,
+        request.gas_priority_fee,
+        &request.latest_block_env.to_string(),
+        &request.prestate_tracer_result.to_string(),
+        request.is_op_stack,
+        request.access_list.map(|access_list| access_list.to_string()),
+    )
+}
+
+fn error_json(code: TraceErrorCode, message: &str) -> String {
+    serde_json::json!({
+        "error": true,
+        "code": code,
+        "message": message,
+        "type": "InvalidRequest"
+    }).to_string()
+}
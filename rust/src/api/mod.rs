@@ -1 +1,3 @@
 pub mod tracer;
+#[cfg(feature = "c-ffi")]
+pub mod c_ffi;
@@ -1,17 +1,190 @@
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 
 use crate::trace::{
-    block::{create_block_env_from_block_details, BlockDetails},
+    binary::to_message_pack,
+    block::BlockDetails,
+    budget::OutputLimits,
+    chain::{trace, ChainKind, ChainTraceResult, TraceRequest},
     database::AccountDetails,
-    trace::{trace_transaction, trace_transaction_op},
+    flat::CallLayout,
+    quantity::{with_quantity_encoding, QuantityEncoding},
+    trace::{TraceTransactionResult, TxParams, TxSpec},
     error::TraceError,
+    inspector::{CallFrame, CallTracerConfig, CancellationHandle, LogEntry, ProgressCallback, TraceProgress},
 };
-use revm::{context::BlockEnv, primitives::{Bytes, HashMap, Address}};
+use crate::frb_generated::StreamSink;
+use revm::{
+    context::result::ExecutionResult,
+    context_interface::transaction::AccessList,
+    primitives::{Bytes, HashMap, Address, U256},
+};
+
+/// A log entry, with hex-encoded fields
+pub struct FfiLogEntry {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+/// A call frame of the trace, with hex-encoded addresses, values and data
+pub struct FfiCallFrame {
+    pub call_type: String,
+    pub from: String,
+    pub to: Option<String>,
+    pub value: String,
+    pub gas: u64,
+    pub gas_used: u64,
+    pub input: String,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub revert_reason: Option<String>,
+    pub logs: Vec<FfiLogEntry>,
+    pub calls: Vec<FfiCallFrame>,
+}
+
+/// Result of `trace_transaction_typed`
+pub struct FfiTraceResult {
+    /// `success`, `revert` or `halt`
+    pub status: String,
+    pub gas_used: u64,
+    /// Return data on success, revert data on revert, empty on halt
+    pub output: String,
+    /// Halt reason when the transaction halted
+    pub halt_reason: Option<String>,
+    pub blob_gas_used: u64,
+    /// Fee paid in wei, as a hex string
+    pub fee_paid: String,
+    pub calls: FfiCallFrame,
+    /// Remaining fields of the trace result (state diff, balance changes, ...) as JSON
+    pub details: String,
+}
+
+/// Error returned by `trace_transaction_typed`
+pub struct FfiTraceError {
+    /// Name of the `TraceError` variant
+    pub kind: String,
+    pub message: String,
+}
+
+impl From<TraceError> for FfiTraceError {
+    fn from(error: TraceError) -> Self {
+        let kind = match &error {
+            TraceError::TxEnvBuild(_) => "TxEnvBuild",
+            #[cfg(feature = "op")]
+            TraceError::OpTxBuild(_) => "OpTxBuild",
+            TraceError::Execution(_) => "Execution",
+            TraceError::BlockConversion(_) => "BlockConversion",
+            TraceError::InvalidAddress(_) => "InvalidAddress",
+            TraceError::InvalidHexData(_) => "InvalidHexData",
+            TraceError::JsonParse(_) => "JsonParse",
+            TraceError::MessagePack(_) => "MessagePack",
+            TraceError::NoTraceResult => "NoTraceResult",
+            TraceError::InvalidParameter(_) => "InvalidParameter",
+            TraceError::Io(_) => "Io",
+            TraceError::FeeValidation(_) => "FeeValidation",
+            TraceError::Database(_) => "Database",
+            #[cfg(feature = "rpc")]
+            TraceError::Rpc(_) => "Rpc",
+            TraceError::Cancelled(_) => "Cancelled",
+            TraceError::IncompletePrestate(_) => "IncompletePrestate",
+        };
+        Self {
+            kind: kind.to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<LogEntry> for FfiLogEntry {
+    fn from(log: LogEntry) -> Self {
+        Self {
+            address: log.address.to_string(),
+            topics: log.topics.iter().map(ToString::to_string).collect(),
+            data: log.data.to_string(),
+        }
+    }
+}
+
+impl From<CallFrame> for FfiCallFrame {
+    fn from(frame: CallFrame) -> Self {
+        Self {
+            call_type: frame.call_type,
+            from: frame.from.to_string(),
+            to: frame.to.map(|to| to.to_string()),
+            value: format!("{:#x}", frame.value),
+            gas: frame.gas.saturating_to(),
+            gas_used: frame.gas_used.saturating_to(),
+            input: frame.input.to_string(),
+            output: frame.output.map(|output| output.to_string()),
+            error: frame.error,
+            revert_reason: frame.revert_reason,
+            logs: frame.logs.into_iter().map(FfiLogEntry::from).collect(),
+            calls: frame.calls.into_iter().map(FfiCallFrame::from).collect(),
+        }
+    }
+}
+
+impl FfiCallFrame {
+    /// Number of frames in the tree rooted at this frame
+    fn frame_count(&self) -> u64 {
+        1 + self.calls.iter().map(FfiCallFrame::frame_count).sum::<u64>()
+    }
+}
+
+impl FfiTraceResult {
+    fn from_result<H: std::fmt::Debug + serde::Serialize>(result: TraceTransactionResult<H>) -> Result<Self, TraceError> {
+        let (status, output, halt_reason) = match &result.execution_result {
+            ExecutionResult::Success { output, .. } => ("success", output.data().clone(), None),
+            ExecutionResult::Revert { output, .. } => ("revert", output.clone(), None),
+            ExecutionResult::Halt { reason, .. } => ("halt", Bytes::new(), Some(format!("{:?}", reason))),
+        };
+        let gas_used = result.execution_result.gas_used();
+        let blob_gas_used = result.blob_gas_used;
+        let fee_paid = format!("{:#x}", result.gas.fee_paid);
+
+        // Everything but the call tree, which is mirrored in `calls`
+        let mut details = serde_json::to_value(&result)?;
+        if let Some(details) = details.as_object_mut() {
+            details.remove("calls");
+        }
+
+        Ok(Self {
+            status: status.to_string(),
+            gas_used,
+            output: output.to_string(),
+            halt_reason,
+            blob_gas_used,
+            fee_paid,
+            calls: FfiCallFrame::from(result.calls),
+            details: details.to_string(),
+        })
+    }
+}
+
+/// Update sent by `trace_transaction_stream`: progress while tracing, then a final
+/// update carrying either the result or the error
+pub struct FfiTraceUpdate {
+    /// Frames completed so far
+    pub frames: u64,
+    /// Execution gas consumed so far
+    pub gas_used: u64,
+    pub result: Option<FfiTraceResult>,
+    pub error: Option<FfiTraceError>,
+}
+
+/// Progress is streamed once every this many completed frames
+const PROGRESS_INTERVAL_FRAMES: u64 = 100;
+
+/// Progress is also streamed once every this much gas, for long frames without calls
+const PROGRESS_INTERVAL_GAS: u64 = 1_000_000;
 
 /// Formats and traces a transaction, returning the result as a JSON string
 ///
 /// This is the main entry point for Flutter/Dart via flutter_rust_bridge.
 /// All errors are converted to JSON error responses for graceful handling on the client side.
+/// The transaction transfers no value and carries no blobs; `trace_with_chain` takes a
+/// full `TxParams` for those.
 ///
 /// # Arguments
 ///
@@ -21,17 +194,19 @@ use revm::{context::BlockEnv, primitives::{Bytes, HashMap, Address}};
 /// * `to` - Recipient address as hex string
 /// * `data` - Transaction data as hex string
 /// * `gas_limit` - Gas limit
-/// * `gas_price` - Gas price in wei
-/// * `gas_priority_fee` - Priority fee in wei
+/// * `gas_price` - Gas price in wei, the `maxFeePerGas` of an EIP-1559 transaction
+/// * `gas_priority_fee` - Priority fee in wei, its `maxPriorityFeePerGas`
 /// * `latest_block_env` - Block environment as JSON string
 /// * `prestate_tracer_result` - Prestate as JSON string
 /// * `is_op_stack` - If true, use Optimism tracer; if false, use standard Ethereum tracer
+/// * `access_list` - Optional EIP-2930 access list as JSON string
 ///
 /// # Returns
 ///
 /// JSON string containing either:
 /// - Success: The trace result
-/// - Error: An error object with details
+/// - Error: `{"error": true, "code": ..., "message": ..., "type": ...}`, where `code` is
+///   a `TraceErrorCode` such as `INVALID_ADDRESS` or `EXECUTION_FAILED`
 #[flutter_rust_bridge::frb(sync)]
 pub fn format_and_trace_transaction(
     chain_id: u64,
@@ -45,6 +220,7 @@ pub fn format_and_trace_transaction(
     latest_block_env: &str,
     prestate_tracer_result: &str,
     is_op_stack: bool,
+    access_list: Option<String>,
 ) -> String {
     match format_and_trace_transaction_internal(
         chain_id,
@@ -58,16 +234,222 @@ pub fn format_and_trace_transaction(
         latest_block_env,
         prestate_tracer_result,
         is_op_stack,
+        access_list.as_deref(),
     ) {
         Ok(result) => result,
-        Err(e) => {
-            // Return error as JSON for client-side handling
-            serde_json::json!({
-                "error": true,
-                "message": e.to_string(),
-                "type": format!("{:?}", e)
-            }).to_string()
-        }
+        Err(e) => error_json(&e),
+    }
+}
+
+/// Traces a transaction like `format_and_trace_transaction`, returning the result as
+/// MessagePack instead of a JSON string
+///
+/// The result has the same shape as the JSON one, see `trace::binary`; it is smaller
+/// and much cheaper to produce and parse for large traces. Errors are returned as
+/// `FfiTraceError` and surface as exceptions on the Dart side.
+#[flutter_rust_bridge::frb(sync)]
+pub fn format_and_trace_transaction_binary(
+    chain_id: u64,
+    from: &str,
+    from_nonce: u64,
+    to: &str,
+    data: &str,
+    gas_limit: u64,
+    gas_price: u128,
+    gas_priority_fee: u128,
+    latest_block_env: &str,
+    prestate_tracer_result: &str,
+    is_op_stack: bool,
+    access_list: Option<String>,
+) -> Result<Vec<u8>, FfiTraceError> {
+    let result = run_trace(
+        chain_id,
+        from,
+        from_nonce,
+        to,
+        data,
+        gas_limit,
+        gas_price,
+        gas_priority_fee,
+        latest_block_env,
+        prestate_tracer_result,
+        is_op_stack,
+        access_list.as_deref(),
+        CallTracerConfig::default(),
+    )?;
+    Ok(to_message_pack(&result)?)
+}
+
+/// Traces a transaction on the chain described by `chain_kind`, returning the result as
+/// a JSON string
+///
+/// Unlike `format_and_trace_transaction`, chain-specific settings travel in `chain_kind`
+/// rather than in the signature, so supporting a new chain does not change this function.
+///
+/// # Arguments
+///
+/// * `chain_kind` - A `ChainKind` as JSON, e.g. `{"kind": "ethereum"}`,
+///   `{"kind": "opStack"}` or `{"kind": "arbitrumNitro", "l1PricePerUnit": "0x..."}`
+/// * `request` - A `TraceRequest` as JSON: `tx` in the `TxParams` format,
+///   `latestBlockEnv`, `prestateTracerResult` and optionally `stateOverrides`,
+///   `tracerConfig` and `abis`, e.g. `[{"address": "0x...", "abi": [...]}]`, to decode
+///   the call tree and the top-level call's return values into `decodedOutput`
+///
+/// # Returns
+///
+/// JSON string containing either the trace result or an error object, in the same
+/// format as `format_and_trace_transaction`
+#[flutter_rust_bridge::frb(sync)]
+pub fn trace_with_chain(chain_kind: &str, request: &str) -> String {
+    trace_with_chain_internal(chain_kind, request)
+        .unwrap_or_else(|e| error_json(&e))
+}
+
+/// Error object returned by the JSON entry points for client-side handling
+///
+/// `code` is a stable `TraceErrorCode` to branch on; `type` is the debug rendering of the
+/// error, kept for older clients.
+fn error_json(error: &TraceError) -> String {
+    serde_json::json!({
+        "error": true,
+        "code": error.code(),
+        "message": error.to_string(),
+        "type": format!("{:?}", error)
+    }).to_string()
+}
+
+/// Traces a transaction, returning typed results instead of a JSON string
+///
+/// Takes the same arguments as `format_and_trace_transaction`. Errors are returned as
+/// `FfiTraceError` and surface as exceptions on the Dart side.
+#[flutter_rust_bridge::frb(sync)]
+pub fn trace_transaction_typed(
+    chain_id: u64,
+    from: &str,
+    from_nonce: u64,
+    to: &str,
+    data: &str,
+    gas_limit: u64,
+    gas_price: u128,
+    gas_priority_fee: u128,
+    latest_block_env: &str,
+    prestate_tracer_result: &str,
+    is_op_stack: bool,
+    access_list: Option<String>,
+) -> Result<FfiTraceResult, FfiTraceError> {
+    let result = run_trace(
+        chain_id,
+        from,
+        from_nonce,
+        to,
+        data,
+        gas_limit,
+        gas_price,
+        gas_priority_fee,
+        latest_block_env,
+        prestate_tracer_result,
+        is_op_stack,
+        access_list.as_deref(),
+        CallTracerConfig::default(),
+    )?;
+    let result = match result {
+        ChainTraceResult::Ethereum(result) => FfiTraceResult::from_result(result)?,
+        ChainTraceResult::OpStack(result) => FfiTraceResult::from_result(result)?,
+    };
+    Ok(result)
+}
+
+/// Traces a transaction off the calling isolate, streaming progress as it goes
+///
+/// Takes the same arguments as `format_and_trace_transaction`. Unlike the other entry
+/// points it is not synchronous: the trace runs on a worker thread, so long traces do
+/// not block Dart. Progress updates are sent every 100 completed frames or million gas;
+/// the last update holds the result or the error, after which the stream closes.
+/// Cancelling the Dart subscription aborts the trace at the next progress update.
+pub fn trace_transaction_stream(
+    sink: StreamSink<FfiTraceUpdate>,
+    chain_id: u64,
+    from: &str,
+    from_nonce: u64,
+    to: &str,
+    data: &str,
+    gas_limit: u64,
+    gas_price: u128,
+    gas_priority_fee: u128,
+    latest_block_env: &str,
+    prestate_tracer_result: &str,
+    is_op_stack: bool,
+    access_list: Option<String>,
+) {
+    // `StreamSink` is not `Clone`: the progress callback and the final update share it
+    let sink = Arc::new(Mutex::new(sink));
+    let progress_sink = Arc::clone(&sink);
+    let cancellation = CancellationHandle::new();
+    let progress_cancellation = cancellation.clone();
+    let last_reported = Mutex::new(TraceProgress::default());
+    let tracer_config = CallTracerConfig {
+        progress: Some(ProgressCallback::new(move |progress| {
+            let Ok(mut last) = last_reported.lock() else {
+                return;
+            };
+            if progress.frames >= last.frames + PROGRESS_INTERVAL_FRAMES
+                || progress.gas_used >= last.gas_used + PROGRESS_INTERVAL_GAS
+            {
+                *last = progress;
+                let update = FfiTraceUpdate {
+                    frames: progress.frames,
+                    gas_used: progress.gas_used,
+                    result: None,
+                    error: None,
+                };
+                // Nobody listens anymore once the stream is closed: stop tracing
+                let closed = progress_sink.lock().map_or(true, |sink| sink.add(update).is_err());
+                if closed {
+                    progress_cancellation.cancel();
+                }
+            }
+        })),
+        cancellation: Some(cancellation),
+        ..CallTracerConfig::default()
+    };
+
+    let result = run_trace(
+        chain_id,
+        from,
+        from_nonce,
+        to,
+        data,
+        gas_limit,
+        gas_price,
+        gas_priority_fee,
+        latest_block_env,
+        prestate_tracer_result,
+        is_op_stack,
+        access_list.as_deref(),
+        tracer_config,
+    )
+    .and_then(|result| match result {
+        ChainTraceResult::Ethereum(result) => FfiTraceResult::from_result(result),
+        ChainTraceResult::OpStack(result) => FfiTraceResult::from_result(result),
+    });
+
+    let update = match result {
+        Ok(result) => FfiTraceUpdate {
+            frames: result.calls.frame_count(),
+            gas_used: result.gas_used,
+            result: Some(result),
+            error: None,
+        },
+        Err(error) => FfiTraceUpdate {
+            frames: 0,
+            gas_used: 0,
+            result: None,
+            error: Some(FfiTraceError::from(error)),
+        },
+    };
+    let guard = sink.lock();
+    if let Ok(sink) = guard {
+        let _ = sink.add(update);
     }
 }
 
@@ -84,10 +466,52 @@ fn format_and_trace_transaction_internal(
     latest_block_env: &str,
     prestate_tracer_result: &str,
     is_op_stack: bool,
+    access_list: Option<&str>,
 ) -> Result<String, TraceError> {
+    let result = run_trace(
+        chain_id,
+        from,
+        from_nonce,
+        to,
+        data,
+        gas_limit,
+        gas_price,
+        gas_priority_fee,
+        latest_block_env,
+        prestate_tracer_result,
+        is_op_stack,
+        access_list,
+        CallTracerConfig::default(),
+    )?;
+    Ok(serde_json::to_string_pretty(&result)?)
+}
+
+fn trace_with_chain_internal(chain_kind: &str, request: &str) -> Result<String, TraceError> {
+    let chain_kind: ChainKind = serde_json::from_str(chain_kind)?;
+    let request: TraceRequest = serde_json::from_str(request)?;
+    let quantity_encoding = request.quantity_encoding;
+    let result = trace(&chain_kind, request)?;
+    Ok(with_quantity_encoding(quantity_encoding, || serde_json::to_string_pretty(&result))?)
+}
+
+/// Parses the FFI arguments and traces the transaction on the selected chain type
+fn run_trace(
+    chain_id: u64,
+    from: &str,
+    from_nonce: u64,
+    to: &str,
+    data: &str,
+    gas_limit: u64,
+    gas_price: u128,
+    gas_priority_fee: u128,
+    latest_block_env: &str,
+    prestate_tracer_result: &str,
+    is_op_stack: bool,
+    access_list: Option<&str>,
+    tracer_config: CallTracerConfig,
+) -> Result<ChainTraceResult, TraceError> {
     // Parse block details from JSON
-    let latest_block: BlockDetails = serde_json::from_str(latest_block_env)?;
-    let latest_block_env: BlockEnv = create_block_env_from_block_details(latest_block)?;
+    let latest_block_env: BlockDetails = serde_json::from_str(latest_block_env)?;
 
     // Parse prestate from JSON
     let prestate_tracer_result: HashMap<Address, AccountDetails> =
@@ -103,40 +527,47 @@ fn format_and_trace_transaction_internal(
     let data_bytes = Bytes::from_str(data)
         .map_err(|_| TraceError::InvalidHexData(data.to_string()))?;
 
-    // Execute trace based on chain type
-    let json = if is_op_stack {
-        // Use Optimism tracer for OP Stack chains
-        let result = trace_transaction_op(
-            chain_id,
-            from_address,
-            from_nonce,
-            to_address,
-            data_bytes,
-            gas_limit,
-            gas_price,
-            gas_priority_fee,
-            latest_block_env,
-            prestate_tracer_result,
-        )?;
-        serde_json::to_string_pretty(&result)?
-    } else {
-        // Use standard Ethereum tracer
-        let result = trace_transaction(
+    // Parse access list from JSON
+    let access_list: Option<AccessList> = access_list
+        .map(serde_json::from_str)
+        .transpose()?;
+
+    let request = TraceRequest {
+        tx: TxParams {
             chain_id,
-            from_address,
+            from: from_address,
             from_nonce,
-            to_address,
-            data_bytes,
+            to: to_address,
+            value: U256::ZERO,
+            data: data_bytes,
             gas_limit,
             gas_price,
             gas_priority_fee,
-            latest_block_env,
-            prestate_tracer_result,
-        )?;
-        serde_json::to_string_pretty(&result)?
+            tx_spec: TxSpec::Eip1559,
+            access_list,
+            blob_versioned_hashes: Vec::new(),
+            max_fee_per_blob_gas: 0,
+            authorization_list: Vec::new(),
+            deposit: None,
+            spec: None,
+        },
+        latest_block_env,
+        prestate_tracer_result,
+        state_overrides: HashMap::default(),
+        tracer_config,
+        output_limits: OutputLimits::default(),
+        call_layout: CallLayout::default(),
+        quantity_encoding: QuantityEncoding::default(),
+        abis: Vec::new(),
     };
 
-    Ok(json)
+    // OP Stack chains resolve their upgrade from the block timestamp
+    let chain_kind = if is_op_stack {
+        ChainKind::OpStack { spec: None }
+    } else {
+        ChainKind::Ethereum
+    };
+    trace(&chain_kind, request)
 }
 
 #[flutter_rust_bridge::frb(init)]
@@ -0,0 +1,266 @@
+//! `revm-tracer` command line tool: traces transactions, calls and blocks against a
+//! JSON-RPC node and prints the result as JSON or as a call tree
+
+use std::fmt::Debug;
+use std::io::IsTerminal;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use serde_json::json;
+use revm::context::result::HaltReason;
+use revm::primitives::{Address, Bytes, B256, U256};
+
+use revm_tracer::trace::{
+    block::{create_block_env_from_block_details, spec_for_block},
+    display::{render_trace_result, DisplayOptions},
+    error::TraceError,
+    estimate::{estimate_gas, GasEstimate},
+    fork::ForkDatabase,
+    inspector::CallTracerConfig,
+    rpc::{trace_call_auto, trace_transaction_by_hash, CallParams, PrestateSource, TraceRpcClient},
+    trace::{execute_and_commit, TraceTransactionOptions, TraceTransactionResult, TxSpec},
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "revm-tracer", version, about = "Trace EVM transactions locally with revm")]
+struct Cli {
+    /// JSON-RPC endpoint to read transactions, blocks and state from
+    #[arg(long, global = true, env = "REVM_TRACER_RPC_URL", default_value = "http://localhost:8545")]
+    rpc: String,
+
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t = Format::Pretty)]
+    format: Format,
+
+    /// Disable colors in pretty output; colors are only used when writing to a terminal
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// The trace result as pretty-printed JSON
+    Json,
+    /// A call tree with one line per frame
+    Pretty,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Replay a mined transaction
+    Trace {
+        /// Hash of the transaction
+        #[arg(long)]
+        tx: B256,
+        /// Where the prestate comes from
+        #[arg(long, value_enum, default_value_t = Prestate::Remote)]
+        prestate: Prestate,
+    },
+    /// Trace a call on top of a block, resolving chain id and nonce from the node
+    Call(CallArgs),
+    /// Replay every transaction of a block in order
+    Block {
+        /// Block number, decimal or hex, or a tag such as `latest`
+        block: String,
+    },
+    /// Find the lowest gas limit at which a call succeeds
+    Estimate(CallArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Prestate {
+    /// `debug_traceTransaction` with the `prestateTracer`; needs the `debug` namespace
+    Remote,
+    /// Plain `eth_*` reads of the previous block's state
+    Lazy,
+    /// Like `lazy`, after replaying the transactions preceding it in its block
+    Replay,
+}
+
+impl From<Prestate> for PrestateSource {
+    fn from(prestate: Prestate) -> Self {
+        match prestate {
+            Prestate::Remote => PrestateSource::RemoteTracer,
+            Prestate::Lazy => PrestateSource::LazyDatabase,
+            Prestate::Replay => PrestateSource::ReplayBlock,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+struct CallArgs {
+    #[arg(long)]
+    from: Address,
+    #[arg(long)]
+    to: Address,
+    /// Calldata as hex
+    #[arg(long, default_value = "0x")]
+    data: Bytes,
+    /// Value in wei, decimal or hex
+    #[arg(long, default_value = "0")]
+    value: U256,
+    /// Gas limit; the search cap for `estimate`
+    #[arg(long, default_value_t = 30_000_000)]
+    gas: u64,
+    /// Gas price in wei; defaults to the block's base fee
+    #[arg(long)]
+    gas_price: Option<u128>,
+    /// Block number, decimal or hex, or a tag such as `latest`
+    #[arg(long, default_value = "latest")]
+    block: String,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let display = DisplayOptions {
+        colors: !cli.no_color && std::io::stdout().is_terminal(),
+        ..DisplayOptions::default()
+    };
+
+    let output = match &cli.command {
+        Command::Trace { tx, prestate } => trace_transaction_by_hash(&cli.rpc, *tx, (*prestate).into(), TraceTransactionOptions::default())
+            .map(|result| render_trace(&result, cli.format, &display)),
+        Command::Call(call) => run_call(&cli.rpc, call).map(|result| render_trace(&result, cli.format, &display)),
+        Command::Block { block } => run_block(&cli.rpc, block, cli.format, &display),
+        Command::Estimate(call) => run_estimate(&cli.rpc, call).map(|estimate| render_estimate(&estimate, cli.format)),
+    };
+
+    match output {
+        Ok(output) => {
+            print!("{}", output);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_call(rpc_url: &str, call: &CallArgs) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let block = block_tag(&call.block);
+    let params = call_params(&client, call, &block)?;
+    trace_call_auto(rpc_url, params, &block, TraceTransactionOptions::default())
+}
+
+fn run_estimate(rpc_url: &str, call: &CallArgs) -> Result<GasEstimate, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let block_details = client.block_details(&block_tag(&call.block))?;
+    // Pin the block so the prestate and the environment come from the same one
+    let block = format!("{:#x}", block_details.number);
+    let params = client.resolve_tx_params(call_params(&client, call, &block)?, &block)?;
+    let prestate = client.prestate(&params, &block)?;
+    estimate_gas(&params, create_block_env_from_block_details(block_details)?, prestate)
+}
+
+/// Traces every transaction of `block` on a fork of its parent, committing each one's
+/// changes before the next. Contract creations are executed but not traced.
+fn run_block(rpc_url: &str, block: &str, format: Format, display: &DisplayOptions) -> Result<String, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let block_details = client.block_details(&block_tag(block))?;
+    let number: u64 = block_details.number.saturating_to();
+    let chain_id = client.chain_id()?;
+    let transactions = client.block_transactions(&format!("{:#x}", number))?;
+    let block_env = create_block_env_from_block_details(block_details)?;
+    let spec = spec_for_block(chain_id, number, block_env.timestamp.saturating_to());
+
+    let mut fork = ForkDatabase::with_client(client, number.saturating_sub(1));
+    let mut traces = Vec::with_capacity(transactions.len());
+    for transaction in &transactions {
+        let result = match transaction.to {
+            Some(_) => {
+                let mut params = transaction.to_tx_params(chain_id)?;
+                params.spec = Some(spec);
+                let result = fork.trace(&params, block_env.clone(), &CallTracerConfig::default())?;
+                fork.commit_state(&result.state_diff);
+                Some(result)
+            }
+            None => {
+                execute_and_commit(transaction.to_tx_env()?, &block_env, &mut fork, Some(spec))?;
+                None
+            }
+        };
+        traces.push((transaction.hash, result));
+    }
+
+    Ok(match format {
+        Format::Json => {
+            let traces: Vec<_> = traces
+                .iter()
+                .map(|(hash, result)| json!({ "txHash": hash, "result": result }))
+                .collect();
+            to_json(&traces)
+        }
+        Format::Pretty => {
+            let mut out = String::new();
+            for (hash, result) in &traces {
+                out.push_str(&format!("{}\n", hash));
+                match result {
+                    Some(result) => out.push_str(&render_trace(result, format, display)),
+                    None => out.push_str("contract creation, not traced\n"),
+                }
+                out.push('\n');
+            }
+            out
+        }
+    })
+}
+
+fn call_params(client: &TraceRpcClient, call: &CallArgs, block: &str) -> Result<CallParams, TraceError> {
+    let gas_price = match call.gas_price {
+        Some(gas_price) => gas_price,
+        None => client.block_details(block)?.base_fee_per_gas.saturating_to(),
+    };
+    Ok(CallParams {
+        from: call.from,
+        to: call.to,
+        value: call.value,
+        data: call.data.clone(),
+        gas_limit: call.gas,
+        gas_price,
+        gas_priority_fee: 0,
+        tx_spec: TxSpec::Legacy,
+        access_list: None,
+    })
+}
+
+/// Block numbers are given to the node as hex; tags and hex numbers pass through
+fn block_tag(block: &str) -> String {
+    match block.parse::<u64>() {
+        Ok(number) => format!("{:#x}", number),
+        Err(_) => block.to_string(),
+    }
+}
+
+fn render_trace<H: Debug + Serialize>(
+    result: &TraceTransactionResult<H>,
+    format: Format,
+    display: &DisplayOptions,
+) -> String {
+    match format {
+        Format::Json => to_json(result),
+        Format::Pretty => render_trace_result(result, display),
+    }
+}
+
+fn render_estimate(estimate: &GasEstimate, format: Format) -> String {
+    match format {
+        Format::Json => to_json(estimate),
+        Format::Pretty if estimate.reverted => {
+            let reason = estimate.revert.as_ref().map(ToString::to_string).unwrap_or_default();
+            format!("fails at the cap of {} gas, using {} {}\n", estimate.gas_limit, estimate.gas_used, reason)
+        }
+        Format::Pretty => format!("gas limit {}, gas used {}\n", estimate.gas_limit, estimate.gas_used),
+    }
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    let mut json = serde_json::to_string_pretty(value).unwrap_or_else(|e| json!({ "error": e.to_string() }).to_string());
+    json.push('\n');
+    json
+}
@@ -37,7 +37,7 @@ flutter_rust_bridge::frb_generated_boilerplate!(
     default_rust_auto_opaque = RustAutoOpaqueMoi,
 );
 pub(crate) const FLUTTER_RUST_BRIDGE_CODEGEN_VERSION: &str = "2.11.1";
-pub(crate) const FLUTTER_RUST_BRIDGE_CODEGEN_CONTENT_HASH: i32 = -357441788;
+pub(crate) const FLUTTER_RUST_BRIDGE_CODEGEN_CONTENT_HASH: i32 = -1203476562;
 
 // Section: executor
 
@@ -77,6 +77,7 @@ fn wire__crate__api__tracer__format_and_trace_transaction_impl(
             let api_latest_block_env = <String>::sse_decode(&mut deserializer);
             let api_prestate_tracer_result = <String>::sse_decode(&mut deserializer);
             let api_is_op_stack = <bool>::sse_decode(&mut deserializer);
+            let api_access_list = <Option<String>>::sse_decode(&mut deserializer);
             deserializer.end();
             transform_result_sse::<_, ()>((move || {
                 let output_ok =
@@ -92,12 +93,67 @@ fn wire__crate__api__tracer__format_and_trace_transaction_impl(
                         &api_latest_block_env,
                         &api_prestate_tracer_result,
                         api_is_op_stack,
+                        api_access_list,
                     ))?;
                 Ok(output_ok)
             })())
         },
     )
 }
+fn wire__crate__api__tracer__format_and_trace_transaction_binary_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "format_and_trace_transaction_binary",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_chain_id = <u64>::sse_decode(&mut deserializer);
+            let api_from = <String>::sse_decode(&mut deserializer);
+            let api_from_nonce = <u64>::sse_decode(&mut deserializer);
+            let api_to = <String>::sse_decode(&mut deserializer);
+            let api_data = <String>::sse_decode(&mut deserializer);
+            let api_gas_limit = <u64>::sse_decode(&mut deserializer);
+            let api_gas_price = <u128>::sse_decode(&mut deserializer);
+            let api_gas_priority_fee = <u128>::sse_decode(&mut deserializer);
+            let api_latest_block_env = <String>::sse_decode(&mut deserializer);
+            let api_prestate_tracer_result = <String>::sse_decode(&mut deserializer);
+            let api_is_op_stack = <bool>::sse_decode(&mut deserializer);
+            let api_access_list = <Option<String>>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, crate::api::tracer::FfiTraceError>((move || {
+                let output_ok = crate::api::tracer::format_and_trace_transaction_binary(
+                    api_chain_id,
+                    &api_from,
+                    api_from_nonce,
+                    &api_to,
+                    &api_data,
+                    api_gas_limit,
+                    api_gas_price,
+                    api_gas_priority_fee,
+                    &api_latest_block_env,
+                    &api_prestate_tracer_result,
+                    api_is_op_stack,
+                    api_access_list,
+                )?;
+                Ok(output_ok)
+            })())
+        },
+    )
+}
 fn wire__crate__api__tracer__init_app_impl(
     port_: flutter_rust_bridge::for_generated::MessagePort,
     ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
@@ -132,9 +188,171 @@ fn wire__crate__api__tracer__init_app_impl(
         },
     )
 }
+fn wire__crate__api__tracer__trace_transaction_stream_impl(
+    port_: flutter_rust_bridge::for_generated::MessagePort,
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_normal::<flutter_rust_bridge::for_generated::SseCodec, _, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "trace_transaction_stream",
+            port: Some(port_),
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Normal,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_sink = <StreamSink<
+                crate::api::tracer::FfiTraceUpdate,
+                flutter_rust_bridge::for_generated::SseCodec,
+            >>::sse_decode(&mut deserializer);
+            let api_chain_id = <u64>::sse_decode(&mut deserializer);
+            let api_from = <String>::sse_decode(&mut deserializer);
+            let api_from_nonce = <u64>::sse_decode(&mut deserializer);
+            let api_to = <String>::sse_decode(&mut deserializer);
+            let api_data = <String>::sse_decode(&mut deserializer);
+            let api_gas_limit = <u64>::sse_decode(&mut deserializer);
+            let api_gas_price = <u128>::sse_decode(&mut deserializer);
+            let api_gas_priority_fee = <u128>::sse_decode(&mut deserializer);
+            let api_latest_block_env = <String>::sse_decode(&mut deserializer);
+            let api_prestate_tracer_result = <String>::sse_decode(&mut deserializer);
+            let api_is_op_stack = <bool>::sse_decode(&mut deserializer);
+            let api_access_list = <Option<String>>::sse_decode(&mut deserializer);
+            deserializer.end();
+            move |context| {
+                transform_result_sse::<_, ()>((move || {
+                    let output_ok = Result::<_, ()>::Ok({
+                        crate::api::tracer::trace_transaction_stream(
+                            api_sink,
+                            api_chain_id,
+                            &api_from,
+                            api_from_nonce,
+                            &api_to,
+                            &api_data,
+                            api_gas_limit,
+                            api_gas_price,
+                            api_gas_priority_fee,
+                            &api_latest_block_env,
+                            &api_prestate_tracer_result,
+                            api_is_op_stack,
+                            api_access_list,
+                        );
+                    })?;
+                    Ok(output_ok)
+                })())
+            }
+        },
+    )
+}
+fn wire__crate__api__tracer__trace_transaction_typed_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "trace_transaction_typed",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_chain_id = <u64>::sse_decode(&mut deserializer);
+            let api_from = <String>::sse_decode(&mut deserializer);
+            let api_from_nonce = <u64>::sse_decode(&mut deserializer);
+            let api_to = <String>::sse_decode(&mut deserializer);
+            let api_data = <String>::sse_decode(&mut deserializer);
+            let api_gas_limit = <u64>::sse_decode(&mut deserializer);
+            let api_gas_price = <u128>::sse_decode(&mut deserializer);
+            let api_gas_priority_fee = <u128>::sse_decode(&mut deserializer);
+            let api_latest_block_env = <String>::sse_decode(&mut deserializer);
+            let api_prestate_tracer_result = <String>::sse_decode(&mut deserializer);
+            let api_is_op_stack = <bool>::sse_decode(&mut deserializer);
+            let api_access_list = <Option<String>>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, crate::api::tracer::FfiTraceError>((move || {
+                let output_ok = crate::api::tracer::trace_transaction_typed(
+                    api_chain_id,
+                    &api_from,
+                    api_from_nonce,
+                    &api_to,
+                    &api_data,
+                    api_gas_limit,
+                    api_gas_price,
+                    api_gas_priority_fee,
+                    &api_latest_block_env,
+                    &api_prestate_tracer_result,
+                    api_is_op_stack,
+                    api_access_list,
+                )?;
+                Ok(output_ok)
+            })())
+        },
+    )
+}
+fn wire__crate__api__tracer__trace_with_chain_impl(
+    ptr_: flutter_rust_bridge::for_generated::PlatformGeneralizedUint8ListPtr,
+    rust_vec_len_: i32,
+    data_len_: i32,
+) -> flutter_rust_bridge::for_generated::WireSyncRust2DartSse {
+    FLUTTER_RUST_BRIDGE_HANDLER.wrap_sync::<flutter_rust_bridge::for_generated::SseCodec, _>(
+        flutter_rust_bridge::for_generated::TaskInfo {
+            debug_name: "trace_with_chain",
+            port: None,
+            mode: flutter_rust_bridge::for_generated::FfiCallMode::Sync,
+        },
+        move || {
+            let message = unsafe {
+                flutter_rust_bridge::for_generated::Dart2RustMessageSse::from_wire(
+                    ptr_,
+                    rust_vec_len_,
+                    data_len_,
+                )
+            };
+            let mut deserializer =
+                flutter_rust_bridge::for_generated::SseDeserializer::new(message);
+            let api_chain_kind = <String>::sse_decode(&mut deserializer);
+            let api_request = <String>::sse_decode(&mut deserializer);
+            deserializer.end();
+            transform_result_sse::<_, ()>((move || {
+                let output_ok = Result::<_, ()>::Ok(crate::api::tracer::trace_with_chain(
+                    &api_chain_kind,
+                    &api_request,
+                ))?;
+                Ok(output_ok)
+            })())
+        },
+    )
+}
 
 // Section: dart2rust
 
+impl SseDecode
+    for StreamSink<crate::api::tracer::FfiTraceUpdate, flutter_rust_bridge::for_generated::SseCodec>
+{
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut inner = <String>::sse_decode(deserializer);
+        return StreamSink::deserialize(inner);
+    }
+}
+
 impl SseDecode for String {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -158,6 +376,140 @@ impl SseDecode for bool {
     }
 }
 
+impl SseDecode for crate::api::tracer::FfiCallFrame {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_call_type = <String>::sse_decode(deserializer);
+        let mut var_from = <String>::sse_decode(deserializer);
+        let mut var_to = <Option<String>>::sse_decode(deserializer);
+        let mut var_value = <String>::sse_decode(deserializer);
+        let mut var_gas = <u64>::sse_decode(deserializer);
+        let mut var_gas_used = <u64>::sse_decode(deserializer);
+        let mut var_input = <String>::sse_decode(deserializer);
+        let mut var_output = <Option<String>>::sse_decode(deserializer);
+        let mut var_error = <Option<String>>::sse_decode(deserializer);
+        let mut var_revert_reason = <Option<String>>::sse_decode(deserializer);
+        let mut var_logs = <Vec<crate::api::tracer::FfiLogEntry>>::sse_decode(deserializer);
+        let mut var_calls = <Vec<crate::api::tracer::FfiCallFrame>>::sse_decode(deserializer);
+        return crate::api::tracer::FfiCallFrame {
+            call_type: var_call_type,
+            from: var_from,
+            to: var_to,
+            value: var_value,
+            gas: var_gas,
+            gas_used: var_gas_used,
+            input: var_input,
+            output: var_output,
+            error: var_error,
+            revert_reason: var_revert_reason,
+            logs: var_logs,
+            calls: var_calls,
+        };
+    }
+}
+
+impl SseDecode for crate::api::tracer::FfiLogEntry {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_address = <String>::sse_decode(deserializer);
+        let mut var_topics = <Vec<String>>::sse_decode(deserializer);
+        let mut var_data = <String>::sse_decode(deserializer);
+        return crate::api::tracer::FfiLogEntry {
+            address: var_address,
+            topics: var_topics,
+            data: var_data,
+        };
+    }
+}
+
+impl SseDecode for crate::api::tracer::FfiTraceError {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_kind = <String>::sse_decode(deserializer);
+        let mut var_message = <String>::sse_decode(deserializer);
+        return crate::api::tracer::FfiTraceError {
+            kind: var_kind,
+            message: var_message,
+        };
+    }
+}
+
+impl SseDecode for crate::api::tracer::FfiTraceResult {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_status = <String>::sse_decode(deserializer);
+        let mut var_gas_used = <u64>::sse_decode(deserializer);
+        let mut var_output = <String>::sse_decode(deserializer);
+        let mut var_halt_reason = <Option<String>>::sse_decode(deserializer);
+        let mut var_blob_gas_used = <u64>::sse_decode(deserializer);
+        let mut var_fee_paid = <String>::sse_decode(deserializer);
+        let mut var_calls = <crate::api::tracer::FfiCallFrame>::sse_decode(deserializer);
+        let mut var_details = <String>::sse_decode(deserializer);
+        return crate::api::tracer::FfiTraceResult {
+            status: var_status,
+            gas_used: var_gas_used,
+            output: var_output,
+            halt_reason: var_halt_reason,
+            blob_gas_used: var_blob_gas_used,
+            fee_paid: var_fee_paid,
+            calls: var_calls,
+            details: var_details,
+        };
+    }
+}
+
+impl SseDecode for crate::api::tracer::FfiTraceUpdate {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut var_frames = <u64>::sse_decode(deserializer);
+        let mut var_gas_used = <u64>::sse_decode(deserializer);
+        let mut var_result = <Option<crate::api::tracer::FfiTraceResult>>::sse_decode(deserializer);
+        let mut var_error = <Option<crate::api::tracer::FfiTraceError>>::sse_decode(deserializer);
+        return crate::api::tracer::FfiTraceUpdate {
+            frames: var_frames,
+            gas_used: var_gas_used,
+            result: var_result,
+            error: var_error,
+        };
+    }
+}
+
+impl SseDecode for Vec<String> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<String>::sse_decode(deserializer));
+        }
+        return ans_;
+    }
+}
+
+impl SseDecode for Vec<crate::api::tracer::FfiCallFrame> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<crate::api::tracer::FfiCallFrame>::sse_decode(deserializer));
+        }
+        return ans_;
+    }
+}
+
+impl SseDecode for Vec<crate::api::tracer::FfiLogEntry> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        let mut len_ = <i32>::sse_decode(deserializer);
+        let mut ans_ = vec![];
+        for idx_ in 0..len_ {
+            ans_.push(<crate::api::tracer::FfiLogEntry>::sse_decode(deserializer));
+        }
+        return ans_;
+    }
+}
+
 impl SseDecode for Vec<u8> {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -170,6 +522,39 @@ impl SseDecode for Vec<u8> {
     }
 }
 
+impl SseDecode for Option<String> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        if (<bool>::sse_decode(deserializer)) {
+            return Some(<String>::sse_decode(deserializer));
+        } else {
+            return None;
+        }
+    }
+}
+
+impl SseDecode for Option<crate::api::tracer::FfiTraceError> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        if (<bool>::sse_decode(deserializer)) {
+            return Some(<crate::api::tracer::FfiTraceError>::sse_decode(deserializer));
+        } else {
+            return None;
+        }
+    }
+}
+
+impl SseDecode for Option<crate::api::tracer::FfiTraceResult> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
+        if (<bool>::sse_decode(deserializer)) {
+            return Some(<crate::api::tracer::FfiTraceResult>::sse_decode(deserializer));
+        } else {
+            return None;
+        }
+    }
+}
+
 impl SseDecode for u64 {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_decode(deserializer: &mut flutter_rust_bridge::for_generated::SseDeserializer) -> Self {
@@ -205,7 +590,13 @@ fn pde_ffi_dispatcher_primary_impl(
 ) {
     // Codec=Pde (Serialization + dispatch), see doc to use other codecs
     match func_id {
-        2 => wire__crate__api__tracer__init_app_impl(port, ptr, rust_vec_len, data_len),
+        3 => wire__crate__api__tracer__init_app_impl(port, ptr, rust_vec_len, data_len),
+        4 => wire__crate__api__tracer__trace_transaction_stream_impl(
+            port,
+            ptr,
+            rust_vec_len,
+            data_len,
+        ),
         _ => unreachable!(),
     }
 }
@@ -221,12 +612,153 @@ fn pde_ffi_dispatcher_sync_impl(
         1 => {
             wire__crate__api__tracer__format_and_trace_transaction_impl(ptr, rust_vec_len, data_len)
         }
+        2 => wire__crate__api__tracer__format_and_trace_transaction_binary_impl(
+            ptr,
+            rust_vec_len,
+            data_len,
+        ),
+        5 => wire__crate__api__tracer__trace_transaction_typed_impl(ptr, rust_vec_len, data_len),
+        6 => wire__crate__api__tracer__trace_with_chain_impl(ptr, rust_vec_len, data_len),
         _ => unreachable!(),
     }
 }
 
 // Section: rust2dart
 
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::tracer::FfiCallFrame {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.call_type.into_into_dart().into_dart(),
+            self.from.into_into_dart().into_dart(),
+            self.to.into_into_dart().into_dart(),
+            self.value.into_into_dart().into_dart(),
+            self.gas.into_into_dart().into_dart(),
+            self.gas_used.into_into_dart().into_dart(),
+            self.input.into_into_dart().into_dart(),
+            self.output.into_into_dart().into_dart(),
+            self.error.into_into_dart().into_dart(),
+            self.revert_reason.into_into_dart().into_dart(),
+            self.logs.into_into_dart().into_dart(),
+            self.calls.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::tracer::FfiCallFrame
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::tracer::FfiCallFrame>
+    for crate::api::tracer::FfiCallFrame
+{
+    fn into_into_dart(self) -> crate::api::tracer::FfiCallFrame {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::tracer::FfiLogEntry {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.address.into_into_dart().into_dart(),
+            self.topics.into_into_dart().into_dart(),
+            self.data.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::tracer::FfiLogEntry
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::tracer::FfiLogEntry>
+    for crate::api::tracer::FfiLogEntry
+{
+    fn into_into_dart(self) -> crate::api::tracer::FfiLogEntry {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::tracer::FfiTraceError {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.kind.into_into_dart().into_dart(),
+            self.message.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::tracer::FfiTraceError
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::tracer::FfiTraceError>
+    for crate::api::tracer::FfiTraceError
+{
+    fn into_into_dart(self) -> crate::api::tracer::FfiTraceError {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::tracer::FfiTraceResult {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.status.into_into_dart().into_dart(),
+            self.gas_used.into_into_dart().into_dart(),
+            self.output.into_into_dart().into_dart(),
+            self.halt_reason.into_into_dart().into_dart(),
+            self.blob_gas_used.into_into_dart().into_dart(),
+            self.fee_paid.into_into_dart().into_dart(),
+            self.calls.into_into_dart().into_dart(),
+            self.details.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::tracer::FfiTraceResult
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::tracer::FfiTraceResult>
+    for crate::api::tracer::FfiTraceResult
+{
+    fn into_into_dart(self) -> crate::api::tracer::FfiTraceResult {
+        self
+    }
+}
+// Codec=Dco (DartCObject based), see doc to use other codecs
+impl flutter_rust_bridge::IntoDart for crate::api::tracer::FfiTraceUpdate {
+    fn into_dart(self) -> flutter_rust_bridge::for_generated::DartAbi {
+        [
+            self.frames.into_into_dart().into_dart(),
+            self.gas_used.into_into_dart().into_dart(),
+            self.result.into_into_dart().into_dart(),
+            self.error.into_into_dart().into_dart(),
+        ]
+        .into_dart()
+    }
+}
+impl flutter_rust_bridge::for_generated::IntoDartExceptPrimitive
+    for crate::api::tracer::FfiTraceUpdate
+{
+}
+impl flutter_rust_bridge::IntoIntoDart<crate::api::tracer::FfiTraceUpdate>
+    for crate::api::tracer::FfiTraceUpdate
+{
+    fn into_into_dart(self) -> crate::api::tracer::FfiTraceUpdate {
+        self
+    }
+}
+
+impl SseEncode
+    for StreamSink<crate::api::tracer::FfiTraceUpdate, flutter_rust_bridge::for_generated::SseCodec>
+{
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        unimplemented!("")
+    }
+}
+
 impl SseEncode for String {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -248,6 +780,95 @@ impl SseEncode for bool {
     }
 }
 
+impl SseEncode for crate::api::tracer::FfiCallFrame {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.call_type, serializer);
+        <String>::sse_encode(self.from, serializer);
+        <Option<String>>::sse_encode(self.to, serializer);
+        <String>::sse_encode(self.value, serializer);
+        <u64>::sse_encode(self.gas, serializer);
+        <u64>::sse_encode(self.gas_used, serializer);
+        <String>::sse_encode(self.input, serializer);
+        <Option<String>>::sse_encode(self.output, serializer);
+        <Option<String>>::sse_encode(self.error, serializer);
+        <Option<String>>::sse_encode(self.revert_reason, serializer);
+        <Vec<crate::api::tracer::FfiLogEntry>>::sse_encode(self.logs, serializer);
+        <Vec<crate::api::tracer::FfiCallFrame>>::sse_encode(self.calls, serializer);
+    }
+}
+
+impl SseEncode for crate::api::tracer::FfiLogEntry {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.address, serializer);
+        <Vec<String>>::sse_encode(self.topics, serializer);
+        <String>::sse_encode(self.data, serializer);
+    }
+}
+
+impl SseEncode for crate::api::tracer::FfiTraceError {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.kind, serializer);
+        <String>::sse_encode(self.message, serializer);
+    }
+}
+
+impl SseEncode for crate::api::tracer::FfiTraceResult {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <String>::sse_encode(self.status, serializer);
+        <u64>::sse_encode(self.gas_used, serializer);
+        <String>::sse_encode(self.output, serializer);
+        <Option<String>>::sse_encode(self.halt_reason, serializer);
+        <u64>::sse_encode(self.blob_gas_used, serializer);
+        <String>::sse_encode(self.fee_paid, serializer);
+        <crate::api::tracer::FfiCallFrame>::sse_encode(self.calls, serializer);
+        <String>::sse_encode(self.details, serializer);
+    }
+}
+
+impl SseEncode for crate::api::tracer::FfiTraceUpdate {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <u64>::sse_encode(self.frames, serializer);
+        <u64>::sse_encode(self.gas_used, serializer);
+        <Option<crate::api::tracer::FfiTraceResult>>::sse_encode(self.result, serializer);
+        <Option<crate::api::tracer::FfiTraceError>>::sse_encode(self.error, serializer);
+    }
+}
+
+impl SseEncode for Vec<String> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <String>::sse_encode(item, serializer);
+        }
+    }
+}
+
+impl SseEncode for Vec<crate::api::tracer::FfiCallFrame> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <crate::api::tracer::FfiCallFrame>::sse_encode(item, serializer);
+        }
+    }
+}
+
+impl SseEncode for Vec<crate::api::tracer::FfiLogEntry> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <i32>::sse_encode(self.len() as _, serializer);
+        for item in self {
+            <crate::api::tracer::FfiLogEntry>::sse_encode(item, serializer);
+        }
+    }
+}
+
 impl SseEncode for Vec<u8> {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
@@ -258,6 +879,36 @@ impl SseEncode for Vec<u8> {
     }
 }
 
+impl SseEncode for Option<String> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <bool>::sse_encode(self.is_some(), serializer);
+        if let Some(value) = self {
+            <String>::sse_encode(value, serializer);
+        }
+    }
+}
+
+impl SseEncode for Option<crate::api::tracer::FfiTraceError> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <bool>::sse_encode(self.is_some(), serializer);
+        if let Some(value) = self {
+            <crate::api::tracer::FfiTraceError>::sse_encode(value, serializer);
+        }
+    }
+}
+
+impl SseEncode for Option<crate::api::tracer::FfiTraceResult> {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
+        <bool>::sse_encode(self.is_some(), serializer);
+        if let Some(value) = self {
+            <crate::api::tracer::FfiTraceResult>::sse_encode(value, serializer);
+        }
+    }
+}
+
 impl SseEncode for u64 {
     // Codec=Sse (Serialization based), see doc to use other codecs
     fn sse_encode(self, serializer: &mut flutter_rust_bridge::for_generated::SseSerializer) {
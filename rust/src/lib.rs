@@ -1,3 +1,7 @@
+#[cfg(feature = "flutter")]
 pub mod api;
+#[cfg(feature = "flutter")]
 mod frb_generated;
 pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,484 @@
+//! Optional decoding of call inputs, outputs and logs using caller-supplied ABIs
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use revm::primitives::{keccak256, Address, HashMap, B256, I256, U256};
+
+use crate::trace::error::TraceError;
+use crate::trace::inspector::{CallFrame, LogEntry};
+use crate::trace::trace::TraceTransactionResult;
+
+/// A decoded function call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedCall {
+    pub name: String,
+    /// Canonical signature, e.g. `transfer(address,uint256)`
+    pub signature: String,
+    pub args: Vec<DecodedParam>,
+}
+
+/// A decoded event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedLog {
+    pub name: String,
+    /// Canonical signature, e.g. `Transfer(address,address,uint256)`
+    pub signature: String,
+    pub args: Vec<DecodedParam>,
+}
+
+/// A decoded argument or return value.
+///
+/// Integers are decimal strings, addresses are checksummed, byte strings are hex and
+/// tuples are objects keyed by component name (arrays if the components are unnamed).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+    pub value: Value,
+}
+
+/// A JSON ABI to register, as passed in a `TraceRequest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbiEntry {
+    /// Contract the ABI belongs to; `None` to match its functions and events on any contract
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    /// The ABI as emitted by solc, an array of functions and events
+    pub abi: Value,
+}
+
+/// Contract ABIs used to decode traces, keyed by contract address or, for ABIs added
+/// without an address, by selector alone.
+///
+/// ABIs registered for an address take precedence, so a proxy can be given its
+/// implementation's ABI while unrelated contracts fall back to global selectors.
+#[derive(Debug, Clone, Default)]
+pub struct AbiRegistry {
+    contracts: HashMap<Address, ContractAbi>,
+    global: ContractAbi,
+}
+
+impl AbiRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a registry from `entries`, registering each ABI for its address or globally
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if an ABI is malformed or uses an unknown type.
+    pub fn from_entries(entries: &[AbiEntry]) -> Result<Self, TraceError> {
+        let mut registry = Self::new();
+        for entry in entries {
+            let abi_json = entry.abi.to_string();
+            match entry.address {
+                Some(address) => registry.add_contract_abi(address, &abi_json)?,
+                None => registry.add_abi(&abi_json)?,
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Registers a JSON ABI (as emitted by solc) for the contract at `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the JSON is malformed or uses an unknown type.
+    pub fn add_contract_abi(&mut self, address: Address, abi_json: &str) -> Result<(), TraceError> {
+        self.contracts.entry(address).or_default().extend_from_json(abi_json)
+    }
+
+    /// Registers a JSON ABI whose functions and events are matched on any contract.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the JSON is malformed or uses an unknown type.
+    pub fn add_abi(&mut self, abi_json: &str) -> Result<(), TraceError> {
+        self.global.extend_from_json(abi_json)
+    }
+
+    /// Fills `decoded_input`, `decoded_output` and each log's `decoded_log` throughout the
+    /// call tree wherever a matching ABI entry is known.
+    pub fn decode_frame(&self, frame: &mut CallFrame) {
+        if let Some(function) = frame.input.get(..4)
+            .and_then(|selector| self.function(frame.to, selector.try_into().ok()?))
+        {
+            frame.decoded_input = function.decode_input(&frame.input[4..]);
+            if frame.error.is_none() {
+                frame.decoded_output = frame.output.as_ref()
+                    .and_then(|output| function.decode_output(output));
+            }
+        }
+        for log in frame.logs.iter_mut() {
+            log.decoded_log = self.decode_log(log);
+        }
+        for call in frame.calls.iter_mut() {
+            self.decode_frame(call);
+        }
+    }
+
+    /// Decodes the call tree of `result` with `decode_frame` and copies the return values
+    /// of the top-level call to `result.decoded_output`
+    pub fn decode_result<T>(&self, result: &mut TraceTransactionResult<T>) {
+        self.decode_frame(&mut result.calls);
+        result.decoded_output = result.calls.decoded_output.clone();
+    }
+
+    fn decode_log(&self, log: &LogEntry) -> Option<DecodedLog> {
+        let topic0 = log.topics.first()?;
+        self.contracts.get(&log.address)
+            .and_then(|contract| contract.events.get(topic0))
+            .or_else(|| self.global.events.get(topic0))?
+            .decode(log)
+    }
+
+    fn function(&self, to: Option<Address>, selector: [u8; 4]) -> Option<&AbiFunction> {
+        to.and_then(|to| self.contracts.get(&to))
+            .and_then(|contract| contract.functions.get(&selector))
+            .or_else(|| self.global.functions.get(&selector))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ContractAbi {
+    functions: HashMap<[u8; 4], AbiFunction>,
+    events: HashMap<B256, AbiEvent>,
+}
+
+impl ContractAbi {
+    fn extend_from_json(&mut self, abi_json: &str) -> Result<(), TraceError> {
+        let items: Vec<JsonAbiItem> = serde_json::from_str(abi_json)?;
+        for item in items {
+            match item.item_type.as_str() {
+                "function" => {
+                    let function = AbiFunction {
+                        name: item.name,
+                        inputs: parse_params(&item.inputs)?,
+                        outputs: parse_params(&item.outputs)?,
+                    };
+                    let selector = keccak256(function.signature())[..4].try_into()
+                        .expect("keccak256 output is 32 bytes");
+                    self.functions.insert(selector, function);
+                }
+                "event" if !item.anonymous => {
+                    let event = AbiEvent {
+                        name: item.name,
+                        inputs: parse_params(&item.inputs)?,
+                        indexed: item.inputs.iter().map(|input| input.indexed).collect(),
+                    };
+                    self.events.insert(keccak256(event.signature()), event);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An ABI entry as it appears in the JSON ABI
+#[derive(Debug, Deserialize)]
+struct JsonAbiItem {
+    #[serde(rename = "type")]
+    item_type: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    inputs: Vec<JsonAbiParam>,
+    #[serde(default)]
+    outputs: Vec<JsonAbiParam>,
+    #[serde(default)]
+    anonymous: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonAbiParam {
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type")]
+    param_type: String,
+    #[serde(default)]
+    components: Vec<JsonAbiParam>,
+    #[serde(default)]
+    indexed: bool,
+}
+
+#[derive(Debug, Clone)]
+struct AbiFunction {
+    name: String,
+    inputs: Vec<(String, ParamType)>,
+    outputs: Vec<(String, ParamType)>,
+}
+
+impl AbiFunction {
+    fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    fn decode_input(&self, data: &[u8]) -> Option<DecodedCall> {
+        Some(DecodedCall {
+            name: self.name.clone(),
+            signature: self.signature(),
+            args: decode_params(&self.inputs, data)?,
+        })
+    }
+
+    fn decode_output(&self, data: &[u8]) -> Option<Vec<DecodedParam>> {
+        decode_params(&self.outputs, data)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AbiEvent {
+    name: String,
+    inputs: Vec<(String, ParamType)>,
+    indexed: Vec<bool>,
+}
+
+impl AbiEvent {
+    fn signature(&self) -> String {
+        signature(&self.name, &self.inputs)
+    }
+
+    /// Indexed parameters come from topics 1.., the rest from the log data.
+    /// Indexed dynamic values are only available as their hash.
+    fn decode(&self, log: &LogEntry) -> Option<DecodedLog> {
+        let non_indexed: Vec<&ParamType> = self.inputs.iter()
+            .zip(&self.indexed)
+            .filter(|(_, indexed)| !**indexed)
+            .map(|((_, param_type), _)| param_type)
+            .collect();
+        let mut data_values = decode_sequence(non_indexed.into_iter(), &log.data)?.into_iter();
+        let mut topics = log.topics.iter().skip(1);
+
+        let args = self.inputs.iter()
+            .zip(&self.indexed)
+            .map(|((name, param_type), indexed)| {
+                let value = if *indexed {
+                    let topic = topics.next()?;
+                    if param_type.is_dynamic() || matches!(param_type, ParamType::Tuple(_) | ParamType::FixedArray(..)) {
+                        Value::String(topic.to_string())
+                    } else {
+                        decode_value(param_type, topic.as_slice())?
+                    }
+                } else {
+                    data_values.next()?
+                };
+                Some(DecodedParam { name: name.clone(), param_type: param_type.canonical(), value })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(DecodedLog { name: self.name.clone(), signature: self.signature(), args })
+    }
+}
+
+/// Solidity ABI types supported by the decoder
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ParamType {
+    Address,
+    Bool,
+    Uint(usize),
+    Int(usize),
+    FixedBytes(usize),
+    Function,
+    Bytes,
+    String,
+    Array(Box<ParamType>),
+    FixedArray(Box<ParamType>, usize),
+    Tuple(Vec<(String, ParamType)>),
+}
+
+impl ParamType {
+    fn parse(param: &JsonAbiParam) -> Result<Self, TraceError> {
+        Self::parse_type(&param.param_type, &param.components)
+    }
+
+    fn parse_type(type_name: &str, components: &[JsonAbiParam]) -> Result<Self, TraceError> {
+        let unsupported = || TraceError::InvalidParameter(format!("unsupported ABI type {}", type_name));
+
+        if let Some(inner) = type_name.strip_suffix(']') {
+            let open = inner.rfind('[').ok_or_else(unsupported)?;
+            let element = Box::new(Self::parse_type(&inner[..open], components)?);
+            let size = &inner[open + 1..];
+            return if size.is_empty() {
+                Ok(ParamType::Array(element))
+            } else {
+                let size = size.parse().map_err(|_| unsupported())?;
+                Ok(ParamType::FixedArray(element, size))
+            };
+        }
+
+        let bits = |digits: &str| -> Result<usize, TraceError> {
+            if digits.is_empty() {
+                return Ok(256);
+            }
+            let bits: usize = digits.parse().map_err(|_| unsupported())?;
+            if bits == 0 || bits > 256 || !bits.is_multiple_of(8) {
+                return Err(unsupported());
+            }
+            Ok(bits)
+        };
+
+        match type_name {
+            "address" => Ok(ParamType::Address),
+            "bool" => Ok(ParamType::Bool),
+            "bytes" => Ok(ParamType::Bytes),
+            "string" => Ok(ParamType::String),
+            "function" => Ok(ParamType::Function),
+            "tuple" => Ok(ParamType::Tuple(parse_params(components)?)),
+            _ => {
+                if let Some(digits) = type_name.strip_prefix("uint") {
+                    Ok(ParamType::Uint(bits(digits)?))
+                } else if let Some(digits) = type_name.strip_prefix("int") {
+                    Ok(ParamType::Int(bits(digits)?))
+                } else if let Some(digits) = type_name.strip_prefix("bytes") {
+                    let size: usize = digits.parse().map_err(|_| unsupported())?;
+                    if size == 0 || size > 32 {
+                        return Err(unsupported());
+                    }
+                    Ok(ParamType::FixedBytes(size))
+                } else {
+                    Err(unsupported())
+                }
+            }
+        }
+    }
+
+    /// Type as it appears in canonical signatures
+    fn canonical(&self) -> String {
+        match self {
+            ParamType::Address => "address".to_string(),
+            ParamType::Bool => "bool".to_string(),
+            ParamType::Uint(bits) => format!("uint{}", bits),
+            ParamType::Int(bits) => format!("int{}", bits),
+            ParamType::FixedBytes(size) => format!("bytes{}", size),
+            ParamType::Function => "function".to_string(),
+            ParamType::Bytes => "bytes".to_string(),
+            ParamType::String => "string".to_string(),
+            ParamType::Array(element) => format!("{}[]", element.canonical()),
+            ParamType::FixedArray(element, size) => format!("{}[{}]", element.canonical(), size),
+            ParamType::Tuple(components) => format!(
+                "({})",
+                components.iter().map(|(_, component)| component.canonical()).collect::<Vec<_>>().join(",")
+            ),
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            ParamType::Bytes | ParamType::String | ParamType::Array(_) => true,
+            ParamType::FixedArray(element, _) => element.is_dynamic(),
+            ParamType::Tuple(components) => components.iter().any(|(_, component)| component.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    /// Size of the value in the head of an encoding
+    fn head_size(&self) -> usize {
+        if self.is_dynamic() {
+            return 32;
+        }
+        match self {
+            ParamType::FixedArray(element, size) => element.head_size() * size,
+            ParamType::Tuple(components) => components.iter().map(|(_, component)| component.head_size()).sum(),
+            _ => 32,
+        }
+    }
+}
+
+fn parse_params(params: &[JsonAbiParam]) -> Result<Vec<(String, ParamType)>, TraceError> {
+    params.iter()
+        .map(|param| Ok((param.name.clone(), ParamType::parse(param)?)))
+        .collect()
+}
+
+fn signature(name: &str, params: &[(String, ParamType)]) -> String {
+    let types: Vec<String> = params.iter().map(|(_, param_type)| param_type.canonical()).collect();
+    format!("{}({})", name, types.join(","))
+}
+
+fn decode_params(params: &[(String, ParamType)], data: &[u8]) -> Option<Vec<DecodedParam>> {
+    let values = decode_sequence(params.iter().map(|(_, param_type)| param_type), data)?;
+    Some(params.iter()
+        .zip(values)
+        .map(|((name, param_type), value)| DecodedParam {
+            name: name.clone(),
+            param_type: param_type.canonical(),
+            value,
+        })
+        .collect())
+}
+
+/// Decodes consecutive values encoded as a tuple starting at the beginning of `data`
+fn decode_sequence<'a>(types: impl Iterator<Item = &'a ParamType>, data: &[u8]) -> Option<Vec<Value>> {
+    let mut head = 0;
+    let mut values = Vec::new();
+    for param_type in types {
+        let value = if param_type.is_dynamic() {
+            let offset = read_usize(data, head)?;
+            decode_value(param_type, data.get(offset..)?)?
+        } else {
+            decode_value(param_type, data.get(head..)?)?
+        };
+        values.push(value);
+        head += param_type.head_size();
+    }
+    Some(values)
+}
+
+/// Decodes one value whose encoding starts at the beginning of `data`
+fn decode_value(param_type: &ParamType, data: &[u8]) -> Option<Value> {
+    let word = data.get(..32)?;
+    match param_type {
+        ParamType::Address => Some(Value::String(Address::from_slice(&word[12..]).to_checksum(None))),
+        ParamType::Bool => Some(Value::Bool(word.iter().any(|byte| *byte != 0))),
+        ParamType::Uint(_) => Some(Value::String(U256::from_be_slice(word).to_string())),
+        ParamType::Int(_) => Some(Value::String(I256::from_raw(U256::from_be_slice(word)).to_string())),
+        ParamType::FixedBytes(size) => Some(hex_value(&word[..*size])),
+        ParamType::Function => Some(hex_value(&word[..24])),
+        ParamType::Bytes => Some(hex_value(read_bytes(data)?)),
+        ParamType::String => Some(Value::String(String::from_utf8_lossy(read_bytes(data)?).into_owned())),
+        ParamType::Array(element) => {
+            let length = read_usize(data, 0)?;
+            let elements = data.get(32..)?;
+            // Every element takes at least one word, which bounds bogus lengths
+            if length > elements.len() / 32 {
+                return None;
+            }
+            decode_sequence(std::iter::repeat_n(element.as_ref(), length), elements).map(Value::Array)
+        }
+        ParamType::FixedArray(element, size) => {
+            decode_sequence(std::iter::repeat_n(element.as_ref(), *size), data).map(Value::Array)
+        }
+        ParamType::Tuple(components) => {
+            let values = decode_sequence(components.iter().map(|(_, component)| component), data)?;
+            if components.iter().all(|(name, _)| !name.is_empty()) {
+                let object: Map<String, Value> = components.iter()
+                    .map(|(name, _)| name.clone())
+                    .zip(values)
+                    .collect();
+                Some(Value::Object(object))
+            } else {
+                Some(Value::Array(values))
+            }
+        }
+    }
+}
+
+fn read_usize(data: &[u8], offset: usize) -> Option<usize> {
+    U256::from_be_slice(data.get(offset..offset.checked_add(32)?)?).try_into().ok()
+}
+
+fn read_bytes(data: &[u8]) -> Option<&[u8]> {
+    let length = read_usize(data, 0)?;
+    data.get(32..32usize.checked_add(length)?)
+}
+
+fn hex_value(bytes: &[u8]) -> Value {
+    Value::String(format!("0x{}", hex::encode(bytes)))
+}
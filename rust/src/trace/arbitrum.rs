@@ -0,0 +1,164 @@
+//! Tracing on Arbitrum Nitro chains (Arbitrum One, Nova) with the Ethereum EVM
+//!
+//! ArbOS differs from Ethereum in ways that matter for gas numbers: part of the gas
+//! limit pays the L1 poster fee before execution, and priority fees are not charged.
+//! Its precompiles are implemented natively by the node and are not emulated here;
+//! calls into them are labelled in the call tree and listed in the result.
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::HaltReason;
+use revm::context::BlockEnv;
+use revm::primitives::{Address, HashMap, U256};
+
+use crate::trace::database::AccountDetails;
+use crate::trace::error::TraceError;
+use crate::trace::quantity;
+use crate::trace::inspector::CallFrame;
+use crate::trace::trace::{trace_transaction, TraceTransactionOptions, TraceTransactionResult, TxParams, TxSpec};
+
+/// ArbOS precompiles, by address
+const ARBOS_PRECOMPILES: [(u64, &str); 15] = [
+    (0x64, "ArbSys"),
+    (0x65, "ArbInfo"),
+    (0x66, "ArbAddressTable"),
+    (0x67, "ArbBLS"),
+    (0x68, "ArbFunctionTable"),
+    (0x69, "ArbosTest"),
+    (0x6b, "ArbOwnerPublic"),
+    (0x6c, "ArbGasInfo"),
+    (0x6d, "ArbAggregator"),
+    (0x6e, "ArbRetryableTx"),
+    (0x6f, "ArbStatistics"),
+    (0x70, "ArbOwner"),
+    (0x71, "ArbWasm"),
+    (0x72, "ArbWasmCache"),
+    (0xc8, "NodeInterface"),
+];
+
+/// Bytes ArbOS adds to every transaction's size when pricing its L1 data
+const TX_FIXED_COST: u64 = 140;
+
+/// L1 gas units charged per byte of transaction data
+const L1_UNITS_PER_BYTE: u64 = 16;
+
+/// Arbitrum-specific gas accounting of a trace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArbitrumDetails {
+    /// Gas bought to pay the L1 poster fee, deducted from the gas limit before execution
+    #[serde(with = "quantity")]
+    pub l1_gas_used: u64,
+    /// Gas used by execution on L2, i.e. `gas.total_used`
+    #[serde(with = "quantity")]
+    pub l2_gas_used: u64,
+    /// `l1_gas_used + l2_gas_used`, the `gasUsed` of the node's receipt
+    #[serde(with = "quantity")]
+    pub total_gas_used: u64,
+    /// L1 poster fee in wei
+    pub poster_fee: U256,
+    /// `total_gas_used * basefee`: everything the sender paid
+    pub total_fee: U256,
+    /// ArbOS precompiles called by the transaction; their results are not emulated, so
+    /// the trace diverges from the chain from these calls on
+    pub arbos_precompiles_called: Vec<Address>,
+}
+
+/// Returns the name of the ArbOS precompile at `address`, if it is one
+pub fn arbos_precompile_name(address: Address) -> Option<&'static str> {
+    let bytes = address.as_slice();
+    if bytes[..18].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    let index = u64::from(u16::from_be_bytes([bytes[18], bytes[19]]));
+    ARBOS_PRECOMPILES.iter()
+        .find(|(precompile, _)| *precompile == index)
+        .map(|(_, name)| *name)
+}
+
+/// Estimates the L1 poster fee of a transaction: its encoded size plus the fixed
+/// overhead, priced at `l1_price_per_unit` per L1 gas unit.
+///
+/// ArbOS prices the brotli-compressed size, so this is an upper bound.
+pub fn estimate_poster_fee(params: &TxParams, l1_price_per_unit: U256) -> U256 {
+    let size = params.enveloped_tx().len() as u64 + TX_FIXED_COST;
+    U256::from(size * L1_UNITS_PER_BYTE) * l1_price_per_unit
+}
+
+/// Trace a transaction on an Arbitrum Nitro chain
+///
+/// The poster fee is converted to gas at the block base fee and deducted from the gas
+/// limit before execution, as ArbOS does. Priority fees are ignored: the sender pays
+/// the base fee only.
+///
+/// # Arguments
+///
+/// * `params` - The transaction; its `gas_limit` covers both the poster fee and execution
+/// * `latest_block_env` - Block environment; `number` is what `block.number` returns,
+///   which on Arbitrum is the L1 block number
+/// * `prestate_tracer_result` - Account states before execution
+/// * `options` - State overrides, call tracer options and ABI registry
+/// * `l1_price_per_unit` - L1 price per gas unit in wei, as returned by
+///   `ArbGasInfo.getL1BaseFeeEstimate()`; zero to ignore the poster fee
+///
+/// # Errors
+///
+/// Returns `TraceError::InvalidParameter` if the gas limit does not cover the poster
+/// fee, or any other `TraceError` if the transaction cannot be built or executed
+pub fn trace_transaction_arb(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    options: TraceTransactionOptions,
+    l1_price_per_unit: U256,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let basefee = U256::from(latest_block_env.basefee.max(1));
+    let poster_fee = estimate_poster_fee(params, l1_price_per_unit);
+    let l1_gas_used: u64 = poster_fee.div_ceil(basefee).try_into().unwrap_or(u64::MAX);
+    let l2_gas_limit = params.gas_limit.checked_sub(l1_gas_used)
+        .ok_or_else(|| TraceError::InvalidParameter(format!(
+            "gas limit {} does not cover the L1 poster fee of {} gas", params.gas_limit, l1_gas_used
+        )))?;
+
+    // Legacy gas prices above the base fee are refunded
+    let gas_price = match params.tx_spec {
+        TxSpec::Legacy => params.gas_price.min(u128::from(latest_block_env.basefee)),
+        TxSpec::Eip1559 => params.gas_price,
+    };
+    let params = TxParams {
+        gas_limit: l2_gas_limit,
+        gas_price,
+        gas_priority_fee: 0,
+        ..params.clone()
+    };
+
+    let mut result = trace_transaction(&params, latest_block_env, prestate_tracer_result, options)?;
+
+    let mut arbos_precompiles_called = Vec::new();
+    label_arbos_calls(&mut result.calls, &mut arbos_precompiles_called);
+    arbos_precompiles_called.sort();
+    arbos_precompiles_called.dedup();
+
+    let l2_gas_used = result.gas.total_used;
+    let total_gas_used = l2_gas_used.saturating_add(l1_gas_used);
+    result.arbitrum = Some(ArbitrumDetails {
+        l1_gas_used,
+        l2_gas_used,
+        total_gas_used,
+        poster_fee,
+        total_fee: U256::from(total_gas_used) * U256::from(result.gas.effective_gas_price),
+        arbos_precompiles_called,
+    });
+    Ok(result)
+}
+
+/// Marks frames calling ArbOS precompiles as precompile calls and collects their addresses
+fn label_arbos_calls(frame: &mut CallFrame, called: &mut Vec<Address>) {
+    if let Some(name) = frame.to.and_then(arbos_precompile_name) {
+        frame.is_precompile = true;
+        frame.precompile = Some(name.to_string());
+        called.extend(frame.to);
+    }
+    for call in frame.calls.iter_mut() {
+        label_arbos_calls(call, called);
+    }
+}
@@ -0,0 +1,232 @@
+//! Assertion helpers for using traces as lightweight simulation tests
+
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use revm::context::result::ExecutionResult;
+use revm::context::BlockEnv;
+use revm::primitives::{Address, Bytes, HashMap, B256, I256};
+
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, AccountDetails, PrestateKeys};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::{CallFrame, CallTracerConfig, LogEntry};
+use crate::trace::revert::decode_error_string;
+use crate::trace::trace::{trace_transaction_with_database, TraceTransactionResult, TxParams};
+
+/// Describes why a trace assertion did not hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionError {
+    pub message: String,
+}
+
+impl AssertionError {
+    fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
+
+impl fmt::Display for AssertionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Trace assertion failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for AssertionError {}
+
+/// Asserts that some frame in the call tree calls `to` with the given 4-byte selector.
+///
+/// Returns the first matching frame in depth-first order.
+pub fn expect_call<H>(
+    result: &TraceTransactionResult<H>,
+    to: Address,
+    selector: [u8; 4],
+) -> Result<&CallFrame, AssertionError> {
+    find_frame(&result.calls, &|frame| {
+        frame.to == Some(to) && frame.input.starts_with(&selector)
+    })
+    .ok_or_else(|| AssertionError::new(format!(
+        "no call to {:?} with selector 0x{}",
+        to,
+        hex::encode(selector)
+    )))
+}
+
+/// Asserts that `address` emitted an event whose first topic is `topic0`.
+///
+/// Logs emitted inside reverted frames are ignored since they never reach the receipt.
+pub fn expect_event<H>(
+    result: &TraceTransactionResult<H>,
+    address: Address,
+    topic0: B256,
+) -> Result<&LogEntry, AssertionError> {
+    find_log(&result.calls, &|log| {
+        log.address == address && log.topics.first() == Some(&topic0)
+    })
+    .ok_or_else(|| AssertionError::new(format!(
+        "no event from {:?} with topic0 {:?}",
+        address, topic0
+    )))
+}
+
+/// Asserts that the balance of `address` changed by exactly `delta` wei.
+///
+/// The balance before execution is taken from `prestate`, the balance after from the
+/// result's state diff. Accounts missing from either side are treated as unchanged/zero.
+pub fn expect_balance_delta<H>(
+    result: &TraceTransactionResult<H>,
+    prestate: &HashMap<Address, AccountDetails>,
+    address: Address,
+    delta: I256,
+) -> Result<(), AssertionError> {
+    let before = prestate.get(&address)
+        .and_then(|account| account.balance)
+        .unwrap_or_default();
+    let after = result.state_diff.get(&address)
+        .map(|account| account.info.balance)
+        .unwrap_or(before);
+    let actual = I256::from_raw(after.wrapping_sub(before));
+
+    if actual == delta {
+        Ok(())
+    } else {
+        Err(AssertionError::new(format!(
+            "expected balance delta {} for {:?}, got {}",
+            delta, address, actual
+        )))
+    }
+}
+
+/// Asserts that the transaction reverted and that its revert data starts with `expected`.
+///
+/// Pass just a 4-byte selector to match a custom error regardless of its arguments,
+/// or the full ABI-encoded revert data for an exact match.
+pub fn expect_revert_with<H>(
+    result: &TraceTransactionResult<H>,
+    expected: &Bytes,
+) -> Result<(), AssertionError> {
+    match &result.execution_result {
+        ExecutionResult::Revert { output, .. } if output.starts_with(expected) => Ok(()),
+        ExecutionResult::Revert { output, .. } => Err(AssertionError::new(format!(
+            "expected revert data starting with {}, got {}",
+            expected, output
+        ))),
+        ExecutionResult::Success { .. } => Err(AssertionError::new(
+            "expected revert, but transaction succeeded"
+        )),
+        ExecutionResult::Halt { .. } => Err(AssertionError::new(
+            "expected revert, but transaction halted"
+        )),
+    }
+}
+
+/// What a transaction is expected to revert with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "camelCase")]
+pub enum RevertExpectation {
+    /// Any revert passes
+    Any,
+    /// Revert data must start with this 4-byte custom error selector
+    Selector([u8; 4]),
+    /// Revert data must equal these bytes exactly
+    Data(Bytes),
+    /// Revert must be `Error(string)` with exactly this message
+    Message(String),
+}
+
+/// Structured outcome of `expect_revert`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertCheck {
+    pub passed: bool,
+    pub reverted: bool,
+    /// Raw revert data, if the transaction reverted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_data: Option<Bytes>,
+    /// Decoded `Error(string)` message, if the revert data is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_message: Option<String>,
+    /// Why the check failed; `None` when it passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+/// Traces a transaction and checks that it reverts as described by `expectation`.
+///
+/// Unlike the other assertions this never fails on a mismatch: it returns a
+/// `RevertCheck` carrying the actual revert data so CI output can show what happened.
+///
+/// # Errors
+///
+/// Returns `TraceError` only if the transaction could not be traced at all.
+pub fn expect_revert(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    expectation: &RevertExpectation,
+) -> Result<RevertCheck, TraceError> {
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+    let result = trace_transaction_with_database(params, latest_block_env, db, &prestate_keys, &CallTracerConfig::default())?;
+
+    let output = match &result.execution_result {
+        ExecutionResult::Revert { output, .. } => output.clone(),
+        ExecutionResult::Success { .. } => return Ok(RevertCheck::not_reverted("transaction succeeded")),
+        ExecutionResult::Halt { .. } => return Ok(RevertCheck::not_reverted("transaction halted")),
+    };
+
+    let revert_message = decode_error_string(&output);
+    let failure = match expectation {
+        RevertExpectation::Any => None,
+        RevertExpectation::Selector(selector) if output.starts_with(selector) => None,
+        RevertExpectation::Selector(selector) => Some(format!(
+            "expected custom error 0x{}", hex::encode(selector)
+        )),
+        RevertExpectation::Data(data) if output == *data => None,
+        RevertExpectation::Data(data) => Some(format!("expected revert data {}", data)),
+        RevertExpectation::Message(message) if revert_message.as_ref() == Some(message) => None,
+        RevertExpectation::Message(message) => Some(format!(
+            "expected revert message {:?}", message
+        )),
+    };
+
+    Ok(RevertCheck {
+        passed: failure.is_none(),
+        reverted: true,
+        revert_data: Some(output),
+        revert_message,
+        failure,
+    })
+}
+
+impl RevertCheck {
+    fn not_reverted(reason: &str) -> Self {
+        Self {
+            passed: false,
+            reverted: false,
+            revert_data: None,
+            revert_message: None,
+            failure: Some(format!("expected revert, but {}", reason)),
+        }
+    }
+}
+
+fn find_frame<'a>(
+    frame: &'a CallFrame,
+    predicate: &dyn Fn(&CallFrame) -> bool,
+) -> Option<&'a CallFrame> {
+    if predicate(frame) {
+        return Some(frame);
+    }
+    frame.calls.iter().find_map(|call| find_frame(call, predicate))
+}
+
+fn find_log<'a>(
+    frame: &'a CallFrame,
+    predicate: &dyn Fn(&LogEntry) -> bool,
+) -> Option<&'a LogEntry> {
+    if frame.error.is_some() {
+        return None;
+    }
+    frame.logs.iter()
+        .find(|log| predicate(log))
+        .or_else(|| frame.calls.iter().find_map(|call| find_log(call, predicate)))
+}
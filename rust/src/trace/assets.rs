@@ -0,0 +1,179 @@
+//! Extraction of native and token transfers from a trace
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{keccak256, Address, HashMap, B256, U256};
+
+use crate::trace::inspector::{CallFrame, LogEntry};
+use crate::trace::trace::TraceTransactionResult;
+use crate::trace::transfers::ValueTransfer;
+
+const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+const TRANSFER_SINGLE_EVENT: &str = "TransferSingle(address,address,address,uint256,uint256)";
+const TRANSFER_BATCH_EVENT: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+
+/// Kind of asset moved by a transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenStandard {
+    /// ETH moved by a call or creation value, or swept by a SELFDESTRUCT
+    Native,
+    Erc20,
+    Erc721,
+    Erc1155,
+}
+
+/// A single movement of an asset between two accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransfer {
+    pub standard: TokenStandard,
+    /// Token contract; `None` for native transfers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<Address>,
+    pub from: Address,
+    pub to: Address,
+    /// Token id for ERC-721 and ERC-1155 transfers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<U256>,
+    /// Amount transferred; always 1 for ERC-721
+    pub amount: U256,
+}
+
+/// Lists every asset transfer that takes effect, frame by frame in execution order.
+///
+/// Native transfers are the result's `value_transfers`, so they are only listed when
+/// the trace ran with `CallTracerConfig::with_value_transfers`; they include
+/// SELFDESTRUCT sweeps. Token transfers come from the standard `Transfer`,
+/// `TransferSingle` and `TransferBatch` events. Frames that reverted are skipped with
+/// all their subcalls, since nothing they moved persists. Within a frame, its own value
+/// transfers come first, then its logs, then its subcalls. Value transfers of frames
+/// left out of `calls` by `max_depth` or `max_frames` come last.
+pub fn extract_asset_transfers<H>(result: &TraceTransactionResult<H>) -> Vec<AssetTransfer> {
+    let topics = EventTopics {
+        transfer: keccak256(TRANSFER_EVENT),
+        transfer_single: keccak256(TRANSFER_SINGLE_EVENT),
+        transfer_batch: keccak256(TRANSFER_BATCH_EVENT),
+    };
+    let mut native: HashMap<&[usize], Vec<&ValueTransfer>> = HashMap::default();
+    for transfer in &result.value_transfers {
+        native.entry(transfer.frame_path.as_slice()).or_default().push(transfer);
+    }
+
+    let mut transfers = Vec::new();
+    collect_transfers(&result.calls, &mut Vec::new(), &mut native, &topics, &mut transfers);
+    // Leftovers belong to frames missing from `calls`; keep them in execution order
+    let mut leftovers: Vec<&ValueTransfer> = native.into_values().flatten().collect();
+    leftovers.sort_by_key(|transfer| transfer.frame_path.clone());
+    transfers.extend(leftovers.into_iter().map(native_transfer));
+    transfers
+}
+
+struct EventTopics {
+    transfer: B256,
+    transfer_single: B256,
+    transfer_batch: B256,
+}
+
+fn collect_transfers(
+    frame: &CallFrame,
+    path: &mut Vec<usize>,
+    native: &mut HashMap<&[usize], Vec<&ValueTransfer>>,
+    topics: &EventTopics,
+    transfers: &mut Vec<AssetTransfer>,
+) {
+    if frame.error.is_some() {
+        return;
+    }
+
+    if let Some(moved) = native.remove(path.as_slice()) {
+        transfers.extend(moved.into_iter().map(native_transfer));
+    }
+    for log in frame.logs.iter() {
+        decode_transfer_log(log, topics, transfers);
+    }
+    for (index, call) in frame.calls.iter().enumerate() {
+        path.push(index);
+        collect_transfers(call, path, native, topics, transfers);
+        path.pop();
+    }
+}
+
+fn native_transfer(transfer: &ValueTransfer) -> AssetTransfer {
+    AssetTransfer {
+        standard: TokenStandard::Native,
+        token: None,
+        from: transfer.from,
+        to: transfer.to,
+        token_id: None,
+        amount: transfer.amount,
+    }
+}
+
+fn decode_transfer_log(log: &LogEntry, topics: &EventTopics, transfers: &mut Vec<AssetTransfer>) {
+    let Some(topic0) = log.topics.first() else {
+        return;
+    };
+    let word = |index: usize| log.data.get(index * 32..(index + 1) * 32).map(U256::from_be_slice);
+
+    if *topic0 == topics.transfer {
+        // ERC-20 and ERC-721 share the signature; ERC-721 indexes the token id
+        match (log.topics.len(), word(0)) {
+            (3, Some(amount)) => transfers.push(AssetTransfer {
+                standard: TokenStandard::Erc20,
+                token: Some(log.address),
+                from: Address::from_word(log.topics[1]),
+                to: Address::from_word(log.topics[2]),
+                token_id: None,
+                amount,
+            }),
+            (4, _) => transfers.push(AssetTransfer {
+                standard: TokenStandard::Erc721,
+                token: Some(log.address),
+                from: Address::from_word(log.topics[1]),
+                to: Address::from_word(log.topics[2]),
+                token_id: Some(U256::from_be_slice(log.topics[3].as_slice())),
+                amount: U256::from(1),
+            }),
+            _ => {}
+        }
+    } else if *topic0 == topics.transfer_single && log.topics.len() == 4 {
+        if let (Some(id), Some(amount)) = (word(0), word(1)) {
+            transfers.push(AssetTransfer {
+                standard: TokenStandard::Erc1155,
+                token: Some(log.address),
+                from: Address::from_word(log.topics[2]),
+                to: Address::from_word(log.topics[3]),
+                token_id: Some(id),
+                amount,
+            });
+        }
+    } else if *topic0 == topics.transfer_batch && log.topics.len() == 4 {
+        let (Some(ids), Some(amounts)) = (read_uint_array(&log.data, 0), read_uint_array(&log.data, 1)) else {
+            return;
+        };
+        for (id, amount) in ids.into_iter().zip(amounts) {
+            transfers.push(AssetTransfer {
+                standard: TokenStandard::Erc1155,
+                token: Some(log.address),
+                from: Address::from_word(log.topics[2]),
+                to: Address::from_word(log.topics[3]),
+                token_id: Some(id),
+                amount,
+            });
+        }
+    }
+}
+
+/// Reads the `uint256[]` whose offset is stored in the `index`-th head word of `data`
+fn read_uint_array(data: &[u8], index: usize) -> Option<Vec<U256>> {
+    let read_usize = |offset: usize| -> Option<usize> {
+        U256::from_be_slice(data.get(offset..offset.checked_add(32)?)?).try_into().ok()
+    };
+    let offset = read_usize(index * 32)?;
+    let length = read_usize(offset)?;
+    let elements = data.get(offset + 32..)?;
+    if length > elements.len() / 32 {
+        return None;
+    }
+    Some(elements.chunks(32).take(length).map(U256::from_be_slice).collect())
+}
@@ -0,0 +1,36 @@
+//! MessagePack encoding of trace results, a compact alternative to JSON across FFI
+//!
+//! Results keep the shape they have in JSON: structs are maps keyed by their camelCase
+//! field names, and addresses, hashes, bytes and quantities are the same strings. Only
+//! the framing is binary, which makes results smaller and faster to encode and parse.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::trace::error::TraceError;
+
+/// Encodes `value` as MessagePack, in the same shape as its JSON serialization
+///
+/// # Errors
+///
+/// Returns `TraceError::MessagePack` if `value` cannot be serialized
+pub fn to_message_pack<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, TraceError> {
+    let mut bytes = Vec::new();
+    let mut serializer = rmp_serde::Serializer::new(&mut bytes)
+        .with_struct_map()
+        .with_human_readable();
+    value.serialize(&mut serializer)
+        .map_err(|e| TraceError::MessagePack(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Decodes MessagePack produced by `to_message_pack`
+///
+/// # Errors
+///
+/// Returns `TraceError::MessagePack` if `bytes` is not valid MessagePack or does not
+/// have the shape of `T`
+pub fn from_message_pack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, TraceError> {
+    let mut deserializer = rmp_serde::Deserializer::from_read_ref(bytes).with_human_readable();
+    T::deserialize(&mut deserializer).map_err(|e| TraceError::MessagePack(e.to_string()))
+}
@@ -1,29 +1,74 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
 use revm::primitives::{Address, U256, B256};
+use revm::primitives::hardfork::SpecId;
+#[cfg(feature = "op")]
+use op_revm::OpSpecId;
 use revm::{
     context::BlockEnv,
     context_interface::block::BlobExcessGasAndPrice,
+    primitives::eip4844::{BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN, BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE},
     primitives::ruint::FromUintError
 };
 
+use crate::trace::error::TraceError;
+
 /// Block details from eth_getBlockByNumber RPC call
-#[derive(Debug, Deserialize)]
+///
+/// Deserializes straight from the raw RPC block: quantities may be hex strings or
+/// numbers, and fields missing or `null` on older blocks or other chains (base fee
+/// before London, excess blob gas before Cancun, mixHash on some L2s) are taken as zero.
+/// `coinbase`, `baseFee` and `prevrandao` are accepted as aliases. It serializes in the
+/// same RPC format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockDetails {
     pub number: U256,
+    #[serde(default, alias = "coinbase", deserialize_with = "null_as_default")]
     pub miner: Address,
     pub timestamp: U256,
-    #[serde(rename(deserialize = "gasLimit"))]
+    #[serde(rename = "gasLimit")]
     pub gas_limit: U256,
-    #[serde(rename(deserialize = "baseFeePerGas"))]
+    #[serde(default, rename = "baseFeePerGas", alias = "baseFee", deserialize_with = "null_as_default")]
     pub base_fee_per_gas: U256,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub difficulty: U256,
-    #[serde(rename(deserialize = "excessBlobGas"))]
+    /// Absent on chains or blocks predating EIP-4844, in which case it is taken as zero
+    #[serde(default, rename = "excessBlobGas", deserialize_with = "null_as_default")]
     pub excess_blob_gas: U256,
+    /// Prevrandao of post-merge blocks; the difficulty is used when absent
+    #[serde(default, rename = "mixHash", alias = "prevrandao")]
+    pub mix_hash: Option<B256>,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
 }
 
+/// Builds a `BlockEnv` from a block returned by `eth_getBlockByNumber`.
+///
+/// The blob base fee update fraction follows mainnet's schedule for the block's number and
+/// timestamp; use `BlockEnvBuilder::from_block_details` when the chain id is known.
 pub fn create_block_env_from_block_details(
     block_details: BlockDetails
 )->Result<BlockEnv, FromUintError<u64>> {
+    // Post-merge blocks report prevrandao as mixHash and a zero difficulty
+    let prevrandao = match block_details.mix_hash {
+        Some(mix_hash) if block_details.difficulty.is_zero() => mix_hash,
+        _ => B256::from(block_details.difficulty),
+    };
+    let spec = spec_for_block(
+        1,
+        block_details.number.saturating_to(),
+        block_details.timestamp.saturating_to(),
+    );
+    let update_fraction = if spec.is_enabled_in(SpecId::PRAGUE) {
+        BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE
+    } else {
+        BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN
+    };
     Ok(BlockEnv {
         number: block_details.number,
         beneficiary: block_details.miner,
@@ -31,16 +76,350 @@ pub fn create_block_env_from_block_details(
         gas_limit: block_details.gas_limit.try_into()?,
         basefee: block_details.base_fee_per_gas.try_into()?,
         difficulty: block_details.difficulty,
-        prevrandao: Some(B256::from(block_details.difficulty)),
+        prevrandao: Some(prevrandao),
+        // Blob base fee is derived from the block's excess blob gas
         blob_excess_gas_and_price: Some(
             BlobExcessGasAndPrice::new(
-                1
-                /*std::cmp::max( //zero excess blob gas causes revm to error
-                    block_details.excess_blob_gas.try_into()?,
-                    1
-                )*/,
-                1 // blob gas price
+                block_details.excess_blob_gas.try_into()?,
+                update_fraction,
             )
         )
     })
 }
+
+/// Gas limit used by `BlockEnvBuilder` when none is given
+pub const DEFAULT_BLOCK_GAS_LIMIT: u64 = 30_000_000;
+
+/// Builds a `BlockEnv` field by field, validating it and filling what is left out with
+/// values consistent with the chain and its hardfork at that block.
+///
+/// The hardfork is resolved with `spec_for_block` unless given. It decides whether the
+/// block has a prevrandao (the merge onwards) and blob fees (Cancun onwards), and which
+/// blob base fee update fraction applies.
+#[derive(Debug, Clone, Default)]
+pub struct BlockEnvBuilder {
+    chain_id: u64,
+    number: U256,
+    timestamp: U256,
+    beneficiary: Address,
+    gas_limit: Option<u64>,
+    basefee: u64,
+    difficulty: U256,
+    prevrandao: Option<B256>,
+    excess_blob_gas: u64,
+    spec: Option<SpecId>,
+}
+
+impl BlockEnvBuilder {
+    pub fn new(chain_id: u64) -> Self {
+        Self { chain_id, ..Self::default() }
+    }
+
+    /// Starts from a block returned by `eth_getBlockByNumber`
+    pub fn from_block_details(chain_id: u64, block_details: BlockDetails) -> Self {
+        Self {
+            chain_id,
+            number: block_details.number,
+            timestamp: block_details.timestamp,
+            beneficiary: block_details.miner,
+            gas_limit: Some(block_details.gas_limit.saturating_to()),
+            basefee: block_details.base_fee_per_gas.saturating_to(),
+            difficulty: block_details.difficulty,
+            prevrandao: block_details.mix_hash.filter(|_| block_details.difficulty.is_zero()),
+            excess_blob_gas: block_details.excess_blob_gas.saturating_to(),
+            spec: None,
+        }
+    }
+
+    pub fn number(mut self, number: u64) -> Self {
+        self.number = U256::from(number);
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = U256::from(timestamp);
+        self
+    }
+
+    pub fn beneficiary(mut self, beneficiary: Address) -> Self {
+        self.beneficiary = beneficiary;
+        self
+    }
+
+    /// Defaults to `DEFAULT_BLOCK_GAS_LIMIT`
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = Some(gas_limit);
+        self
+    }
+
+    pub fn basefee(mut self, basefee: u64) -> Self {
+        self.basefee = basefee;
+        self
+    }
+
+    /// Difficulty of a pre-merge block
+    pub fn difficulty(mut self, difficulty: U256) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// The `mixHash` of a post-merge block. When not given, the difficulty is used as a
+    /// 32-byte word, since nodes returning raw headers often report it there
+    pub fn prevrandao(mut self, prevrandao: B256) -> Self {
+        self.prevrandao = Some(prevrandao);
+        self
+    }
+
+    /// The `excessBlobGas` of the block header
+    pub fn excess_blob_gas(mut self, excess_blob_gas: u64) -> Self {
+        self.excess_blob_gas = excess_blob_gas;
+        self
+    }
+
+    /// Hardfork of the block; defaults to `spec_for_block`
+    pub fn spec(mut self, spec: SpecId) -> Self {
+        self.spec = Some(spec);
+        self
+    }
+
+    /// Builds the block environment
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError::InvalidParameter` if the gas limit is zero, if a post-merge
+    /// block has a non-zero difficulty alongside an explicit prevrandao, or if a block
+    /// predating Cancun has excess blob gas
+    pub fn build(self) -> Result<BlockEnv, TraceError> {
+        let spec = self.spec.unwrap_or_else(|| {
+            spec_for_block(self.chain_id, self.number.saturating_to(), self.timestamp.saturating_to())
+        });
+
+        let gas_limit = self.gas_limit.unwrap_or(DEFAULT_BLOCK_GAS_LIMIT);
+        if gas_limit == 0 {
+            return Err(TraceError::InvalidParameter("block gas limit must be non-zero".to_string()));
+        }
+
+        let (difficulty, prevrandao) = if spec.is_enabled_in(SpecId::MERGE) {
+            if self.prevrandao.is_some() && !self.difficulty.is_zero() {
+                return Err(TraceError::InvalidParameter(format!(
+                    "difficulty {} of a post-merge block must be zero", self.difficulty
+                )));
+            }
+            (U256::ZERO, Some(self.prevrandao.unwrap_or(B256::from(self.difficulty))))
+        } else {
+            (self.difficulty, None)
+        };
+
+        let blob_excess_gas_and_price = if spec.is_enabled_in(SpecId::CANCUN) {
+            let update_fraction = if spec.is_enabled_in(SpecId::PRAGUE) {
+                BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE
+            } else {
+                BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN
+            };
+            Some(BlobExcessGasAndPrice::new(self.excess_blob_gas, update_fraction))
+        } else if self.excess_blob_gas != 0 {
+            return Err(TraceError::InvalidParameter(format!(
+                "excess blob gas {} set on a block predating Cancun", self.excess_blob_gas
+            )));
+        } else {
+            None
+        };
+
+        Ok(BlockEnv {
+            number: self.number,
+            beneficiary: self.beneficiary,
+            timestamp: self.timestamp,
+            gas_limit,
+            basefee: self.basefee,
+            difficulty,
+            prevrandao,
+            blob_excess_gas_and_price,
+        })
+    }
+}
+
+/// Ethereum mainnet forks activated by block number, latest first
+const MAINNET_BLOCK_FORKS: [(u64, SpecId); 15] = [
+    (15_537_394, SpecId::MERGE),
+    (15_050_000, SpecId::GRAY_GLACIER),
+    (13_773_000, SpecId::ARROW_GLACIER),
+    (12_965_000, SpecId::LONDON),
+    (12_244_000, SpecId::BERLIN),
+    (9_200_000, SpecId::MUIR_GLACIER),
+    (9_069_000, SpecId::ISTANBUL),
+    (7_280_000, SpecId::PETERSBURG),
+    (4_370_000, SpecId::BYZANTIUM),
+    (2_675_000, SpecId::SPURIOUS_DRAGON),
+    (2_463_000, SpecId::TANGERINE),
+    (1_920_000, SpecId::DAO_FORK),
+    (1_150_000, SpecId::HOMESTEAD),
+    (200_000, SpecId::FRONTIER_THAWING),
+    (0, SpecId::FRONTIER),
+];
+
+/// Ethereum mainnet forks activated by timestamp, latest first
+const MAINNET_TIMESTAMP_FORKS: [(u64, SpecId); 4] = [
+    (1_764_798_551, SpecId::OSAKA),
+    (1_746_612_311, SpecId::PRAGUE),
+    (1_710_338_135, SpecId::CANCUN),
+    (1_681_338_455, SpecId::SHANGHAI),
+];
+
+/// Sepolia forks activated by timestamp, latest first
+const SEPOLIA_TIMESTAMP_FORKS: [(u64, SpecId); 4] = [
+    (1_760_427_360, SpecId::OSAKA),
+    (1_741_159_776, SpecId::PRAGUE),
+    (1_706_655_072, SpecId::CANCUN),
+    (1_677_557_088, SpecId::SHANGHAI),
+];
+
+/// Block at which Sepolia went through the merge; it launched on London
+const SEPOLIA_MERGE_BLOCK: u64 = 1_735_371;
+
+/// Returns the hardfork active at a block of a known chain.
+///
+/// Ethereum mainnet (1) and Sepolia (11155111) are resolved from their fork schedules;
+/// any other chain gets `SpecId::default()`.
+///
+/// # Arguments
+///
+/// * `chain_id` - The chain the block belongs to
+/// * `number` - Block number, used for forks activated by block
+/// * `timestamp` - Block timestamp, used for forks activated by time (Shanghai onwards)
+pub fn spec_for_block(chain_id: u64, number: u64, timestamp: u64) -> SpecId {
+    let by_timestamp = |forks: &[(u64, SpecId)]| {
+        forks.iter().find(|(activation, _)| timestamp >= *activation).map(|(_, spec)| *spec)
+    };
+    match chain_id {
+        1 => by_timestamp(&MAINNET_TIMESTAMP_FORKS).unwrap_or_else(|| {
+            MAINNET_BLOCK_FORKS.iter()
+                .find(|(activation, _)| number >= *activation)
+                .map_or(SpecId::FRONTIER, |(_, spec)| *spec)
+        }),
+        11_155_111 => by_timestamp(&SEPOLIA_TIMESTAMP_FORKS).unwrap_or(if number >= SEPOLIA_MERGE_BLOCK {
+            SpecId::MERGE
+        } else {
+            SpecId::LONDON
+        }),
+        _ => SpecId::default(),
+    }
+}
+
+/// Superchain upgrades activated by timestamp, latest first. Delta only changed batch
+/// derivation, so op-revm has no spec for it and its blocks execute under Canyon.
+#[cfg(feature = "op")]
+const SUPERCHAIN_TIMESTAMP_FORKS: [(u64, OpSpecId); 6] = [
+    (1_746_806_401, OpSpecId::ISTHMUS),
+    (1_736_445_601, OpSpecId::HOLOCENE),
+    (1_726_070_401, OpSpecId::GRANITE),
+    (1_720_627_201, OpSpecId::FJORD),
+    (1_710_374_401, OpSpecId::ECOTONE),
+    (1_704_992_401, OpSpecId::CANYON),
+];
+
+/// Returns the OP Stack upgrade active at a block of a known chain.
+///
+/// OP Mainnet (10) and Base (8453) follow the Superchain upgrade schedule; any other
+/// chain gets `OpSpecId::default()`.
+#[cfg(feature = "op")]
+pub fn op_spec_for_block(chain_id: u64, timestamp: u64) -> OpSpecId {
+    match chain_id {
+        10 | 8453 => SUPERCHAIN_TIMESTAMP_FORKS.iter()
+            .find(|(activation, _)| timestamp >= *activation)
+            .map_or(OpSpecId::REGOLITH, |(_, spec)| *spec),
+        _ => OpSpecId::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_details(fixture: &str) -> BlockDetails {
+        serde_json::from_str(fixture).expect("fixture is an eth_getBlockByNumber result")
+    }
+
+    #[test]
+    fn mainnet_cancun_block() {
+        let details = block_details(include_str!("../../tests/fixtures/blocks/mainnet_cancun.json"));
+        let block_env = create_block_env_from_block_details(details).unwrap();
+
+        assert_eq!(block_env.number, U256::from(21_650_000));
+        assert_eq!(block_env.beneficiary, "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5".parse::<Address>().unwrap());
+        assert_eq!(block_env.gas_limit, 30_000_000);
+        assert_eq!(block_env.basefee, 10_000_000_000);
+        assert_eq!(block_env.difficulty, U256::ZERO);
+        assert_eq!(
+            block_env.prevrandao,
+            Some("0x8c6c8a3f1b2d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7".parse().unwrap())
+        );
+        assert_eq!(
+            block_env.blob_excess_gas_and_price,
+            Some(BlobExcessGasAndPrice::new(0x2000000, BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN))
+        );
+    }
+
+    #[test]
+    fn mainnet_prague_block_uses_prague_blob_fraction() {
+        let mut details = block_details(include_str!("../../tests/fixtures/blocks/mainnet_cancun.json"));
+        details.timestamp = U256::from(MAINNET_TIMESTAMP_FORKS[1].0);
+        let block_env = create_block_env_from_block_details(details).unwrap();
+
+        let blob = block_env.blob_excess_gas_and_price.unwrap();
+        assert_eq!(blob, BlobExcessGasAndPrice::new(0x2000000, BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE));
+        assert_ne!(
+            blob.blob_gasprice,
+            BlobExcessGasAndPrice::new(0x2000000, BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN).blob_gasprice
+        );
+    }
+
+    #[test]
+    fn op_mainnet_block() {
+        let details = block_details(include_str!("../../tests/fixtures/blocks/op_mainnet.json"));
+        let block_env = create_block_env_from_block_details(details).unwrap();
+
+        assert_eq!(block_env.number, U256::from(126_400_000));
+        assert_eq!(block_env.gas_limit, 60_000_000);
+        assert_eq!(block_env.basefee, 252);
+        assert_eq!(block_env.difficulty, U256::ZERO);
+        assert_eq!(
+            block_env.prevrandao,
+            Some("0xd1c2b3a4958677685a4b3c2d1e0f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b".parse().unwrap())
+        );
+        let blob = block_env.blob_excess_gas_and_price.unwrap();
+        assert_eq!(blob.excess_blob_gas, 0);
+        assert_eq!(blob.blob_gasprice, 1);
+    }
+
+    #[test]
+    fn pre_merge_block() {
+        let details = block_details(include_str!("../../tests/fixtures/blocks/mainnet_pre_merge.json"));
+        let difficulty = U256::from(0x1b4f5c6a8d2e3f_u64);
+        let block_env = create_block_env_from_block_details(details.clone()).unwrap();
+
+        assert_eq!(block_env.number, U256::from(12_000_000));
+        assert_eq!(block_env.basefee, 0);
+        assert_eq!(block_env.difficulty, difficulty);
+        assert_eq!(block_env.prevrandao, Some(B256::from(difficulty)));
+        assert_eq!(
+            block_env.blob_excess_gas_and_price,
+            Some(BlobExcessGasAndPrice::new(0, BLOB_BASE_FEE_UPDATE_FRACTION_CANCUN))
+        );
+
+        // With the chain known, a pre-merge block has neither prevrandao nor blob fees
+        let block_env = BlockEnvBuilder::from_block_details(1, details).build().unwrap();
+        assert_eq!(block_env.difficulty, difficulty);
+        assert_eq!(block_env.prevrandao, None);
+        assert_eq!(block_env.blob_excess_gas_and_price, None);
+    }
+
+    #[test]
+    fn block_details_round_trip() {
+        let details = block_details(include_str!("../../tests/fixtures/blocks/mainnet_pre_merge.json"));
+        let json = serde_json::to_value(&details).unwrap();
+
+        assert_eq!(json["number"], "0xb71b00");
+        assert_eq!(json["baseFeePerGas"], "0x0");
+        assert_eq!(block_details(&json.to_string()).gas_limit, details.gas_limit);
+    }
+}
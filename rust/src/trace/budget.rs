@@ -0,0 +1,110 @@
+//! Size limits on trace output, so huge calldata, init code or log volumes do not blow
+//! up the JSON passed across FFI
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::Bytes;
+
+use crate::trace::inspector::{CallFrame, FrameTruncation};
+use crate::trace::trace::TraceTransactionResult;
+
+/// Smallest data size `max_json_bytes` shrinks frame inputs and outputs to
+const MIN_DATA_BYTES: usize = 64;
+
+/// Limits on the size of a trace result; `None` leaves that dimension unlimited
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct OutputLimits {
+    /// Bytes kept of each frame's `input` and `output`
+    pub max_data_bytes: Option<usize>,
+    /// Logs kept across the whole call tree, in execution order
+    pub max_logs: Option<usize>,
+    /// Target size of the serialized result. Inputs and outputs are shrunk, then logs
+    /// dropped, until it fits; the rest of the result is never cut, so it may remain above.
+    pub max_json_bytes: Option<usize>,
+}
+
+impl OutputLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_data_bytes.is_none() && self.max_logs.is_none() && self.max_json_bytes.is_none()
+    }
+}
+
+/// Cuts the call tree of `result` down to `limits`.
+///
+/// Frames that lost data get a `truncated` entry with the original sizes and the number
+/// of dropped logs, and `result.output_truncated` is set.
+pub fn apply_output_limits<H: Serialize>(result: &mut TraceTransactionResult<H>, limits: &OutputLimits) {
+    let mut truncated = false;
+    if let Some(max_data_bytes) = limits.max_data_bytes {
+        truncated |= truncate_data(&mut result.calls, max_data_bytes);
+    }
+    if let Some(max_logs) = limits.max_logs {
+        let mut remaining = max_logs;
+        truncated |= drop_logs(&mut result.calls, &mut remaining);
+    }
+
+    if let Some(max_json_bytes) = limits.max_json_bytes {
+        let mut max_data_bytes = largest_data(&result.calls);
+        while json_size(result) > max_json_bytes {
+            if max_data_bytes > MIN_DATA_BYTES {
+                max_data_bytes = (max_data_bytes / 2).max(MIN_DATA_BYTES);
+                truncated |= truncate_data(&mut result.calls, max_data_bytes);
+            } else if drop_logs(&mut result.calls, &mut 0) {
+                truncated = true;
+            } else {
+                break;
+            }
+        }
+    }
+
+    result.output_truncated |= truncated;
+}
+
+/// Cuts every `input` and `output` of the tree to `max_data_bytes`
+fn truncate_data(frame: &mut CallFrame, max_data_bytes: usize) -> bool {
+    let mut truncated = false;
+    if frame.input.len() > max_data_bytes {
+        frame.truncated.get_or_insert_with(FrameTruncation::default)
+            .input_size.get_or_insert(frame.input.len());
+        frame.input = Bytes::copy_from_slice(&frame.input[..max_data_bytes]);
+        truncated = true;
+    }
+    if let Some(output) = frame.output.as_mut().filter(|output| output.len() > max_data_bytes) {
+        frame.truncated.get_or_insert_with(FrameTruncation::default)
+            .output_size.get_or_insert(output.len());
+        *output = Bytes::copy_from_slice(&output[..max_data_bytes]);
+        truncated = true;
+    }
+    for call in frame.calls.iter_mut() {
+        truncated |= truncate_data(call, max_data_bytes);
+    }
+    truncated
+}
+
+/// Keeps the first `remaining` logs of the tree, in execution order, and drops the rest
+fn drop_logs(frame: &mut CallFrame, remaining: &mut usize) -> bool {
+    // Logs of a frame are emitted around its subcalls; they are counted first here,
+    // which only matters for which logs are kept at the boundary
+    let mut truncated = false;
+    if frame.logs.len() > *remaining {
+        let dropped = frame.logs.len() - *remaining;
+        frame.logs.truncate(*remaining);
+        frame.truncated.get_or_insert_with(FrameTruncation::default).dropped_logs += dropped;
+        truncated = true;
+    }
+    *remaining -= frame.logs.len();
+    for call in frame.calls.iter_mut() {
+        truncated |= drop_logs(call, remaining);
+    }
+    truncated
+}
+
+/// Largest `input` or `output` in the tree
+fn largest_data(frame: &CallFrame) -> usize {
+    let own = frame.input.len().max(frame.output.as_ref().map_or(0, |output| output.len()));
+    frame.calls.iter().map(largest_data).fold(own, usize::max)
+}
+
+fn json_size<H: Serialize>(result: &TraceTransactionResult<H>) -> usize {
+    serde_json::to_vec(result).map_or(0, |json| json.len())
+}
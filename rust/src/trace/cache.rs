@@ -0,0 +1,147 @@
+//! On-disk cache of fetched account state, so repeated local simulations at the same
+//! block do not request the same accounts and slots from a node again
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{Address, HashMap};
+
+use crate::trace::database::AccountDetails;
+#[cfg(feature = "rpc")]
+use crate::trace::database::RpcDatabase;
+use crate::trace::error::TraceError;
+
+/// Current version of the prestate cache file format
+pub const PRESTATE_CACHE_VERSION: u32 = 1;
+
+/// Account state keyed by chain id, block number and address
+///
+/// State at a given block never changes, so entries stay valid forever and the cache only
+/// grows. Storage is cached slot by slot: an account may be cached with only the slots
+/// earlier simulations read.
+#[derive(Debug, Clone, Default)]
+pub struct PrestateCache {
+    blocks: HashMap<(u64, u64), HashMap<Address, AccountDetails>>,
+}
+
+/// Cached state of one block, as stored on disk
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedBlock {
+    chain_id: u64,
+    block_number: u64,
+    accounts: HashMap<Address, AccountDetails>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    blocks: Vec<CachedBlock>,
+}
+
+impl PrestateCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a cache written by `save`. A missing file gives an empty cache, so the same
+    /// path can be used for the first and every later run.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the file cannot be read, is not a cache file or was written
+    /// by a newer version
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let file: CacheFile = serde_json::from_slice(&bytes)?;
+        if file.version > PRESTATE_CACHE_VERSION {
+            return Err(TraceError::InvalidParameter(format!(
+                "unsupported prestate cache version {}", file.version
+            )));
+        }
+        let blocks = file.blocks.into_iter()
+            .map(|block| ((block.chain_id, block.block_number), block.accounts))
+            .collect();
+        Ok(Self { blocks })
+    }
+
+    /// Writes the cache as JSON, replacing `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        let mut blocks: Vec<CachedBlock> = self.blocks.iter()
+            .map(|(&(chain_id, block_number), accounts)| CachedBlock {
+                chain_id,
+                block_number,
+                accounts: accounts.clone(),
+            })
+            .collect();
+        // Keeps the file stable across runs for diffing
+        blocks.sort_unstable_by_key(|block| (block.chain_id, block.block_number));
+        let file = CacheFile { version: PRESTATE_CACHE_VERSION, blocks };
+        fs::write(path, serde_json::to_vec(&file)?)?;
+        Ok(())
+    }
+
+    pub fn get(&self, chain_id: u64, block_number: u64, address: &Address) -> Option<&AccountDetails> {
+        self.blocks.get(&(chain_id, block_number))?.get(address)
+    }
+
+    /// Every account cached at a block, in the format `trace_transaction` takes as prestate
+    pub fn prestate(&self, chain_id: u64, block_number: u64) -> Option<&HashMap<Address, AccountDetails>> {
+        self.blocks.get(&(chain_id, block_number))
+    }
+
+    /// Adds the state of one account. Fields `details` leaves out keep their cached value,
+    /// and its storage slots are added to the cached ones.
+    pub fn insert(&mut self, chain_id: u64, block_number: u64, address: Address, details: AccountDetails) {
+        let accounts = self.blocks.entry((chain_id, block_number)).or_default();
+        match accounts.get_mut(&address) {
+            Some(cached) => merge_account(cached, details),
+            None => {
+                accounts.insert(address, details);
+            }
+        }
+    }
+
+    /// Adds every account of a prestate, e.g. the result of `trace_prestate`
+    pub fn insert_prestate(&mut self, chain_id: u64, block_number: u64, prestate: HashMap<Address, AccountDetails>) {
+        for (address, details) in prestate {
+            self.insert(chain_id, block_number, address, details);
+        }
+    }
+
+    /// Seeds `db` with everything cached at `block_number`, so only state missing from the
+    /// cache is requested from the node. `db` must read the state at that block.
+    #[cfg(feature = "rpc")]
+    pub fn preload(&self, chain_id: u64, block_number: u64, db: &mut RpcDatabase) {
+        if let Some(prestate) = self.prestate(chain_id, block_number) {
+            db.preload(prestate);
+        }
+    }
+
+    /// Adds everything `db` fetched from the node at `block_number`
+    #[cfg(feature = "rpc")]
+    pub fn record(&mut self, chain_id: u64, block_number: u64, db: &RpcDatabase) {
+        self.insert_prestate(chain_id, block_number, db.fetched_prestate());
+    }
+}
+
+fn merge_account(cached: &mut AccountDetails, details: AccountDetails) {
+    if details.balance.is_some() {
+        cached.balance = details.balance;
+    }
+    if details.nonce.is_some() {
+        cached.nonce = details.nonce;
+    }
+    if details.code.is_some() {
+        cached.code = details.code;
+    }
+    if let Some(storage) = details.storage {
+        cached.storage.get_or_insert_with(HashMap::default).extend(storage);
+    }
+}
@@ -0,0 +1,206 @@
+//! Single entry point dispatching a trace to the tracer of the chain it runs on
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::HaltReason;
+use revm::database::InMemoryDB;
+use revm::primitives::hardfork::SpecId;
+use revm::primitives::{Address, HashMap, U256};
+#[cfg(feature = "op")]
+use op_revm::{OpHaltReason, OpSpecId};
+
+use crate::trace::abi::{AbiEntry, AbiRegistry};
+use crate::trace::arbitrum::trace_transaction_arb;
+use crate::trace::budget::{apply_output_limits, OutputLimits};
+#[cfg(feature = "op")]
+use crate::trace::block::op_spec_for_block;
+use crate::trace::block::{create_block_env_from_block_details, BlockDetails};
+use crate::trace::database::{
+    apply_state_overrides,
+    create_in_memory_database_from_prestate_trace,
+    AccountDetails,
+    AccountOverride,
+    PrestateKeys,
+};
+use crate::trace::error::TraceError;
+use crate::trace::flat::{apply_call_layout, CallLayout};
+use crate::trace::inspector::CallTracerConfig;
+use crate::trace::parallel::parallel_map;
+use crate::trace::quantity::QuantityEncoding;
+#[cfg(feature = "op")]
+use crate::trace::trace::trace_transaction_op_with_database;
+use crate::trace::trace::{trace_transaction_with_database, TraceTransactionOptions, TraceTransactionResult, TxParams};
+
+/// The kind of chain a transaction is traced on, with its chain-specific settings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ChainKind {
+    /// Ethereum and chains without execution differences from it
+    Ethereum,
+    /// OP Stack chains (OP Mainnet, Base, ...)
+    #[cfg(feature = "op")]
+    #[serde(rename_all = "camelCase")]
+    OpStack {
+        /// Upgrade to execute under; `None` resolves it from the block with `op_spec_for_block`
+        #[serde(default)]
+        spec: Option<OpSpecId>,
+    },
+    /// Arbitrum Nitro chains (Arbitrum One, Nova)
+    #[serde(rename_all = "camelCase")]
+    ArbitrumNitro {
+        /// L1 price per gas unit for the poster fee; see `trace_transaction_arb`
+        #[serde(default)]
+        l1_price_per_unit: U256,
+    },
+    /// A chain running the Ethereum EVM with its own configuration
+    Custom { cfg: CustomChainConfig },
+}
+
+/// Configuration of a `ChainKind::Custom` chain
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CustomChainConfig {
+    /// Hardfork the chain runs; overrides `TxParams::spec` when set
+    pub spec: Option<SpecId>,
+}
+
+/// A transaction to trace along with the state and block it executes on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceRequest {
+    pub tx: TxParams,
+    pub latest_block_env: BlockDetails,
+    pub prestate_tracer_result: HashMap<Address, AccountDetails>,
+    #[serde(default)]
+    pub state_overrides: HashMap<Address, AccountOverride>,
+    #[serde(default)]
+    pub tracer_config: CallTracerConfig,
+    /// Size limits applied to the result, see `apply_output_limits`
+    #[serde(default)]
+    pub output_limits: OutputLimits,
+    /// Whether calls are returned as a tree, a flat list or both
+    #[serde(default)]
+    pub call_layout: CallLayout,
+    /// How quantities are written when the result is serialized; not applied by `trace`
+    /// itself but by the bindings that return JSON, see `with_quantity_encoding`
+    #[serde(default)]
+    pub quantity_encoding: QuantityEncoding,
+    /// ABIs to decode call inputs, outputs and logs with, see `AbiRegistry`. The return
+    /// values of the top-level call end up in the result's `decodedOutput`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub abis: Vec<AbiEntry>,
+}
+
+/// Result of `trace`, whose halt reason type depends on the chain
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ChainTraceResult {
+    Ethereum(TraceTransactionResult<HaltReason>),
+    #[cfg(feature = "op")]
+    OpStack(TraceTransactionResult<OpHaltReason>),
+}
+
+/// Traces a transaction with the tracer of `chain_kind`
+///
+/// Arbitrum and custom chains share the Ethereum result type; the Arbitrum gas
+/// accounting is in the `arbitrum` section of the result. The result is decoded with
+/// `request.abis`, then `request.output_limits` and `request.call_layout` are applied
+/// to it before it is returned.
+///
+/// # Errors
+///
+/// Returns `TraceError` if the block details or overrides are invalid, or if the
+/// transaction cannot be built or executed
+pub fn trace(chain_kind: &ChainKind, request: TraceRequest) -> Result<ChainTraceResult, TraceError> {
+    let output_limits = request.output_limits.clone();
+    let call_layout = request.call_layout;
+    let abi_registry = (!request.abis.is_empty())
+        .then(|| AbiRegistry::from_entries(&request.abis))
+        .transpose()?;
+    let mut result = trace_unlimited(chain_kind, request)?;
+    if let Some(abi_registry) = abi_registry {
+        match &mut result {
+            ChainTraceResult::Ethereum(result) => abi_registry.decode_result(result),
+            #[cfg(feature = "op")]
+            ChainTraceResult::OpStack(result) => abi_registry.decode_result(result),
+        }
+    }
+    if !output_limits.is_unlimited() {
+        match &mut result {
+            ChainTraceResult::Ethereum(result) => apply_output_limits(result, &output_limits),
+            #[cfg(feature = "op")]
+            ChainTraceResult::OpStack(result) => apply_output_limits(result, &output_limits),
+        }
+    }
+    match &mut result {
+        ChainTraceResult::Ethereum(result) => apply_call_layout(result, call_layout),
+        #[cfg(feature = "op")]
+        ChainTraceResult::OpStack(result) => apply_call_layout(result, call_layout),
+    }
+    Ok(result)
+}
+
+/// Traces independent requests on all cores with `trace`, returning the results in the
+/// order of `requests`
+///
+/// Each request carries its own prestate. To trace many transactions against one shared
+/// prestate, use `Simulator::trace_parallel`.
+pub fn trace_transactions_parallel(
+    chain_kind: &ChainKind,
+    requests: Vec<TraceRequest>,
+) -> Vec<Result<ChainTraceResult, TraceError>> {
+    parallel_map(requests, |request| trace(chain_kind, request))
+}
+
+fn trace_unlimited(chain_kind: &ChainKind, request: TraceRequest) -> Result<ChainTraceResult, TraceError> {
+    let TraceRequest {
+        mut tx,
+        latest_block_env,
+        prestate_tracer_result,
+        state_overrides,
+        tracer_config,
+        output_limits: _,
+        call_layout: _,
+        quantity_encoding: _,
+        abis: _,
+    } = request;
+    let latest_block_env = create_block_env_from_block_details(latest_block_env)?;
+
+    match chain_kind {
+        ChainKind::Ethereum => {
+            let (db, prestate_keys) = prepare_database(prestate_tracer_result, state_overrides)?;
+            trace_transaction_with_database(&tx, latest_block_env, db, &prestate_keys, &tracer_config)
+                .map(ChainTraceResult::Ethereum)
+        }
+        #[cfg(feature = "op")]
+        ChainKind::OpStack { spec } => {
+            let op_spec = spec.unwrap_or_else(|| {
+                op_spec_for_block(tx.chain_id, latest_block_env.timestamp.saturating_to())
+            });
+            let (db, prestate_keys) = prepare_database(prestate_tracer_result, state_overrides)?;
+            trace_transaction_op_with_database(&tx, latest_block_env, db, &prestate_keys, &tracer_config, op_spec, None)
+                .map(ChainTraceResult::OpStack)
+        }
+        ChainKind::ArbitrumNitro { l1_price_per_unit } => trace_transaction_arb(
+            &tx,
+            latest_block_env,
+            prestate_tracer_result,
+            TraceTransactionOptions { state_overrides, tracer_config, abi_registry: None },
+            *l1_price_per_unit,
+        ).map(ChainTraceResult::Ethereum),
+        ChainKind::Custom { cfg } => {
+            tx.spec = cfg.spec.or(tx.spec);
+            let (db, prestate_keys) = prepare_database(prestate_tracer_result, state_overrides)?;
+            trace_transaction_with_database(&tx, latest_block_env, db, &prestate_keys, &tracer_config)
+                .map(ChainTraceResult::Ethereum)
+        }
+    }
+}
+
+fn prepare_database(
+    mut prestate_tracer_result: HashMap<Address, AccountDetails>,
+    state_overrides: HashMap<Address, AccountOverride>,
+) -> Result<(InMemoryDB, PrestateKeys), TraceError> {
+    apply_state_overrides(&mut prestate_tracer_result, state_overrides)?;
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
+    Ok((create_in_memory_database_from_prestate_trace(prestate_tracer_result), prestate_keys))
+}
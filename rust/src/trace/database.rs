@@ -1,17 +1,45 @@
-use serde::Deserialize;
-use revm::database::InMemoryDB;
-use revm::state::{AccountInfo, Bytecode};
+use std::collections::HashSet;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use revm::database::{CacheDB, InMemoryDB};
+use revm::state::{AccountInfo, Bytecode, EvmState};
+use revm::DatabaseCommit;
 use revm::primitives::{Address, StorageKey, StorageValue, Bytes, HashMap, U256};
+use revm::primitives::KECCAK_EMPTY;
+#[cfg(feature = "rpc")]
+use revm::primitives::B256;
+#[cfg(feature = "rpc")]
+use revm::Database;
+#[cfg(feature = "rpc")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "rpc")]
+use serde_json::json;
+
+#[cfg(feature = "rpc")]
+use crate::trace::block::BlockDetails;
+#[cfg(feature = "rpc")]
+use crate::trace::rpc::TraceRpcClient;
+use crate::trace::error::TraceError;
+use crate::trace::precompiles::precompile_name;
 
 /// Account state details from prestate tracer
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AccountDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub nonce: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub code: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub storage: Option<HashMap<StorageKey, StorageValue>>,
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    name = "create_database",
+    skip_all,
+    fields(accounts = prestate_tracer_result.len()),
+))]
 pub fn create_in_memory_database_from_prestate_trace(
     prestate_tracer_result: HashMap<Address, AccountDetails>
 )->InMemoryDB { 
@@ -55,3 +83,435 @@ pub fn create_in_memory_database_from_prestate_trace(
     };
     database
 }
+
+/// Database a trace runs on when sharing a `SharedPrestate`: reads fall through to the
+/// shared prestate, writes stay in this layer
+pub type OverlayDB = CacheDB<Arc<InMemoryDB>>;
+
+/// A prestate database built once and shared read-only between traces
+///
+/// Cloning an `InMemoryDB` copies every account and storage map of the prestate, which
+/// for a bundle touching large contracts dominates the cost of tracing many candidates.
+/// Each trace instead gets an `overlay`, which starts empty and only holds the state that
+/// trace writes.
+#[derive(Debug, Clone)]
+pub struct SharedPrestate {
+    db: Arc<InMemoryDB>,
+    prestate_keys: PrestateKeys,
+}
+
+impl SharedPrestate {
+    pub fn new(prestate_tracer_result: HashMap<Address, AccountDetails>) -> Self {
+        let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
+        let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+        Self { db: Arc::new(db), prestate_keys }
+    }
+
+    /// Accounts and slots of the prestate, for `PrestateKeys::warnings`
+    pub fn prestate_keys(&self) -> &PrestateKeys {
+        &self.prestate_keys
+    }
+
+    /// An empty writable layer on top of the prestate
+    pub fn overlay(&self) -> OverlayDB {
+        CacheDB::new(Arc::clone(&self.db))
+    }
+}
+
+/// Commits the state changes of a trace into `db`, so the next transaction executes on
+/// top of them, e.g. a swap spending an allowance granted by the previous transaction.
+///
+/// `state_diff` is the `state_diff` of a `TraceTransactionResult`. Call
+/// `PrestateKeys::extend_from_state` with it too, so that the accounts it adds are not
+/// reported as missing from the prestate by later traces.
+pub fn apply_state_diff<DB: DatabaseCommit>(db: &mut DB, state_diff: &EvmState) {
+    db.commit(state_diff.clone());
+}
+
+/// Per-account state override, in the format accepted by geth's `debug_traceCall`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage; slots not listed read as zero
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<HashMap<StorageKey, StorageValue>>,
+    /// Patches individual slots, keeping the rest of the storage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<HashMap<StorageKey, StorageValue>>,
+    /// Replaces the code with a stub returning these bytes to every call, e.g. to pin the
+    /// answer of a price oracle. Cannot be combined with `code`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mock_return: Option<Bytes>,
+}
+
+/// Runtime code returning `data` to every call, whatever the calldata and value
+///
+/// # Errors
+///
+/// Returns `TraceError::InvalidParameter` if `data` is longer than 65535 bytes
+pub fn mock_return_code(data: &Bytes) -> Result<Bytes, TraceError> {
+    let len = u16::try_from(data.len()).map_err(|_| TraceError::InvalidParameter(format!(
+        "mock return data of {} bytes exceeds 65535", data.len()
+    )))?;
+    let [len_hi, len_lo] = len.to_be_bytes();
+    // PUSH2 len, DUP1, PUSH2 13, PUSH1 0, CODECOPY, PUSH1 0, RETURN, then the data at offset 13
+    let mut code = vec![0x61, len_hi, len_lo, 0x80, 0x61, 0x00, 0x0d, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3];
+    code.extend_from_slice(data);
+    Ok(code.into())
+}
+
+/// Replaces the code of an account, keeping its balance, nonce and storage
+pub(crate) fn set_account_code(info: &mut AccountInfo, code: Bytes) {
+    if code.is_empty() {
+        info.code_hash = KECCAK_EMPTY;
+        info.code = None;
+    } else {
+        let bytecode = Bytecode::new_raw(code);
+        info.code_hash = bytecode.hash_slow();
+        info.code = Some(bytecode);
+    }
+}
+
+/// Merges `state_overrides` over a prestate before it is turned into a database.
+///
+/// Follows geth semantics: `balance`, `nonce` and `code` replace the account's values,
+/// `state` replaces its entire storage and `stateDiff` patches individual slots.
+/// `mockReturn` replaces the code with `mock_return_code`.
+/// Overridden accounts missing from the prestate are created.
+///
+/// # Errors
+///
+/// Returns `TraceError::InvalidParameter` if an override sets both `state` and `stateDiff`,
+/// or both `code` and `mockReturn`.
+pub fn apply_state_overrides(
+    prestate_tracer_result: &mut HashMap<Address, AccountDetails>,
+    state_overrides: HashMap<Address, AccountOverride>,
+) -> Result<(), TraceError> {
+    for (address, account_override) in state_overrides.into_iter() {
+        if account_override.state.is_some() && account_override.state_diff.is_some() {
+            return Err(TraceError::InvalidParameter(format!(
+                "account {:?} has both state and stateDiff overrides", address
+            )));
+        }
+        if account_override.code.is_some() && account_override.mock_return.is_some() {
+            return Err(TraceError::InvalidParameter(format!(
+                "account {:?} has both code and mockReturn overrides", address
+            )));
+        }
+
+        let details = prestate_tracer_result.entry(address).or_insert(AccountDetails {
+            balance: None,
+            nonce: None,
+            code: None,
+            storage: None,
+        });
+        if let Some(balance) = account_override.balance {
+            details.balance = Some(balance);
+        }
+        if let Some(nonce) = account_override.nonce {
+            details.nonce = Some(nonce);
+        }
+        if let Some(code) = account_override.code {
+            details.code = Some(code);
+        }
+        if let Some(data) = account_override.mock_return {
+            details.code = Some(mock_return_code(&data)?);
+        }
+        if let Some(state) = account_override.state {
+            details.storage = Some(state);
+        }
+        if let Some(state_diff) = account_override.state_diff {
+            details.storage.get_or_insert_with(HashMap::default).extend(state_diff);
+        }
+    }
+    Ok(())
+}
+
+/// Reduces a prestate to the accounts and storage slots that execution actually touched.
+///
+/// `state` is the `state_diff` of a trace run against `prestate_tracer_result`. The
+/// returned prestate is in the same prestateTracer format and replays that trace
+/// identically offline. Touched accounts keep their balance, nonce and code; storage is
+/// limited to loaded slots. Accounts the prestate never contained are left out, since
+/// they were already treated as empty.
+pub fn extract_used_prestate(
+    prestate_tracer_result: &HashMap<Address, AccountDetails>,
+    state: &EvmState,
+) -> HashMap<Address, AccountDetails> {
+    state.iter()
+        .filter_map(|(address, account)| {
+            let details = prestate_tracer_result.get(address)?;
+            let storage = details.storage.as_ref().map(|storage| {
+                storage.iter()
+                    .filter(|(slot, _)| account.storage.contains_key(*slot))
+                    .map(|(slot, value)| (*slot, *value))
+                    .collect()
+            });
+            Some((*address, AccountDetails {
+                balance: details.balance,
+                nonce: details.nonce,
+                code: details.code.clone(),
+                storage,
+            }))
+        })
+        .collect()
+}
+
+/// An account or storage slot that execution accessed but the prestate did not provide.
+/// Such values silently default to empty/zero, so the trace may not match the chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PrestateWarning {
+    MissingAccount { address: Address },
+    MissingStorageSlot { address: Address, slot: StorageKey },
+}
+
+/// The accounts and slots present in a prestate, captured before the prestate is
+/// consumed by `create_in_memory_database_from_prestate_trace`
+#[derive(Debug, Default, Clone)]
+pub struct PrestateKeys {
+    accounts: HashMap<Address, HashSet<StorageKey>>,
+}
+
+impl PrestateKeys {
+    pub fn from_prestate(prestate_tracer_result: &HashMap<Address, AccountDetails>) -> Self {
+        let accounts = prestate_tracer_result.iter()
+            .map(|(address, details)| {
+                let slots = details.storage.as_ref()
+                    .map(|storage| storage.keys().copied().collect())
+                    .unwrap_or_default();
+                (*address, slots)
+            })
+            .collect();
+        Self { accounts }
+    }
+
+    /// Records the accounts and slots of `state` as known, for when it is committed on top
+    /// of the prestate before executing a later transaction.
+    pub fn extend_from_state(&mut self, state: &EvmState) {
+        for (address, account) in state.iter() {
+            self.accounts.entry(*address)
+                .or_default()
+                .extend(account.storage.keys().copied());
+        }
+    }
+
+    /// Records `address` as known, for accounts set up directly in the database
+    pub fn insert_account(&mut self, address: Address) {
+        self.accounts.entry(address).or_default();
+    }
+
+    /// Lists every account/slot in `state` that was loaded without being in the prestate.
+    /// Accounts created during execution are skipped since they have no prior state. The
+    /// block's `beneficiary`, credited with the priority fee, and precompiles are not
+    /// reported as missing accounts, since their state does not affect execution.
+    pub fn warnings(&self, state: &EvmState, beneficiary: Address) -> Vec<PrestateWarning> {
+        let mut warnings = Vec::new();
+        for (address, account) in state.iter() {
+            if account.is_created() {
+                continue;
+            }
+            match self.accounts.get(address) {
+                None => {
+                    if *address != beneficiary && precompile_name(*address).is_none() {
+                        warnings.push(PrestateWarning::MissingAccount { address: *address });
+                    }
+                    for slot in account.storage.keys() {
+                        warnings.push(PrestateWarning::MissingStorageSlot { address: *address, slot: *slot });
+                    }
+                }
+                Some(known_slots) => {
+                    for slot in account.storage.keys() {
+                        if !known_slots.contains(slot) {
+                            warnings.push(PrestateWarning::MissingStorageSlot { address: *address, slot: *slot });
+                        }
+                    }
+                }
+            }
+        }
+        warnings.sort_by_key(|warning| match warning {
+            PrestateWarning::MissingAccount { address } => (*address, None),
+            PrestateWarning::MissingStorageSlot { address, slot } => (*address, Some(*slot)),
+        });
+        warnings
+    }
+}
+
+/// A `Database` that fetches accounts, code and storage lazily from a JSON-RPC node at
+/// a fixed block, caching every value it reads.
+///
+/// Requests are made with a blocking `TraceRpcClient`, so it must not be used from within
+/// an async runtime; wrap the call in `spawn_blocking` there.
+#[cfg(feature = "rpc")]
+pub struct RpcDatabase {
+    client: TraceRpcClient,
+    /// Block tag or hex-encoded number the state is read at
+    block: String,
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    contracts: HashMap<B256, Bytecode>,
+    storage: HashMap<(Address, StorageKey), StorageValue>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+#[cfg(feature = "rpc")]
+impl RpcDatabase {
+    /// Creates a database reading the state at `block`, e.g. `"latest"` or `"0x12a05f2"`
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError::Rpc` if the HTTP client cannot be created
+    pub fn new(rpc_url: impl Into<String>, block: impl Into<String>) -> Result<Self, TraceError> {
+        Ok(Self::with_client(TraceRpcClient::new(rpc_url)?, block))
+    }
+
+    /// Creates a database reading the state at `block` through an existing client
+    pub fn with_client(client: TraceRpcClient, block: impl Into<String>) -> Self {
+        Self {
+            client,
+            block: block.into(),
+            accounts: HashMap::default(),
+            contracts: HashMap::default(),
+            storage: HashMap::default(),
+            block_hashes: HashMap::default(),
+        }
+    }
+
+    /// Fetches the header of the block the state is read at
+    pub fn block_details(&self) -> Result<BlockDetails, TraceError> {
+        Ok(self.client.block_details(&self.block)?)
+    }
+
+    /// Fetches the chain id of the node with `eth_chainId`
+    pub fn chain_id(&self) -> Result<u64, TraceError> {
+        Ok(self.client.chain_id()?)
+    }
+
+    /// Everything fetched from the node so far, as a prestate
+    ///
+    /// Accounts the node reported as empty are left out; only their fetched storage, if
+    /// any, is included.
+    pub fn fetched_prestate(&self) -> HashMap<Address, AccountDetails> {
+        let mut prestate: HashMap<Address, AccountDetails> = HashMap::default();
+        for (address, account) in self.accounts.iter() {
+            let Some(account) = account else {
+                continue;
+            };
+            let code = account.code.as_ref()
+                .map(|code| code.original_bytes())
+                .filter(|code| !code.is_empty());
+            prestate.insert(*address, AccountDetails {
+                balance: Some(account.balance),
+                nonce: Some(account.nonce),
+                code,
+                storage: None,
+            });
+        }
+        for ((address, index), value) in self.storage.iter() {
+            prestate.entry(*address)
+                .or_insert(AccountDetails { balance: None, nonce: None, code: None, storage: None })
+                .storage.get_or_insert_with(HashMap::default)
+                .insert(*index, *value);
+        }
+        prestate
+    }
+
+    /// Seeds the database with state already known at its block, e.g. from a
+    /// `PrestateCache`, so it is not requested again
+    ///
+    /// Accounts without both a balance and a nonce only contribute their storage.
+    pub fn preload(&mut self, prestate: &HashMap<Address, AccountDetails>) {
+        for (address, details) in prestate.iter() {
+            if let (Some(balance), Some(nonce)) = (details.balance, details.nonce) {
+                let (code_hash, code) = match &details.code {
+                    Some(code) if !code.is_empty() => {
+                        let bytecode = Bytecode::new_raw(code.clone());
+                        let code_hash = bytecode.hash_slow();
+                        self.contracts.insert(code_hash, bytecode.clone());
+                        (code_hash, Some(bytecode))
+                    }
+                    _ => (KECCAK_EMPTY, None),
+                };
+                self.accounts.insert(*address, Some(AccountInfo { balance, nonce, code_hash, code }));
+            }
+            for (index, value) in details.storage.iter().flatten() {
+                self.storage.insert((*address, *index), *value);
+            }
+        }
+    }
+
+    fn request<T: DeserializeOwned>(&self, method: &str, params: serde_json::Value) -> Result<T, TraceError> {
+        Ok(self.client.request(method, params)?)
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl Database for RpcDatabase {
+    type Error = TraceError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            return Ok(account.clone());
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%address, block = %self.block, "fetching account from node");
+        let params = json!([address, self.block]);
+        let balance: U256 = self.request("eth_getBalance", params.clone())?;
+        let nonce: U256 = self.request("eth_getTransactionCount", params.clone())?;
+        let code: Bytes = self.request("eth_getCode", params)?;
+
+        let nonce = nonce.try_into()
+            .map_err(|_| TraceError::Database(format!("nonce of {} does not fit in u64", address)))?;
+        // Nodes report missing accounts as empty ones
+        let account = if balance.is_zero() && nonce == 0 && code.is_empty() {
+            None
+        } else {
+            let bytecode = Bytecode::new_raw(code);
+            let code_hash = bytecode.hash_slow();
+            self.contracts.insert(code_hash, bytecode.clone());
+            Some(AccountInfo { balance, nonce, code_hash, code: Some(bytecode) })
+        };
+        self.accounts.insert(address, account.clone());
+        Ok(account)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK_EMPTY {
+            return Ok(Bytecode::default());
+        }
+        // Code is always fetched together with its account, so a miss cannot be resolved
+        self.contracts.get(&code_hash).cloned()
+            .ok_or_else(|| TraceError::Database(format!("unknown code hash {}", code_hash)))
+    }
+
+    fn storage(&mut self, address: Address, index: StorageKey) -> Result<StorageValue, Self::Error> {
+        if let Some(value) = self.storage.get(&(address, index)) {
+            return Ok(*value);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%address, slot = %index, block = %self.block, "fetching storage slot from node");
+        let value: U256 = self.request("eth_getStorageAt", json!([address, index, self.block]))?;
+        self.storage.insert((address, index), value);
+        Ok(value)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        if let Some(hash) = self.block_hashes.get(&number) {
+            return Ok(*hash);
+        }
+        #[derive(Deserialize)]
+        struct BlockHash {
+            hash: B256,
+        }
+        let block: Option<BlockHash> = self.request("eth_getBlockByNumber", json!([U256::from(number), false]))?;
+        let hash = block.map(|block| block.hash).unwrap_or_default();
+        self.block_hashes.insert(number, hash);
+        Ok(hash)
+    }
+}
@@ -0,0 +1,60 @@
+//! Read-after-write dependency analysis across a sequence of traced transactions
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{Address, HashMap, StorageKey};
+
+use crate::trace::trace::TraceTransactionResult;
+
+/// A storage slot written by one transaction and later read by another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageDependency {
+    /// Index of the transaction that last wrote the slot
+    pub writer: usize,
+    /// Index of the transaction that read the written value
+    pub reader: usize,
+    pub address: Address,
+    pub slot: StorageKey,
+}
+
+/// Finds read-after-write storage dependencies between transactions.
+///
+/// `results` must be in execution order and come from transactions executed against a
+/// shared, evolving state (e.g. the members of a bundle). A slot counts as read by a
+/// transaction if execution loaded it at all, since both SLOAD results and SSTORE gas
+/// costs depend on the prior value. Each dependency points at the most recent writer.
+///
+/// Transactions that never appear as each other's writer/reader can be reordered or
+/// executed in parallel without changing storage outcomes.
+pub fn analyze_storage_dependencies<H>(
+    results: &[TraceTransactionResult<H>],
+) -> Vec<StorageDependency> {
+    let mut last_writer: HashMap<(Address, StorageKey), usize> = HashMap::default();
+    let mut dependencies = Vec::new();
+
+    for (index, result) in results.iter().enumerate() {
+        for (address, account) in result.state_diff.iter() {
+            for slot in account.storage.keys() {
+                if let Some(&writer) = last_writer.get(&(*address, *slot)) {
+                    dependencies.push(StorageDependency {
+                        writer,
+                        reader: index,
+                        address: *address,
+                        slot: *slot,
+                    });
+                }
+            }
+        }
+
+        for (address, account) in result.state_diff.iter() {
+            for (slot, value) in account.storage.iter() {
+                if value.is_changed() {
+                    last_writer.insert((*address, *slot), index);
+                }
+            }
+        }
+    }
+
+    dependencies.sort_by_key(|dependency| (dependency.reader, dependency.writer));
+    dependencies
+}
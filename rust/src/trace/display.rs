@@ -0,0 +1,281 @@
+//! Human-readable rendering of call trees, for CLIs and logs
+//!
+//! Each frame is one line: optional gas columns, the frame's place in the tree, its
+//! status, call type, addresses and function. Functions are shown by name and arguments
+//! if the frame was decoded with an `AbiRegistry`, and by selector otherwise.
+//!
+//! ```text
+//!     gas used   self gas
+//!        46109      24309  ✓ CALL 0x1111…1111 → 0x2222…2222 swap(amount=1000)
+//!        21800      21800  └─ ✗ CALL 0x2222…2222 → 0x3333…3333 0xa9059cbb reverted: "insufficient balance"
+//! ```
+//!
+//! `CallFrame`, `LogEntry`, `DecodedRevert` and `TraceTransactionResult` implement
+//! `Display` using `DisplayOptions::default()`.
+
+use std::fmt::{self, Debug, Display, Write};
+
+use serde_json::Value;
+use revm::context::result::ExecutionResult;
+use revm::primitives::{Address, HashMap};
+
+use crate::trace::abi::DecodedParam;
+use crate::trace::inspector::{CallFrame, LogEntry};
+use crate::trace::revert::DecodedRevert;
+use crate::trace::trace::TraceTransactionResult;
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// How frames are placed in the tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DepthMarker {
+    /// Box-drawing branches, e.g. `├─ ` and `│  └─ `
+    #[default]
+    Tree,
+    /// Two spaces per level
+    Indent,
+    /// Call depth in brackets, e.g. `[2] `, indented by two spaces per level
+    Numbered,
+}
+
+/// What `render_call_tree` shows and how
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    pub depth_marker: DepthMarker,
+    /// Color successful frames green and failed ones red with ANSI escapes
+    pub colors: bool,
+    /// Prefix every line with gas used and self gas used columns
+    pub gas_columns: bool,
+    /// List each frame's logs below it
+    pub logs: bool,
+    /// Show addresses in full instead of as `0x1234…abcd`
+    pub full_addresses: bool,
+    /// Frames deeper than this are left out, `None` for no limit
+    pub max_depth: Option<usize>,
+    /// Names shown instead of these addresses, e.g. `WETH`
+    pub labels: HashMap<Address, String>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            depth_marker: DepthMarker::Tree,
+            colors: false,
+            gas_columns: true,
+            logs: false,
+            full_addresses: false,
+            max_depth: None,
+            labels: HashMap::default(),
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Default options with ANSI colors, for terminals
+    pub fn colored() -> Self {
+        Self { colors: true, ..Self::default() }
+    }
+
+    /// Renders `frame` and its subcalls, one line per frame
+    pub fn render(&self, frame: &CallFrame) -> String {
+        render_call_tree(frame, self)
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.colors {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn address(&self, address: &Address) -> String {
+        if let Some(label) = self.labels.get(address) {
+            return label.clone();
+        }
+        let full = address.to_checksum(None);
+        if self.full_addresses {
+            full
+        } else {
+            format!("{}…{}", &full[..6], &full[full.len() - 4..])
+        }
+    }
+}
+
+/// Renders the outcome of a trace and the gas it used on one line, followed by its call tree
+pub fn render_trace_result<H: Debug>(result: &TraceTransactionResult<H>, options: &DisplayOptions) -> String {
+    let status = match &result.execution_result {
+        ExecutionResult::Success { gas_used, .. } => format!("success, gas used {}", gas_used),
+        ExecutionResult::Revert { gas_used, .. } => format!("reverted, gas used {}", gas_used),
+        ExecutionResult::Halt { reason, gas_used } => format!("halted ({:?}), gas used {}", reason, gas_used),
+    };
+    let color = if result.execution_result.is_success() { GREEN } else { RED };
+    format!("{}\n{}", options.paint(&status, color), render_call_tree(&result.calls, options))
+}
+
+/// Renders `frame` and its subcalls as a tree, one line per frame
+pub fn render_call_tree(frame: &CallFrame, options: &DisplayOptions) -> String {
+    let mut out = String::new();
+    if options.gas_columns {
+        let header = format!("{:>10} {:>10}", "gas used", "self gas");
+        let _ = writeln!(out, "{}", options.paint(&header, DIM));
+    }
+    write_frame(&mut out, frame, options, 1, "", None);
+    out
+}
+
+/// Writes a frame and its subcalls. `prefix` holds the tree branches of the frame's
+/// ancestors; `last` is whether the frame is its parent's last subcall, `None` for the
+/// top-level frame.
+fn write_frame(
+    out: &mut String,
+    frame: &CallFrame,
+    options: &DisplayOptions,
+    depth: usize,
+    prefix: &str,
+    last: Option<bool>,
+) {
+    if options.gas_columns {
+        let gas = format!("{:>10} {:>10}  ", frame.gas_used, frame.self_gas_used);
+        out.push_str(&options.paint(&gas, DIM));
+    }
+
+    let (marker, child_prefix) = match (options.depth_marker, last) {
+        (DepthMarker::Tree, None) => (String::new(), String::new()),
+        (DepthMarker::Tree, Some(last)) => (
+            format!("{}{}", prefix, if last { "└─ " } else { "├─ " }),
+            format!("{}{}", prefix, if last { "   " } else { "│  " }),
+        ),
+        (DepthMarker::Indent, _) => ("  ".repeat(depth - 1), String::new()),
+        (DepthMarker::Numbered, _) => (format!("{}[{}] ", "  ".repeat(depth - 1), depth), String::new()),
+    };
+    out.push_str(&marker);
+
+    let status = if frame.error.is_none() {
+        options.paint("✓", GREEN)
+    } else {
+        options.paint("✗", RED)
+    };
+    let _ = write!(out, "{} {}", status, frame.call_type);
+    let _ = write!(out, " {}", options.address(&frame.from));
+    if let Some(to) = &frame.to {
+        let _ = write!(out, " → {}", options.address(to));
+    }
+    if let Some(function) = function_name(frame) {
+        let _ = write!(out, " {}", function);
+    }
+    if !frame.value.is_zero() {
+        let _ = write!(out, " value={}", frame.value);
+    }
+    if let Some(error) = &frame.error {
+        let reason = match &frame.decoded_revert {
+            Some(revert) => format!("{}: {}", error, revert),
+            None => error.clone(),
+        };
+        let _ = write!(out, " {}", options.paint(&reason, RED));
+    }
+    out.push('\n');
+
+    let detail_prefix = match options.depth_marker {
+        DepthMarker::Tree if frame.calls.is_empty() => format!("{}   ", child_prefix),
+        DepthMarker::Tree => format!("{}│  ", child_prefix),
+        _ => "  ".repeat(depth),
+    };
+    if options.logs {
+        for log in &frame.logs {
+            if options.gas_columns {
+                out.push_str(&" ".repeat(23));
+            }
+            let _ = writeln!(out, "{}{}", detail_prefix, log);
+        }
+    }
+
+    if options.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        if !frame.calls.is_empty() {
+            if options.gas_columns {
+                out.push_str(&" ".repeat(23));
+            }
+            let _ = writeln!(out, "{}… {} subcalls", detail_prefix, frame.calls.len());
+        }
+        return;
+    }
+    for (i, call) in frame.calls.iter().enumerate() {
+        let last = i + 1 == frame.calls.len();
+        write_frame(out, call, options, depth + 1, &child_prefix, Some(last));
+    }
+}
+
+/// The decoded function with its arguments, the precompile name, or the raw selector
+fn function_name(frame: &CallFrame) -> Option<String> {
+    if let Some(decoded) = &frame.decoded_input {
+        return Some(format!("{}({})", decoded.name, params(&decoded.args)));
+    }
+    if let Some(precompile) = &frame.precompile {
+        return Some(precompile.clone());
+    }
+    if frame.call_type == "CREATE" || frame.call_type == "CREATE2" {
+        return None;
+    }
+    match frame.input.get(..4) {
+        Some(selector) => Some(format!("0x{}", hex::encode(selector))),
+        None if frame.to.is_some() && frame.call_type != "SELFDESTRUCT" => Some("fallback()".to_string()),
+        None => None,
+    }
+}
+
+fn params(params: &[DecodedParam]) -> String {
+    params
+        .iter()
+        .map(|param| {
+            let value = match &param.value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            if param.name.is_empty() {
+                value
+            } else {
+                format!("{}={}", param.name, value)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Display for CallFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&render_call_tree(self, &DisplayOptions::default()))
+    }
+}
+
+impl Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "emit ")?;
+        match &self.decoded_log {
+            Some(decoded) => write!(f, "{}({})", decoded.name, params(&decoded.args))?,
+            None => {
+                let topics = self.topics.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "[{}] {}", topics.join(", "), self.data)?;
+            }
+        }
+        write!(f, " from {}", self.address.to_checksum(None))
+    }
+}
+
+impl Display for DecodedRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodedRevert::Error { message } => write!(f, "{:?}", message),
+            DecodedRevert::Panic { code, description } => write!(f, "panic {:#x} ({})", code, description),
+            DecodedRevert::Custom { selector } => write!(f, "custom error {}", selector),
+        }
+    }
+}
+
+impl<H: Debug> Display for TraceTransactionResult<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&render_trace_result(self, &DisplayOptions::default()))
+    }
+}
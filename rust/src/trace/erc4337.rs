@@ -0,0 +1,351 @@
+//! ERC-4337 account abstraction helpers
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::HaltReason;
+use revm::context::BlockEnv;
+use revm::handler::instructions::EthInstructions;
+use revm::handler::EthPrecompiles;
+use revm::primitives::{Address, Bytes, HashMap, B256, U256};
+use revm::{Context, InspectEvm, MainContext, MainnetEvm};
+#[cfg(feature = "op")]
+use op_revm::{L1BlockInfo, OpSpecId};
+
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, AccountDetails, PrestateKeys};
+use crate::trace::error::{ExecutionError, TraceError};
+use crate::trace::fingerprint::calls_fingerprint;
+use crate::trace::inspector::{CallFrame, CallTracerConfig, ValidationRulesTracer, ValidationViolations};
+use crate::trace::trace::{prepare_mainnet_execution, trace_transaction_with_database, TraceTransactionResult, TxParams, TxSpec};
+
+/// Placeholder preVerificationGas used while packing a user operation for gas calculation
+const PVG_PLACEHOLDER: u64 = 21_000;
+
+/// Selector of EntryPoint v0.7 `handleOps(PackedUserOperation[],address)`
+const HANDLE_OPS_SELECTOR: [u8; 4] = [0x76, 0x5e, 0x82, 0x7f];
+
+/// An EntryPoint v0.7 `PackedUserOperation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    /// `verificationGasLimit` (high 128 bits) and `callGasLimit` (low 128 bits)
+    pub account_gas_limits: B256,
+    pub pre_verification_gas: U256,
+    /// `maxPriorityFeePerGas` (high 128 bits) and `maxFeePerGas` (low 128 bits)
+    pub gas_fees: B256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl PackedUserOperation {
+    /// ABI-encodes the user operation as a single tuple argument, matching its layout
+    /// inside `handleOps` calldata.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let dynamic_fields = [
+            &self.init_code,
+            &self.call_data,
+            &self.paymaster_and_data,
+            &self.signature,
+        ];
+
+        // Head is 9 words; dynamic fields are appended to the tail in declaration order
+        let mut offsets = [0usize; 4];
+        let mut tail = Vec::new();
+        for (offset, field) in offsets.iter_mut().zip(dynamic_fields) {
+            *offset = 9 * 32 + tail.len();
+            encode_bytes(field, &mut tail);
+        }
+
+        let mut encoded = Vec::with_capacity(32 + 9 * 32 + tail.len());
+        encoded.extend_from_slice(&uint_word(U256::from(32)));
+        encoded.extend_from_slice(&address_word(self.sender));
+        encoded.extend_from_slice(&uint_word(self.nonce));
+        encoded.extend_from_slice(&uint_word(U256::from(offsets[0])));
+        encoded.extend_from_slice(&uint_word(U256::from(offsets[1])));
+        encoded.extend_from_slice(self.account_gas_limits.as_slice());
+        encoded.extend_from_slice(&uint_word(self.pre_verification_gas));
+        encoded.extend_from_slice(self.gas_fees.as_slice());
+        encoded.extend_from_slice(&uint_word(U256::from(offsets[2])));
+        encoded.extend_from_slice(&uint_word(U256::from(offsets[3])));
+        encoded.extend_from_slice(&tail);
+        encoded
+    }
+
+    /// `maxFeePerGas`, the low 128 bits of `gas_fees`
+    pub fn max_fee_per_gas(&self) -> u128 {
+        u128::from_be_bytes(self.gas_fees[16..].try_into().expect("16 bytes"))
+    }
+
+    /// `maxPriorityFeePerGas`, the high 128 bits of `gas_fees`
+    pub fn max_priority_fee_per_gas(&self) -> u128 {
+        u128::from_be_bytes(self.gas_fees[..16].try_into().expect("16 bytes"))
+    }
+
+    /// Encodes a `handleOps([self], beneficiary)` call
+    pub fn handle_ops_calldata(&self, beneficiary: Address) -> Bytes {
+        let encoded = self.abi_encode();
+        let mut calldata = Vec::with_capacity(4 + 4 * 32 + encoded.len());
+        calldata.extend_from_slice(&HANDLE_OPS_SELECTOR);
+        calldata.extend_from_slice(&uint_word(U256::from(64)));
+        calldata.extend_from_slice(&address_word(beneficiary));
+        // Array of one element: length, offset of the element, then the tuple itself
+        calldata.extend_from_slice(&uint_word(U256::from(1)));
+        calldata.extend_from_slice(&uint_word(U256::from(32)));
+        calldata.extend_from_slice(&encoded[32..]);
+        Bytes::from(calldata)
+    }
+}
+
+/// Trace of a user operation, with the EntryPoint's subcalls split by phase
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationTrace {
+    /// Trace of the whole `handleOps` transaction
+    pub result: TraceTransactionResult<HaltReason>,
+    /// Account deployment, `validateUserOp` and `validatePaymasterUserOp` calls
+    pub validation_calls: Vec<CallFrame>,
+    /// Calls made by the EntryPoint's inner call: the account call and the paymaster's `postOp`
+    pub execution_calls: Vec<CallFrame>,
+}
+
+impl UserOperationTrace {
+    /// Splits the top-level subcalls of `handleOps` around the EntryPoint's call into
+    /// itself (`innerHandleOp`), which runs the execution phase. Everything before it
+    /// belongs to validation; if validation fails, there is no execution phase.
+    fn from_result(entry_point: Address, result: TraceTransactionResult<HaltReason>) -> Self {
+        let top_calls = &result.calls.calls;
+        let inner = top_calls.iter()
+            .position(|call| call.from == entry_point && call.to == Some(entry_point));
+        let validation_calls = top_calls[..inner.unwrap_or(top_calls.len())].to_vec();
+        let execution_calls = inner
+            .map(|index| top_calls[index].calls.clone())
+            .unwrap_or_default();
+        Self { result, validation_calls, execution_calls }
+    }
+
+    /// Fingerprint of the validation phase, see `fingerprint::calls_fingerprint`. If it
+    /// differs between simulations of the same user operation, its validation depends on
+    /// state that changed in between.
+    pub fn validation_fingerprint(&self) -> B256 {
+        calls_fingerprint(&self.validation_calls)
+    }
+}
+
+/// Traces a user operation as a bundler would submit it: in a `handleOps` transaction
+/// sent by `bundler` to an EntryPoint v0.7, with the bundler as beneficiary.
+///
+/// The transaction pays the user operation's fees and uses the block gas limit. The
+/// bundler's balance is topped up in the prestate so the transaction can always pay for
+/// gas; its nonce is taken from the prestate.
+///
+/// # Arguments
+///
+/// * `chain_id` - Chain the user operation is signed for
+/// * `entry_point` - EntryPoint contract address
+/// * `bundler` - Sender of the bundle transaction
+/// * `user_op` - The user operation to trace
+/// * `prestate_tracer_result` - Account states before execution
+/// * `latest_block_env` - Block environment for execution
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be built or executed; a failing user
+/// operation is a reverted `handleOps`, not an error.
+pub fn trace_user_operation(
+    chain_id: u64,
+    entry_point: Address,
+    bundler: Address,
+    user_op: &PackedUserOperation,
+    mut prestate_tracer_result: HashMap<Address, AccountDetails>,
+    latest_block_env: BlockEnv,
+) -> Result<UserOperationTrace, TraceError> {
+    let params = handle_ops_params(chain_id, entry_point, bundler, user_op, &mut prestate_tracer_result, &latest_block_env);
+
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+    let result = trace_transaction_with_database(&params, latest_block_env, db, &prestate_keys, &CallTracerConfig::default())?;
+
+    Ok(UserOperationTrace::from_result(entry_point, result))
+}
+
+/// Bundle overhead constants used by `calculate_pre_verification_gas`.
+///
+/// The defaults match the reference bundler's `DefaultGasOverheads`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasOverheads {
+    /// Fixed transaction cost, shared by all user operations in the bundle
+    pub fixed: u64,
+    /// Per-user-operation cost added to the bundle
+    pub per_user_op: u64,
+    /// Per-word cost of the packed user operation
+    pub per_user_op_word: u64,
+    pub zero_byte: u64,
+    pub non_zero_byte: u64,
+    /// Expected number of user operations sharing the fixed cost
+    pub bundle_size: u64,
+    /// Length of the dummy signature used when the user operation is not yet signed
+    pub sig_size: usize,
+}
+
+impl Default for GasOverheads {
+    fn default() -> Self {
+        Self {
+            fixed: 21_000,
+            per_user_op: 18_300,
+            per_user_op_word: 4,
+            zero_byte: 4,
+            non_zero_byte: 16,
+            bundle_size: 1,
+            sig_size: 65,
+        }
+    }
+}
+
+/// Packs the user operation the way `calculate_pre_verification_gas` prices it: with a
+/// placeholder preVerificationGas and a dummy signature if none is present yet.
+fn pack_for_gas_calculation(user_op: &PackedUserOperation, overheads: &GasOverheads) -> Vec<u8> {
+    let mut user_op = user_op.clone();
+    user_op.pre_verification_gas = U256::from(PVG_PLACEHOLDER);
+    if user_op.signature.is_empty() {
+        user_op.signature = Bytes::from(vec![1u8; overheads.sig_size]);
+    }
+    user_op.abi_encode()
+}
+
+/// Computes the L1-independent preVerificationGas of a user operation: calldata byte
+/// costs of the packed operation plus its share of the bundle overhead.
+pub fn calculate_pre_verification_gas(user_op: &PackedUserOperation, overheads: &GasOverheads) -> u64 {
+    let packed = pack_for_gas_calculation(user_op, overheads);
+    let call_data_cost: u64 = packed.iter()
+        .map(|byte| if *byte == 0 { overheads.zero_byte } else { overheads.non_zero_byte })
+        .sum();
+    let length_in_words = packed.len().div_ceil(32) as u64;
+
+    call_data_cost
+        + overheads.fixed / overheads.bundle_size.max(1)
+        + overheads.per_user_op
+        + overheads.per_user_op_word * length_in_words
+}
+
+/// Computes preVerificationGas for OP Stack chains, where the bundler must additionally
+/// recover the L1 data fee of posting the user operation.
+///
+/// The L1 fee is estimated from the packed user operation with `l1_block_info` and
+/// converted to L2 gas at `l2_gas_price` (rounded up).
+#[cfg(feature = "op")]
+pub fn calculate_pre_verification_gas_op(
+    user_op: &PackedUserOperation,
+    overheads: &GasOverheads,
+    l1_block_info: &mut L1BlockInfo,
+    op_spec: OpSpecId,
+    l2_gas_price: u128,
+) -> u64 {
+    let l2_component = calculate_pre_verification_gas(user_op, overheads);
+    let packed = pack_for_gas_calculation(user_op, overheads);
+    let l1_fee = l1_block_info.calculate_tx_l1_cost(&packed, op_spec);
+    let l1_component = l1_fee.div_ceil(U256::from(l2_gas_price.max(1)));
+
+    l2_component.saturating_add(l1_component.try_into().unwrap_or(u64::MAX))
+}
+
+fn encode_bytes(data: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&uint_word(U256::from(data.len())));
+    out.extend_from_slice(data);
+    out.resize(out.len() + (32 - data.len() % 32) % 32, 0);
+}
+
+fn uint_word(value: U256) -> [u8; 32] {
+    value.to_be_bytes::<32>()
+}
+
+fn address_word(address: Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address.as_slice());
+    word
+}
+
+/// Checks the validation phase of a user operation against the ERC-7562 rules: banned
+/// opcodes, `GAS` outside of calls, deployments and storage access outside the sender's
+/// associated storage.
+///
+/// The user operation is executed in a `handleOps` transaction exactly as in
+/// `trace_user_operation`. Factory and paymaster are treated as unstaked.
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be built or executed
+pub fn check_user_operation_validation(
+    chain_id: u64,
+    entry_point: Address,
+    bundler: Address,
+    user_op: &PackedUserOperation,
+    mut prestate_tracer_result: HashMap<Address, AccountDetails>,
+    latest_block_env: BlockEnv,
+) -> Result<ValidationViolations, TraceError> {
+    let params = handle_ops_params(chain_id, entry_point, bundler, user_op, &mut prestate_tracer_result, &latest_block_env);
+    let (tx, cfg_env) = prepare_mainnet_execution(&params, &latest_block_env)?;
+
+    let paymaster = user_op.paymaster_and_data.get(..20).map(Address::from_slice);
+    let context = Context::mainnet()
+        .with_db(create_in_memory_database_from_prestate_trace(prestate_tracer_result))
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env);
+
+    let mut my_evm = MainnetEvm::new_with_inspector(
+        context,
+        ValidationRulesTracer::new(entry_point, user_op.sender, paymaster),
+        EthInstructions::new_mainnet(),
+        EthPrecompiles::default()
+    );
+
+    my_evm.inspect_one_tx(tx)
+        .map_err(ExecutionError::from)?;
+
+    Ok(my_evm.inspector.into_result())
+}
+
+/// Builds the `handleOps` transaction carrying `user_op` and tops up the bundler's
+/// balance in the prestate so it can pay for it.
+pub(crate) fn handle_ops_params(
+    chain_id: u64,
+    entry_point: Address,
+    bundler: Address,
+    user_op: &PackedUserOperation,
+    prestate_tracer_result: &mut HashMap<Address, AccountDetails>,
+    latest_block_env: &BlockEnv,
+) -> TxParams {
+    let params = TxParams {
+        chain_id,
+        from: bundler,
+        from_nonce: prestate_tracer_result.get(&bundler)
+            .and_then(|details| details.nonce)
+            .unwrap_or(0),
+        to: entry_point,
+        value: U256::ZERO,
+        data: user_op.handle_ops_calldata(bundler),
+        gas_limit: latest_block_env.gas_limit,
+        gas_price: user_op.max_fee_per_gas(),
+        gas_priority_fee: user_op.max_priority_fee_per_gas(),
+        tx_spec: TxSpec::Eip1559,
+        access_list: None,
+        blob_versioned_hashes: Vec::new(),
+        max_fee_per_blob_gas: 0,
+        authorization_list: Vec::new(),
+        deposit: None,
+        spec: None,
+    };
+
+    let required_balance = U256::from(params.gas_limit) * U256::from(params.gas_price);
+    let bundler_details = prestate_tracer_result.entry(bundler).or_insert(AccountDetails {
+        balance: None,
+        nonce: None,
+        code: None,
+        storage: None,
+    });
+    bundler_details.balance = Some(bundler_details.balance.unwrap_or_default().max(required_balance));
+
+    params
+}
@@ -1,9 +1,19 @@
 //! Error types for the REVM tracer
 
 use std::fmt;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use revm::context::result::{EVMError, InvalidHeader, InvalidTransaction};
 use revm::context::tx::TxEnvBuildError;
+use revm::database_interface::DBErrorMarker;
 use revm::primitives::ruint::FromUintError;
+#[cfg(feature = "op")]
 use op_revm::transaction::abstraction::OpBuildError;
+#[cfg(feature = "op")]
+use op_revm::OpTransactionError;
+
+use crate::trace::database::PrestateWarning;
+use crate::trace::inspector::CallFrame;
 
 /// Main error type for tracing operations
 #[derive(Debug)]
@@ -11,9 +21,10 @@ pub enum TraceError {
     /// Error building transaction environment
     TxEnvBuild(TxEnvBuildError),
     /// Error building Optimism transaction
+    #[cfg(feature = "op")]
     OpTxBuild(OpBuildError),
     /// Error executing transaction
-    Execution(String),
+    Execution(ExecutionError),
     /// Error converting block details
     BlockConversion(FromUintError<u64>),
     /// Error parsing address
@@ -22,21 +33,247 @@ pub enum TraceError {
     InvalidHexData(String),
     /// Error parsing JSON
     JsonParse(serde_json::Error),
+    /// Error encoding or decoding MessagePack
+    MessagePack(String),
     /// No trace result available
     NoTraceResult,
+    /// Error validating a caller-supplied parameter
+    InvalidParameter(String),
+    /// Error reading or writing a file
+    Io(std::io::Error),
+    /// Fee caps rejected before execution
+    FeeValidation(FeeValidationError),
+    /// Error reading state from the backing database
+    Database(String),
+    /// Error talking to a JSON-RPC node
+    #[cfg(feature = "rpc")]
+    Rpc(RpcClientError),
+    /// The trace was aborted through a `CancellationHandle`; holds the call frames
+    /// collected until then
+    Cancelled(Option<Box<CallFrame>>),
+    /// Execution accessed accounts or slots missing from the prestate, with
+    /// `CallTracerConfig::strict_prestate` set
+    IncompletePrestate(Vec<PrestateWarning>),
+}
+
+/// Stable, machine-readable category of a `TraceError`
+///
+/// Serialized in SCREAMING_SNAKE_CASE as the `code` of the JSON error object. Codes are
+/// only ever added, so clients can branch on them across versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TraceErrorCode {
+    /// The transaction environment could not be built from the parameters
+    InvalidTransaction,
+    /// The EVM failed to execute the transaction for a reason other than the ones below
+    ExecutionFailed,
+    /// The transaction is not valid in its block (nonce, balance, gas limit, fee caps, ...)
+    /// or the block environment is not valid
+    TransactionRejected,
+    /// The block details do not fit the block environment
+    InvalidBlock,
+    InvalidAddress,
+    InvalidHexData,
+    /// An input is not valid JSON or does not have the expected shape
+    InvalidJson,
+    /// A result could not be encoded as MessagePack, or MessagePack input could not be
+    /// decoded
+    InvalidMessagePack,
+    NoTraceResult,
+    InvalidParameter,
+    Io,
+    /// The EIP-1559 fee caps are inconsistent or below the base fee
+    InvalidFees,
+    /// State could not be read from the backing database
+    Database,
+    /// A request to a JSON-RPC node failed
+    Rpc,
+    /// The trace was cancelled by the host
+    Cancelled,
+    /// The request could not be decoded; only returned by the C ABI
+    InvalidRequest,
+    /// The tracer panicked; only returned by the C ABI
+    Internal,
+    /// The prestate misses state the transaction accessed, in strict prestate mode
+    IncompletePrestate,
+}
+
+impl fmt::Display for TraceErrorCode {
+    /// Writes the code as serialized, e.g. `INVALID_JSON`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(code)) => f.write_str(&code),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+/// Typed failure of the EVM to execute a transaction, from revm's `EVMError`
+///
+/// A revert or halt is not an `ExecutionError`: it is a successful trace whose
+/// `execution_result` says so.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionError {
+    /// The transaction is not valid in its block: nonce mismatch, insufficient funds, gas
+    /// limit above the block's, fee caps below the base fee, ...
+    InvalidTransaction(InvalidTransaction),
+    /// An OP Stack specific validation failed, e.g. a deposit system transaction after Regolith
+    #[cfg(feature = "op")]
+    InvalidOpTransaction(OpTransactionError),
+    /// The block environment misses a field required by the hardfork
+    InvalidHeader(InvalidHeader),
+    /// The database failed to read state
+    Database(String),
+    /// Any other failure reported by revm
+    Custom(String),
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::InvalidTransaction(e) => write!(f, "invalid transaction: {}", e),
+            #[cfg(feature = "op")]
+            ExecutionError::InvalidOpTransaction(e) => write!(f, "invalid transaction: {}", e),
+            ExecutionError::InvalidHeader(e) => write!(f, "invalid block header: {}", e),
+            ExecutionError::Database(msg) => write!(f, "database error: {}", msg),
+            ExecutionError::Custom(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+impl<DB: fmt::Display> From<EVMError<DB>> for ExecutionError {
+    fn from(error: EVMError<DB>) -> Self {
+        match error {
+            EVMError::Transaction(e) => ExecutionError::InvalidTransaction(e),
+            EVMError::Header(e) => ExecutionError::InvalidHeader(e),
+            EVMError::Database(e) => ExecutionError::Database(e.to_string()),
+            other => ExecutionError::Custom(other.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "op")]
+impl<DB: fmt::Display> From<EVMError<DB, OpTransactionError>> for ExecutionError {
+    fn from(error: EVMError<DB, OpTransactionError>) -> Self {
+        match error {
+            EVMError::Transaction(OpTransactionError::Base(e)) => ExecutionError::InvalidTransaction(e),
+            EVMError::Transaction(e) => ExecutionError::InvalidOpTransaction(e),
+            EVMError::Header(e) => ExecutionError::InvalidHeader(e),
+            EVMError::Database(e) => ExecutionError::Database(e.to_string()),
+            other => ExecutionError::Custom(other.to_string()),
+        }
+    }
+}
+
+/// Invalid combination of EIP-1559 fee caps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeeValidationError {
+    /// `maxPriorityFeePerGas` exceeds `maxFeePerGas`
+    PriorityFeeAboveMaxFee {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+    /// `maxFeePerGas` is below the block base fee
+    MaxFeeBelowBaseFee {
+        max_fee_per_gas: u128,
+        basefee: u64,
+    },
+}
+
+impl fmt::Display for FeeValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeValidationError::PriorityFeeAboveMaxFee { max_fee_per_gas, max_priority_fee_per_gas } => write!(
+                f,
+                "maxPriorityFeePerGas ({}) is greater than maxFeePerGas ({})",
+                max_priority_fee_per_gas, max_fee_per_gas
+            ),
+            FeeValidationError::MaxFeeBelowBaseFee { max_fee_per_gas, basefee } => write!(
+                f,
+                "maxFeePerGas ({}) is less than the block base fee ({})",
+                max_fee_per_gas, basefee
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FeeValidationError {}
+
+/// Failure of a request made by `TraceRpcClient`
+#[cfg(feature = "rpc")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpcClientError {
+    /// The HTTP client could not be created
+    Client(String),
+    /// The request could not be sent, or no response arrived before the timeout
+    Transport(String),
+    /// The node answered with a non-success HTTP status
+    Status(u16),
+    /// The node answered with a JSON-RPC error object
+    Rpc { code: i64, message: String },
+    /// The result did not have the expected shape
+    Decode(String),
+    /// The requested block or transaction does not exist
+    NotFound(String),
+}
+
+#[cfg(feature = "rpc")]
+impl RpcClientError {
+    /// Whether the request may succeed when sent again: transport failures, rate
+    /// limiting and server-side HTTP errors
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RpcClientError::Transport(_) => true,
+            RpcClientError::Status(status) => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl fmt::Display for RpcClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcClientError::Client(msg) => write!(f, "failed to create HTTP client: {}", msg),
+            RpcClientError::Transport(msg) => write!(f, "request failed: {}", msg),
+            RpcClientError::Status(status) => write!(f, "HTTP error {}", status),
+            RpcClientError::Rpc { code, message } => write!(f, "RPC error {}: {}", code, message),
+            RpcClientError::Decode(msg) => write!(f, "unexpected response: {}", msg),
+            RpcClientError::NotFound(what) => write!(f, "{} not found", what),
+        }
+    }
 }
 
+#[cfg(feature = "rpc")]
+impl std::error::Error for RpcClientError {}
+
 impl fmt::Display for TraceError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TraceError::TxEnvBuild(e) => write!(f, "Failed to build transaction environment: {:?}", e),
+            #[cfg(feature = "op")]
             TraceError::OpTxBuild(e) => write!(f, "Failed to build Optimism transaction: {:?}", e),
             TraceError::Execution(msg) => write!(f, "Transaction execution failed: {}", msg),
             TraceError::BlockConversion(e) => write!(f, "Failed to convert block details: {}", e),
             TraceError::InvalidAddress(addr) => write!(f, "Invalid address: {}", addr),
             TraceError::InvalidHexData(data) => write!(f, "Invalid hex data: {}", data),
             TraceError::JsonParse(e) => write!(f, "Failed to parse JSON: {}", e),
+            TraceError::MessagePack(msg) => write!(f, "MessagePack error: {}", msg),
             TraceError::NoTraceResult => write!(f, "No trace result available from inspector"),
+            TraceError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
+            TraceError::Io(e) => write!(f, "I/O error: {}", e),
+            TraceError::FeeValidation(e) => write!(f, "Invalid fee caps: {}", e),
+            TraceError::Database(msg) => write!(f, "Database error: {}", msg),
+            #[cfg(feature = "rpc")]
+            TraceError::Rpc(e) => write!(f, "JSON-RPC error: {}", e),
+            TraceError::Cancelled(_) => write!(f, "Trace cancelled"),
+            TraceError::IncompletePrestate(warnings) => write!(
+                f,
+                "Prestate is missing {} accounts or storage slots accessed by the transaction",
+                warnings.len()
+            ),
         }
     }
 }
@@ -46,11 +283,61 @@ impl std::error::Error for TraceError {
         match self {
             TraceError::BlockConversion(e) => Some(e),
             TraceError::JsonParse(e) => Some(e),
+            TraceError::Io(e) => Some(e),
+            TraceError::FeeValidation(e) => Some(e),
+            TraceError::Execution(e) => Some(e),
+            #[cfg(feature = "rpc")]
+            TraceError::Rpc(e) => Some(e),
             _ => None,
         }
     }
 }
 
+impl TraceError {
+    /// Machine-readable category of the error
+    pub fn code(&self) -> TraceErrorCode {
+        match self {
+            TraceError::TxEnvBuild(_) => TraceErrorCode::InvalidTransaction,
+            #[cfg(feature = "op")]
+            TraceError::OpTxBuild(_) => TraceErrorCode::InvalidTransaction,
+            TraceError::Execution(ExecutionError::InvalidTransaction(_) | ExecutionError::InvalidHeader(_)) => {
+                TraceErrorCode::TransactionRejected
+            }
+            #[cfg(feature = "op")]
+            TraceError::Execution(ExecutionError::InvalidOpTransaction(_)) => TraceErrorCode::TransactionRejected,
+            TraceError::Execution(ExecutionError::Database(_)) => TraceErrorCode::Database,
+            TraceError::Execution(ExecutionError::Custom(_)) => TraceErrorCode::ExecutionFailed,
+            TraceError::BlockConversion(_) => TraceErrorCode::InvalidBlock,
+            TraceError::InvalidAddress(_) => TraceErrorCode::InvalidAddress,
+            TraceError::InvalidHexData(_) => TraceErrorCode::InvalidHexData,
+            TraceError::JsonParse(_) => TraceErrorCode::InvalidJson,
+            TraceError::MessagePack(_) => TraceErrorCode::InvalidMessagePack,
+            TraceError::NoTraceResult => TraceErrorCode::NoTraceResult,
+            TraceError::InvalidParameter(_) => TraceErrorCode::InvalidParameter,
+            TraceError::Io(_) => TraceErrorCode::Io,
+            TraceError::FeeValidation(_) => TraceErrorCode::InvalidFees,
+            TraceError::Database(_) => TraceErrorCode::Database,
+            #[cfg(feature = "rpc")]
+            TraceError::Rpc(_) => TraceErrorCode::Rpc,
+            TraceError::Cancelled(_) => TraceErrorCode::Cancelled,
+            TraceError::IncompletePrestate(_) => TraceErrorCode::IncompletePrestate,
+        }
+    }
+}
+
+/// Serializes as `{"code": ..., "message": ...}`, the message being the `Display` output
+impl Serialize for TraceError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TraceError", 2)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Lets `TraceError` be returned by `Database` implementations such as `RpcDatabase`
+impl DBErrorMarker for TraceError {}
+
 // Conversion implementations for ergonomic error handling
 
 impl From<TxEnvBuildError> for TraceError {
@@ -59,12 +346,19 @@ impl From<TxEnvBuildError> for TraceError {
     }
 }
 
+#[cfg(feature = "op")]
 impl From<OpBuildError> for TraceError {
     fn from(error: OpBuildError) -> Self {
         TraceError::OpTxBuild(error)
     }
 }
 
+impl From<ExecutionError> for TraceError {
+    fn from(error: ExecutionError) -> Self {
+        TraceError::Execution(error)
+    }
+}
+
 impl From<FromUintError<u64>> for TraceError {
     fn from(error: FromUintError<u64>) -> Self {
         TraceError::BlockConversion(error)
@@ -77,6 +371,25 @@ impl From<serde_json::Error> for TraceError {
     }
 }
 
+impl From<FeeValidationError> for TraceError {
+    fn from(error: FeeValidationError) -> Self {
+        TraceError::FeeValidation(error)
+    }
+}
+
+#[cfg(feature = "rpc")]
+impl From<RpcClientError> for TraceError {
+    fn from(error: RpcClientError) -> Self {
+        TraceError::Rpc(error)
+    }
+}
+
+impl From<std::io::Error> for TraceError {
+    fn from(error: std::io::Error) -> Self {
+        TraceError::Io(error)
+    }
+}
+
 impl From<hex::FromHexError> for TraceError {
     fn from(error: hex::FromHexError) -> Self {
         TraceError::InvalidHexData(error.to_string())
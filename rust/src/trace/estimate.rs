@@ -0,0 +1,108 @@
+//! Gas limit estimation by binary search over plain executions
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::{ExecutionResult, HaltReason};
+use revm::context::BlockEnv;
+use revm::database::InMemoryDB;
+use revm::primitives::{Address, HashMap};
+
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, AccountDetails};
+use crate::trace::error::TraceError;
+use crate::trace::revert::{decode_revert_reason, DecodedRevert};
+use crate::trace::trace::{execute_transaction_with_database, TxParams};
+
+/// Cost of the cheapest possible transaction; no lower gas limit can succeed
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+/// Result of `estimate_gas`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasEstimate {
+    /// Lowest gas limit at which the transaction succeeds; the cap when it never does
+    pub gas_limit: u64,
+    /// Gas used when executed with `gas_limit`
+    pub gas_used: u64,
+    /// Whether the transaction reverts or halts even with the cap
+    pub reverted: bool,
+    /// Decoded revert data when the transaction reverts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert: Option<DecodedRevert>,
+}
+
+/// Finds the lowest gas limit at which a transaction succeeds, like `eth_estimateGas`.
+///
+/// `params.gas_limit` is the upper bound, capped to the block gas limit. The transaction
+/// is first run at the cap; if it fails there it fails everywhere and is reported as
+/// reverted. Otherwise the limit is binary-searched between the gas it used and the cap,
+/// since calls forwarding 63/64 of their gas can need more than they end up using. Runs
+/// skip the call tracer and start from clones of the same database.
+///
+/// # Arguments
+///
+/// * `params` - The transaction to estimate; its gas limit is the search cap
+/// * `latest_block_env` - Block environment for execution
+/// * `prestate_tracer_result` - Account states before execution
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be executed at the cap, e.g. because
+/// of an invalid nonce or a balance too low to pay for gas.
+pub fn estimate_gas(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+) -> Result<GasEstimate, TraceError> {
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+    let cap = params.gas_limit.min(latest_block_env.gas_limit);
+
+    let execution_result = execute_with_gas_limit(params, &latest_block_env, &db, cap)?;
+    if !execution_result.is_success() {
+        let revert = match &execution_result {
+            ExecutionResult::Revert { output, .. } => decode_revert_reason(output),
+            _ => None,
+        };
+        return Ok(GasEstimate {
+            gas_limit: cap,
+            gas_used: execution_result.gas_used(),
+            reverted: true,
+            revert,
+        });
+    }
+
+    // `low` always fails and `high` always succeeds
+    let mut low = execution_result.gas_used().max(MIN_TRANSACTION_GAS) - 1;
+    let mut high = cap;
+    let mut gas_used = execution_result.gas_used();
+    while low + 1 < high {
+        let mid = low + (high - low) / 2;
+        // Limits below the intrinsic cost are rejected before execution; that is a failure too
+        match execute_with_gas_limit(params, &latest_block_env, &db, mid) {
+            Ok(execution_result) if execution_result.is_success() => {
+                high = mid;
+                gas_used = execution_result.gas_used();
+            }
+            _ => low = mid,
+        }
+    }
+
+    Ok(GasEstimate {
+        gas_limit: high,
+        gas_used,
+        reverted: false,
+        revert: None,
+    })
+}
+
+fn execute_with_gas_limit(
+    params: &TxParams,
+    latest_block_env: &BlockEnv,
+    db: &InMemoryDB,
+    gas_limit: u64,
+) -> Result<ExecutionResult<HaltReason>, TraceError> {
+    let params = TxParams {
+        gas_limit,
+        ..params.clone()
+    };
+    execute_transaction_with_database(&params, latest_block_env.clone(), db.clone())
+        .map(|(execution_result, _)| execution_result)
+}
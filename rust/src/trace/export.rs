@@ -0,0 +1,117 @@
+//! Exports of the call tree to standard profiling formats, weighted by gas
+
+use serde_json::{json, Value};
+use revm::primitives::HashMap;
+
+use crate::trace::inspector::CallFrame;
+
+/// Output format of `flamegraph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlamegraphFormat {
+    /// Folded stacks, one `root;child;grandchild gas` line per frame, as read by
+    /// `flamegraph.pl`, inferno and speedscope
+    Folded,
+    /// speedscope's JSON file format, with frames laid out in execution order
+    Speedscope,
+}
+
+/// Renders the call tree rooted at `frame` as a flamegraph weighted by gas
+pub fn flamegraph(frame: &CallFrame, format: FlamegraphFormat) -> String {
+    match format {
+        FlamegraphFormat::Folded => folded_stacks(frame),
+        FlamegraphFormat::Speedscope => speedscope(frame).to_string(),
+    }
+}
+
+/// One line per frame with its stack and its self gas, `CallFrame::self_gas_used`.
+/// Frames whose own code spent no gas are left out, as flamegraph tools expect.
+pub fn folded_stacks(frame: &CallFrame) -> String {
+    let mut lines = Vec::new();
+    fold(frame, &mut Vec::new(), &mut lines);
+    lines.join("\n")
+}
+
+fn fold(frame: &CallFrame, stack: &mut Vec<String>, lines: &mut Vec<String>) {
+    stack.push(frame_name(frame));
+    if !frame.self_gas_used.is_zero() {
+        lines.push(format!("{} {}", stack.join(";"), frame.self_gas_used));
+    }
+    for call in frame.calls.iter() {
+        fold(call, stack, lines);
+    }
+    stack.pop();
+}
+
+/// The call tree as a speedscope evented profile, where time is gas: each frame spans
+/// its `gas_used`, and subcalls follow each other from the start of their caller
+pub fn speedscope(frame: &CallFrame) -> Value {
+    let mut frames = SpeedscopeFrames::default();
+    let mut events = Vec::new();
+    let end = lay_out(frame, 0, &mut frames, &mut events);
+    let name = frame_name(frame);
+
+    json!({
+        "$schema": "https://www.speedscope.app/file-format-schema.json",
+        "name": name,
+        "exporter": "revm_tracer",
+        "shared": {
+            "frames": frames.names.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        },
+        "profiles": [{
+            "type": "evented",
+            "name": name,
+            "unit": "none",
+            "startValue": 0,
+            "endValue": end,
+            "events": events,
+        }],
+    })
+}
+
+#[derive(Default)]
+struct SpeedscopeFrames {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl SpeedscopeFrames {
+    fn index(&mut self, name: String) -> usize {
+        if let Some(index) = self.indices.get(&name) {
+            return *index;
+        }
+        self.names.push(name.clone());
+        self.indices.insert(name, self.names.len() - 1);
+        self.names.len() - 1
+    }
+}
+
+/// Emits the open and close events of `frame` starting at `at`, returns where it ends
+fn lay_out(frame: &CallFrame, at: u64, frames: &mut SpeedscopeFrames, events: &mut Vec<Value>) -> u64 {
+    let index = frames.index(frame_name(frame));
+    events.push(json!({ "type": "O", "frame": index, "at": at }));
+    let mut cursor = at;
+    for call in frame.calls.iter() {
+        cursor = lay_out(call, cursor, frames, events);
+    }
+    // Subcalls can spend a stipend their caller never paid, so they may overrun it
+    let end = cursor.max(at.saturating_add(frame.gas_used.saturating_to::<u64>()));
+    events.push(json!({ "type": "C", "frame": index, "at": end }));
+    end
+}
+
+/// `address::function`, with the decoded function name if known, else the selector
+fn frame_name(frame: &CallFrame) -> String {
+    let address = frame.to.map_or_else(|| "unknown".to_string(), |to| to.to_string());
+    let function = if let Some(decoded) = &frame.decoded_input {
+        decoded.name.clone()
+    } else if let Some(precompile) = &frame.precompile {
+        precompile.clone()
+    } else if frame.call_type.starts_with("CREATE") {
+        "constructor".to_string()
+    } else if frame.input.len() >= 4 {
+        format!("0x{}", hex::encode(&frame.input[..4]))
+    } else {
+        "fallback".to_string()
+    };
+    format!("{}::{}", address, function)
+}
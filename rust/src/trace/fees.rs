@@ -0,0 +1,129 @@
+//! Attribution of who paid for a transaction's gas
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{address, keccak256, Address, I256, U256};
+
+use crate::trace::inspector::{CallFrame, LogEntry};
+use crate::trace::trace::TraceTransactionResult;
+
+/// OP Stack predeploys that collect transaction fees
+const OP_FEE_VAULTS: [(Address, FeeRole); 4] = [
+    (address!("4200000000000000000000000000000000000011"), FeeRole::SequencerFeeVault),
+    (address!("4200000000000000000000000000000000000019"), FeeRole::BaseFeeVault),
+    (address!("420000000000000000000000000000000000001a"), FeeRole::L1FeeVault),
+    (address!("420000000000000000000000000000000000001b"), FeeRole::OperatorFeeVault),
+];
+
+const USER_OPERATION_EVENT: &str =
+    "UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)";
+
+/// Why an account appears in a fee report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FeeRole {
+    /// The transaction sender (e.g. the bundler for ERC-4337)
+    Sender,
+    /// The block beneficiary receiving priority fees
+    FeeRecipient,
+    SequencerFeeVault,
+    BaseFeeVault,
+    L1FeeVault,
+    OperatorFeeVault,
+}
+
+/// Net balance change of an account involved in paying or collecting fees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeParty {
+    pub address: Address,
+    pub role: FeeRole,
+    pub balance_delta: I256,
+}
+
+/// Gas cost settled by the EntryPoint for one user operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasPayment {
+    pub sender: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    /// The paymaster if one sponsored the operation, otherwise the sender's deposit
+    pub payer: Address,
+    pub actual_gas_cost: U256,
+    pub success: bool,
+}
+
+/// Who paid for gas and how much each party lost or gained
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeePayerReport {
+    pub parties: Vec<FeeParty>,
+    /// Per-user-operation settlements when the transaction is an EntryPoint bundle
+    pub user_operations: Vec<UserOperationGasPayment>,
+}
+
+/// Builds a fee payer report for a traced transaction.
+///
+/// Balance deltas come from the result's `balance_changes`, so they include value
+/// transfers as well as fees. For ERC-4337 bundles the EntryPoint's
+/// `UserOperationEvent` logs identify whether the sender or a paymaster deposit
+/// covered each operation.
+pub fn fee_payer_report<H>(
+    result: &TraceTransactionResult<H>,
+    fee_recipient: Address,
+) -> FeePayerReport {
+    let mut candidates = vec![
+        (result.calls.from, FeeRole::Sender),
+        (fee_recipient, FeeRole::FeeRecipient),
+    ];
+    candidates.extend(OP_FEE_VAULTS);
+
+    let parties = candidates.into_iter()
+        .filter(|(address, _)| result.state_diff.contains_key(address))
+        .map(|(address, role)| FeeParty {
+            address,
+            role,
+            balance_delta: result.balance_changes.get(&address)
+                .map(|change| change.delta)
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let topic0 = keccak256(USER_OPERATION_EVENT);
+    let mut user_operations = Vec::new();
+    collect_user_operation_payments(&result.calls, topic0.as_slice(), &mut user_operations);
+
+    FeePayerReport { parties, user_operations }
+}
+
+/// Walks non-reverted frames in execution order collecting `UserOperationEvent`s
+fn collect_user_operation_payments(
+    frame: &CallFrame,
+    topic0: &[u8],
+    payments: &mut Vec<UserOperationGasPayment>,
+) {
+    if frame.error.is_some() {
+        return;
+    }
+    payments.extend(frame.logs.iter().filter_map(|log| decode_user_operation_event(log, topic0)));
+    for call in &frame.calls {
+        collect_user_operation_payments(call, topic0, payments);
+    }
+}
+
+fn decode_user_operation_event(log: &LogEntry, topic0: &[u8]) -> Option<UserOperationGasPayment> {
+    if log.topics.len() != 4 || log.topics[0].as_slice() != topic0 || log.data.len() < 128 {
+        return None;
+    }
+    let sender = Address::from_word(log.topics[2]);
+    let paymaster = Some(Address::from_word(log.topics[3])).filter(|p| !p.is_zero());
+    let word = |index: usize| U256::from_be_slice(&log.data[index * 32..(index + 1) * 32]);
+
+    Some(UserOperationGasPayment {
+        sender,
+        paymaster,
+        payer: paymaster.unwrap_or(sender),
+        success: !word(1).is_zero(),
+        actual_gas_cost: word(2),
+    })
+}
@@ -0,0 +1,48 @@
+//! Filters that prune a call tree down to the frames a consumer cares about
+
+use revm::primitives::Address;
+
+use crate::trace::inspector::CallFrame;
+use crate::trace::trace::TraceTransactionResult;
+
+/// Returns true if `frame` itself (ignoring its subcalls) involves one of `addresses`
+fn frame_touches(frame: &CallFrame, addresses: &[Address]) -> bool {
+    addresses.contains(&frame.from)
+        || frame.to.is_some_and(|to| addresses.contains(&to))
+}
+
+/// Prunes `frame` to the subtrees that involve any of `addresses`.
+///
+/// A frame whose `from` or `to` matches is kept together with all of its subcalls. A
+/// frame that does not match is kept only as context for matching descendants, with
+/// its non-matching subcalls removed. Returns `None` if nothing in the tree matches.
+pub fn filter_frame_by_addresses(frame: &CallFrame, addresses: &[Address]) -> Option<CallFrame> {
+    if frame_touches(frame, addresses) {
+        return Some(frame.clone());
+    }
+
+    let calls: Vec<CallFrame> = frame.calls.iter()
+        .filter_map(|call| filter_frame_by_addresses(call, addresses))
+        .collect();
+    if calls.is_empty() {
+        return None;
+    }
+
+    Some(CallFrame {
+        calls,
+        ..frame.clone_without_calls()
+    })
+}
+
+/// Applies `filter_frame_by_addresses` to the call tree of `result`.
+///
+/// The root frame is always kept so the result keeps its shape; if nothing matches it
+/// is returned without subcalls.
+pub fn filter_result_by_addresses<H>(
+    mut result: TraceTransactionResult<H>,
+    addresses: &[Address],
+) -> TraceTransactionResult<H> {
+    result.calls = filter_frame_by_addresses(&result.calls, addresses)
+        .unwrap_or_else(|| result.calls.clone_without_calls());
+    result
+}
@@ -0,0 +1,112 @@
+//! Canonical serialization and fingerprints of call trees
+//!
+//! A fingerprint identifies what a transaction did: the calls it made with their inputs,
+//! outputs and errors, the logs it emitted and, if `CallTracerConfig::with_storage` was
+//! set, the storage it read and wrote. Gas amounts are left out, since they change with
+//! the gas limit and fees a transaction is simulated with, as are decoded and labeled
+//! fields, which depend on the ABIs and layouts given to the tracer.
+//!
+//! Bundlers can fingerprint a user operation's validation when it is first simulated and
+//! again before inclusion: a different fingerprint means the validation now behaves
+//! differently, which ERC-4337 reputation rules treat as the operation having changed.
+//! Fingerprint the full tree, before `OutputLimits` or a flat `CallLayout` are applied.
+
+use serde::Serialize;
+use revm::primitives::{keccak256, Address, Bytes, StorageKey, StorageValue, B256, U256};
+
+use crate::trace::inspector::{CallFrame, LogEntry, StorageAccess};
+
+/// The fields of a `CallFrame` that make up its fingerprint, in a fixed order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanonicalFrame {
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub input: Bytes,
+    pub output: Option<Bytes>,
+    pub error: Option<String>,
+    pub logs: Vec<CanonicalLog>,
+    pub storage_reads: Vec<CanonicalStorageAccess>,
+    pub storage_writes: Vec<CanonicalStorageAccess>,
+    pub calls: Vec<CanonicalFrame>,
+}
+
+/// The fields of a `LogEntry` that make up its frame's fingerprint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanonicalLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+/// A `StorageAccess` without its label
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanonicalStorageAccess {
+    pub address: Address,
+    pub slot: StorageKey,
+    pub value_before: StorageValue,
+    pub value_after: StorageValue,
+}
+
+impl From<&CallFrame> for CanonicalFrame {
+    fn from(frame: &CallFrame) -> Self {
+        Self {
+            call_type: frame.call_type.clone(),
+            from: frame.from,
+            to: frame.to,
+            value: frame.value,
+            input: frame.input.clone(),
+            output: frame.output.clone(),
+            error: frame.error.clone(),
+            logs: frame.logs.iter().map(CanonicalLog::from).collect(),
+            storage_reads: frame.storage_reads.iter().map(CanonicalStorageAccess::from).collect(),
+            storage_writes: frame.storage_writes.iter().map(CanonicalStorageAccess::from).collect(),
+            calls: frame.calls.iter().map(CanonicalFrame::from).collect(),
+        }
+    }
+}
+
+impl From<&LogEntry> for CanonicalLog {
+    fn from(log: &LogEntry) -> Self {
+        Self {
+            address: log.address,
+            topics: log.topics.clone(),
+            data: log.data.clone(),
+        }
+    }
+}
+
+impl From<&StorageAccess> for CanonicalStorageAccess {
+    fn from(access: &StorageAccess) -> Self {
+        Self {
+            address: access.address,
+            slot: access.slot,
+            value_before: access.value_before,
+            value_after: access.value_after,
+        }
+    }
+}
+
+/// Canonical serialization of `frames`: compact JSON of their `CanonicalFrame`s, with
+/// fields in declaration order and hex-encoded numbers and bytes
+pub fn canonical_trace(frames: &[CallFrame]) -> Vec<u8> {
+    let frames: Vec<CanonicalFrame> = frames.iter().map(CanonicalFrame::from).collect();
+    // Plain structs of primitives always serialize
+    serde_json::to_vec(&frames).unwrap_or_default()
+}
+
+/// Keccak-256 of the canonical serialization of `frame` and its subcalls
+pub fn trace_fingerprint(frame: &CallFrame) -> B256 {
+    calls_fingerprint(std::slice::from_ref(frame))
+}
+
+/// Keccak-256 of the canonical serialization of a list of sibling frames, e.g. the
+/// `validation_calls` of a `UserOperationTrace`
+pub fn calls_fingerprint(frames: &[CallFrame]) -> B256 {
+    keccak256(canonical_trace(frames))
+}
@@ -0,0 +1,118 @@
+//! Self-contained trace fixtures, to reproduce a trace without access to a node
+//!
+//! A fixture holds everything `chain::trace` needs: the chain kind, transaction, block,
+//! prestate, overrides and tracer options, in a single JSON file. It can be attached to
+//! a bug report or checked into a test suite and replayed with `replay_fixture`. When
+//! recorded with `record_fixture`, it also holds the fingerprint of the trace, so a
+//! replay can tell whether the tracer still produces the same call tree.
+//!
+//! Callbacks in the tracer config (`progress`, `cancellation`, `events`) are not saved.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::B256;
+
+use crate::trace::chain::{trace, ChainKind, ChainTraceResult, TraceRequest};
+use crate::trace::error::TraceError;
+use crate::trace::fingerprint::trace_fingerprint;
+
+/// Current version of the fixture file format
+pub const TRACE_FIXTURE_VERSION: u32 = 1;
+
+/// A trace request and the chain it runs on, as stored in a fixture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFixture {
+    pub version: u32,
+    pub chain_kind: ChainKind,
+    pub request: TraceRequest,
+    /// `trace_fingerprint` of the recorded result's call tree
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<B256>,
+}
+
+impl TraceFixture {
+    /// A fixture for `request` without a recorded fingerprint
+    pub fn new(chain_kind: ChainKind, request: TraceRequest) -> Self {
+        Self {
+            version: TRACE_FIXTURE_VERSION,
+            chain_kind,
+            request,
+            fingerprint: None,
+        }
+    }
+
+    /// Reads a fixture written by `save`
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the file cannot be read, is not a fixture or was written
+    /// by a newer version
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceError> {
+        let fixture: Self = serde_json::from_slice(&fs::read(path)?)?;
+        if fixture.version > TRACE_FIXTURE_VERSION {
+            return Err(TraceError::InvalidParameter(format!(
+                "unsupported trace fixture version {}", fixture.version
+            )));
+        }
+        Ok(fixture)
+    }
+
+    /// Writes the fixture as JSON, replacing `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Traces the fixture's request with `chain::trace`
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the request cannot be traced
+    pub fn replay(&self) -> Result<ChainTraceResult, TraceError> {
+        trace(&self.chain_kind, self.request.clone())
+    }
+
+    /// Whether `result` has the recorded call tree; `None` if no fingerprint was recorded
+    pub fn matches(&self, result: &ChainTraceResult) -> Option<bool> {
+        self.fingerprint.map(|fingerprint| fingerprint == result_fingerprint(result))
+    }
+}
+
+/// Traces `request` and saves it to `path` as a fixture along with the fingerprint of
+/// the result, which is returned
+///
+/// # Errors
+///
+/// Returns `TraceError` if the request cannot be traced or the file cannot be written
+pub fn record_fixture(
+    path: impl AsRef<Path>,
+    chain_kind: ChainKind,
+    request: TraceRequest,
+) -> Result<ChainTraceResult, TraceError> {
+    let mut fixture = TraceFixture::new(chain_kind, request);
+    let result = fixture.replay()?;
+    fixture.fingerprint = Some(result_fingerprint(&result));
+    fixture.save(path)?;
+    Ok(result)
+}
+
+/// Loads the fixture at `path` and traces it. Use `TraceFixture::matches` on the result
+/// to check it against the recording.
+///
+/// # Errors
+///
+/// Returns `TraceError` if the fixture cannot be loaded or traced
+pub fn replay_fixture(path: impl AsRef<Path>) -> Result<ChainTraceResult, TraceError> {
+    TraceFixture::load(path)?.replay()
+}
+
+fn result_fingerprint(result: &ChainTraceResult) -> B256 {
+    match result {
+        ChainTraceResult::Ethereum(result) => trace_fingerprint(&result.calls),
+        #[cfg(feature = "op")]
+        ChainTraceResult::OpStack(result) => trace_fingerprint(&result.calls),
+    }
+}
@@ -0,0 +1,67 @@
+//! Flat view of the call tree, one entry per frame addressed by its position in the tree
+//!
+//! A flat list is easier to stream into databases and spreadsheets than the nested tree.
+//! Frames are addressed as in Parity traces: by the index of each subcall from the
+//! top-level frame down.
+
+use serde::{Deserialize, Serialize};
+
+use crate::trace::inspector::CallFrame;
+use crate::trace::trace::TraceTransactionResult;
+
+/// How a trace result lays out its call frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CallLayout {
+    /// Nested tree in `calls` only
+    #[default]
+    Tree,
+    /// Flat list in `flatCalls`; `calls` keeps the top-level frame without subcalls
+    Flat,
+    /// Both the nested tree and the flat list
+    Both,
+}
+
+/// A call frame without its subcalls, and where it sits in the call tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlatFrame {
+    /// Index of each subcall from the top-level frame down, empty for the top-level frame
+    pub trace_address: Vec<usize>,
+    /// Number of direct subcalls
+    pub subtraces: usize,
+    #[serde(flatten)]
+    pub frame: CallFrame,
+}
+
+/// Lists every frame of the tree rooted at `frame` in execution order
+pub fn flatten_calls(frame: &CallFrame) -> Vec<FlatFrame> {
+    let mut frames = Vec::new();
+    flatten(frame, &mut Vec::new(), &mut frames);
+    frames
+}
+
+fn flatten(frame: &CallFrame, trace_address: &mut Vec<usize>, frames: &mut Vec<FlatFrame>) {
+    frames.push(FlatFrame {
+        trace_address: trace_address.clone(),
+        subtraces: frame.calls.len(),
+        frame: frame.clone_without_calls(),
+    });
+    for (index, call) in frame.calls.iter().enumerate() {
+        trace_address.push(index);
+        flatten(call, trace_address, frames);
+        trace_address.pop();
+    }
+}
+
+/// Lays out the call frames of `result` as `layout` asks, filling `flat_calls` unless
+/// only the tree is wanted
+pub fn apply_call_layout<H>(result: &mut TraceTransactionResult<H>, layout: CallLayout) {
+    if layout == CallLayout::Tree {
+        return;
+    }
+    result.flat_calls = flatten_calls(&result.calls);
+    if layout == CallLayout::Flat {
+        result.calls = result.calls.clone_without_calls();
+    }
+}
@@ -0,0 +1,191 @@
+//! A local fork of a remote chain: state is read lazily from a node at a pinned block and
+//! everything written locally stays in an overlay on top of it
+//!
+//! This lets test contracts be deployed over mainnet state and interact with real
+//! protocols without running a local node.
+
+use std::cell::RefCell;
+
+use revm::context::result::HaltReason;
+use revm::context::BlockEnv;
+use revm::database::CacheDB;
+use revm::primitives::{Address, Bytes, HashMap, StorageKey, StorageValue, B256, U256};
+use revm::state::{AccountInfo, Bytecode, EvmState};
+use revm::{Database, DatabaseCommit, DatabaseRef};
+
+use crate::trace::block::create_block_env_from_block_details;
+use crate::trace::database::{set_account_code, AccountDetails, PrestateKeys, RpcDatabase};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::CallTracerConfig;
+use crate::trace::rpc::TraceRpcClient;
+use crate::trace::trace::{trace_transaction_with_database, TraceTransactionResult, TxParams};
+
+/// Remote state behind the overlay. `CacheDB` reads through `DatabaseRef`, while
+/// `RpcDatabase` caches what it fetches and so needs `&mut`.
+struct RemoteState(RefCell<RpcDatabase>);
+
+impl DatabaseRef for RemoteState {
+    type Error = TraceError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.0.borrow_mut().basic(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.0.borrow_mut().code_by_hash(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: StorageKey) -> Result<StorageValue, Self::Error> {
+        self.0.borrow_mut().storage(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.0.borrow_mut().block_hash(number)
+    }
+}
+
+/// A `Database` forking a remote chain at a fixed block
+///
+/// Reads that miss the local overlay are fetched from the node at `block_number`.
+/// Committed transactions, deployed code and edited balances or slots are kept locally
+/// and never sent anywhere. Like `RpcDatabase`, it makes blocking requests and must not
+/// be used from within an async runtime.
+pub struct ForkDatabase {
+    db: CacheDB<RemoteState>,
+    block_number: u64,
+}
+
+impl ForkDatabase {
+    /// Forks the chain served at `rpc_url` at `block_number`
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError::Rpc` if the HTTP client cannot be created
+    pub fn new(rpc_url: impl Into<String>, block_number: u64) -> Result<Self, TraceError> {
+        Ok(Self::with_client(TraceRpcClient::new(rpc_url)?, block_number))
+    }
+
+    /// Forks the chain at `block_number` through an existing client
+    pub fn with_client(client: TraceRpcClient, block_number: u64) -> Self {
+        let remote = RpcDatabase::with_client(client, format!("{:#x}", block_number));
+        Self {
+            db: CacheDB::new(RemoteState(RefCell::new(remote))),
+            block_number,
+        }
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.block_number
+    }
+
+    /// Environment of the pinned block, for executing on top of its state as `eth_call`
+    /// would
+    pub fn block_env(&self) -> Result<BlockEnv, TraceError> {
+        let block_details = self.db.db.0.borrow().block_details()?;
+        Ok(create_block_env_from_block_details(block_details)?)
+    }
+
+    /// Sets the balance of `address`, fetching the rest of the account first
+    pub fn set_balance(&mut self, address: Address, balance: U256) -> Result<(), TraceError> {
+        let mut info = self.db.basic(address)?.unwrap_or_default();
+        info.balance = balance;
+        self.db.insert_account_info(address, info);
+        Ok(())
+    }
+
+    /// Deploys `code` at `address`, replacing any code it has on the remote chain while
+    /// keeping its balance, nonce and storage
+    pub fn set_code(&mut self, address: Address, code: Bytes) -> Result<(), TraceError> {
+        let mut info = self.db.basic(address)?.unwrap_or_default();
+        set_account_code(&mut info, code);
+        self.db.insert_account_info(address, info);
+        Ok(())
+    }
+
+    /// Sets one storage slot of `address`
+    pub fn set_storage(&mut self, address: Address, slot: StorageKey, value: StorageValue) -> Result<(), TraceError> {
+        self.db.insert_account_storage(address, slot, value)
+    }
+
+    /// Traces `params` on top of the fork's current state without changing it; pass the
+    /// result's `state_diff` to `commit_state` to keep its effects
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if a request to the node fails or the transaction cannot be
+    /// built or executed
+    pub fn trace(
+        &mut self,
+        params: &TxParams,
+        block_env: BlockEnv,
+        tracer_config: &CallTracerConfig,
+    ) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+        let mut result = trace_transaction_with_database(
+            params,
+            block_env,
+            &mut *self,
+            &PrestateKeys::default(),
+            tracer_config,
+        )?;
+        // Every account and slot is fetched on demand, so nothing is ever missing
+        result.warnings.clear();
+        Ok(result)
+    }
+
+    /// Keeps the state changes of a trace, so later traces run on top of them
+    pub fn commit_state(&mut self, state_diff: &EvmState) {
+        self.db.commit(state_diff.clone());
+    }
+
+    /// Everything read from the node so far, as a prestate, e.g. for a `PrestateCache`.
+    /// Local changes are not included.
+    pub fn fetched_prestate(&self) -> HashMap<Address, AccountDetails> {
+        self.db.db.0.borrow().fetched_prestate()
+    }
+}
+
+impl Database for ForkDatabase {
+    type Error = TraceError;
+
+    fn basic(&mut self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic(address)
+    }
+
+    fn code_by_hash(&mut self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: Address, index: StorageKey) -> Result<StorageValue, Self::Error> {
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&mut self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash(number)
+    }
+}
+
+impl DatabaseRef for ForkDatabase {
+    type Error = TraceError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.db.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.db.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: StorageKey) -> Result<StorageValue, Self::Error> {
+        self.db.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl DatabaseCommit for ForkDatabase {
+    fn commit(&mut self, changes: EvmState) {
+        self.db.commit(changes);
+    }
+}
@@ -0,0 +1,210 @@
+//! Serialization of call frames exactly as geth's `callTracer` outputs them
+//!
+//! `CallFrame` serializes to a superset of geth's schema, with a few formats of its own,
+//! e.g. `gas` as a decimal number. `GethCallFrame` writes only geth's fields, in geth's
+//! order and formats, so traces can be diffed against a geth node.
+
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use revm::primitives::U256;
+
+use crate::trace::inspector::{CallFrame, LogEntry};
+use crate::trace::revert::{decode_error_string, PANIC_SELECTOR};
+use crate::trace::trace::{TraceTransactionResult, TxParams};
+
+/// A `CallFrame` and its subcalls in geth's `callTracer` format
+///
+/// Failed frames report their revert data as `output` and drop their logs and those of
+/// their subcalls, as geth does. Creations report the deployed code as `output`.
+#[derive(Debug, Clone, Copy)]
+pub struct GethCallFrame<'a> {
+    frame: &'a CallFrame,
+    /// Gas limit and gas used of the transaction, which geth reports for the top-level
+    /// frame instead of what is left once intrinsic gas is charged
+    transaction_gas: Option<(u64, u64)>,
+}
+
+impl<'a> GethCallFrame<'a> {
+    pub fn new(frame: &'a CallFrame) -> Self {
+        Self { frame, transaction_gas: None }
+    }
+
+    /// The call tree of a traced transaction, with the gas of the top-level frame taken
+    /// from the transaction as geth does
+    pub fn for_transaction<H>(params: &TxParams, result: &'a TraceTransactionResult<H>) -> Self {
+        Self {
+            frame: &result.calls,
+            transaction_gas: Some((params.gas_limit, result.execution_result.gas_used())),
+        }
+    }
+}
+
+impl Serialize for GethCallFrame<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_frame(self.frame, self.transaction_gas, true, serializer)
+    }
+}
+
+/// A subcall, whose logs are only kept if every frame above it succeeded
+struct GethSubcall<'a> {
+    frame: &'a CallFrame,
+    keep_logs: bool,
+}
+
+impl Serialize for GethSubcall<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_frame(self.frame, None, self.keep_logs, serializer)
+    }
+}
+
+struct GethLog<'a>(&'a LogEntry);
+
+impl Serialize for GethLog<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("GethLog", 4)?;
+        state.serialize_field("address", &format!("{:#x}", self.0.address))?;
+        let topics: Vec<String> = self.0.topics.iter().map(|topic| format!("{:#x}", topic)).collect();
+        state.serialize_field("topics", &topics)?;
+        state.serialize_field("data", &self.0.data)?;
+        state.serialize_field("position", &format!("{:#x}", self.0.position))?;
+        state.end()
+    }
+}
+
+/// Writes the fields geth's `callFrame` marshals, in its order and with its `omitempty`
+fn serialize_frame<S: Serializer>(
+    frame: &CallFrame,
+    transaction_gas: Option<(u64, u64)>,
+    keep_logs: bool,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let failed = frame.error.is_some();
+    let (gas, gas_used) = transaction_gas.unwrap_or((
+        frame.gas.saturating_to::<u64>(),
+        frame.gas_used.saturating_to::<u64>(),
+    ));
+
+    let mut state = serializer.serialize_struct("GethCallFrame", 12)?;
+    state.serialize_field("from", &format!("{:#x}", frame.from))?;
+    state.serialize_field("gas", &format!("{:#x}", gas))?;
+    state.serialize_field("gasUsed", &format!("{:#x}", gas_used))?;
+    if let Some(to) = frame.to {
+        state.serialize_field("to", &format!("{:#x}", to))?;
+    }
+    state.serialize_field("input", &frame.input)?;
+
+    let output = if failed {
+        frame.revert_reason.clone()
+    } else {
+        frame.deployed_code.as_ref().or(frame.output.as_ref())
+            .filter(|output| !output.is_empty())
+            .map(|output| format!("0x{}", hex::encode(output)))
+    };
+    if let Some(output) = output {
+        state.serialize_field("output", &output)?;
+    }
+    if let Some(error) = &frame.error {
+        state.serialize_field("error", error)?;
+    }
+    if let Some(reason) = frame.revert_reason.as_deref().and_then(geth_revert_reason) {
+        state.serialize_field("revertReason", &reason)?;
+    }
+
+    let keep_logs = keep_logs && !failed;
+    if !frame.calls.is_empty() {
+        let calls: Vec<GethSubcall> = frame.calls.iter()
+            .map(|frame| GethSubcall { frame, keep_logs })
+            .collect();
+        state.serialize_field("calls", &calls)?;
+    }
+    if keep_logs && !frame.logs.is_empty() {
+        let logs: Vec<GethLog> = frame.logs.iter().map(GethLog).collect();
+        state.serialize_field("logs", &logs)?;
+    }
+    if frame.call_type != "STATICCALL" {
+        state.serialize_field("value", &format!("{:#x}", frame.value))?;
+    }
+    state.serialize_field("type", &frame.call_type)?;
+    state.end()
+}
+
+/// `revertReason` as geth's `abi.UnpackRevert` produces it, from hex revert data: the
+/// message of an `Error(string)` or geth's description of a panic code. Other revert
+/// data has no reason.
+fn geth_revert_reason(revert_data: &str) -> Option<String> {
+    let data = hex::decode(revert_data.trim_start_matches("0x")).ok()?;
+    if let Some(message) = decode_error_string(&data) {
+        return Some(message);
+    }
+    let code = U256::from_be_slice(data.strip_prefix(&PANIC_SELECTOR)?.get(..32)?);
+    let reason = match code.saturating_to::<u64>() {
+        _ if code > U256::from(u8::MAX) => None,
+        0x00 => Some("generic panic"),
+        0x01 => Some("assert(false)"),
+        0x11 => Some("arithmetic underflow or overflow"),
+        0x12 => Some("division or modulo by zero"),
+        0x21 => Some("enum overflow"),
+        0x22 => Some("invalid encoded storage byte array accessed"),
+        0x31 => Some("out-of-bounds array access; popping on an empty array"),
+        0x32 => Some("out-of-bounds access of an array or bytesN"),
+        0x41 => Some("out of memory"),
+        0x51 => Some("uninitialized function"),
+        _ => None,
+    };
+    Some(reason.map_or_else(|| format!("unknown panic code: {:#x}", code), str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Serializes the fixture's `frame` and checks it against the `geth` callTracer output
+    /// recorded alongside it
+    fn assert_matches_geth(fixture: &str) -> String {
+        let fixture: Value = serde_json::from_str(fixture).unwrap();
+        let frame: CallFrame = serde_json::from_value(fixture["frame"].clone()).unwrap();
+        let serialized = serde_json::to_string(&GethCallFrame::new(&frame)).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&serialized).unwrap(), fixture["geth"]);
+        serialized
+    }
+
+    #[test]
+    fn successful_call_with_logs() {
+        let serialized = assert_matches_geth(include_str!("../../tests/fixtures/geth/erc20_transfer.json"));
+
+        // Fields come in the order geth marshals them, without `calls` for a leaf frame
+        let keys = ["from", "gas", "gasUsed", "to", "input", "output", "logs", "value", "type"];
+        let positions: Vec<usize> = keys.iter()
+            .map(|key| serialized.find(&format!("\"{}\":", key)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{serialized}");
+        assert!(!serialized.contains("\"calls\""));
+    }
+
+    #[test]
+    fn reverted_call_drops_subcall_logs() {
+        let serialized = assert_matches_geth(include_str!("../../tests/fixtures/geth/reverted_swap.json"));
+        assert!(!serialized.contains("\"logs\""));
+    }
+
+    #[test]
+    fn panic_revert_reason() {
+        assert_matches_geth(include_str!("../../tests/fixtures/geth/panic_underflow.json"));
+    }
+
+    #[test]
+    fn out_of_gas_has_no_output() {
+        let serialized = assert_matches_geth(include_str!("../../tests/fixtures/geth/out_of_gas.json"));
+        assert!(!serialized.contains("\"output\""));
+        assert!(!serialized.contains("\"revertReason\""));
+    }
+
+    #[test]
+    fn unknown_panic_code() {
+        let data = format!("0x{}{:064x}", hex::encode(PANIC_SELECTOR), 0x99);
+        assert_eq!(geth_revert_reason(&data).as_deref(), Some("unknown panic code: 0x99"));
+        assert_eq!(geth_revert_reason("0xdeadbeef"), None);
+    }
+}
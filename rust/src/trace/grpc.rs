@@ -0,0 +1,168 @@
+//! gRPC service streaming trace events, for backend services consuming traces over the
+//! network
+//!
+//! The schema is in `proto/revm_tracer.proto`. Requests take the same JSON as
+//! `trace_with_chain`; the response streams a `TraceEvent` for every frame start, frame
+//! end and log as the transaction executes, and ends with the result or the error.
+
+use std::fmt::Debug;
+use std::net::SocketAddr;
+
+use serde::Serialize;
+use revm::context::result::ExecutionResult;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+use crate::trace::chain::{trace, ChainKind, ChainTraceResult, TraceRequest};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::{CallFrame, CancellationHandle, LogEntry, TraceEvent, TraceEventCallback};
+use crate::trace::trace::TraceTransactionResult;
+
+/// Types and service generated from `proto/revm_tracer.proto`
+pub mod proto {
+    tonic::include_proto!("revm_tracer.v1");
+}
+
+use proto::trace_event::Event;
+use proto::trace_service_server::TraceService;
+pub use proto::trace_service_server::TraceServiceServer;
+
+/// Events buffered per stream before the tracer waits for the client to catch up
+const EVENT_BUFFER: usize = 256;
+
+/// Implementation of `TraceService`; mount it with `TraceServiceServer::new` or run it
+/// on its own with `serve`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraceGrpcService;
+
+#[tonic::async_trait]
+impl TraceService for TraceGrpcService {
+    type TraceTransactionStream = ReceiverStream<Result<proto::TraceEvent, Status>>;
+
+    async fn trace_transaction(
+        &self,
+        request: Request<proto::TraceTransactionRequest>,
+    ) -> Result<Response<Self::TraceTransactionStream>, Status> {
+        let request = request.into_inner();
+        let chain_kind: ChainKind = if request.chain_kind_json.is_empty() {
+            ChainKind::Ethereum
+        } else {
+            serde_json::from_str(&request.chain_kind_json)
+                .map_err(|e| Status::invalid_argument(format!("invalid chain kind: {}", e)))?
+        };
+        let mut trace_request: TraceRequest = serde_json::from_str(&request.request_json)
+            .map_err(|e| Status::invalid_argument(format!("invalid request: {}", e)))?;
+
+        let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+        // Stop tracing once the client has gone away
+        let cancellation = CancellationHandle::new();
+        let events = sender.clone();
+        let on_disconnect = cancellation.clone();
+        trace_request.tracer_config.events = Some(TraceEventCallback::new(move |event| {
+            if events.blocking_send(Ok(proto::TraceEvent::from(event))).is_err() {
+                on_disconnect.cancel();
+            }
+        }));
+        trace_request.tracer_config.cancellation = Some(cancellation);
+
+        tokio::task::spawn_blocking(move || {
+            let result = trace(&chain_kind, trace_request).and_then(|result| match result {
+                ChainTraceResult::Ethereum(result) => result_message(result),
+                #[cfg(feature = "op")]
+                ChainTraceResult::OpStack(result) => result_message(result),
+            });
+            let event = match result {
+                Ok(result) => Event::Result(result),
+                Err(error) => Event::Error(proto::TraceError {
+                    code: error.code().to_string(),
+                    message: error.to_string(),
+                }),
+            };
+            let _ = sender.blocking_send(Ok(proto::TraceEvent { depth: 0, event: Some(event) }));
+        });
+
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
+
+/// Serves `TraceService` on `address` until the server fails
+///
+/// # Errors
+///
+/// Returns the transport error that stopped the server
+pub async fn serve(address: SocketAddr) -> Result<(), tonic::transport::Error> {
+    Server::builder()
+        .add_service(TraceServiceServer::new(TraceGrpcService))
+        .serve(address)
+        .await
+}
+
+fn result_message<H: Debug + Serialize>(
+    result: TraceTransactionResult<H>,
+) -> Result<proto::TraceTransactionResult, TraceError> {
+    let (status, output, halt_reason) = match &result.execution_result {
+        ExecutionResult::Success { output, .. } => ("success", output.data().to_string(), None),
+        ExecutionResult::Revert { output, .. } => ("revert", output.to_string(), None),
+        ExecutionResult::Halt { reason, .. } => ("halt", "0x".to_string(), Some(format!("{:?}", reason))),
+    };
+
+    // Everything but the call tree, which is sent as `calls`
+    let mut details = serde_json::to_value(&result)?;
+    if let Some(details) = details.as_object_mut() {
+        details.remove("calls");
+    }
+
+    Ok(proto::TraceTransactionResult {
+        status: status.to_string(),
+        gas_used: result.execution_result.gas_used(),
+        output,
+        halt_reason,
+        calls: Some(proto::CallFrame::from(&result.calls)),
+        details_json: details.to_string(),
+    })
+}
+
+impl From<TraceEvent> for proto::TraceEvent {
+    fn from(event: TraceEvent) -> Self {
+        let (depth, event) = match event {
+            TraceEvent::CallStart { depth, frame } => (depth, Event::CallStart((&frame).into())),
+            TraceEvent::CreateStart { depth, frame } => (depth, Event::CreateStart((&frame).into())),
+            TraceEvent::Log { depth, log } => (depth, Event::Log((&log).into())),
+            TraceEvent::CallEnd { depth, frame } => (depth, Event::CallEnd((&frame).into())),
+        };
+        Self { depth: depth as u64, event: Some(event) }
+    }
+}
+
+impl From<&CallFrame> for proto::CallFrame {
+    fn from(frame: &CallFrame) -> Self {
+        Self {
+            call_type: frame.call_type.clone(),
+            from: frame.from.to_string(),
+            to: frame.to.map(|to| to.to_string()),
+            value: format!("{:#x}", frame.value),
+            gas: frame.gas.saturating_to(),
+            gas_used: frame.gas_used.saturating_to(),
+            self_gas_used: frame.self_gas_used.saturating_to(),
+            input: frame.input.to_string(),
+            output: frame.output.as_ref().map(ToString::to_string),
+            error: frame.error.clone(),
+            revert_reason: frame.revert_reason.clone(),
+            logs: frame.logs.iter().map(proto::LogEntry::from).collect(),
+            calls: frame.calls.iter().map(proto::CallFrame::from).collect(),
+        }
+    }
+}
+
+impl From<&LogEntry> for proto::LogEntry {
+    fn from(log: &LogEntry) -> Self {
+        Self {
+            address: log.address.to_string(),
+            topics: log.topics.iter().map(ToString::to_string).collect(),
+            data: log.data.to_string(),
+            position: log.position as u64,
+        }
+    }
+}
@@ -1,15 +1,46 @@
 use revm::{
+    bytecode::opcode,
     context::ContextTr,
-    interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter, InterpreterTypes},
+    context_interface::LocalContextTr,
+    context_interface::Block,
+    interpreter::{CallInput, CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme, InstructionResult, Interpreter},
+    interpreter::interpreter::EthInterpreter,
+    interpreter::interpreter_types::{Jumps, MemoryTr},
 };
-use revm::Inspector;
-use revm::primitives::{Address, U256, Bytes, Log, B256};
+use revm::{Database, Inspector};
+use revm::primitives::{Address, U256, Bytes, HashMap, Log, B256, KECCAK_EMPTY, StorageKey, StorageValue};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+use crate::trace::abi::{DecodedCall, DecodedLog, DecodedParam};
+use crate::trace::database::AccountDetails;
+use crate::trace::error::TraceError;
+use crate::trace::labels::{SlotLabels, SlotPreimages, StorageLabeler, StorageLayouts};
+use crate::trace::precompiles::{decode_precompile, precompile_name, PrecompileData};
+use crate::trace::proxy::{resolve_proxy, ProxyImplementation};
+use crate::trace::quantity;
+use crate::trace::transfers::{ValueTransfer, ValueTransferKind, ValueTransferTracker};
+use crate::trace::revert::{decode_revert_reason, DecodedRevert};
 
 // Constants for repeated strings
 const ERROR_EXECUTION_REVERTED: &str = "execution reverted";
 const HEX_PREFIX: &str = "0x";
 
+/// Steps executed between two checks of `ExecutionLimits::timeout_ms`
+#[cfg(not(target_arch = "wasm32"))]
+const TIMEOUT_CHECK_INTERVAL: u64 = 1024;
+
+/// Steps executed between two progress reports within a frame, so that a long loop
+/// without calls still reports progress and can be cancelled
+const PROGRESS_STEP_INTERVAL: u64 = 10_000;
+
 /// Represents a log entry emitted during contract execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -17,6 +48,12 @@ pub struct LogEntry {
     pub address: Address,
     pub topics: Vec<B256>,
     pub data: Bytes,
+    /// Event decoded with an `AbiRegistry`, if one was given and knows the event
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub decoded_log: Option<DecodedLog>,
+    /// Number of subcalls the emitting frame had made before the log, as in geth
+    #[serde(default, with = "quantity")]
+    pub position: usize,
 }
 
 impl From<Log> for LogEntry {
@@ -25,10 +62,28 @@ impl From<Log> for LogEntry {
             address: log.address,
             topics: log.data.topics().to_vec(),
             data: log.data.data.clone(),
+            decoded_log: None,
+            position: 0,
         }
     }
 }
 
+/// A storage slot read or written by a call frame.
+/// For reads `value_before` and `value_after` are equal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageAccess {
+    /// Account owning the storage, which differs from the frame's `to` for DELEGATECALL
+    pub address: Address,
+    pub slot: StorageKey,
+    pub value_before: StorageValue,
+    pub value_after: StorageValue,
+    /// Name registered for the slot in `CallTracerConfig::slot_labels`, or the variable
+    /// stored in it if `CallTracerConfig::storage_layout` covers the account
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub label: Option<String>,
+}
+
 /// Represents a single call or contract creation in the execution trace.
 /// This structure captures all relevant information about a call including
 /// inputs, outputs, gas usage, logs, and any subcalls made during execution.
@@ -39,42 +94,825 @@ pub struct CallFrame {
     pub call_type: String,
     pub from: Address,
     pub to: Option<Address>,
-    #[serde(with = "hex_u256")]
+    #[serde(with = "quantity")]
     pub value: U256,
+    #[serde(with = "quantity")]
     pub gas: U256,
+    /// Gas spent by this frame and all of its subcalls
+    #[serde(with = "quantity")]
     pub gas_used: U256,
+    /// Gas spent by this frame's own code, excluding what its subcalls spent
+    #[serde(default, with = "quantity")]
+    pub self_gas_used: U256,
     pub input: Bytes,
     pub output: Option<Bytes>,
+    /// Input decoded with an `AbiRegistry`, if one was given and knows the function
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub decoded_input: Option<DecodedCall>,
+    /// Return values decoded with an `AbiRegistry`; only set for successful calls
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub decoded_output: Option<Vec<DecodedParam>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub revert_reason: Option<String>,
+    /// `revert_reason` decoded as `Error(string)`, `Panic(uint256)` or a custom error selector
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub decoded_revert: Option<DecodedRevert>,
     pub logs: Vec<LogEntry>,
+    /// Whether the callee is a precompiled contract
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub is_precompile: bool,
+    /// Name of the precompile, e.g. "ecrecover"
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub precompile: Option<String>,
+    /// Decoded precompile arguments and result, if `CallTracerConfig::decode_precompiles` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub precompile_data: Option<PrecompileData>,
+    /// SLOADs executed by this frame, if `CallTracerConfig::with_storage` is set
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub storage_reads: Vec<StorageAccess>,
+    /// SSTOREs executed by this frame, if `CallTracerConfig::with_storage` is set
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub storage_writes: Vec<StorageAccess>,
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub calls: Vec<CallFrame>,
+    /// STATICCALLs made by this frame and left out of `calls`, if
+    /// `CallTracerConfig::static_calls` is `StaticCallCapture::Count`
+    #[serde(skip_serializing_if = "is_zero", default)]
+    pub static_calls: usize,
+    /// What `apply_output_limits` cut from this frame, if anything
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub truncated: Option<FrameTruncation>,
+    /// Implementation this frame delegated to as a standard proxy, if
+    /// `CallTracerConfig::resolve_proxies` is set
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub implementation: Option<ProxyImplementation>,
+    /// Runtime code returned by a successful creation, whose `output` is the created
+    /// address; kept for `GethCallFrame`, which reports the code as geth does
+    #[serde(skip)]
+    pub deployed_code: Option<Bytes>,
+}
+
+/// Parts of a call frame left out to keep a trace within its `OutputLimits`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameTruncation {
+    /// Size in bytes of the full `input`, if it was cut
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub input_size: Option<usize>,
+    /// Size in bytes of the full `output`, if it was cut
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub output_size: Option<usize>,
+    /// Number of this frame's logs left out
+    #[serde(skip_serializing_if = "is_zero", default)]
+    pub dropped_logs: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+impl CallFrame {
+    /// Clones this frame without its subcalls, avoiding a deep copy of the tree.
+    pub fn clone_without_calls(&self) -> CallFrame {
+        CallFrame {
+            call_type: self.call_type.clone(),
+            from: self.from,
+            to: self.to,
+            value: self.value,
+            gas: self.gas,
+            gas_used: self.gas_used,
+            self_gas_used: self.self_gas_used,
+            input: self.input.clone(),
+            output: self.output.clone(),
+            decoded_input: self.decoded_input.clone(),
+            decoded_output: self.decoded_output.clone(),
+            error: self.error.clone(),
+            revert_reason: self.revert_reason.clone(),
+            decoded_revert: self.decoded_revert.clone(),
+            logs: self.logs.clone(),
+            is_precompile: self.is_precompile,
+            precompile: self.precompile.clone(),
+            precompile_data: self.precompile_data.clone(),
+            storage_reads: self.storage_reads.clone(),
+            storage_writes: self.storage_writes.clone(),
+            calls: Vec::new(),
+            static_calls: self.static_calls,
+            truncated: self.truncated.clone(),
+            implementation: self.implementation.clone(),
+            deployed_code: self.deployed_code.clone(),
+        }
+    }
+}
+
+/// Options of the call tracer, mirroring geth's `callTracer` config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CallTracerConfig {
+    /// Record only the top-level call, no subcalls
+    pub only_top_call: bool,
+    /// Record logs emitted by each frame
+    #[serde(alias = "withLog")]
+    pub with_logs: bool,
+    /// Deepest call depth to record, the top-level call being depth 1
+    pub max_depth: Option<usize>,
+    /// Maximum number of frames to record; later calls are left out
+    pub max_frames: Option<usize>,
+    /// Record the storage slots each frame reads and writes
+    pub with_storage: bool,
+    /// Decode the arguments and results of calls into precompiles
+    pub decode_precompiles: bool,
+    /// Read the EIP-1967 and EIP-1822 slots of frames that delegate, to show which
+    /// implementation a proxy ran
+    pub resolve_proxies: bool,
+    /// Collect every ETH transfer of the call tree in the result's `value_transfers`
+    pub with_value_transfers: bool,
+    /// Whether STATICCALL frames are recorded, for UIs that only show state changes
+    pub static_calls: StaticCallCapture,
+    /// Called every time a frame completes and every `PROGRESS_STEP_INTERVAL` steps,
+    /// e.g. to report progress of long traces
+    #[serde(skip)]
+    pub progress: Option<ProgressCallback>,
+    /// Limits past which execution is stopped, unlike `max_depth` and `max_frames`
+    /// which only limit what is recorded
+    pub limits: ExecutionLimits,
+    /// Aborts the trace once cancelled, see `CancellationHandle`
+    #[serde(skip)]
+    pub cancellation: Option<CancellationHandle>,
+    /// Receives a `TraceEvent` for every recorded frame start, frame end and log
+    #[serde(skip)]
+    pub events: Option<TraceEventCallback>,
+    /// Keep only the top-level frame, without subcalls or logs, so memory does not grow
+    /// with the trace; the rest of the trace is only available through `events`
+    pub stream_only: bool,
+    /// Solc storage layouts used to name storage slots in `storage_reads`,
+    /// `storage_writes` and the result's `storage_changes`
+    pub storage_layout: Option<StorageLayouts>,
+    /// Names of storage slots per address, used like `storage_layout` and taking
+    /// precedence over it
+    pub slot_labels: Option<SlotLabels>,
+    /// Fail with `TraceError::IncompletePrestate` instead of returning `warnings` when
+    /// execution accessed state the prestate did not provide
+    pub strict_prestate: bool,
+}
+
+/// What the call tracer records of STATICCALL frames. Frames left out are skipped with
+/// their subcalls, which cannot change state either, as they run; precompiles called
+/// with STATICCALL, like `ecrecover`, are left out too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StaticCallCapture {
+    /// Record them like any other frame
+    #[default]
+    Record,
+    /// Leave them out
+    Drop,
+    /// Leave them out and count them in their caller's `CallFrame::static_calls`
+    Count,
+}
+
+/// Incremental trace output of the call tracer, sent to `CallTracerConfig::events` in
+/// execution order.
+///
+/// `depth` is 1 for the top-level call. Frames are sent as recorded by the inspector:
+/// ABI decoding, done once execution has finished, is not applied to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TraceEvent {
+    /// A call frame was entered; `frame` has no output, logs or subcalls yet
+    CallStart { depth: usize, frame: CallFrame },
+    /// A contract creation was entered; `frame.to` is unknown until it ends
+    CreateStart { depth: usize, frame: CallFrame },
+    /// A log was emitted by the frame at `depth`
+    Log { depth: usize, log: LogEntry },
+    /// The frame at `depth` ended; `frame` is complete except for its subcalls
+    CallEnd { depth: usize, frame: CallFrame },
+}
+
+/// Callback receiving `TraceEvent`s from the call tracer
+#[derive(Clone)]
+pub struct TraceEventCallback(Arc<dyn Fn(TraceEvent) + Send + Sync>);
+
+impl TraceEventCallback {
+    pub fn new(callback: impl Fn(TraceEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Sends every event over `sender`; events are dropped once the receiver is gone
+    pub fn channel(sender: Sender<TraceEvent>) -> Self {
+        Self::new(move |event| {
+            let _ = sender.send(event);
+        })
+    }
+}
+
+impl fmt::Debug for TraceEventCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TraceEventCallback")
+    }
+}
+
+/// Handle to abort a trace in progress from another thread
+///
+/// Clones share the same flag. The call tracer checks it before every instruction;
+/// once it is set, execution halts and the trace fails with `TraceError::Cancelled`
+/// carrying the frames collected so far.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationHandle(Arc<AtomicBool>);
+
+impl CancellationHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the trace to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Limits on the execution of a traced transaction, protecting the host from
+/// pathological transactions.
+///
+/// Once a limit is hit, every executing frame is halted with `OutOfGas` as soon as it
+/// runs its next instruction. The trace collected so far is returned with `truncated`
+/// set, and its execution result is that of the halted transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ExecutionLimits {
+    /// Wall-clock time execution may take, in milliseconds. Not enforced on wasm32,
+    /// which has no monotonic clock.
+    pub timeout_ms: Option<u64>,
+    /// Deepest call depth allowed, the top-level call being depth 1
+    pub max_call_depth: Option<usize>,
+    /// Maximum number of frames entered, recorded or not
+    pub max_frames: Option<u64>,
+    /// Maximum number of logs emitted
+    pub max_logs: Option<u64>,
+}
+
+/// The `ExecutionLimits` limit that stopped execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TruncationReason {
+    Timeout,
+    CallDepth,
+    Frames,
+    Logs,
+}
+
+/// How far a trace has got, as reported to a `ProgressCallback`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceProgress {
+    /// Frames completed so far, including those not recorded because of limits
+    pub frames: u64,
+    /// Execution gas consumed so far, excluding the intrinsic cost
+    pub gas_used: u64,
+}
+
+/// Callback receiving `TraceProgress` updates from the call tracer
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(TraceProgress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl Fn(TraceProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+impl Default for CallTracerConfig {
+    fn default() -> Self {
+        Self {
+            only_top_call: false,
+            with_logs: true,
+            max_depth: None,
+            max_frames: None,
+            with_storage: false,
+            decode_precompiles: false,
+            resolve_proxies: false,
+            with_value_transfers: false,
+            static_calls: StaticCallCapture::Record,
+            progress: None,
+            limits: ExecutionLimits::default(),
+            cancellation: None,
+            events: None,
+            stream_only: false,
+            storage_layout: None,
+            slot_labels: None,
+            strict_prestate: false,
+        }
+    }
+}
+
+/// Memory held by the frames a `CallTracer` recorded, to size traces on constrained
+/// hosts such as mobile devices. Bytes are estimated from the size of frames, logs and
+/// storage accesses and the length of their data, without allocator overhead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceMemoryStats {
+    /// Frames held when the trace ended
+    pub frames: usize,
+    /// Most frames held at once
+    pub peak_frames: usize,
+    /// Bytes held when the trace ended
+    pub bytes: usize,
+    /// Most bytes held at once; with `stream_only`, completed subcalls are released
+    pub peak_bytes: usize,
+}
+
+/// Index of a recorded frame in `CallTracer::frames`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FrameId(usize);
+
+/// A recorded frame, kept without subcalls until `CallTracer::into_result` builds the tree
+#[derive(Debug)]
+struct ArenaFrame {
+    frame: CallFrame,
+    parent: Option<FrameId>,
+    /// Subcalls attached to the frame so far
+    subcalls: usize,
+    /// Bytes of input allocated for the frame; zero if it shares its input
+    input_bytes: usize,
+    /// Bytes accounted for the frame in `TraceMemoryStats`
+    bytes: usize,
+}
+
+/// Calldata copied out of callers' memory, kept once per distinct content so frames
+/// with the same input share one buffer, e.g. a chain of proxies forwarding calldata
+#[derive(Debug, Default)]
+struct InputInterner {
+    /// Inputs by hash of their content
+    inputs: HashMap<u64, Vec<Bytes>>,
+}
+
+impl InputInterner {
+    /// Returns the shared copy of `input`, and whether it had to be allocated
+    fn intern(&mut self, input: &[u8]) -> (Bytes, bool) {
+        let hash = BuildHasherDefault::<DefaultHasher>::default().hash_one(input);
+        let inputs = self.inputs.entry(hash).or_default();
+        if let Some(shared) = inputs.iter().find(|shared| shared.as_ref() == input) {
+            return (shared.clone(), false);
+        }
+        let shared = Bytes::copy_from_slice(input);
+        inputs.push(shared.clone());
+        (shared, true)
+    }
 }
 
 /// Inspector that traces all calls and contract creations during EVM execution.
-/// Maintains a stack of call frames to properly track nested calls.
+///
+/// Recorded frames are stored flat, in the order they were entered, with the index of
+/// their parent. The nested tree is only built once, by `into_result`, so deep traces
+/// do not grow and move `calls` vectors while executing.
 #[derive(Debug, Default)]
 pub struct CallTracer {
-    call_stack: Vec<CallFrame>,
+    /// Every recorded frame, parents before their subcalls
+    frames: Vec<ArenaFrame>,
+    /// Recorded frames currently executing, innermost last
+    open_frames: Vec<FrameId>,
+    /// Memory held by `frames`
+    memory: TraceMemoryStats,
+    /// Inputs shared between recorded frames
+    inputs: InputInterner,
+    config: CallTracerConfig,
+    /// Number of frames recorded so far, for `max_frames`
+    recorded_frames: usize,
+    /// Depth of currently executing frames that are not recorded
+    skipped_depth: usize,
+    /// Latest value of every slot touched so far, valid as of the current point in execution
+    storage_values: HashMap<(Address, StorageKey), StorageValue>,
+    /// Previous entries of `storage_values`, undone when the frame that changed them reverts
+    storage_undo_log: Vec<((Address, StorageKey), Option<StorageValue>)>,
+    /// Length of `storage_undo_log` when each executing frame was entered
+    storage_checkpoints: Vec<usize>,
+    /// Slot of an SLOAD whose value is only on the stack after the step
+    pending_sload: Option<(Address, StorageKey)>,
+    /// Progress reported to `config.progress`
+    progress: TraceProgress,
+    /// Gas consumed before each executing frame was entered, and the frame's gas limit
+    progress_frames: Vec<(u64, u64)>,
+    /// When the top-level frame was entered, for `ExecutionLimits::timeout_ms`
+    #[cfg(not(target_arch = "wasm32"))]
+    started_at: Option<Instant>,
+    /// Steps executed so far, to check the timeout every `TIMEOUT_CHECK_INTERVAL` steps
+    steps: u64,
+    /// Frames entered so far, recorded or not
+    frames_entered: u64,
+    /// Logs emitted so far, recorded or not
+    logs_emitted: u64,
+    /// Limit that stopped execution, if any
+    truncated: Option<TruncationReason>,
+    /// Whether `config.cancellation` stopped execution
+    cancelled: bool,
+    /// Hash inputs that may derive storage slots, recorded if `config.storage_layout` is set
+    preimages: SlotPreimages,
+    /// Transfers made so far, if `config.with_value_transfers` is set
+    value_transfers: ValueTransferTracker,
+    /// Gas spent by the completed subcalls of each executing frame, recorded or not
+    subcall_gas: Vec<u64>,
 }
 
 impl CallTracer {
     /// Creates a new CallTracer instance.
     pub fn new() -> Self {
+        Self::new_with_config(CallTracerConfig::default())
+    }
+
+    /// Creates a new CallTracer instance with the given options.
+    pub fn new_with_config(config: CallTracerConfig) -> Self {
         Self {
-            call_stack: Vec::new(),
+            frames: Vec::new(),
+            open_frames: Vec::new(),
+            memory: TraceMemoryStats::default(),
+            inputs: InputInterner::default(),
+            config,
+            recorded_frames: 0,
+            skipped_depth: 0,
+            storage_values: HashMap::default(),
+            storage_undo_log: Vec::new(),
+            storage_checkpoints: Vec::new(),
+            pending_sload: None,
+            progress: TraceProgress::default(),
+            progress_frames: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            started_at: None,
+            steps: 0,
+            frames_entered: 0,
+            logs_emitted: 0,
+            truncated: None,
+            cancelled: false,
+            preimages: SlotPreimages::default(),
+            value_transfers: ValueTransferTracker::default(),
+            subcall_gas: Vec::new(),
+        }
+    }
+
+    /// Whether execution was stopped through `config.cancellation`
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Labeler for the storage slots touched by the trace, if `config.storage_layout` or
+    /// `config.slot_labels` is set
+    pub fn take_storage_labeler(&mut self) -> Option<StorageLabeler> {
+        let layouts = self.config.storage_layout.take();
+        let slot_labels = self.config.slot_labels.take();
+        if layouts.is_none() && slot_labels.is_none() {
+            return None;
+        }
+        Some(StorageLabeler {
+            layouts: layouts.unwrap_or_default(),
+            slot_labels: slot_labels.unwrap_or_default(),
+            preimages: std::mem::take(&mut self.preimages),
+        })
+    }
+
+    /// ETH transfers of the frames that succeeded, if `config.with_value_transfers` is set
+    pub fn take_value_transfers(&mut self) -> Vec<ValueTransfer> {
+        std::mem::take(&mut self.value_transfers).into_transfers()
+    }
+
+    /// The limit that stopped execution, if `config.limits` cut the trace short
+    pub fn truncation_reason(&self) -> Option<TruncationReason> {
+        self.truncated
+    }
+
+    /// Frames entered so far, including those not recorded because of `config` limits
+    pub fn frames_entered(&self) -> u64 {
+        self.frames_entered
+    }
+
+    /// Logs emitted so far, including those not recorded
+    pub fn logs_emitted(&self) -> u64 {
+        self.logs_emitted
+    }
+
+    /// Memory held by the recorded frames, now and at its peak
+    pub fn memory_stats(&self) -> TraceMemoryStats {
+        self.memory
+    }
+
+    /// The innermost recorded frame that is executing
+    fn current_frame(&self) -> Option<&CallFrame> {
+        let id = self.open_frames.last()?;
+        Some(&self.frames[id.0].frame)
+    }
+
+    fn current_frame_mut(&mut self) -> Option<&mut CallFrame> {
+        let id = self.open_frames.last()?;
+        Some(&mut self.frames[id.0].frame)
+    }
+
+    /// Calldata of a frame being entered. Inputs revm passes as bytes are shared as they
+    /// are. Inputs in the caller's memory are shared with the caller's own input if they
+    /// match, as when a proxy forwards its calldata, and interned otherwise. Returns the
+    /// input and the bytes newly allocated for it.
+    fn capture_input<CTX: ContextTr>(&mut self, context: &mut CTX, input: &CallInput) -> (Bytes, usize) {
+        let CallInput::SharedBuffer(range) = input else {
+            let input = input.bytes(context);
+            let len = input.len();
+            return (input, len);
+        };
+        let Some(slice) = context.local().shared_memory_buffer_slice(range.clone()) else {
+            return (Bytes::new(), 0);
+        };
+        if slice.is_empty() {
+            return (Bytes::new(), 0);
+        }
+        if let Some(caller) = self.current_frame().filter(|caller| caller.input.as_ref() == &*slice) {
+            return (caller.input.clone(), 0);
+        }
+        // Completed frames are dropped when streaming, so their inputs must not be kept
+        if self.config.stream_only {
+            return (Bytes::copy_from_slice(&slice), slice.len());
+        }
+        match self.inputs.intern(&slice) {
+            (input, true) => (input, slice.len()),
+            (input, false) => (input, 0),
+        }
+    }
+
+    /// Records `frame` under the innermost executing frame. An `open` frame becomes the
+    /// innermost one until it is finalized; others, like SELFDESTRUCT, are complete and
+    /// attached to their parent right away. `input_bytes` is what was allocated for its
+    /// input, see `capture_input`.
+    fn push_frame(&mut self, frame: CallFrame, input_bytes: usize, open: bool) {
+        let id = FrameId(self.frames.len());
+        let parent = self.open_frames.last().copied();
+        if let Some(parent) = parent.filter(|_| !open) {
+            self.frames[parent.0].subcalls += 1;
+        }
+        self.frames.push(ArenaFrame { frame, parent, subcalls: 0, input_bytes, bytes: 0 });
+        self.memory.frames += 1;
+        self.memory.peak_frames = self.memory.peak_frames.max(self.memory.frames);
+        self.account_frame(id);
+        if open {
+            self.open_frames.push(id);
+        }
+    }
+
+    /// Brings the bytes accounted for a frame up to date with its contents
+    fn account_frame(&mut self, id: FrameId) {
+        let arena_frame = &mut self.frames[id.0];
+        let bytes = frame_bytes(&arena_frame.frame) + arena_frame.input_bytes;
+        self.memory.bytes = self.memory.bytes - arena_frame.bytes + bytes;
+        arena_frame.bytes = bytes;
+        self.memory.peak_bytes = self.memory.peak_bytes.max(self.memory.bytes);
+    }
+
+    /// Adds `bytes` of data attached to the innermost executing frame
+    fn grow_current_frame(&mut self, bytes: usize) {
+        if let Some(id) = self.open_frames.last() {
+            self.frames[id.0].bytes += bytes;
+            self.memory.bytes += bytes;
+            self.memory.peak_bytes = self.memory.peak_bytes.max(self.memory.bytes);
+        }
+    }
+
+    /// Drops a completed frame and its subcalls, which come after it in `frames`
+    fn release_frame(&mut self, id: FrameId) {
+        for arena_frame in self.frames.drain(id.0..) {
+            self.memory.frames -= 1;
+            self.memory.bytes -= arena_frame.bytes;
+        }
+    }
+
+    /// Stops execution for `reason`, unless an earlier limit already did
+    fn truncate(&mut self, reason: TruncationReason) {
+        if self.truncated.is_none() {
+            self.truncated = Some(reason);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(?reason, frames = self.frames_entered, "execution limit reached");
+        }
+    }
+
+    /// Sends an event to `config.events`, building it only if anyone listens
+    fn emit(&self, event: impl FnOnce() -> TraceEvent) {
+        if let Some(events) = &self.config.events {
+            (events.0)(event());
+        }
+    }
+
+    /// Records the input of a KECCAK256 about to run, when it is long enough to derive a slot
+    fn record_preimage(&mut self, interp: &Interpreter<EthInterpreter>) {
+        let (Ok(offset), Ok(length)) = (interp.stack.peek(0), interp.stack.peek(1)) else {
+            return;
+        };
+        let (Ok(offset), Ok(length)) = (usize::try_from(offset), usize::try_from(length)) else {
+            return;
+        };
+        if (length == 32 || length == 64) && offset.saturating_add(length) <= interp.memory.size() {
+            self.preimages.record(&interp.memory.slice(offset..offset + length));
+        }
+    }
+
+    /// Counts a frame being entered against the frame and depth limits
+    fn check_frame_limits(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        self.started_at.get_or_insert_with(Instant::now);
+        self.frames_entered += 1;
+        let depth = self.open_frames.len() + self.skipped_depth + 1;
+        let too_deep = self.config.limits.max_call_depth.is_some_and(|max_call_depth| depth > max_call_depth);
+        let too_many_frames = self.config.limits.max_frames.is_some_and(|max_frames| self.frames_entered > max_frames);
+        if too_deep {
+            self.truncate(TruncationReason::CallDepth);
+        } else if too_many_frames {
+            self.truncate(TruncationReason::Frames);
+        }
+    }
+
+    /// Counts a step and returns true if execution must stop
+    fn check_step_limits(&mut self) -> bool {
+        self.steps += 1;
+        if !self.cancelled && self.config.cancellation.as_ref().is_some_and(CancellationHandle::is_cancelled) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(frames = self.frames_entered, "trace cancelled");
+            self.cancelled = true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.truncated.is_none() && self.steps.is_multiple_of(TIMEOUT_CHECK_INTERVAL) {
+            let timed_out = self.config.limits.timeout_ms
+                .zip(self.started_at)
+                .is_some_and(|(timeout_ms, started_at)| started_at.elapsed() >= Duration::from_millis(timeout_ms));
+            if timed_out {
+                self.truncate(TruncationReason::Timeout);
+            }
+        }
+        self.cancelled || self.truncated.is_some()
+    }
+
+    /// Starts accounting the gas of a new frame for progress reporting
+    fn enter_progress_frame(&mut self, gas_limit: u64) {
+        if self.config.progress.is_some() {
+            self.progress_frames.push((self.progress.gas_used, gas_limit));
+        }
+    }
+
+    /// Updates the gas consumed so far from the gas left in the executing frame
+    fn update_progress_gas(&mut self, gas_remaining: u64) {
+        if let Some((gas_before, gas_limit)) = self.progress_frames.last() {
+            self.progress.gas_used = gas_before + gas_limit.saturating_sub(gas_remaining);
+        }
+    }
+
+    /// Ends a frame that spent `gas_spent` and reports progress
+    fn exit_progress_frame(&mut self, gas_spent: u64) {
+        let Some(callback) = &self.config.progress else {
+            return;
+        };
+        if let Some((gas_before, _)) = self.progress_frames.pop() {
+            self.progress.gas_used = gas_before + gas_spent;
+        }
+        self.progress.frames += 1;
+        (callback.0)(self.progress);
+    }
+
+    /// Reports progress every `PROGRESS_STEP_INTERVAL` steps, between frame exits
+    fn report_step_progress(&self) {
+        if let Some(callback) = &self.config.progress {
+            if self.steps > 0 && self.steps.is_multiple_of(PROGRESS_STEP_INTERVAL) {
+                (callback.0)(self.progress);
+            }
+        }
+    }
+
+    /// Starts counting the gas spent by subcalls of a new frame
+    fn enter_gas_frame(&mut self) {
+        self.subcall_gas.push(0);
+    }
+
+    /// Ends a frame that spent `gas_spent` and returns the part its own code spent
+    fn exit_gas_frame(&mut self, gas_spent: u64) -> u64 {
+        let subcall_gas = self.subcall_gas.pop().unwrap_or_default();
+        if let Some(parent) = self.subcall_gas.last_mut() {
+            *parent += gas_spent;
+        }
+        // A subcall may spend the free stipend of a value transfer, which its caller did not pay
+        gas_spent.saturating_sub(subcall_gas)
+    }
+
+    /// Decides whether a frame entered now is recorded. Once a frame is skipped, so
+    /// are all of its subcalls. An `excluded` frame is skipped regardless of limits.
+    fn enter_frame(&mut self, excluded: bool) -> bool {
+        let depth = self.open_frames.len() + 1;
+        let within_limits = !excluded
+            && self.skipped_depth == 0
+            && !(self.config.only_top_call && depth > 1)
+            && self.config.max_depth.is_none_or(|max_depth| depth <= max_depth)
+            && self.config.max_frames.is_none_or(|max_frames| self.recorded_frames < max_frames);
+
+        if within_limits {
+            self.recorded_frames += 1;
+        } else {
+            self.skipped_depth += 1;
+        }
+        within_limits
+    }
+
+    /// Returns true if the frame being exited was skipped by `enter_frame`.
+    fn exit_skipped_frame(&mut self) -> bool {
+        if self.skipped_depth > 0 {
+            self.skipped_depth -= 1;
+            true
+        } else {
+            false
         }
     }
 
     /// Consumes the tracer and returns the root call frame, if any.
-    pub fn into_result(mut self) -> Option<CallFrame> {
-        self.call_stack.pop()
+    ///
+    /// Builds the tree from the last frame back: subcalls always come after their parent,
+    /// so a frame has all of its own by the time it is moved into its parent.
+    pub fn into_result(self) -> Option<CallFrame> {
+        let mut frames = self.frames;
+        while frames.len() > 1 {
+            let ArenaFrame { mut frame, parent, .. } = frames.pop()?;
+            frame.calls.reverse();
+            if let Some(FrameId(parent)) = parent {
+                let subcalls = frames[parent].subcalls;
+                let calls = &mut frames[parent].frame.calls;
+                if calls.is_empty() {
+                    calls.reserve_exact(subcalls);
+                }
+                calls.push(frame);
+            }
+        }
+        let mut root = frames.pop()?.frame;
+        root.calls.reverse();
+        Some(root)
+    }
+
+    /// Marks the start of a frame, so its storage writes can be undone if it reverts.
+    fn enter_storage_frame(&mut self) {
+        if self.config.with_storage {
+            self.storage_checkpoints.push(self.storage_undo_log.len());
+        }
+    }
+
+    /// Ends a frame, restoring the slot values it changed if it did not succeed.
+    fn exit_storage_frame(&mut self, is_success: bool) {
+        let Some(checkpoint) = self.storage_checkpoints.pop() else {
+            return;
+        };
+        if is_success {
+            return;
+        }
+        for (key, previous) in self.storage_undo_log.drain(checkpoint..).rev() {
+            match previous {
+                Some(value) => self.storage_values.insert(key, value),
+                None => self.storage_values.remove(&key),
+            };
+        }
+    }
+
+    /// Updates the latest known value of a slot, remembering the previous one.
+    fn set_storage_value(&mut self, key: (Address, StorageKey), value: StorageValue) {
+        let previous = self.storage_values.insert(key, value);
+        self.storage_undo_log.push((key, previous));
+    }
+
+    /// Returns the current value of a slot without touching the journal, so recording
+    /// never changes warm/cold gas accounting. Slots not seen yet in this transaction
+    /// still hold their pre-transaction value, which is read from the database.
+    fn current_storage_value<DB: Database>(&self, db: &mut DB, address: Address, slot: StorageKey) -> StorageValue {
+        match self.storage_values.get(&(address, slot)) {
+            Some(value) => *value,
+            None => db.storage(address, slot).unwrap_or_default(),
+        }
+    }
+
+    /// Sets the implementation of the current frame if it delegates as a standard proxy.
+    /// Only the first delegate call of a frame is considered.
+    fn resolve_proxy<DB: Database>(&mut self, db: &mut DB, proxy: Address, implementation: Address) {
+        if self.current_frame().is_none_or(|frame| frame.implementation.is_some()) {
+            return;
+        }
+        let resolved = resolve_proxy(proxy, implementation, |address, slot| {
+            self.current_storage_value(db, address, slot)
+        });
+        if let Some(frame) = self.current_frame_mut() {
+            frame.implementation = resolved;
+        }
     }
 
     /// Converts a call scheme byte to its string representation.
+    ///
+    /// EOF call opcodes (EXTCALL, EXTDELEGATECALL, EXTSTATICCALL) have no scheme here:
+    /// EOF (EIP-7692) was dropped from Osaka and removed from revm before v29, so
+    /// there are no EOF frames to classify. Should it return, they will show up as
+    /// "UNKNOWN" rather than being mislabelled.
     fn call_type_from_scheme(scheme: u8) -> &'static str {
         match scheme {
             0 => "CALL",
@@ -85,51 +923,101 @@ impl CallTracer {
         }
     }
 
+    /// Converts a create scheme to its string representation.
+    ///
+    /// `Custom` creations (contracts placed at a caller-chosen address, e.g. by system
+    /// calls) follow CREATE semantics. EOFCREATE/RETURNCONTRACT do not exist in this
+    /// revm version, see `call_type_from_scheme`.
+    fn create_type_from_scheme(scheme: CreateScheme) -> &'static str {
+        match scheme {
+            CreateScheme::Create2 { .. } => "CREATE2",
+            _ => "CREATE",
+        }
+    }
+
     /// Common logic for finalizing a frame after execution completes.
     /// Updates gas usage, sets output/error info, and adds to parent frame or root.
     fn finalize_frame(
         &mut self,
         gas_spent: u64,
+        self_gas_spent: u64,
         is_success: bool,
         output: Bytes,
         created_address: Option<Address>,
     ) {
-        if let Some(mut frame) = self.call_stack.pop() {
-            frame.gas_used = U256::from(gas_spent);
-
-            if is_success {
-                // For contract creation, set the created address as output
-                if let Some(address) = created_address {
-                    frame.to = Some(address);
-                    frame.output = Some(Bytes::from(address.into_array()));
-                } else {
-                    frame.output = Some(output);
-                }
+        let Some(id) = self.open_frames.pop() else {
+            return;
+        };
+        let frame = &mut self.frames[id.0].frame;
+        frame.gas_used = U256::from(gas_spent);
+        frame.self_gas_used = U256::from(self_gas_spent);
+
+        if is_success {
+            // For contract creation, set the created address as output
+            if let Some(address) = created_address {
+                frame.to = Some(address);
+                frame.output = Some(Bytes::from(address.into_array()));
+                frame.deployed_code = Some(output);
             } else {
-                frame.error = Some(ERROR_EXECUTION_REVERTED.to_string());
-                if !output.is_empty() {
-                    frame.revert_reason = Some(format!("{}{}", HEX_PREFIX, hex::encode(&output)));
-                }
+                frame.output = Some(output);
+            }
+        } else {
+            frame.error = Some(ERROR_EXECUTION_REVERTED.to_string());
+            if !output.is_empty() {
+                frame.revert_reason = Some(format!("{}{}", HEX_PREFIX, hex::encode(&output)));
+                frame.decoded_revert = decode_revert_reason(&output);
             }
+        }
 
-            // Add this frame as a subcall to the parent frame, or push it back if it's the root
-            if let Some(parent) = self.call_stack.last_mut() {
-                parent.calls.push(frame);
-            } else {
-                self.call_stack.push(frame);
+        let depth = self.open_frames.len() + 1;
+        self.emit(|| TraceEvent::CallEnd { depth, frame: self.frames[id.0].frame.clone_without_calls() });
+
+        // Attach the frame to its parent; with `stream_only`, only the root is kept, but
+        // the parent still counts the subcall for the position of its later logs
+        match self.frames[id.0].parent {
+            Some(parent) => {
+                self.frames[parent.0].subcalls += 1;
+                if self.config.stream_only {
+                    self.release_frame(id);
+                } else {
+                    self.account_frame(id);
+                }
             }
+            None => self.account_frame(id),
         }
     }
 }
 
-impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer {
-    fn initialize_interp(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX) {}
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for CallTracer {
+    fn initialize_interp(&mut self, _interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {}
 
     fn call(
         &mut self,
         context: &mut CTX,
         inputs: &mut CallInputs,
     ) -> Option<CallOutcome> {
+        self.check_frame_limits();
+        self.enter_storage_frame();
+        self.enter_progress_frame(inputs.gas_limit);
+        self.enter_gas_frame();
+        if self.config.with_value_transfers {
+            let amount = inputs.transfer_value().unwrap_or_default();
+            self.value_transfers.enter(inputs.caller, inputs.target_address, amount, ValueTransferKind::Call);
+        }
+        // The delegating frame is recorded unless an ancestor was skipped
+        if self.config.resolve_proxies && inputs.scheme == CallScheme::DelegateCall && self.skipped_depth == 0 {
+            self.resolve_proxy(context.db_mut(), inputs.target_address, inputs.bytecode_address);
+        }
+        let excluded = inputs.scheme == CallScheme::StaticCall && self.config.static_calls != StaticCallCapture::Record;
+        if excluded && self.skipped_depth == 0 && self.config.static_calls == StaticCallCapture::Count {
+            if let Some(caller) = self.current_frame_mut() {
+                caller.static_calls += 1;
+            }
+        }
+        if !self.enter_frame(excluded) {
+            return None;
+        }
+
         let call_type = Self::call_type_from_scheme(inputs.scheme as u8).to_string();
         // Get transfer value, defaulting to zero if not available
         let value = inputs.transfer_value().unwrap_or(U256::ZERO);
@@ -139,6 +1027,8 @@ impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer
             from = inputs.target_address;
             to = Some(inputs.bytecode_address);
         }
+        let precompile = precompile_name(inputs.bytecode_address);
+        let (input, input_bytes) = self.capture_input(context, &inputs.input);
 
         let frame = CallFrame {
             call_type,
@@ -147,26 +1037,60 @@ impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer
             value,
             gas: U256::from(inputs.gas_limit),
             gas_used: U256::ZERO, // Will be updated in call_end
-            input: inputs.input.bytes(context),
+            self_gas_used: U256::ZERO,
+            input,
             output: None,
+            decoded_input: None,
+            decoded_output: None,
             error: None,
             revert_reason: None,
+            decoded_revert: None,
             logs: Vec::new(),
+            is_precompile: precompile.is_some(),
+            precompile: precompile.map(str::to_string),
+            precompile_data: None,
+            storage_reads: Vec::new(),
+            storage_writes: Vec::new(),
             calls: Vec::new(),
+            static_calls: 0,
+            truncated: None,
+            implementation: None,
+            deployed_code: None,
         };
 
-        self.call_stack.push(frame);
+        let depth = self.open_frames.len() + 1;
+        self.emit(|| TraceEvent::CallStart { depth, frame: frame.clone() });
+        self.push_frame(frame, input_bytes, true);
         None
     }
 
     fn call_end(
         &mut self,
         _context: &mut CTX,
-        _inputs: &CallInputs,
+        inputs: &CallInputs,
         outcome: &mut CallOutcome,
     ) {
+        self.exit_storage_frame(outcome.result.is_ok());
+        self.exit_progress_frame(outcome.result.gas.spent());
+        let self_gas_spent = self.exit_gas_frame(outcome.result.gas.spent());
+        if self.config.with_value_transfers {
+            self.value_transfers.exit(outcome.result.is_ok(), None);
+        }
+        if self.exit_skipped_frame() {
+            return;
+        }
+        if self.config.decode_precompiles {
+            if let Some(frame) = self.current_frame_mut().filter(|frame| frame.is_precompile) {
+                frame.precompile_data = decode_precompile(
+                    inputs.bytecode_address,
+                    &frame.input,
+                    &outcome.result.output,
+                );
+            }
+        }
         self.finalize_frame(
             outcome.result.gas.spent(),
+            self_gas_spent,
             outcome.result.is_ok(),
             outcome.result.output.clone(),
             None,
@@ -178,11 +1102,18 @@ impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer
         _context: &mut CTX,
         inputs: &mut CreateInputs,
     ) -> Option<CreateOutcome> {
-        let call_type = if inputs.scheme == revm::interpreter::CreateScheme::Create {
-            "CREATE"
-        } else {
-            "CREATE2"
-        }.to_string();
+        self.check_frame_limits();
+        self.enter_storage_frame();
+        self.enter_progress_frame(inputs.gas_limit);
+        self.enter_gas_frame();
+        if self.config.with_value_transfers {
+            self.value_transfers.enter(inputs.caller, Address::ZERO, inputs.value, ValueTransferKind::Create);
+        }
+        if !self.enter_frame(false) {
+            return None;
+        }
+
+        let call_type = Self::create_type_from_scheme(inputs.scheme).to_string();
 
         let frame = CallFrame {
             call_type,
@@ -191,15 +1122,31 @@ impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer
             value: inputs.value,
             gas: U256::from(inputs.gas_limit),
             gas_used: U256::ZERO,
+            self_gas_used: U256::ZERO,
             input: inputs.init_code.clone(),
             output: None,
+            decoded_input: None,
+            decoded_output: None,
             error: None,
             revert_reason: None,
+            decoded_revert: None,
             logs: Vec::new(),
+            is_precompile: false,
+            precompile: None,
+            precompile_data: None,
+            storage_reads: Vec::new(),
+            storage_writes: Vec::new(),
             calls: Vec::new(),
+            static_calls: 0,
+            truncated: None,
+            implementation: None,
+            deployed_code: None,
         };
 
-        self.call_stack.push(frame);
+        let depth = self.open_frames.len() + 1;
+        self.emit(|| TraceEvent::CreateStart { depth, frame: frame.clone() });
+        let input_bytes = frame.input.len();
+        self.push_frame(frame, input_bytes, true);
         None
     }
 
@@ -209,44 +1156,800 @@ impl<CTX: ContextTr, INTR: InterpreterTypes> Inspector<CTX, INTR> for CallTracer
         _inputs: &CreateInputs,
         outcome: &mut CreateOutcome,
     ) {
+        self.exit_storage_frame(outcome.result.is_ok());
+        self.exit_progress_frame(outcome.result.gas.spent());
+        let self_gas_spent = self.exit_gas_frame(outcome.result.gas.spent());
+        if self.config.with_value_transfers {
+            self.value_transfers.exit(outcome.result.is_ok(), outcome.address);
+        }
+        if self.exit_skipped_frame() {
+            return;
+        }
         self.finalize_frame(
             outcome.result.gas.spent(),
+            self_gas_spent,
             outcome.result.is_ok(),
             outcome.result.output.clone(),
             outcome.address,
         );
     }
 
-    fn step(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX) {}
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.config.with_value_transfers {
+            self.value_transfers.selfdestruct(contract, target, value);
+        }
+        if self.skipped_depth > 0 || self.config.only_top_call || self.config.stream_only {
+            return;
+        }
+        // Recorded like geth does, as a subcall that spends no gas of its own
+        if !self.open_frames.is_empty() {
+            self.push_frame(CallFrame {
+                call_type: "SELFDESTRUCT".to_string(),
+                from: contract,
+                to: Some(target),
+                value,
+                gas: U256::ZERO,
+                gas_used: U256::ZERO,
+                self_gas_used: U256::ZERO,
+                input: Bytes::new(),
+                output: None,
+                decoded_input: None,
+                decoded_output: None,
+                error: None,
+                revert_reason: None,
+                decoded_revert: None,
+                logs: Vec::new(),
+                is_precompile: false,
+                precompile: None,
+                precompile_data: None,
+                storage_reads: Vec::new(),
+                storage_writes: Vec::new(),
+                calls: Vec::new(),
+                static_calls: 0,
+                truncated: None,
+                implementation: None,
+                deployed_code: None,
+            }, 0, false);
+        }
+    }
 
-    fn step_end(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX) {}
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        self.update_progress_gas(interp.gas.remaining());
+        self.report_step_progress();
+        if self.check_step_limits() {
+            interp.halt(InstructionResult::OutOfGas);
+            return;
+        }
+        if self.config.storage_layout.is_some() && interp.bytecode.opcode() == opcode::KECCAK256 {
+            self.record_preimage(interp);
+        }
+        if !self.config.with_storage {
+            return;
+        }
+        let address = interp.input.target_address;
+        match interp.bytecode.opcode() {
+            opcode::SLOAD => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.pending_sload = Some((address, slot));
+                }
+            }
+            opcode::SSTORE => {
+                let (Ok(slot), Ok(value_after)) = (interp.stack.peek(0), interp.stack.peek(1)) else {
+                    return;
+                };
+                let value_before = self.current_storage_value(context.db_mut(), address, slot);
+                self.set_storage_value((address, slot), value_after);
+                if self.skipped_depth > 0 {
+                    return;
+                }
+                if let Some(frame) = self.current_frame_mut() {
+                    frame.storage_writes.push(StorageAccess { address, slot, value_before, value_after, label: None });
+                    self.grow_current_frame(size_of::<StorageAccess>());
+                }
+            }
+            _ => {}
+        }
+    }
 
-    fn log(&mut self, _interp: &mut Interpreter<INTR>, _context: &mut CTX, log: Log) {
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        // The loaded value replaces the slot on top of the stack once SLOAD has run
+        let Some((address, slot)) = self.pending_sload.take() else {
+            return;
+        };
+        let Ok(value) = interp.stack.peek(0) else {
+            return;
+        };
+        self.set_storage_value((address, slot), value);
+        if self.skipped_depth > 0 {
+            return;
+        }
+        if let Some(frame) = self.current_frame_mut() {
+            frame.storage_reads.push(StorageAccess { address, slot, value_before: value, value_after: value, label: None });
+            self.grow_current_frame(size_of::<StorageAccess>());
+        }
+    }
+
+    fn log(&mut self, _interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX, log: Log) {
+        self.logs_emitted += 1;
+        if self.config.limits.max_logs.is_some_and(|max_logs| self.logs_emitted > max_logs) {
+            self.truncate(TruncationReason::Logs);
+            return;
+        }
+        if !self.config.with_logs || self.skipped_depth > 0 {
+            return;
+        }
+        let mut log = LogEntry::from(log);
+        log.position = self.open_frames.last().map_or(0, |id| self.frames[id.0].subcalls);
+        let depth = self.open_frames.len();
+        self.emit(|| TraceEvent::Log { depth, log: log.clone() });
+        if self.config.stream_only {
+            return;
+        }
         // Add the log to the current frame (top of the stack)
-        if let Some(frame) = self.call_stack.last_mut() {
-            frame.logs.push(LogEntry::from(log));
+        let bytes = log_bytes(&log);
+        if let Some(frame) = self.current_frame_mut() {
+            frame.logs.push(log);
+            self.grow_current_frame(bytes);
+        }
+    }
+}
+
+/// Estimated bytes held by a frame, not counting its subcalls or its input, which may
+/// be shared
+fn frame_bytes(frame: &CallFrame) -> usize {
+    let optional_len = |data: &Option<Bytes>| data.as_ref().map_or(0, |data| data.len());
+    let optional_str = |text: &Option<String>| text.as_ref().map_or(0, |text| text.len());
+    size_of::<CallFrame>()
+        + frame.call_type.len()
+        + optional_len(&frame.output)
+        + optional_len(&frame.deployed_code)
+        + optional_str(&frame.error)
+        + optional_str(&frame.revert_reason)
+        + frame.logs.iter().map(log_bytes).sum::<usize>()
+        + (frame.storage_reads.len() + frame.storage_writes.len()) * size_of::<StorageAccess>()
+}
+
+fn log_bytes(log: &LogEntry) -> usize {
+    size_of::<LogEntry>() + log.topics.len() * size_of::<B256>() + log.data.len()
+}
+
+/// Inspector that records the pre-execution state of every account and storage slot
+/// touched during execution, in the format of geth's `prestateTracer`.
+///
+/// Values are read from the database the first time an account or slot is touched.
+/// The database is not modified while a transaction executes, so this yields the state
+/// the transaction started from with any `Database`, including one backed by a remote node.
+#[derive(Debug, Default)]
+pub struct PrestateTracer {
+    accounts: HashMap<Address, AccountDetails>,
+    error: Option<String>,
+}
+
+impl PrestateTracer {
+    /// Creates a new PrestateTracer instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consumes the tracer and returns the recorded prestate.
+    ///
+    /// Fails with `TraceError::Database` if any read from the database failed, since
+    /// the prestate would then be incomplete.
+    pub fn into_result(self) -> Result<HashMap<Address, AccountDetails>, TraceError> {
+        match self.error {
+            Some(error) => Err(TraceError::Database(error)),
+            None => Ok(self.accounts),
+        }
+    }
+
+    fn record_account<DB: Database>(&mut self, db: &mut DB, address: Address) {
+        if self.accounts.contains_key(&address) {
+            return;
+        }
+        match Self::read_account(db, address) {
+            Ok(details) => {
+                self.accounts.insert(address, details);
+            }
+            Err(error) => {
+                self.error.get_or_insert(error.to_string());
+            }
+        }
+    }
+
+    fn read_account<DB: Database>(db: &mut DB, address: Address) -> Result<AccountDetails, DB::Error> {
+        let info = db.basic(address)?.unwrap_or_default();
+        let code = if info.code_hash == KECCAK_EMPTY {
+            None
+        } else {
+            let bytecode = match info.code {
+                Some(bytecode) => bytecode,
+                None => db.code_by_hash(info.code_hash)?,
+            };
+            Some(bytecode.original_bytes())
+        };
+        Ok(AccountDetails {
+            balance: Some(info.balance),
+            nonce: Some(info.nonce),
+            code,
+            storage: None,
+        })
+    }
+
+    fn record_slot<DB: Database>(&mut self, db: &mut DB, address: Address, slot: StorageKey) {
+        self.record_account(db, address);
+        let Some(account) = self.accounts.get_mut(&address) else {
+            return;
+        };
+        let storage = account.storage.get_or_insert_with(HashMap::default);
+        if storage.contains_key(&slot) {
+            return;
+        }
+        match db.storage(address, slot) {
+            Ok(value) => {
+                storage.insert(slot, value);
+            }
+            Err(error) => {
+                self.error.get_or_insert(error.to_string());
+            }
         }
     }
 }
 
-// Custom serialization for U256 to hex string
-mod hex_u256 {
-    use super::*;
-    use serde::{Serializer, Deserializer};
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for PrestateTracer {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        match interp.bytecode.opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    self.record_slot(context.db_mut(), interp.input.target_address, slot);
+                }
+            }
+            opcode::BALANCE
+            | opcode::EXTCODESIZE
+            | opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::SELFDESTRUCT => {
+                if let Ok(word) = interp.stack.peek(0) {
+                    let address = Address::from_word(B256::from(word.to_be_bytes()));
+                    self.record_account(context.db_mut(), address);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut CTX,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        // The block beneficiary receives the fees, so it belongs to the prestate too
+        if self.accounts.is_empty() {
+            let beneficiary = context.block().beneficiary();
+            self.record_account(context.db_mut(), beneficiary);
+        }
+        self.record_account(context.db_mut(), inputs.caller);
+        self.record_account(context.db_mut(), inputs.target_address);
+        self.record_account(context.db_mut(), inputs.bytecode_address);
+        None
+    }
 
-    pub fn serialize<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_str(&format!("0x{:x}", value))
+    fn create(
+        &mut self,
+        context: &mut CTX,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        if self.accounts.is_empty() {
+            let beneficiary = context.block().beneficiary();
+            self.record_account(context.db_mut(), beneficiary);
+        }
+        self.record_account(context.db_mut(), inputs.caller);
+        None
     }
 
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<U256, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let s = String::deserialize(deserializer)?;
-        let s = s.trim_start_matches("0x");
-        U256::from_str_radix(s, 16).map_err(serde::de::Error::custom)
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        if let Some(address) = outcome.address {
+            self.record_account(context.db_mut(), address);
+        }
     }
-}
\ No newline at end of file
+}
+
+/// Options of the opcode logger, mirroring geth's struct logger config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpcodeTracerConfig {
+    /// Capture memory at every step; large and off by default
+    pub enable_memory: bool,
+    pub disable_stack: bool,
+    pub disable_storage: bool,
+}
+
+/// A single executed opcode, as in geth's `structLogs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc: u64,
+    pub op: String,
+    /// Gas remaining before the opcode executes
+    pub gas: u64,
+    pub gas_cost: u64,
+    /// Call depth, starting at 1 for the top-level frame
+    pub depth: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// Memory as 32-byte words
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<B256>>,
+    /// Storage of the executing contract seen so far; only set on SLOAD/SSTORE
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<StorageKey, U256>>,
+}
+
+/// Inspector that logs every executed opcode, like geth's default `debug_traceTransaction`
+/// tracer. Needed to explain low-level failures the call tracer cannot, at the price of
+/// one entry per step.
+#[derive(Debug, Default)]
+pub struct OpcodeTracer {
+    config: OpcodeTracerConfig,
+    logs: Vec<StructLog>,
+    depth: u64,
+    /// Slots read or written per contract, as reported in `StructLog::storage`
+    storage: HashMap<Address, BTreeMap<StorageKey, U256>>,
+    /// Slot of an SLOAD whose value is only on the stack after the step
+    pending_sload: Option<(Address, StorageKey)>,
+}
+
+impl OpcodeTracer {
+    /// Creates a new OpcodeTracer with the given options.
+    pub fn new(config: OpcodeTracerConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Consumes the tracer and returns the logged steps in execution order.
+    pub fn into_result(self) -> Vec<StructLog> {
+        self.logs
+    }
+
+    fn opcode_name(op: u8) -> String {
+        match opcode::OpCode::new(op) {
+            Some(op) => op.as_str().to_string(),
+            None => format!("opcode 0x{:x} not defined", op),
+        }
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for OpcodeTracer {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let op = interp.bytecode.opcode();
+        let address = interp.input.target_address;
+
+        let mut storage_changed = false;
+        if !self.config.disable_storage {
+            match op {
+                opcode::SLOAD => {
+                    if let Ok(slot) = interp.stack.peek(0) {
+                        self.pending_sload = Some((address, slot));
+                    }
+                }
+                opcode::SSTORE => {
+                    if let (Ok(slot), Ok(value)) = (interp.stack.peek(0), interp.stack.peek(1)) {
+                        self.storage.entry(address).or_default().insert(slot, value);
+                        storage_changed = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let stack = (!self.config.disable_stack).then(|| interp.stack.data().clone());
+        let memory = self.config.enable_memory.then(|| {
+            interp.memory.slice(0..interp.memory.size())
+                .chunks(32)
+                .map(B256::right_padding_from)
+                .collect()
+        });
+        let storage = storage_changed
+            .then(|| self.storage.get(&address).cloned().unwrap_or_default());
+
+        self.logs.push(StructLog {
+            pc: interp.bytecode.pc() as u64,
+            op: Self::opcode_name(op),
+            gas: interp.gas.remaining(),
+            gas_cost: 0, // Will be updated in step_end
+            depth: self.depth,
+            stack,
+            memory,
+            storage,
+        });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(log) = self.logs.last_mut() else {
+            return;
+        };
+        log.gas_cost = log.gas.saturating_sub(interp.gas.remaining());
+
+        if let Some((address, slot)) = self.pending_sload.take() {
+            if let Ok(value) = interp.stack.peek(0) {
+                let storage = self.storage.entry(address).or_default();
+                storage.insert(slot, value);
+                log.storage = Some(storage.clone());
+            }
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CallInputs,
+        _outcome: &mut CallOutcome,
+    ) {
+        self.depth -= 1;
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.depth -= 1;
+    }
+}
+
+/// Inspector fanning every hook out to several tracers, so their outputs come from a
+/// single execution instead of one re-execution per tracer.
+///
+/// Tracers are called in field order. A tracer stopping execution, like `CallTracer`
+/// on a limit or cancellation, stops it for all of them.
+#[derive(Debug, Default)]
+pub struct MultiInspector {
+    pub call_tracer: Option<CallTracer>,
+    pub prestate_tracer: Option<PrestateTracer>,
+    pub opcode_tracer: Option<OpcodeTracer>,
+}
+
+/// Forwards a hook to every tracer of a `MultiInspector`, returning the first outcome
+/// one of them produced
+macro_rules! fan_out {
+    ($self:ident, $tracer:ident => $hook:expr) => {{
+        let mut outcome = None;
+        if let Some($tracer) = $self.call_tracer.as_mut() {
+            outcome = outcome.or($hook);
+        }
+        if let Some($tracer) = $self.prestate_tracer.as_mut() {
+            outcome = outcome.or($hook);
+        }
+        if let Some($tracer) = $self.opcode_tracer.as_mut() {
+            outcome = outcome.or($hook);
+        }
+        outcome
+    }};
+}
+
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for MultiInspector {
+    fn initialize_interp(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        fan_out!(self, tracer => { tracer.initialize_interp(interp, context); None::<()> });
+    }
+
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        fan_out!(self, tracer => { tracer.step(interp, context); None::<()> });
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX) {
+        fan_out!(self, tracer => { tracer.step_end(interp, context); None::<()> });
+    }
+
+    fn log(&mut self, interp: &mut Interpreter<EthInterpreter>, context: &mut CTX, log: Log) {
+        fan_out!(self, tracer => { tracer.log(interp, context, log.clone()); None::<()> });
+    }
+
+    fn call(
+        &mut self,
+        context: &mut CTX,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        fan_out!(self, tracer => tracer.call(context, inputs))
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut CTX,
+        inputs: &CallInputs,
+        outcome: &mut CallOutcome,
+    ) {
+        fan_out!(self, tracer => { tracer.call_end(context, inputs, outcome); None::<()> });
+    }
+
+    fn create(
+        &mut self,
+        context: &mut CTX,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        fan_out!(self, tracer => tracer.create(context, inputs))
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        fan_out!(self, tracer => { tracer.create_end(context, inputs, outcome); None::<()> });
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        fan_out!(self, tracer => {
+            Inspector::<CTX, EthInterpreter>::selfdestruct(tracer, contract, target, value);
+            None::<()>
+        });
+    }
+}
+
+/// Entity whose validation code is running, as named by ERC-7562
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationEntity {
+    Factory,
+    Account,
+    Paymaster,
+}
+
+/// A single ERC-7562 validation rule broken during a user operation's validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationViolation {
+    /// Rule identifier from ERC-7562, e.g. `OP-011` or `STO-021`
+    pub rule: String,
+    pub entity: ValidationEntity,
+    /// Contract whose code broke the rule
+    pub address: Address,
+    pub opcode: String,
+    /// Storage owner and slot, for storage rule violations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<(Address, StorageKey)>,
+}
+
+/// Validation rule violations found while tracing a user operation, in execution order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationViolations {
+    pub violations: Vec<ValidationViolation>,
+}
+
+impl ValidationViolations {
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Opcodes ERC-7562 bans during validation (OP-011)
+const VALIDATION_BANNED_OPCODES: [u8; 15] = [
+    opcode::GASPRICE,
+    opcode::GASLIMIT,
+    opcode::DIFFICULTY,
+    opcode::TIMESTAMP,
+    opcode::BASEFEE,
+    opcode::BLOCKHASH,
+    opcode::NUMBER,
+    opcode::SELFBALANCE,
+    opcode::BALANCE,
+    opcode::ORIGIN,
+    opcode::COINBASE,
+    opcode::SELFDESTRUCT,
+    opcode::BLOBHASH,
+    opcode::BLOBBASEFEE,
+    opcode::INVALID,
+];
+
+/// Associated storage of the sender may sit up to this many slots past `keccak256(sender || x)`
+const MAX_ASSOCIATED_SLOT_OFFSET: u64 = 128;
+
+/// Inspector that checks a `handleOps` execution against the ERC-7562 validation rules.
+///
+/// Only the validation phase is checked: the EntryPoint's subcalls before it calls into
+/// itself to execute the operation. Entities are treated as unstaked, so a factory or
+/// paymaster touching its own storage is reported; callers that know an entity is staked
+/// can drop those violations.
+#[derive(Debug, Default)]
+pub struct ValidationRulesTracer {
+    entry_point: Address,
+    sender: Address,
+    paymaster: Option<Address>,
+    depth: usize,
+    /// Entity of the current validation call; `None` outside validation
+    entity: Option<ValidationEntity>,
+    validation_done: bool,
+    create2_count: usize,
+    /// Set after GAS, which must be immediately followed by a call
+    pending_gas: Option<Address>,
+    /// Set on KECCAK256 of data starting with the sender, to record the hash
+    pending_sender_hash: bool,
+    /// Hashes of `sender || x`, bases of the sender's associated storage slots
+    sender_hashes: Vec<U256>,
+    violations: Vec<ValidationViolation>,
+}
+
+impl ValidationRulesTracer {
+    /// Creates a tracer for a user operation from `sender`, sponsored by `paymaster` if any
+    pub fn new(entry_point: Address, sender: Address, paymaster: Option<Address>) -> Self {
+        Self {
+            entry_point,
+            sender,
+            paymaster,
+            ..Self::default()
+        }
+    }
+
+    /// Consumes the tracer and returns the violations found
+    pub fn into_result(self) -> ValidationViolations {
+        ValidationViolations { violations: self.violations }
+    }
+
+    fn report(&mut self, rule: &str, address: Address, op: u8, storage: Option<(Address, StorageKey)>) {
+        let Some(entity) = self.entity else {
+            return;
+        };
+        self.violations.push(ValidationViolation {
+            rule: rule.to_string(),
+            entity,
+            address,
+            opcode: OpcodeTracer::opcode_name(op),
+            storage,
+        });
+    }
+
+    /// Storage of the sender itself, or a slot derived from its address like mapping entries
+    fn is_sender_storage(&self, address: Address, slot: StorageKey) -> bool {
+        address == self.sender
+            || address == self.entry_point
+            || slot == U256::from_be_bytes(self.sender.into_word().0)
+            || self.sender_hashes.iter()
+                .any(|base| slot >= *base && slot - *base <= U256::from(MAX_ASSOCIATED_SLOT_OFFSET))
+    }
+
+    fn enter(&mut self, caller: Address, target: Address) {
+        self.depth += 1;
+        // Depth 2 holds the EntryPoint's own subcalls
+        if self.depth != 2 || self.validation_done {
+            return;
+        }
+        if caller == self.entry_point && target == self.entry_point {
+            self.validation_done = true;
+            self.entity = None;
+        } else if target == self.sender {
+            self.entity = Some(ValidationEntity::Account);
+        } else if Some(target) == self.paymaster {
+            self.entity = Some(ValidationEntity::Paymaster);
+        } else {
+            self.entity = Some(ValidationEntity::Factory);
+        }
+    }
+
+    fn exit(&mut self) {
+        if self.depth == 2 {
+            self.entity = None;
+        }
+        self.depth -= 1;
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for ValidationRulesTracer {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if self.entity.is_none() {
+            return;
+        }
+        let op = interp.bytecode.opcode();
+        let address = interp.input.target_address;
+
+        if let Some(gas_address) = self.pending_gas.take() {
+            if !matches!(op, opcode::CALL | opcode::CALLCODE | opcode::DELEGATECALL | opcode::STATICCALL) {
+                self.report("OP-012", gas_address, opcode::GAS, None);
+            }
+        }
+
+        match op {
+            op if VALIDATION_BANNED_OPCODES.contains(&op) => self.report("OP-011", address, op, None),
+            opcode::GAS => self.pending_gas = Some(address),
+            opcode::CREATE => self.report("OP-032", address, op, None),
+            opcode::CREATE2 => {
+                // Only the factory may deploy, and only the sender itself
+                self.create2_count += 1;
+                if self.entity != Some(ValidationEntity::Factory) || self.create2_count > 1 {
+                    self.report("OP-031", address, op, None);
+                }
+            }
+            opcode::SLOAD | opcode::SSTORE => {
+                if let Ok(slot) = interp.stack.peek(0) {
+                    if !self.is_sender_storage(address, slot) {
+                        self.report("STO-021", address, op, Some((address, slot)));
+                    }
+                }
+            }
+            opcode::KECCAK256 => {
+                let (Ok(offset), Ok(length)) = (interp.stack.peek(0), interp.stack.peek(1)) else {
+                    return;
+                };
+                let (Ok(offset), Ok(length)) = (usize::try_from(offset), usize::try_from(length)) else {
+                    return;
+                };
+                if length < 32 || offset.saturating_add(32) > interp.memory.size() {
+                    return;
+                }
+                let first_word = B256::from_slice(&interp.memory.slice(offset..offset + 32));
+                self.pending_sender_hash = first_word == self.sender.into_word();
+            }
+            _ => {}
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        if !std::mem::take(&mut self.pending_sender_hash) {
+            return;
+        }
+        if let Ok(hash) = interp.stack.peek(0) {
+            self.sender_hashes.push(hash);
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.enter(inputs.caller, inputs.target_address);
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CallInputs,
+        _outcome: &mut CallOutcome,
+    ) {
+        self.exit();
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut CTX,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.enter(inputs.caller, Address::ZERO);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        _outcome: &mut CreateOutcome,
+    ) {
+        self.exit();
+    }
+}
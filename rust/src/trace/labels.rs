@@ -0,0 +1,369 @@
+//! Storage slot labels for readable storage diffs, given per slot or derived from
+//! solc storage layouts
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{keccak256, Address, HashMap, StorageKey, StorageValue, B256, U256};
+use revm::state::EvmState;
+
+use crate::trace::inspector::CallFrame;
+
+/// Registry of human-readable names for storage slots, e.g. "owner" or "implementation".
+///
+/// Deserializes from `{ "0xAddress": { "0xSlot": "label" } }`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SlotLabels(HashMap<Address, HashMap<StorageKey, String>>);
+
+impl SlotLabels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `label` for `slot` of `address`, replacing any previous label.
+    pub fn insert(&mut self, address: Address, slot: StorageKey, label: impl Into<String>) {
+        self.0.entry(address).or_default().insert(slot, label.into());
+    }
+
+    /// Looks up the label for `slot` of `address`.
+    pub fn get(&self, address: &Address, slot: &StorageKey) -> Option<&str> {
+        self.0.get(address)
+            .and_then(|slots| slots.get(slot))
+            .map(String::as_str)
+    }
+}
+
+/// A changed storage slot, annotated with its label if one is registered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabeledStorageChange {
+    pub address: Address,
+    pub slot: StorageKey,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub original_value: StorageValue,
+    pub present_value: StorageValue,
+}
+
+/// Lists every storage slot changed in `state_diff`, annotated with labels from `labels`.
+///
+/// Results are sorted by address and slot so the output is stable across runs.
+pub fn label_storage_changes(state_diff: &EvmState, labels: &SlotLabels) -> Vec<LabeledStorageChange> {
+    let mut changes: Vec<LabeledStorageChange> = state_diff.iter()
+        .flat_map(|(address, account)| {
+            account.storage.iter()
+                .filter(|(_, value)| value.is_changed())
+                .map(move |(slot, value)| LabeledStorageChange {
+                    address: *address,
+                    slot: *slot,
+                    label: labels.get(address, slot).map(str::to_string),
+                    original_value: value.original_value,
+                    present_value: value.present_value,
+                })
+        })
+        .collect();
+    changes.sort_by_key(|change| (change.address, change.slot));
+    changes
+}
+
+/// Labels the storage reads and writes of every frame of the tree with `labels`,
+/// keeping any label already set for slots that have none registered.
+pub fn label_storage_accesses(frame: &mut CallFrame, labels: &SlotLabels) {
+    for access in frame.storage_reads.iter_mut().chain(frame.storage_writes.iter_mut()) {
+        if let Some(label) = labels.get(&access.address, &access.slot) {
+            access.label = Some(label.to_string());
+        }
+    }
+    for call in frame.calls.iter_mut() {
+        label_storage_accesses(call, labels);
+    }
+}
+
+/// Deepest chain of hashed slots followed when labeling, e.g. `a[x][y][z]` is three
+const MAX_LABEL_NESTING: usize = 8;
+
+/// Furthest a slot is looked up past the hash it is derived from, which bounds the size
+/// of structs in mappings and of dynamic arrays that can be labeled
+const MAX_DERIVED_OFFSET: u64 = 1 << 32;
+
+/// Storage layouts of contracts by address, to name their slots after the variables
+/// declared in the source
+///
+/// Deserializes from `{ "0xAddress": <solc storageLayout output> }`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct StorageLayouts(HashMap<Address, StorageLayout>);
+
+impl StorageLayouts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, address: Address, layout: StorageLayout) {
+        self.0.insert(address, layout);
+    }
+
+    pub fn get(&self, address: &Address) -> Option<&StorageLayout> {
+        self.0.get(address)
+    }
+}
+
+/// Storage layout of one contract, as emitted by solc with `outputSelection` including
+/// `storageLayout`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageVariable>,
+    pub types: HashMap<String, StorageType>,
+}
+
+/// A state variable or struct member
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageVariable {
+    pub label: String,
+    /// Decimal slot number, relative to the enclosing struct for members
+    pub slot: String,
+    /// Byte offset within the slot, for variables packed together
+    pub offset: u32,
+    /// Key into `StorageLayout::types`
+    #[serde(rename = "type")]
+    pub type_id: String,
+}
+
+/// A type referenced by a storage layout
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct StorageType {
+    /// `inplace`, `mapping`, `dynamic_array` or `bytes`
+    pub encoding: String,
+    pub label: String,
+    /// Decimal size in bytes when stored in place
+    pub number_of_bytes: String,
+    /// Key type of a mapping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Value type of a mapping
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// Element type of an array
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Members of a struct
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<StorageVariable>>,
+}
+
+impl StorageVariable {
+    fn slot(&self) -> U256 {
+        self.slot.parse().unwrap_or_default()
+    }
+}
+
+impl StorageType {
+    fn size(&self) -> U256 {
+        self.number_of_bytes.parse().unwrap_or_default()
+    }
+
+    /// Number of slots taken in place, at least one
+    fn slots(&self) -> U256 {
+        self.size().div_ceil(U256::from(32)).max(U256::from(1))
+    }
+}
+
+/// Inputs of the KECCAK256 computations seen during execution, which is how storage
+/// slots of mapping entries and dynamic arrays are derived
+#[derive(Debug, Clone, Default)]
+pub struct SlotPreimages(BTreeMap<U256, SlotPreimage>);
+
+#[derive(Debug, Clone, Copy)]
+enum SlotPreimage {
+    /// `keccak256(key || slot)`, the slot of a mapping entry
+    MappingEntry { key: B256, slot: U256 },
+    /// `keccak256(slot)`, the start of the data of a dynamic array or long bytes
+    ArrayData { slot: U256 },
+}
+
+impl SlotPreimages {
+    /// Records the input of a KECCAK256; only 32 and 64 byte inputs can derive a slot
+    pub fn record(&mut self, input: &[u8]) {
+        let preimage = match input.len() {
+            32 => SlotPreimage::ArrayData { slot: U256::from_be_slice(input) },
+            64 => SlotPreimage::MappingEntry {
+                key: B256::from_slice(&input[..32]),
+                slot: U256::from_be_slice(&input[32..]),
+            },
+            _ => return,
+        };
+        self.0.insert(U256::from_be_bytes(keccak256(input).0), preimage);
+    }
+
+    /// Hash derived slots that `slot` may belong to, i.e. at or shortly below it, nearest
+    /// first
+    fn candidates(&self, slot: U256) -> impl Iterator<Item = (U256, SlotPreimage)> + '_ {
+        self.0.range(..=slot).rev()
+            .take_while(move |(hash, _)| slot - **hash < U256::from(MAX_DERIVED_OFFSET))
+            .map(|(hash, preimage)| (*hash, *preimage))
+    }
+}
+
+/// Names storage slots after source variables, using the layouts of the contracts and
+/// the hashes computed while the transaction executed, or after registered slot labels
+#[derive(Debug, Clone, Default)]
+pub struct StorageLabeler {
+    pub layouts: StorageLayouts,
+    pub slot_labels: SlotLabels,
+    pub preimages: SlotPreimages,
+}
+
+impl StorageLabeler {
+    /// Name of `slot` of `address`, e.g. `owner`, `balances[0xAb5…]` or
+    /// `positions[7].liquidity`. Packed variables sharing the slot are listed together.
+    /// A label registered in `slot_labels` wins over the layout.
+    pub fn label(&self, address: &Address, slot: StorageKey) -> Option<String> {
+        if let Some(label) = self.slot_labels.get(address, &slot) {
+            return Some(label.to_string());
+        }
+        let layout = self.layouts.get(address)?;
+        let labels: Vec<String> = self.locate(layout, slot, 0).into_iter().map(|(label, _)| label).collect();
+        (!labels.is_empty()).then(|| labels.join(", "))
+    }
+
+    /// Labels the storage reads and writes of every frame of the tree
+    pub fn label_frames(&self, frame: &mut CallFrame) {
+        for access in frame.storage_reads.iter_mut().chain(frame.storage_writes.iter_mut()) {
+            access.label = self.label(&access.address, access.slot);
+        }
+        for call in frame.calls.iter_mut() {
+            self.label_frames(call);
+        }
+    }
+
+    /// Lists every storage slot changed in `state_diff`, labeled like `label_storage_changes`
+    pub fn label_changes(&self, state_diff: &EvmState) -> Vec<LabeledStorageChange> {
+        let mut changes = label_storage_changes(state_diff, &SlotLabels::new());
+        for change in changes.iter_mut() {
+            change.label = self.label(&change.address, change.slot);
+        }
+        changes
+    }
+
+    /// Innermost variables stored at `slot`, with their types
+    fn locate<'a>(&self, layout: &'a StorageLayout, slot: U256, nesting: usize) -> Vec<(String, &'a str)> {
+        let found: Vec<_> = layout.storage.iter()
+            .flat_map(|variable| locate_in(layout, &variable.type_id, variable.slot(), slot, variable.label.clone()))
+            .collect();
+        if !found.is_empty() || nesting >= MAX_LABEL_NESTING {
+            return found;
+        }
+
+        // Not a declared slot, so it may be derived from one by hashing
+        self.preimages.candidates(slot)
+            .map(|(hash, preimage)| self.locate_derived(layout, hash, preimage, slot, nesting))
+            .find(|found| !found.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Variables at `slot` within the mapping entry or array data starting at `hash`
+    fn locate_derived<'a>(
+        &self,
+        layout: &'a StorageLayout,
+        hash: U256,
+        preimage: SlotPreimage,
+        slot: U256,
+        nesting: usize,
+    ) -> Vec<(String, &'a str)> {
+        let container_slot = match preimage {
+            SlotPreimage::MappingEntry { slot, .. } | SlotPreimage::ArrayData { slot } => slot,
+        };
+        let Some((path, container)) = self.locate(layout, container_slot, nesting + 1).into_iter()
+            .find_map(|(path, type_id)| Some((path, layout.types.get(type_id)?)))
+        else {
+            return Vec::new();
+        };
+
+        match (preimage, container.encoding.as_str()) {
+            (SlotPreimage::MappingEntry { key, .. }, "mapping") => {
+                let Some(value_type) = container.value.as_deref() else {
+                    return Vec::new();
+                };
+                let key_type = container.key.as_deref()
+                    .and_then(|key_type| layout.types.get(key_type))
+                    .map_or("", |key_type| key_type.label.as_str());
+                locate_in(layout, value_type, hash, slot, format!("{}[{}]", path, format_key(key, key_type)))
+            }
+            (SlotPreimage::ArrayData { .. }, "dynamic_array") => {
+                let Some(element_type) = container.base.as_deref() else {
+                    return Vec::new();
+                };
+                let Some(element) = layout.types.get(element_type) else {
+                    return Vec::new();
+                };
+                let offset = slot - hash;
+                if element.size() < U256::from(32) && !element.size().is_zero() {
+                    // Several elements share each slot
+                    let per_slot = U256::from(32) / element.size();
+                    let first = offset * per_slot;
+                    let label = format!("{}[{}..{}]", path, first, first + per_slot - U256::from(1));
+                    return vec![(label, element_type)];
+                }
+                let index = offset / element.slots();
+                locate_in(layout, element_type, hash + index * element.slots(), slot, format!("{}[{}]", path, index))
+            }
+            (SlotPreimage::ArrayData { .. }, "bytes") => vec![(path, "")],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Innermost variables of `type_id`, stored in place from `base`, that occupy `slot`
+fn locate_in<'a>(
+    layout: &'a StorageLayout,
+    type_id: &'a str,
+    base: U256,
+    slot: U256,
+    path: String,
+) -> Vec<(String, &'a str)> {
+    let Some(storage_type) = layout.types.get(type_id) else {
+        return if slot == base { vec![(path, type_id)] } else { Vec::new() };
+    };
+    if slot < base || slot - base >= storage_type.slots() {
+        return Vec::new();
+    }
+    if storage_type.encoding != "inplace" {
+        return vec![(path, type_id)];
+    }
+
+    if let Some(members) = &storage_type.members {
+        return members.iter()
+            .flat_map(|member| locate_in(
+                layout,
+                &member.type_id,
+                base + member.slot(),
+                slot,
+                format!("{}.{}", path, member.label),
+            ))
+            .collect();
+    }
+    if let Some(element) = storage_type.base.as_deref().and_then(|base| layout.types.get(base)) {
+        // Static array; elements smaller than a slot are packed and not told apart
+        if element.size() >= U256::from(32) {
+            let index = (slot - base) / element.slots();
+            let element_type = storage_type.base.as_deref().unwrap_or_default();
+            return locate_in(layout, element_type, base + index * element.slots(), slot, format!("{}[{}]", path, index));
+        }
+    }
+    vec![(path, type_id)]
+}
+
+/// Renders a mapping key according to its Solidity type
+fn format_key(key: B256, key_type: &str) -> String {
+    if key_type == "address" || key_type.starts_with("contract ") {
+        Address::from_word(key).to_string()
+    } else if key_type.starts_with("uint") || key_type.starts_with("enum ") {
+        U256::from_be_bytes(key.0).to_string()
+    } else {
+        key.to_string()
+    }
+}
@@ -0,0 +1,84 @@
+//! Prometheus metrics for services tracing at scale, enabled with the `metrics` feature
+//!
+//! `TraceMetrics` registers its collectors on a registry owned by the host, which keeps
+//! serving them with the rest of its metrics:
+//!
+//! - `revm_tracer_traces_total`: traces run, successful or not
+//! - `revm_tracer_trace_failures_total{code}`: traces that returned a `TraceError`, by
+//!   `TraceErrorCode`
+//! - `revm_tracer_gas_simulated_total`: gas used by successfully traced transactions,
+//!   reverted and halted ones included
+//! - `revm_tracer_trace_duration_seconds`: wall-clock time of each trace
+
+use std::time::{Duration, Instant};
+
+use prometheus::{exponential_buckets, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry};
+
+use crate::trace::chain::{trace, ChainKind, ChainTraceResult, TraceRequest};
+use crate::trace::error::TraceError;
+
+/// Trace counters and the trace duration histogram
+#[derive(Debug, Clone)]
+pub struct TraceMetrics {
+    traces: IntCounter,
+    failures: IntCounterVec,
+    gas_simulated: IntCounter,
+    duration: Histogram,
+}
+
+impl TraceMetrics {
+    /// Creates the collectors and registers them on `registry`
+    ///
+    /// # Errors
+    ///
+    /// Returns `prometheus::Error::AlreadyReg` if metrics with the same names are
+    /// already registered
+    pub fn register(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Self {
+            traces: IntCounter::new("revm_tracer_traces_total", "Traces run")?,
+            failures: IntCounterVec::new(
+                Opts::new("revm_tracer_trace_failures_total", "Traces that failed, by error code"),
+                &["code"],
+            )?,
+            gas_simulated: IntCounter::new("revm_tracer_gas_simulated_total", "Gas used by traced transactions")?,
+            // 1 ms to about 16 s
+            duration: Histogram::with_opts(
+                HistogramOpts::new("revm_tracer_trace_duration_seconds", "Wall-clock time of a trace")
+                    .buckets(exponential_buckets(0.001, 2.0, 15)?),
+            )?,
+        };
+        registry.register(Box::new(metrics.traces.clone()))?;
+        registry.register(Box::new(metrics.failures.clone()))?;
+        registry.register(Box::new(metrics.gas_simulated.clone()))?;
+        registry.register(Box::new(metrics.duration.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records a trace run outside of `trace`, e.g. with another entry point. `gas_used`
+    /// is that of the traced transaction, `None` if tracing failed.
+    pub fn record(&self, outcome: Result<u64, &TraceError>, duration: Duration) {
+        self.traces.inc();
+        self.duration.observe(duration.as_secs_f64());
+        match outcome {
+            Ok(gas_used) => self.gas_simulated.inc_by(gas_used),
+            Err(error) => self.failures.with_label_values(&[&error.code().to_string()]).inc(),
+        }
+    }
+
+    /// Traces `request` with `chain::trace` and records it
+    ///
+    /// # Errors
+    ///
+    /// Returns the `TraceError` of `chain::trace`
+    pub fn trace(&self, chain_kind: &ChainKind, request: TraceRequest) -> Result<ChainTraceResult, TraceError> {
+        let started_at = Instant::now();
+        let result = trace(chain_kind, request);
+        let outcome = result.as_ref().map(|result| match result {
+            ChainTraceResult::Ethereum(result) => result.execution_result.gas_used(),
+            #[cfg(feature = "op")]
+            ChainTraceResult::OpStack(result) => result.execution_result.gas_used(),
+        });
+        self.record(outcome, started_at.elapsed());
+        result
+    }
+}
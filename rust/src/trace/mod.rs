@@ -1,8 +1,62 @@
 pub mod trace;
 pub mod inspector;
 pub mod database;
+pub mod cache;
 pub mod block;
+pub mod binary;
 pub mod error;
+pub mod asserts;
+pub mod dependencies;
+pub mod labels;
+pub mod sweep;
+pub mod filter;
+pub mod query;
+pub mod receipt;
+pub mod flat;
+pub mod display;
+pub mod fingerprint;
+pub mod fixtures;
+pub mod budget;
+pub mod erc4337;
+pub mod tracebundle;
+pub mod fees;
+pub mod state;
+pub mod transfers;
+#[cfg(feature = "op")]
+pub mod op_block;
+pub mod arbitrum;
+pub mod chain;
+pub mod verify;
+pub mod precompiles;
+pub mod proxy;
+pub mod quantity;
+pub mod profile;
+pub mod export;
+pub mod geth;
+pub mod parity;
+pub mod revert;
+pub mod abi;
+pub mod assets;
+pub mod estimate;
+pub mod simulator;
+mod parallel;
+pub mod system;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "rpc")]
+pub mod fork;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "bench")]
+pub mod workloads;
+#[cfg(feature = "tracing")]
+mod telemetry;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
 // Re-export commonly used types
 pub use inspector::LogEntry;
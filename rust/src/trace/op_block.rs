@@ -0,0 +1,126 @@
+//! Replay of OP Stack blocks, starting with the L1 attributes deposited transaction
+
+use serde::{Deserialize, Serialize};
+use revm::context::BlockEnv;
+use revm::primitives::{address, Address, Bytes, HashMap, B256, U256};
+use revm::database::CacheDB;
+use op_revm::{L1BlockInfo, OpHaltReason, OpSpecId};
+
+use crate::trace::block::op_spec_for_block;
+use crate::trace::database::{apply_state_diff, create_in_memory_database_from_prestate_trace, AccountDetails, SharedPrestate};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::CallTracerConfig;
+use crate::trace::trace::{trace_transaction_op_with_database, DepositParams, TraceTransactionResult, TxParams, TxSpec};
+
+/// Sender of the L1 attributes deposited transaction
+pub const L1_ATTRIBUTES_DEPOSITOR: Address = address!("deaddeaddeaddeaddeaddeaddeaddeaddead0001");
+
+/// The L1Block predeploy that stores L1 attributes
+pub const L1_BLOCK_PREDEPLOY: Address = address!("4200000000000000000000000000000000000015");
+
+/// Gas limit of the L1 attributes deposited transaction
+const L1_ATTRIBUTES_GAS_LIMIT: u64 = 1_000_000;
+
+/// The L1 attributes deposited transaction that opens every OP Stack block.
+///
+/// Both fields can be copied from the first transaction of the block as returned by
+/// `eth_getBlockByNumber` (its `sourceHash` and `input`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1AttributesTx {
+    pub source_hash: B256,
+    /// `setL1BlockValues*` calldata
+    pub data: Bytes,
+}
+
+/// Traces of every replayed transaction in an OP Stack block
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpBlockReplay {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub l1_attributes: Option<TraceTransactionResult<OpHaltReason>>,
+    pub transactions: Vec<TraceTransactionResult<OpHaltReason>>,
+}
+
+/// Replays an OP Stack block: first the L1 attributes deposited transaction, so the
+/// L1Block predeploy holds the block's L1 values, then each transaction in order,
+/// committing state between them.
+///
+/// Without the L1 attributes transaction, user transactions read whatever L1Block
+/// state the prestate contains, which typically belongs to the previous block.
+///
+/// # Errors
+///
+/// Returns the first `TraceError` encountered; reverted transactions are traced normally.
+pub fn replay_op_block(
+    chain_id: u64,
+    block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    l1_attributes: Option<L1AttributesTx>,
+    transactions: Vec<TxParams>,
+) -> Result<OpBlockReplay, TraceError> {
+    let depositor_nonce = prestate_tracer_result.get(&L1_ATTRIBUTES_DEPOSITOR)
+        .and_then(|account| account.nonce)
+        .unwrap_or_default();
+    let prestate = SharedPrestate::new(prestate_tracer_result);
+    let mut prestate_keys = prestate.prestate_keys().clone();
+    // Each transaction runs on an empty layer over the state committed so far
+    let mut db = prestate.overlay();
+    let op_spec = op_spec_for_block(chain_id, block_env.timestamp.saturating_to());
+
+    let mut execute = |params: &TxParams| -> Result<TraceTransactionResult<OpHaltReason>, TraceError> {
+        let result = trace_transaction_op_with_database(params, block_env.clone(), CacheDB::new(&db), &prestate_keys, &CallTracerConfig::default(), op_spec, None)?;
+        prestate_keys.extend_from_state(&result.state_diff);
+        apply_state_diff(&mut db, &result.state_diff);
+        Ok(result)
+    };
+
+    let l1_attributes = l1_attributes
+        .map(|attributes| execute(&TxParams {
+            chain_id,
+            from: L1_ATTRIBUTES_DEPOSITOR,
+            from_nonce: depositor_nonce,
+            to: L1_BLOCK_PREDEPLOY,
+            value: U256::ZERO,
+            data: attributes.data,
+            gas_limit: L1_ATTRIBUTES_GAS_LIMIT,
+            gas_price: 0,
+            gas_priority_fee: 0,
+            tx_spec: TxSpec::Legacy,
+            access_list: None,
+            blob_versioned_hashes: Vec::new(),
+            max_fee_per_blob_gas: 0,
+            authorization_list: Vec::new(),
+            deposit: Some(DepositParams {
+                source_hash: attributes.source_hash,
+                mint: 0,
+                is_system_transaction: false,
+            }),
+            spec: None,
+        }))
+        .transpose()?;
+
+    let transactions = transactions.iter()
+        .map(&mut execute)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(OpBlockReplay { l1_attributes, transactions })
+}
+
+/// Reads the L1 block info that L1 fees are computed from out of the L1Block predeploy
+/// storage in a prestate, as op-revm does at the start of block `l2_block`.
+///
+/// Slots missing from the prestate read as zero, which yields a zero L1 fee. The result
+/// can be passed to `trace_transaction_op` or `calculate_pre_verification_gas_op`.
+pub fn l1_block_info_from_prestate(
+    prestate_tracer_result: &HashMap<Address, AccountDetails>,
+    l2_block: U256,
+    op_spec: OpSpecId,
+) -> L1BlockInfo {
+    let l1_block = prestate_tracer_result.get(&L1_BLOCK_PREDEPLOY)
+        .map(|account| HashMap::from_iter([(L1_BLOCK_PREDEPLOY, account.clone())]))
+        .unwrap_or_default();
+    let mut db = create_in_memory_database_from_prestate_trace(l1_block);
+    L1BlockInfo::try_fetch(&mut db, l2_block, op_spec)
+        .unwrap_or_else(|never| match never {})
+}
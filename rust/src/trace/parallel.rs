@@ -0,0 +1,38 @@
+//! Running independent traces across threads
+
+use std::sync::Mutex;
+
+/// Applies `f` to every item on a pool of scoped threads, one per available core, and
+/// returns the results in item order.
+///
+/// wasm32 has no threads, so items are processed in order on the calling thread there.
+pub(crate) fn parallel_map<T: Send, R: Send>(items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R> {
+    let workers = std::thread::available_parallelism().map_or(1, |workers| workers.get());
+    if cfg!(target_arch = "wasm32") || workers <= 1 || items.len() <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let len = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.min(len))
+            .map(|_| scope.spawn(|| {
+                let mut done = Vec::new();
+                loop {
+                    // The lock is released before `f` runs, so workers only contend on the queue
+                    let next = queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).next();
+                    let Some((index, item)) = next else {
+                        break;
+                    };
+                    done.push((index, f(item)));
+                }
+                done
+            }))
+            .collect();
+        handles.into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
@@ -0,0 +1,348 @@
+//! Conversion of traces into the flat format of Parity/OpenEthereum's `trace_` namespace
+//!
+//! Each frame of the call tree becomes one entry of a flat list, addressed by the indices
+//! of its subcalls from the top-level frame down, as returned by `trace_transaction`,
+//! `trace_filter` and `trace_replayTransaction`. Indexers built around that format can
+//! consume traces from this crate as they would from an Erigon, Nethermind or reth node.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{Address, Bytes, B256, U256};
+
+use crate::trace::database::AccountDetails;
+use crate::trace::inspector::CallFrame;
+use crate::trace::trace::TraceTransactionResult;
+
+/// Error Parity reports for a frame that reverted
+const PARITY_REVERTED: &str = "Reverted";
+
+/// Kind of a flat trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParityTraceType {
+    Call,
+    Create,
+    Suicide,
+}
+
+/// `action` of a call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityCallAction {
+    pub from: Address,
+    /// `call`, `callcode`, `delegatecall` or `staticcall`
+    pub call_type: String,
+    pub gas: U256,
+    pub input: Bytes,
+    pub to: Address,
+    pub value: U256,
+}
+
+/// `action` of a contract creation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityCreateAction {
+    pub from: Address,
+    pub gas: U256,
+    pub init: Bytes,
+    pub value: U256,
+    /// `create` or `create2`
+    pub creation_method: String,
+}
+
+/// `action` of a SELFDESTRUCT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParitySuicideAction {
+    pub address: Address,
+    pub refund_address: Address,
+    pub balance: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParityAction {
+    Call(ParityCallAction),
+    Create(ParityCreateAction),
+    Suicide(ParitySuicideAction),
+}
+
+/// `result` of a successful call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityCallResult {
+    pub gas_used: U256,
+    pub output: Bytes,
+}
+
+/// `result` of a successful creation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityCreateResult {
+    pub address: Address,
+    /// Runtime code of the created contract
+    pub code: Bytes,
+    pub gas_used: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParityResult {
+    Call(ParityCallResult),
+    Create(ParityCreateResult),
+}
+
+/// Where a transaction was mined, as `trace_transaction` and `trace_filter` report it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTransactionLocation {
+    pub block_hash: B256,
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    pub transaction_position: u64,
+}
+
+/// One frame of the call tree as a flat Parity trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTrace {
+    pub action: ParityAction,
+    /// `None` for failed frames and SELFDESTRUCTs, serialized as `null`
+    pub result: Option<ParityResult>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub error: Option<String>,
+    /// Number of direct subcalls
+    pub subtraces: usize,
+    /// Index of each subcall from the top-level frame down, empty for the top-level frame
+    pub trace_address: Vec<usize>,
+    #[serde(rename = "type")]
+    pub trace_type: ParityTraceType,
+    #[serde(flatten, skip_serializing_if = "Option::is_none", default)]
+    pub location: Option<ParityTransactionLocation>,
+}
+
+/// Converts the call tree rooted at `frame` into flat traces, in execution order
+///
+/// # Arguments
+///
+/// * `frame` - Top-level frame of the transaction
+/// * `location` - Block and position of the transaction, for `trace_transaction` and
+///   `trace_filter` output; `None` for simulated transactions
+pub fn parity_traces(frame: &CallFrame, location: Option<ParityTransactionLocation>) -> Vec<ParityTrace> {
+    let mut traces = Vec::new();
+    flatten(frame, &mut Vec::new(), &location, &mut traces);
+    traces
+}
+
+fn flatten(
+    frame: &CallFrame,
+    trace_address: &mut Vec<usize>,
+    location: &Option<ParityTransactionLocation>,
+    traces: &mut Vec<ParityTrace>,
+) {
+    traces.push(parity_trace(frame, trace_address.clone(), location.clone()));
+    for (index, call) in frame.calls.iter().enumerate() {
+        trace_address.push(index);
+        flatten(call, trace_address, location, traces);
+        trace_address.pop();
+    }
+}
+
+fn parity_trace(
+    frame: &CallFrame,
+    trace_address: Vec<usize>,
+    location: Option<ParityTransactionLocation>,
+) -> ParityTrace {
+    let call_type = frame.call_type.to_ascii_lowercase();
+    let error = frame.error.as_ref().map(|error| {
+        if frame.revert_reason.is_some() || error == "execution reverted" {
+            PARITY_REVERTED.to_string()
+        } else {
+            error.clone()
+        }
+    });
+    let gas_used = frame.gas_used;
+
+    let (action, result, trace_type) = match call_type.as_str() {
+        "selfdestruct" => (
+            ParityAction::Suicide(ParitySuicideAction {
+                address: frame.from,
+                refund_address: frame.to.unwrap_or_default(),
+                balance: frame.value,
+            }),
+            None,
+            ParityTraceType::Suicide,
+        ),
+        "create" | "create2" => (
+            ParityAction::Create(ParityCreateAction {
+                from: frame.from,
+                gas: frame.gas,
+                init: frame.input.clone(),
+                value: frame.value,
+                creation_method: call_type.clone(),
+            }),
+            frame.error.is_none().then(|| ParityResult::Create(ParityCreateResult {
+                address: frame.to.unwrap_or_default(),
+                code: frame.deployed_code.clone().unwrap_or_default(),
+                gas_used,
+            })),
+            ParityTraceType::Create,
+        ),
+        _ => (
+            ParityAction::Call(ParityCallAction {
+                from: frame.from,
+                call_type,
+                gas: frame.gas,
+                input: frame.input.clone(),
+                to: frame.to.unwrap_or_default(),
+                value: frame.value,
+            }),
+            frame.error.is_none().then(|| ParityResult::Call(ParityCallResult {
+                gas_used,
+                output: frame.output.clone().unwrap_or_default(),
+            })),
+            ParityTraceType::Call,
+        ),
+    };
+
+    ParityTrace {
+        action,
+        result,
+        error,
+        subtraces: frame.calls.len(),
+        trace_address,
+        trace_type,
+        location,
+    }
+}
+
+/// A change of one field in a Parity `stateDiff`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParityDelta<T> {
+    #[serde(rename = "=")]
+    Unchanged,
+    /// Field of an account created by the transaction
+    #[serde(rename = "+")]
+    Born(T),
+    /// Field of an account destroyed by the transaction
+    #[serde(rename = "-")]
+    Died(T),
+    #[serde(rename = "*")]
+    Changed(ParityChange<T>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParityChange<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq> ParityDelta<T> {
+    fn between(from: T, to: T) -> Self {
+        if from == to {
+            ParityDelta::Unchanged
+        } else {
+            ParityDelta::Changed(ParityChange { from, to })
+        }
+    }
+}
+
+/// How the transaction changed one account
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityAccountDiff {
+    pub balance: ParityDelta<U256>,
+    pub nonce: ParityDelta<U256>,
+    pub code: ParityDelta<Bytes>,
+    /// Slots and values as 32-byte words, as Parity reports them
+    pub storage: BTreeMap<B256, ParityDelta<B256>>,
+}
+
+/// Output of `trace_replayTransaction`
+///
+/// `vmTrace` is always `null`: opcode-level traces are available through the struct
+/// logger instead.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTraceResults {
+    pub output: Bytes,
+    pub state_diff: Option<BTreeMap<Address, ParityAccountDiff>>,
+    pub trace: Vec<ParityTrace>,
+    pub vm_trace: Option<()>,
+}
+
+/// Converts a traced transaction into the output of `trace_replayTransaction` with the
+/// `trace` and, if `with_state_diff` is set, `stateDiff` trace types
+pub fn parity_trace_results<H>(result: &TraceTransactionResult<H>, with_state_diff: bool) -> ParityTraceResults {
+    ParityTraceResults {
+        output: result.execution_result.output().cloned().unwrap_or_default(),
+        state_diff: with_state_diff.then(|| parity_state_diff(result)),
+        trace: parity_traces(&result.calls, None),
+        vm_trace: None,
+    }
+}
+
+/// Builds a Parity `stateDiff` from the geth `diffMode` view of a traced transaction
+pub fn parity_state_diff<H>(result: &TraceTransactionResult<H>) -> BTreeMap<Address, ParityAccountDiff> {
+    let (pre, post) = (&result.diff_mode.pre, &result.diff_mode.post);
+    let mut diff = BTreeMap::new();
+
+    for address in pre.keys().chain(post.keys()) {
+        if diff.contains_key(address) {
+            continue;
+        }
+        let destroyed = result.state_diff.get(address).is_some_and(|account| account.is_selfdestructed());
+        let account = match (pre.get(address), post.get(address)) {
+            (None, Some(after)) => account_delta(after, true),
+            (Some(before), _) if destroyed => account_delta(before, false),
+            (Some(before), after) => account_changes(before, after),
+            (None, None) => continue,
+        };
+        diff.insert(*address, account);
+    }
+    diff
+}
+
+/// Every field of an account that was created (`born`) or destroyed as a whole
+fn account_delta(account: &AccountDetails, born: bool) -> ParityAccountDiff {
+    fn delta<T>(born: bool, value: T) -> ParityDelta<T> {
+        if born { ParityDelta::Born(value) } else { ParityDelta::Died(value) }
+    }
+    ParityAccountDiff {
+        balance: delta(born, account.balance.unwrap_or_default()),
+        nonce: delta(born, U256::from(account.nonce.unwrap_or_default())),
+        code: delta(born, account.code.clone().unwrap_or_default()),
+        storage: account.storage.iter().flatten()
+            .map(|(slot, value)| (B256::from(*slot), delta(born, B256::from(*value))))
+            .collect(),
+    }
+}
+
+/// Changes of an account that existed before and after the transaction. `post` only
+/// holds what changed, and neither side holds slots that are zero.
+fn account_changes(before: &AccountDetails, after: Option<&AccountDetails>) -> ParityAccountDiff {
+    let after = after.cloned().unwrap_or_default();
+    let before_storage = before.storage.clone().unwrap_or_default();
+    let after_storage = after.storage.clone().unwrap_or_default();
+    let storage = before_storage.keys().chain(after_storage.keys())
+        .map(|slot| {
+            let from = before_storage.get(slot).copied().unwrap_or_default();
+            let to = after_storage.get(slot).copied().unwrap_or_default();
+            (B256::from(*slot), ParityDelta::between(B256::from(from), B256::from(to)))
+        })
+        .collect();
+
+    ParityAccountDiff {
+        balance: after.balance.map_or(ParityDelta::Unchanged, |to| {
+            ParityDelta::between(before.balance.unwrap_or_default(), to)
+        }),
+        nonce: after.nonce.map_or(ParityDelta::Unchanged, |to| {
+            ParityDelta::between(U256::from(before.nonce.unwrap_or_default()), U256::from(to))
+        }),
+        code: after.code.map_or(ParityDelta::Unchanged, |to| {
+            ParityDelta::between(before.code.clone().unwrap_or_default(), to)
+        }),
+        storage,
+    }
+}
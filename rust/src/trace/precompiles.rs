@@ -0,0 +1,99 @@
+//! Identification and decoding of calls into precompiled contracts
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{Address, Bytes, B256, U256};
+
+/// Precompiles active on Ethereum (Prague) and OP Stack chains, by address.
+///
+/// The OP Stack fee contracts (GasPriceOracle, L1Block) are regular predeploys with
+/// bytecode, so their calls are traced like any other contract.
+const PRECOMPILES: [(u64, &str); 18] = [
+    (0x01, "ecrecover"),
+    (0x02, "sha256"),
+    (0x03, "ripemd160"),
+    (0x04, "identity"),
+    (0x05, "modexp"),
+    (0x06, "bn254_add"),
+    (0x07, "bn254_mul"),
+    (0x08, "bn254_pairing"),
+    (0x09, "blake2f"),
+    (0x0a, "point_evaluation"),
+    (0x0b, "bls12_g1add"),
+    (0x0c, "bls12_g1msm"),
+    (0x0d, "bls12_g2add"),
+    (0x0e, "bls12_g2msm"),
+    (0x0f, "bls12_pairing_check"),
+    (0x10, "bls12_map_fp_to_g1"),
+    (0x11, "bls12_map_fp2_to_g2"),
+    (0x100, "p256verify"),
+];
+
+/// Decoded arguments and result of a precompile call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PrecompileData {
+    #[serde(rename_all = "camelCase")]
+    EcRecover {
+        hash: B256,
+        v: U256,
+        r: B256,
+        s: B256,
+        /// Recovered signer; `None` when the signature is invalid
+        signer: Option<Address>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ModExp {
+        base_length: U256,
+        exponent_length: U256,
+        modulus_length: U256,
+    },
+    #[serde(rename_all = "camelCase")]
+    PointEvaluation {
+        versioned_hash: B256,
+        z: B256,
+        y: B256,
+        commitment: Bytes,
+        proof: Bytes,
+    },
+}
+
+/// Returns the name of the precompile at `address`, if it is one
+pub fn precompile_name(address: Address) -> Option<&'static str> {
+    let bytes = address.as_slice();
+    if bytes[..18].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    let index = u64::from(u16::from_be_bytes([bytes[18], bytes[19]]));
+    PRECOMPILES.iter()
+        .find(|(precompile, _)| *precompile == index)
+        .map(|(_, name)| *name)
+}
+
+/// Decodes the input and output of a precompile call, for the precompiles whose
+/// arguments are meaningful to a reader. Returns `None` for the others or if the
+/// input is malformed.
+pub fn decode_precompile(address: Address, input: &[u8], output: &[u8]) -> Option<PrecompileData> {
+    let word = |index: usize| input.get(index * 32..(index + 1) * 32);
+    match precompile_name(address)? {
+        "ecrecover" => Some(PrecompileData::EcRecover {
+            hash: B256::from_slice(word(0)?),
+            v: U256::from_be_slice(word(1)?),
+            r: B256::from_slice(word(2)?),
+            s: B256::from_slice(word(3)?),
+            signer: (output.len() == 32).then(|| Address::from_slice(&output[12..])),
+        }),
+        "modexp" => Some(PrecompileData::ModExp {
+            base_length: U256::from_be_slice(word(0)?),
+            exponent_length: U256::from_be_slice(word(1)?),
+            modulus_length: U256::from_be_slice(word(2)?),
+        }),
+        "point_evaluation" if input.len() == 192 => Some(PrecompileData::PointEvaluation {
+            versioned_hash: B256::from_slice(&input[0..32]),
+            z: B256::from_slice(&input[32..64]),
+            y: B256::from_slice(&input[64..96]),
+            commitment: Bytes::copy_from_slice(&input[96..144]),
+            proof: Bytes::copy_from_slice(&input[144..192]),
+        }),
+        _ => None,
+    }
+}
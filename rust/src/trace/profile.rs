@@ -0,0 +1,223 @@
+//! Gas profiling: where a transaction spends its gas, per contract, opcode and function
+
+use serde::{Deserialize, Serialize};
+use revm::{
+    bytecode::opcode,
+    context::{BlockEnv, ContextTr},
+    handler::{instructions::EthInstructions, EthPrecompiles},
+    interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    interpreter::interpreter::EthInterpreter,
+    interpreter::interpreter_types::Jumps,
+    Context, InspectEvm, Inspector, MainContext, MainnetEvm,
+};
+use revm::primitives::{Address, FixedBytes, HashMap};
+
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, AccountDetails};
+use crate::trace::error::{ExecutionError, TraceError};
+use crate::trace::trace::{prepare_mainnet_execution, TxParams};
+
+/// Gas a value-carrying call hands to its callee for free, on top of what the caller forwards
+const CALL_STIPEND: u64 = 2300;
+
+/// Gas spent by one opcode in the code of one contract, over the whole transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpcodeGas {
+    /// Contract whose code ran the opcode; for DELEGATECALL, the implementation
+    pub address: Address,
+    pub opcode: String,
+    /// Times the opcode was executed
+    pub count: u64,
+    /// Gas charged for those executions. Calls and creations only count their own cost,
+    /// not the gas their callee spent.
+    pub gas: u64,
+}
+
+/// Gas spent by calls into one function of one contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionGas {
+    /// Contract whose code ran; for DELEGATECALL, the implementation
+    pub address: Address,
+    /// First four bytes of the calldata; `None` for contract creations and calls with
+    /// shorter calldata, such as plain transfers
+    pub selector: Option<FixedBytes<4>>,
+    pub calls: u64,
+    /// Gas spent by these calls, subcalls included
+    pub gas: u64,
+    /// Gas spent by the function's own code, excluding subcalls
+    pub self_gas: u64,
+}
+
+/// Flat gas profile of a transaction, each list sorted by gas, highest first
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasProfile {
+    /// Gas used by the transaction, intrinsic gas and refunds included
+    pub gas_used: u64,
+    pub opcodes: Vec<OpcodeGas>,
+    pub functions: Vec<FunctionGas>,
+}
+
+/// An executing frame, as followed by the profiler
+#[derive(Debug)]
+struct ProfiledFrame {
+    /// Code address; for creations, filled in once the init code starts running
+    address: Option<Address>,
+    selector: Option<FixedBytes<4>>,
+    /// Gas spent by the completed subcalls of the frame
+    subcall_gas: u64,
+}
+
+/// Inspector aggregating gas by contract and opcode and by contract and function
+#[derive(Debug, Default)]
+pub struct GasProfiler {
+    frames: Vec<ProfiledFrame>,
+    /// Opcode being executed, with the gas left before it
+    pending_step: Option<((Address, u8), u64)>,
+    /// Last opcode executed, whose cost still includes the gas it forwarded if it was a
+    /// call or creation
+    last_opcode: Option<(Address, u8)>,
+    /// Count and gas by contract and opcode
+    opcodes: HashMap<(Address, u8), (u64, u64)>,
+    /// Calls, gas and self gas by contract and selector
+    functions: HashMap<(Address, Option<FixedBytes<4>>), (u64, u64, u64)>,
+}
+
+impl GasProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consumes the profiler and returns the profile; `gas_used` is left for the caller
+    /// to fill in from the execution result
+    pub fn into_profile(self) -> GasProfile {
+        let mut opcodes: Vec<OpcodeGas> = self.opcodes.into_iter()
+            .map(|((address, op), (count, gas))| OpcodeGas {
+                address,
+                opcode: opcode::OpCode::new(op)
+                    .map_or_else(|| format!("0x{:02x}", op), |op| op.as_str().to_string()),
+                count,
+                gas,
+            })
+            .collect();
+        opcodes.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| (a.address, &a.opcode).cmp(&(b.address, &b.opcode))));
+
+        let mut functions: Vec<FunctionGas> = self.functions.into_iter()
+            .map(|((address, selector), (calls, gas, self_gas))| FunctionGas { address, selector, calls, gas, self_gas })
+            .collect();
+        functions.sort_by(|a, b| b.gas.cmp(&a.gas).then_with(|| (a.address, a.selector).cmp(&(b.address, b.selector))));
+
+        GasProfile { gas_used: 0, opcodes, functions }
+    }
+
+    /// Takes the gas handed to a new frame out of the cost of the opcode that made it
+    fn enter(&mut self, address: Option<Address>, selector: Option<FixedBytes<4>>, forwarded_gas: u64) {
+        if let Some(key) = self.last_opcode.take() {
+            if let Some((_, gas)) = self.opcodes.get_mut(&key) {
+                *gas = gas.saturating_sub(forwarded_gas);
+            }
+        }
+        self.frames.push(ProfiledFrame { address, selector, subcall_gas: 0 });
+    }
+
+    fn exit(&mut self, gas_spent: u64, created: Option<Address>) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        if let Some(parent) = self.frames.last_mut() {
+            parent.subcall_gas += gas_spent;
+        }
+        let address = frame.address.or(created).unwrap_or_default();
+        let entry = self.functions.entry((address, frame.selector)).or_default();
+        entry.0 += 1;
+        entry.1 += gas_spent;
+        entry.2 += gas_spent.saturating_sub(frame.subcall_gas);
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX, EthInterpreter> for GasProfiler {
+    fn step(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        // Init code runs at the address being created
+        let address = *frame.address.get_or_insert(interp.input.target_address);
+        self.pending_step = Some(((address, interp.bytecode.opcode()), interp.gas.remaining()));
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter<EthInterpreter>, _context: &mut CTX) {
+        let Some((key, gas_before)) = self.pending_step.take() else {
+            return;
+        };
+        let entry = self.opcodes.entry(key).or_default();
+        entry.0 += 1;
+        entry.1 += gas_before.saturating_sub(interp.gas.remaining());
+        self.last_opcode = Some(key);
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        let input = inputs.input.bytes(context);
+        let selector = (input.len() >= 4).then(|| FixedBytes::from_slice(&input[..4]));
+        let carries_value = inputs.transfer_value().is_some_and(|value| !value.is_zero());
+        let forwarded_gas = if carries_value {
+            inputs.gas_limit.saturating_sub(CALL_STIPEND)
+        } else {
+            inputs.gas_limit
+        };
+        self.enter(Some(inputs.bytecode_address), selector, forwarded_gas);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.exit(outcome.result.gas.spent(), None);
+    }
+
+    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.enter(None, None, inputs.gas_limit);
+        None
+    }
+
+    fn create_end(&mut self, _context: &mut CTX, _inputs: &CreateInputs, outcome: &mut CreateOutcome) {
+        self.exit(outcome.result.gas.spent(), outcome.address);
+    }
+}
+
+/// Execute a transaction and profile where its gas goes
+///
+/// # Arguments
+///
+/// * `params` - The transaction to profile
+/// * `latest_block_env` - Block environment for execution
+/// * `prestate_tracer_result` - Account states before execution
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be built or executed
+pub fn profile_transaction(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+) -> Result<GasProfile, TraceError> {
+    let (tx, cfg_env) = prepare_mainnet_execution(params, &latest_block_env)?;
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+
+    let context = Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env);
+
+    let mut my_evm = MainnetEvm::new_with_inspector(
+        context,
+        GasProfiler::new(),
+        EthInstructions::new_mainnet(),
+        EthPrecompiles::default()
+    );
+
+    let execution_result = my_evm.inspect_one_tx(tx)
+        .map_err(ExecutionError::from)?;
+
+    let mut profile = my_evm.inspector.into_profile();
+    profile.gas_used = execution_result.gas_used();
+    Ok(profile)
+}
@@ -0,0 +1,66 @@
+//! Resolution of the implementation behind standard upgradeable proxies
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{b256, Address, B256, StorageKey, StorageValue};
+
+/// EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`, also used
+/// by UUPS proxies
+pub const EIP1967_IMPLEMENTATION_SLOT: B256 = b256!("360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// EIP-1967 beacon slot, `keccak256("eip1967.proxy.beacon") - 1`
+pub const EIP1967_BEACON_SLOT: B256 = b256!("a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// EIP-1822 implementation slot, `keccak256("PROXIABLE")`
+pub const EIP1822_PROXIABLE_SLOT: B256 = b256!("c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7");
+
+/// Which standard a proxy stores its implementation by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProxyStandard {
+    /// EIP-1967 implementation slot, including UUPS proxies
+    Eip1967,
+    /// EIP-1967 beacon slot; the implementation is whatever the beacon returned
+    Beacon,
+    /// EIP-1822 `PROXIABLE` slot
+    Eip1822,
+}
+
+/// Implementation a proxy frame delegated to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyImplementation {
+    pub standard: ProxyStandard,
+    pub address: Address,
+    /// Beacon the implementation was taken from, for beacon proxies
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub beacon: Option<Address>,
+}
+
+/// Identifies `proxy` delegating to `implementation` as a standard proxy, reading its
+/// storage with `storage`. Returns `None` for other delegate calls, e.g. into libraries.
+///
+/// A beacon proxy is recognized by its beacon slot alone, since where the beacon keeps
+/// the implementation is not standardized.
+pub fn resolve_proxy(
+    proxy: Address,
+    implementation: Address,
+    mut storage: impl FnMut(Address, StorageKey) -> StorageValue,
+) -> Option<ProxyImplementation> {
+    let mut slot_address = |slot: B256| {
+        let value = storage(proxy, StorageKey::from_be_bytes(slot.0));
+        Address::from_word(B256::from(value.to_be_bytes()))
+    };
+
+    if slot_address(EIP1967_IMPLEMENTATION_SLOT) == implementation {
+        return Some(ProxyImplementation { standard: ProxyStandard::Eip1967, address: implementation, beacon: None });
+    }
+    if slot_address(EIP1822_PROXIABLE_SLOT) == implementation {
+        return Some(ProxyImplementation { standard: ProxyStandard::Eip1822, address: implementation, beacon: None });
+    }
+    let beacon = slot_address(EIP1967_BEACON_SLOT);
+    (!beacon.is_zero()).then_some(ProxyImplementation {
+        standard: ProxyStandard::Beacon,
+        address: implementation,
+        beacon: Some(beacon),
+    })
+}
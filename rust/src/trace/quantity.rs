@@ -0,0 +1,78 @@
+//! Encoding of numeric quantities in trace output
+//!
+//! Gas amounts, values and positions are written as `0x`-prefixed hex strings, following
+//! the Ethereum JSON-RPC conventions. `QuantityEncoding::Legacy` keeps the format earlier
+//! versions produced, where 64 and 128-bit amounts were plain JSON numbers, for
+//! consumers that have not migrated yet.
+//!
+//! The encoding is chosen when serializing, with `with_quantity_encoding`. Fields using
+//! this module accept numbers, hex strings and decimal strings when deserialized.
+
+use std::cell::Cell;
+use std::fmt::LowerHex;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use revm::primitives::U256;
+
+/// How quantities are written when serialized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuantityEncoding {
+    /// `0x`-prefixed hex strings without leading zeros
+    #[default]
+    Hex,
+    /// JSON numbers for 64 and 128-bit amounts, hex strings for 256-bit ones
+    Legacy,
+}
+
+thread_local! {
+    static ENCODING: Cell<QuantityEncoding> = const { Cell::new(QuantityEncoding::Hex) };
+}
+
+/// Runs `serialize` with quantities written as `encoding`, e.g. around
+/// `serde_json::to_string`. The encoding only applies to the current thread.
+pub fn with_quantity_encoding<R>(encoding: QuantityEncoding, serialize: impl FnOnce() -> R) -> R {
+    let previous = ENCODING.with(|current| current.replace(encoding));
+    let result = serialize();
+    ENCODING.with(|current| current.set(previous));
+    result
+}
+
+/// Encoding serializers on this thread are currently using
+pub fn quantity_encoding() -> QuantityEncoding {
+    ENCODING.with(Cell::get)
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + LowerHex,
+    S: Serializer,
+{
+    match quantity_encoding() {
+        QuantityEncoding::Hex => serializer.serialize_str(&format!("{:#x}", value)),
+        QuantityEncoding::Legacy => value.serialize(serializer),
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    U256: TryInto<T>,
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Quantity {
+        Number(u64),
+        Text(String),
+    }
+
+    let value = match Quantity::deserialize(deserializer)? {
+        Quantity::Number(number) => U256::from(number),
+        Quantity::Text(text) => match text.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16),
+            None => U256::from_str_radix(&text, 10),
+        }
+        .map_err(serde::de::Error::custom)?,
+    };
+    value.try_into().map_err(|_| serde::de::Error::custom("quantity out of range"))
+}
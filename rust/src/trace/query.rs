@@ -0,0 +1,173 @@
+//! Queries over a call tree, for the common questions about a trace
+//!
+//! Frames are visited depth-first in execution order, each with its `frame_path`: the
+//! index of each subcall from the top-level frame down, as in
+//! `TraceTransactionResult::top_gas_consumers`. `find` takes such a path back to its frame.
+//!
+//! Logs are listed in the order they were emitted, which interleaves a frame's logs with
+//! those of its subcalls according to `LogEntry::position`. This is the order of the
+//! transaction receipt's logs, minus those of frames that reverted.
+
+use revm::primitives::{Address, B256};
+
+use crate::trace::inspector::{CallFrame, LogEntry};
+use crate::trace::trace::TraceTransactionResult;
+
+/// A frame of the call tree and where it sits
+#[derive(Debug, Clone)]
+pub struct FrameEntry<'a> {
+    /// Index of each subcall from the top-level frame down, empty for the top-level frame
+    pub frame_path: Vec<usize>,
+    pub frame: &'a CallFrame,
+}
+
+/// A log of the call tree and where it was emitted
+#[derive(Debug, Clone)]
+pub struct TraceLog<'a> {
+    /// Path of the emitting frame, see `FrameEntry::frame_path`
+    pub frame_path: Vec<usize>,
+    /// Index of the log in its frame's `logs`
+    pub log_index: usize,
+    /// Index the log has among the logs of the transaction receipt, i.e. its `logIndex`
+    /// if the transaction came first in its block; `None` if its frame or an ancestor
+    /// reverted, which removes it from the receipt
+    pub receipt_log_index: Option<usize>,
+    pub log: &'a LogEntry,
+}
+
+/// Depth-first iterator over a frame and its subcalls, parents before their subcalls
+#[derive(Debug, Clone)]
+pub struct FrameIter<'a> {
+    /// Frames still to visit, the next one last
+    stack: Vec<FrameEntry<'a>>,
+}
+
+impl<'a> Iterator for FrameIter<'a> {
+    type Item = FrameEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.stack.pop()?;
+        for (index, call) in entry.frame.calls.iter().enumerate().rev() {
+            let mut frame_path = entry.frame_path.clone();
+            frame_path.push(index);
+            self.stack.push(FrameEntry { frame_path, frame: call });
+        }
+        Some(entry)
+    }
+}
+
+impl CallFrame {
+    /// Iterates over this frame and all of its subcalls, with paths relative to this frame
+    pub fn iter(&self) -> FrameIter<'_> {
+        FrameIter { stack: vec![FrameEntry { frame_path: Vec::new(), frame: self }] }
+    }
+
+    /// The subcall at `frame_path` below this frame; this frame itself for an empty path
+    pub fn find(&self, frame_path: &[usize]) -> Option<&CallFrame> {
+        frame_path.iter().try_fold(self, |frame, index| frame.calls.get(*index))
+    }
+
+    /// The 4-byte function selector this frame was called with; `None` for creations
+    /// and calls with less than 4 bytes of input
+    pub fn selector(&self) -> Option<[u8; 4]> {
+        if self.call_type == "CREATE" || self.call_type == "CREATE2" {
+            return None;
+        }
+        self.input.get(..4)?.try_into().ok()
+    }
+}
+
+impl<T> TraceTransactionResult<T> {
+    /// Iterates over every frame of the call tree
+    pub fn frames(&self) -> FrameIter<'_> {
+        self.calls.iter()
+    }
+
+    /// The frame at `frame_path`, e.g. `[0, 2]` for the third subcall of the first subcall
+    pub fn find(&self, frame_path: &[usize]) -> Option<&CallFrame> {
+        self.calls.find(frame_path)
+    }
+
+    /// Frames calling `address`. Delegate calls are matched on the code they run, which
+    /// is their `to`, not on the account whose storage they use.
+    pub fn frames_to(&self, address: Address) -> Vec<FrameEntry<'_>> {
+        self.frames().filter(|entry| entry.frame.to == Some(address)).collect()
+    }
+
+    /// Frames calling the function with `selector`
+    pub fn frames_with_selector(&self, selector: [u8; 4]) -> Vec<FrameEntry<'_>> {
+        self.frames().filter(|entry| entry.frame.selector() == Some(selector)).collect()
+    }
+
+    /// Frames that reverted or halted, outermost first
+    pub fn failed_frames(&self) -> Vec<FrameEntry<'_>> {
+        self.frames().filter(|entry| entry.frame.error.is_some()).collect()
+    }
+
+    /// Every log of the call tree in the order it was emitted, with its receipt index.
+    /// Add the number of logs of earlier transactions in the block to `receipt_log_index`
+    /// to get the block-level `logIndex`.
+    pub fn logs(&self) -> Vec<TraceLog<'_>> {
+        let mut logs = Vec::new();
+        collect_logs(&self.calls, &mut Vec::new(), false, &mut 0, &mut logs);
+        logs
+    }
+
+    /// Logs emitted by `address` with `topic0` as first topic, `None` matching any, e.g.
+    /// every ERC-20 `Transfer` of a token
+    pub fn logs_matching(&self, address: Option<Address>, topic0: Option<B256>) -> Vec<TraceLog<'_>> {
+        let mut logs = self.logs();
+        logs.retain(|entry| {
+            address.is_none_or(|address| entry.log.address == address)
+                && topic0.is_none_or(|topic0| entry.log.topics.first() == Some(&topic0))
+        });
+        logs
+    }
+}
+
+/// Appends the logs of `frame` and its subcalls in emission order. `reverted` is whether
+/// an ancestor reverted; `receipt_logs` counts the logs kept in the receipt so far.
+fn collect_logs<'a>(
+    frame: &'a CallFrame,
+    frame_path: &mut Vec<usize>,
+    reverted: bool,
+    receipt_logs: &mut usize,
+    logs: &mut Vec<TraceLog<'a>>,
+) {
+    let reverted = reverted || frame.error.is_some();
+
+    // A log with position `n` was emitted after the frame's first `n` subcalls
+    let mut next_log = 0;
+    for (index, call) in frame.calls.iter().enumerate() {
+        while frame.logs.get(next_log).is_some_and(|log| log.position <= index) {
+            push_log(frame, next_log, frame_path, reverted, receipt_logs, logs);
+            next_log += 1;
+        }
+        frame_path.push(index);
+        collect_logs(call, frame_path, reverted, receipt_logs, logs);
+        frame_path.pop();
+    }
+    for log_index in next_log..frame.logs.len() {
+        push_log(frame, log_index, frame_path, reverted, receipt_logs, logs);
+    }
+}
+
+fn push_log<'a>(
+    frame: &'a CallFrame,
+    log_index: usize,
+    frame_path: &[usize],
+    reverted: bool,
+    receipt_logs: &mut usize,
+    logs: &mut Vec<TraceLog<'a>>,
+) {
+    let receipt_log_index = (!reverted).then(|| {
+        *receipt_logs += 1;
+        *receipt_logs - 1
+    });
+    logs.push(TraceLog {
+        frame_path: frame_path.to_vec(),
+        log_index,
+        receipt_log_index,
+        log: &frame.logs[log_index],
+    });
+}
@@ -0,0 +1,173 @@
+//! Receipts for simulated transactions, shaped like `eth_getTransactionReceipt` results
+//!
+//! A simulation has no block or hash of its own, so these come from a `ReceiptContext`.
+//! The defaults describe the transaction as the first of an unknown block, with zero
+//! hashes and number. Logs are those of the execution result, i.e. without the logs of
+//! reverted frames, exactly as the node would put them in the receipt.
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::{ExecutionResult, Output};
+use revm::primitives::{keccak256, Address, Bytes, B256};
+
+use crate::trace::quantity;
+use crate::trace::trace::TraceTransactionResult;
+
+/// Where a simulated transaction sits in its block, for `to_receipt_in`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReceiptContext {
+    pub transaction_hash: B256,
+    pub transaction_index: u64,
+    pub block_hash: B256,
+    pub block_number: u64,
+    /// `cumulativeGasUsed` of the previous transaction in the block, zero for the first
+    pub cumulative_gas_before: u64,
+    /// Logs emitted by earlier transactions in the block, i.e. the `logIndex` of the
+    /// transaction's first log
+    pub log_index_offset: u64,
+    /// EIP-2718 type of the transaction, e.g. 2 for EIP-1559
+    pub transaction_type: u8,
+}
+
+impl Default for ReceiptContext {
+    fn default() -> Self {
+        Self {
+            transaction_hash: B256::ZERO,
+            transaction_index: 0,
+            block_hash: B256::ZERO,
+            block_number: 0,
+            cumulative_gas_before: 0,
+            log_index_offset: 0,
+            transaction_type: 2,
+        }
+    }
+}
+
+/// A receipt as returned by `eth_getTransactionReceipt`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedReceipt {
+    pub transaction_hash: B256,
+    #[serde(with = "quantity")]
+    pub transaction_index: u64,
+    pub block_hash: B256,
+    #[serde(with = "quantity")]
+    pub block_number: u64,
+    pub from: Address,
+    /// `None` for contract creations
+    pub to: Option<Address>,
+    #[serde(with = "quantity")]
+    pub cumulative_gas_used: u64,
+    #[serde(with = "quantity")]
+    pub gas_used: u64,
+    #[serde(with = "quantity")]
+    pub effective_gas_price: u128,
+    /// Blob gas of an EIP-4844 transaction, left out for other types
+    #[serde(default, with = "quantity", skip_serializing_if = "is_zero")]
+    pub blob_gas_used: u64,
+    /// Address of the contract created, if the transaction deployed one
+    pub contract_address: Option<Address>,
+    pub logs: Vec<ReceiptLog>,
+    /// 2048-bit bloom filter of the logs' addresses and topics
+    pub logs_bloom: Bytes,
+    #[serde(rename = "type", with = "quantity")]
+    pub transaction_type: u8,
+    /// 1 if the transaction succeeded, 0 if it reverted or halted
+    #[serde(with = "quantity")]
+    pub status: u64,
+}
+
+/// A log of a `SimulatedReceipt`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiptLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    pub block_hash: B256,
+    #[serde(with = "quantity")]
+    pub block_number: u64,
+    pub transaction_hash: B256,
+    #[serde(with = "quantity")]
+    pub transaction_index: u64,
+    #[serde(with = "quantity")]
+    pub log_index: u64,
+    pub removed: bool,
+}
+
+impl<T> TraceTransactionResult<T> {
+    /// The receipt of the transaction as the first of an unknown block, see `ReceiptContext`
+    pub fn to_receipt(&self) -> SimulatedReceipt {
+        self.to_receipt_in(&ReceiptContext::default())
+    }
+
+    /// The receipt of the transaction at the position in a block given by `context`
+    pub fn to_receipt_in(&self, context: &ReceiptContext) -> SimulatedReceipt {
+        let (logs, contract_address) = match &self.execution_result {
+            ExecutionResult::Success { logs, output, .. } => {
+                let contract_address = match output {
+                    Output::Create(_, address) => *address,
+                    Output::Call(_) => None,
+                };
+                (logs.as_slice(), contract_address)
+            }
+            _ => (&[][..], None),
+        };
+        let is_create = self.calls.call_type == "CREATE" || self.calls.call_type == "CREATE2";
+
+        let mut bloom = [0u8; 256];
+        let logs = logs.iter()
+            .zip(context.log_index_offset..)
+            .map(|(log, log_index)| {
+                accrue_bloom(&mut bloom, log.address.as_slice());
+                for topic in log.data.topics() {
+                    accrue_bloom(&mut bloom, topic.as_slice());
+                }
+                ReceiptLog {
+                    address: log.address,
+                    topics: log.data.topics().to_vec(),
+                    data: log.data.data.clone(),
+                    block_hash: context.block_hash,
+                    block_number: context.block_number,
+                    transaction_hash: context.transaction_hash,
+                    transaction_index: context.transaction_index,
+                    log_index,
+                    removed: false,
+                }
+            })
+            .collect();
+
+        let gas_used = self.execution_result.gas_used();
+        SimulatedReceipt {
+            transaction_hash: context.transaction_hash,
+            transaction_index: context.transaction_index,
+            block_hash: context.block_hash,
+            block_number: context.block_number,
+            from: self.calls.from,
+            to: if is_create { None } else { self.calls.to },
+            cumulative_gas_used: context.cumulative_gas_before + gas_used,
+            gas_used,
+            effective_gas_price: self.gas.effective_gas_price,
+            blob_gas_used: self.blob_gas_used,
+            contract_address,
+            logs,
+            logs_bloom: Bytes::copy_from_slice(&bloom),
+            transaction_type: context.transaction_type,
+            status: u64::from(self.execution_result.is_success()),
+        }
+    }
+}
+
+/// Sets the three bloom bits of `input`: the low 11 bits of each of the first three
+/// byte pairs of its hash, counted from the end of the filter
+fn accrue_bloom(bloom: &mut [u8; 256], input: &[u8]) {
+    let hash = keccak256(input);
+    for pair in hash[..6].chunks(2) {
+        let bit = (usize::from(pair[0]) << 8 | usize::from(pair[1])) & 2047;
+        bloom[255 - bit / 8] |= 1 << (bit % 8);
+    }
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
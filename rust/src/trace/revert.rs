@@ -0,0 +1,68 @@
+//! Decoding of revert data into human-readable reasons
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{FixedBytes, U256};
+
+/// Selector of the standard `Error(string)` revert
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Selector of the compiler-generated `Panic(uint256)` revert
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Revert data decoded according to the Solidity ABI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DecodedRevert {
+    /// `require(false, "message")` / `revert("message")`
+    Error { message: String },
+    /// Failed `assert`, arithmetic overflow, out-of-bounds access, ...
+    Panic { code: U256, description: String },
+    /// A custom error; only its selector is known without the contract ABI
+    Custom { selector: FixedBytes<4> },
+}
+
+/// Decodes revert data. Returns `None` if it is shorter than a selector, i.e. a bare
+/// `revert()`.
+pub fn decode_revert_reason(data: &[u8]) -> Option<DecodedRevert> {
+    let selector: [u8; 4] = data.get(..4)?.try_into().ok()?;
+    let decoded = match selector {
+        ERROR_STRING_SELECTOR => decode_error_string(data)
+            .map(|message| DecodedRevert::Error { message }),
+        PANIC_SELECTOR => data.get(4..36).map(|word| {
+            let code = U256::from_be_slice(word);
+            DecodedRevert::Panic { code, description: panic_description(code).to_string() }
+        }),
+        _ => None,
+    };
+    Some(decoded.unwrap_or(DecodedRevert::Custom { selector: FixedBytes(selector) }))
+}
+
+/// Decodes `Error(string)` revert data into its message
+pub fn decode_error_string(data: &[u8]) -> Option<String> {
+    let payload = data.strip_prefix(&ERROR_STRING_SELECTOR)?;
+    let offset: usize = U256::from_be_slice(payload.get(0..32)?).try_into().ok()?;
+    let length: usize = U256::from_be_slice(payload.get(offset..offset.checked_add(32)?)?).try_into().ok()?;
+    let start = offset + 32;
+    let bytes = payload.get(start..start.checked_add(length)?)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Meaning of a Solidity panic code
+fn panic_description(code: U256) -> &'static str {
+    if code > U256::from(u8::MAX) {
+        return "unknown panic code";
+    }
+    match code.to::<u8>() {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "incorrectly encoded storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "memory allocation overflow",
+        0x51 => "call to zero-initialized internal function",
+        _ => "unknown panic code",
+    }
+}
@@ -0,0 +1,792 @@
+//! JSON-RPC client fetching what a trace needs from a node: block details, prestate,
+//! nonces and the chain id
+//!
+//! `TraceRpcClient` is blocking and `AsyncTraceRpcClient` is its async counterpart. Both
+//! retry transport failures, rate limiting and server errors with a linear backoff, and
+//! report failures as `RpcClientError`.
+
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use revm::context::result::HaltReason;
+use revm::context::{BlockEnv, TxEnv};
+use revm::context_interface::transaction::{AccessList, SignedAuthorization};
+use revm::database::State;
+use revm::primitives::{Address, Bytes, HashMap, TxKind, B256, U256};
+use revm::primitives::hardfork::SpecId;
+
+use crate::trace::block::{create_block_env_from_block_details, spec_for_block, BlockDetails};
+use crate::trace::database::{AccountDetails, RpcDatabase};
+use crate::trace::error::{RpcClientError, TraceError};
+use crate::trace::system::{apply_block_system_calls, beacon_root_slots, history_storage_slot, system_contracts, BEACON_ROOTS_ADDRESS};
+use crate::trace::trace::{execute_and_commit, trace_prestate, trace_transaction, TraceTransactionOptions, TraceTransactionResult, TxParams, TxSpec};
+
+/// Timeouts and retry policy of the RPC clients
+#[derive(Debug, Clone)]
+pub struct RpcClientConfig {
+    /// Timeout of a single HTTP request
+    pub timeout: Duration,
+    /// How many times a retryable failure is retried before giving up
+    pub max_retries: u32,
+    /// Delay before the first retry; the n-th retry waits n times as long
+    pub retry_backoff: Duration,
+}
+
+impl Default for RpcClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+/// A transaction to trace whose chain id and sender nonce are resolved from the node,
+/// see `trace_call_auto`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallParams {
+    pub from: Address,
+    pub to: Address,
+    #[serde(default)]
+    pub value: U256,
+    #[serde(default)]
+    pub data: Bytes,
+    pub gas_limit: u64,
+    /// Gas price, i.e. `maxFeePerGas` for EIP-1559 transactions
+    #[serde(alias = "maxFeePerGas")]
+    pub gas_price: u128,
+    /// Priority fee, i.e. `maxPriorityFeePerGas`; ignored for legacy transactions
+    #[serde(default, alias = "maxPriorityFeePerGas")]
+    pub gas_priority_fee: u128,
+    #[serde(default)]
+    pub tx_spec: TxSpec,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+}
+
+impl CallParams {
+    /// Completes the call into `TxParams` with the given chain id and sender nonce
+    pub fn into_tx_params(self, chain_id: u64, from_nonce: u64) -> TxParams {
+        TxParams {
+            chain_id,
+            from: self.from,
+            from_nonce,
+            to: self.to,
+            value: self.value,
+            data: self.data,
+            gas_limit: self.gas_limit,
+            gas_price: self.gas_price,
+            gas_priority_fee: self.gas_priority_fee,
+            tx_spec: self.tx_spec,
+            access_list: self.access_list,
+            blob_versioned_hashes: Vec::new(),
+            max_fee_per_blob_gas: 0,
+            authorization_list: Vec::new(),
+            deposit: None,
+            spec: None,
+        }
+    }
+}
+
+/// A transaction as returned by `eth_getTransactionByHash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    pub hash: B256,
+    #[serde(default, rename = "type")]
+    pub transaction_type: Option<U256>,
+    pub from: Address,
+    /// `None` for contract creations
+    pub to: Option<Address>,
+    pub nonce: U256,
+    pub gas: U256,
+    #[serde(default)]
+    pub gas_price: Option<U256>,
+    #[serde(default)]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<U256>,
+    pub value: U256,
+    pub input: Bytes,
+    #[serde(default)]
+    pub access_list: Option<AccessList>,
+    #[serde(default)]
+    pub blob_versioned_hashes: Vec<B256>,
+    #[serde(default)]
+    pub max_fee_per_blob_gas: Option<U256>,
+    /// EIP-7702 authorizations of set-code transactions (type 4)
+    #[serde(default)]
+    pub authorization_list: Vec<SignedAuthorization>,
+    #[serde(default)]
+    pub chain_id: Option<U256>,
+    /// `None` while the transaction is pending
+    #[serde(default)]
+    pub block_number: Option<U256>,
+    #[serde(default)]
+    pub transaction_index: Option<U256>,
+}
+
+impl RpcTransaction {
+    /// Converts the transaction into `TxParams`, using `chain_id` when the transaction
+    /// does not carry one (pre-EIP-155 legacy transactions)
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError::InvalidParameter` for contract creations and for values that
+    /// do not fit the `TxParams` fields. Set-code transactions keep their authorizations.
+    pub fn to_tx_params(&self, chain_id: u64) -> Result<TxParams, TraceError> {
+        let to = self.to.ok_or_else(|| TraceError::InvalidParameter(
+            format!("transaction {} is a contract creation, which is not supported", self.hash)
+        ))?;
+        let (tx_spec, gas_price, gas_priority_fee) = match self.max_fee_per_gas {
+            Some(max_fee_per_gas) => (
+                TxSpec::Eip1559,
+                field(max_fee_per_gas, "maxFeePerGas")?,
+                field(self.max_priority_fee_per_gas.unwrap_or_default(), "maxPriorityFeePerGas")?,
+            ),
+            None => (TxSpec::Legacy, field(self.gas_price.unwrap_or_default(), "gasPrice")?, 0),
+        };
+
+        Ok(TxParams {
+            chain_id: match self.chain_id {
+                Some(chain_id) => field(chain_id, "chainId")?,
+                None => chain_id,
+            },
+            from: self.from,
+            from_nonce: field(self.nonce, "nonce")?,
+            to,
+            value: self.value,
+            data: self.input.clone(),
+            gas_limit: field(self.gas, "gas")?,
+            gas_price,
+            gas_priority_fee,
+            tx_spec,
+            access_list: self.access_list.clone(),
+            blob_versioned_hashes: self.blob_versioned_hashes.clone(),
+            max_fee_per_blob_gas: field(self.max_fee_per_blob_gas.unwrap_or_default(), "maxFeePerBlobGas")?,
+            authorization_list: self.authorization_list.clone(),
+            deposit: None,
+            spec: None,
+        })
+    }
+
+    /// Builds the transaction environment to re-execute the transaction as it was
+    /// mined, contract creations included
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` for values out of range or fields that do not form a valid
+    /// transaction of its type
+    pub fn to_tx_env(&self) -> Result<TxEnv, TraceError> {
+        let builder = TxEnv::builder()
+            .tx_type(self.transaction_type.map(|tx_type| field(tx_type, "type")).transpose()?)
+            .chain_id(self.chain_id.map(|chain_id| field(chain_id, "chainId")).transpose()?)
+            .caller(self.from)
+            .kind(self.to.map_or(TxKind::Create, TxKind::Call))
+            .value(self.value)
+            .nonce(field(self.nonce, "nonce")?)
+            .gas_limit(field(self.gas, "gas")?)
+            .data(self.input.clone());
+        let builder = match self.max_fee_per_gas {
+            Some(max_fee_per_gas) => builder
+                .gas_price(field(max_fee_per_gas, "maxFeePerGas")?)
+                .gas_priority_fee(Some(field(self.max_priority_fee_per_gas.unwrap_or_default(), "maxPriorityFeePerGas")?)),
+            None => builder.gas_price(field(self.gas_price.unwrap_or_default(), "gasPrice")?),
+        };
+        let builder = match &self.access_list {
+            Some(access_list) => builder.access_list(access_list.clone()),
+            None => builder,
+        };
+        let builder = match self.max_fee_per_blob_gas {
+            Some(max_fee_per_blob_gas) => builder
+                .blob_hashes(self.blob_versioned_hashes.clone())
+                .max_fee_per_blob_gas(field(max_fee_per_blob_gas, "maxFeePerBlobGas")?),
+            None => builder,
+        };
+        let builder = if self.authorization_list.is_empty() {
+            builder
+        } else {
+            builder.authorization_list_signed(self.authorization_list.clone())
+        };
+        Ok(builder.build()?)
+    }
+}
+
+/// Block returned by `eth_getBlockByNumber` with full transaction objects
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlockTransactions {
+    pub(crate) parent_hash: B256,
+    /// Absent before Cancun
+    #[serde(default)]
+    pub(crate) parent_beacon_block_root: Option<B256>,
+    pub(crate) transactions: Vec<RpcTransaction>,
+}
+
+/// Where `trace_transaction_by_hash` gets the prestate of a mined transaction from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PrestateSource {
+    /// `debug_traceTransaction` with geth's `prestateTracer`; exact, but the node must
+    /// serve the `debug` namespace
+    #[default]
+    RemoteTracer,
+    /// `RpcDatabase` reading the state of the previous block with plain `eth_*` calls;
+    /// transactions earlier in the same block are not taken into account
+    LazyDatabase,
+    /// Like `LazyDatabase`, but the transactions preceding the traced one in its block
+    /// and the block's system calls are executed first, so it sees the state it saw
+    /// on-chain. Costs one pass over every earlier transaction.
+    ReplayBlock,
+}
+
+/// Blocking JSON-RPC client
+///
+/// Must not be used from within an async runtime; use `AsyncTraceRpcClient` there.
+#[derive(Debug, Clone)]
+pub struct TraceRpcClient {
+    client: reqwest::blocking::Client,
+    url: String,
+    config: RpcClientConfig,
+}
+
+impl TraceRpcClient {
+    /// Creates a client for `url` with the default timeouts and retry policy
+    pub fn new(url: impl Into<String>) -> Result<Self, RpcClientError> {
+        Self::with_config(url, RpcClientConfig::default())
+    }
+
+    /// Creates a client for `url` with the given timeouts and retry policy
+    pub fn with_config(url: impl Into<String>, config: RpcClientConfig) -> Result<Self, RpcClientError> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| RpcClientError::Client(e.to_string()))?;
+        Ok(Self { client, url: url.into(), config })
+    }
+
+    /// Sends a JSON-RPC request and deserializes its result, retrying retryable failures
+    pub fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, RpcClientError> {
+        let body = request_body(method, params);
+        let mut attempt = 0;
+        loop {
+            match self.send(&body) {
+                Err(e) if e.is_retryable() && attempt < self.config.max_retries => {
+                    attempt += 1;
+                    std::thread::sleep(self.config.retry_backoff * attempt);
+                }
+                response => return parse_response(method, response?),
+            }
+        }
+    }
+
+    fn send(&self, body: &Value) -> Result<Value, RpcClientError> {
+        let response = self.client
+            .post(&self.url)
+            .json(body)
+            .send()
+            .map_err(|e| RpcClientError::Transport(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(RpcClientError::Status(response.status().as_u16()));
+        }
+        response.json().map_err(|e| RpcClientError::Decode(e.to_string()))
+    }
+
+    /// Fetches the chain id with `eth_chainId`
+    pub fn chain_id(&self) -> Result<u64, RpcClientError> {
+        to_u64(self.request("eth_chainId", json!([]))?, "chain id")
+    }
+
+    /// Fetches the nonce of `address` at `block` with `eth_getTransactionCount`
+    pub fn transaction_count(&self, address: Address, block: &str) -> Result<u64, RpcClientError> {
+        to_u64(self.request("eth_getTransactionCount", json!([address, block]))?, "nonce")
+    }
+
+    /// Fetches the header of `block`, a tag such as `"latest"` or a hex-encoded number
+    pub fn block_details(&self, block: &str) -> Result<BlockDetails, RpcClientError> {
+        self.request::<Option<BlockDetails>>("eth_getBlockByNumber", json!([block, false]))?
+            .ok_or_else(|| RpcClientError::NotFound(format!("block {}", block)))
+    }
+
+    /// Fetches the prestate of `params` at `block` with `debug_traceCall` and geth's
+    /// `prestateTracer`; the node must serve the `debug` namespace
+    pub fn prestate(&self, params: &TxParams, block: &str) -> Result<HashMap<Address, AccountDetails>, RpcClientError> {
+        self.request("debug_traceCall", prestate_params(params, block))
+    }
+
+    /// Fetches a transaction with `eth_getTransactionByHash`
+    pub fn transaction_by_hash(&self, hash: B256) -> Result<RpcTransaction, RpcClientError> {
+        self.request::<Option<RpcTransaction>>("eth_getTransactionByHash", json!([hash]))?
+            .ok_or_else(|| RpcClientError::NotFound(format!("transaction {}", hash)))
+    }
+
+    /// Fetches the transactions of `block`, in block order
+    pub fn block_transactions(&self, block: &str) -> Result<Vec<RpcTransaction>, RpcClientError> {
+        Ok(self.block_with_transactions(block)?.transactions)
+    }
+
+    /// Fetches `block` with its transactions and the parent roots its system calls write
+    pub(crate) fn block_with_transactions(&self, block: &str) -> Result<BlockTransactions, RpcClientError> {
+        self.request::<Option<BlockTransactions>>("eth_getBlockByNumber", json!([block, true]))?
+            .ok_or_else(|| RpcClientError::NotFound(format!("block {}", block)))
+    }
+
+    /// Fetches the prestate of a mined transaction with `debug_traceTransaction` and
+    /// geth's `prestateTracer`
+    pub fn transaction_prestate(&self, hash: B256) -> Result<HashMap<Address, AccountDetails>, RpcClientError> {
+        self.request("debug_traceTransaction", json!([hash, { "tracer": "prestateTracer" }]))
+    }
+
+    /// Completes `call` with the node's chain id and the sender's nonce at `block`
+    pub fn resolve_tx_params(&self, call: CallParams, block: &str) -> Result<TxParams, RpcClientError> {
+        let chain_id = self.chain_id()?;
+        let from_nonce = self.transaction_count(call.from, block)?;
+        Ok(call.into_tx_params(chain_id, from_nonce))
+    }
+
+    /// Fetches the system contracts deployed under `spec` at `block`: their code, the
+    /// beacon root recorded for `block` and the hash of its parent, so that they can be
+    /// merged into a prestate that does not contain them
+    pub fn system_contracts_prestate(
+        &self,
+        block: &str,
+        spec: SpecId,
+    ) -> Result<HashMap<Address, AccountDetails>, RpcClientError> {
+        let details = self.block_details(block)?;
+        let number = to_u64(details.number, "block number")?;
+        let timestamp = to_u64(details.timestamp, "timestamp")?;
+
+        let mut prestate = HashMap::default();
+        for (address, _) in system_contracts(spec) {
+            let slots = if address == BEACON_ROOTS_ADDRESS {
+                let (timestamp_slot, root_slot) = beacon_root_slots(timestamp);
+                vec![timestamp_slot, root_slot]
+            } else if number > 0 {
+                vec![history_storage_slot(number - 1)]
+            } else {
+                Vec::new()
+            };
+            let mut storage = HashMap::default();
+            for slot in slots {
+                let value: U256 = self.request("eth_getStorageAt", json!([address, slot, block]))?;
+                storage.insert(slot, value);
+            }
+            let code: Bytes = self.request("eth_getCode", json!([address, block]))?;
+            prestate.insert(address, AccountDetails {
+                balance: None,
+                nonce: None,
+                code: (!code.is_empty()).then_some(code),
+                storage: Some(storage),
+            });
+        }
+        Ok(prestate)
+    }
+}
+
+/// Async JSON-RPC client, the counterpart of `TraceRpcClient`
+#[derive(Debug, Clone)]
+pub struct AsyncTraceRpcClient {
+    client: reqwest::Client,
+    url: String,
+    config: RpcClientConfig,
+}
+
+impl AsyncTraceRpcClient {
+    /// Creates a client for `url` with the default timeouts and retry policy
+    pub fn new(url: impl Into<String>) -> Result<Self, RpcClientError> {
+        Self::with_config(url, RpcClientConfig::default())
+    }
+
+    /// Creates a client for `url` with the given timeouts and retry policy
+    pub fn with_config(url: impl Into<String>, config: RpcClientConfig) -> Result<Self, RpcClientError> {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| RpcClientError::Client(e.to_string()))?;
+        Ok(Self { client, url: url.into(), config })
+    }
+
+    /// Sends a JSON-RPC request and deserializes its result, retrying retryable failures
+    pub async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, RpcClientError> {
+        let body = request_body(method, params);
+        let mut attempt = 0;
+        loop {
+            match self.send(&body).await {
+                Err(e) if e.is_retryable() && attempt < self.config.max_retries => {
+                    attempt += 1;
+                    tokio::time::sleep(self.config.retry_backoff * attempt).await;
+                }
+                response => return parse_response(method, response?),
+            }
+        }
+    }
+
+    async fn send(&self, body: &Value) -> Result<Value, RpcClientError> {
+        let response = self.client
+            .post(&self.url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| RpcClientError::Transport(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(RpcClientError::Status(response.status().as_u16()));
+        }
+        response.json().await.map_err(|e| RpcClientError::Decode(e.to_string()))
+    }
+
+    /// Fetches the chain id with `eth_chainId`
+    pub async fn chain_id(&self) -> Result<u64, RpcClientError> {
+        to_u64(self.request("eth_chainId", json!([])).await?, "chain id")
+    }
+
+    /// Fetches the nonce of `address` at `block` with `eth_getTransactionCount`
+    pub async fn transaction_count(&self, address: Address, block: &str) -> Result<u64, RpcClientError> {
+        to_u64(self.request("eth_getTransactionCount", json!([address, block])).await?, "nonce")
+    }
+
+    /// Fetches the header of `block`, a tag such as `"latest"` or a hex-encoded number
+    pub async fn block_details(&self, block: &str) -> Result<BlockDetails, RpcClientError> {
+        self.request::<Option<BlockDetails>>("eth_getBlockByNumber", json!([block, false])).await?
+            .ok_or_else(|| RpcClientError::NotFound(format!("block {}", block)))
+    }
+
+    /// Fetches the prestate of `params` at `block` with `debug_traceCall` and geth's
+    /// `prestateTracer`; the node must serve the `debug` namespace
+    pub async fn prestate(&self, params: &TxParams, block: &str) -> Result<HashMap<Address, AccountDetails>, RpcClientError> {
+        self.request("debug_traceCall", prestate_params(params, block)).await
+    }
+
+    /// Fetches a transaction with `eth_getTransactionByHash`
+    pub async fn transaction_by_hash(&self, hash: B256) -> Result<RpcTransaction, RpcClientError> {
+        self.request::<Option<RpcTransaction>>("eth_getTransactionByHash", json!([hash])).await?
+            .ok_or_else(|| RpcClientError::NotFound(format!("transaction {}", hash)))
+    }
+
+    /// Fetches the transactions of `block`, in block order
+    pub async fn block_transactions(&self, block: &str) -> Result<Vec<RpcTransaction>, RpcClientError> {
+        self.request::<Option<BlockTransactions>>("eth_getBlockByNumber", json!([block, true])).await?
+            .map(|block| block.transactions)
+            .ok_or_else(|| RpcClientError::NotFound(format!("block {}", block)))
+    }
+
+    /// Fetches the prestate of a mined transaction with `debug_traceTransaction` and
+    /// geth's `prestateTracer`
+    pub async fn transaction_prestate(&self, hash: B256) -> Result<HashMap<Address, AccountDetails>, RpcClientError> {
+        self.request("debug_traceTransaction", json!([hash, { "tracer": "prestateTracer" }])).await
+    }
+
+    /// Completes `call` with the node's chain id and the sender's nonce at `block`
+    pub async fn resolve_tx_params(&self, call: CallParams, block: &str) -> Result<TxParams, RpcClientError> {
+        let chain_id = self.chain_id().await?;
+        let from_nonce = self.transaction_count(call.from, block).await?;
+        Ok(call.into_tx_params(chain_id, from_nonce))
+    }
+}
+
+/// Traces a transaction on top of the latest block of a node in one call.
+///
+/// Block details and the prestate are fetched with `TraceRpcClient`, so the node must
+/// serve `debug_traceCall`. Use `trace_transaction_rpc` for nodes that do not.
+///
+/// # Errors
+///
+/// Returns `TraceError::Rpc` if a request to the node fails, or any other `TraceError`
+/// if the transaction cannot be built or executed
+pub fn trace_from_rpc(
+    rpc_url: &str,
+    params: &TxParams,
+    options: TraceTransactionOptions,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let (block, latest_block_env) = pinned_block(&client, "latest")?;
+    trace_at_block(&client, params, &block, latest_block_env, options)
+}
+
+/// Traces a call on top of `block` like `trace_from_rpc`, resolving the chain id with
+/// `eth_chainId` and the sender's nonce at `block` with `eth_getTransactionCount`
+/// instead of requiring the caller to know them.
+///
+/// # Arguments
+///
+/// * `rpc_url` - JSON-RPC endpoint serving `debug_traceCall`
+/// * `call` - The transaction to trace, without chain id and nonce
+/// * `block` - Block tag such as `"latest"` or hex-encoded block number
+/// * `options` - State overrides, call tracer options and ABI registry
+///
+/// # Errors
+///
+/// Returns `TraceError::Rpc` if a request to the node fails, or any other `TraceError`
+/// if the transaction cannot be built or executed
+pub fn trace_call_auto(
+    rpc_url: &str,
+    call: CallParams,
+    block: &str,
+    options: TraceTransactionOptions,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let (block, latest_block_env) = pinned_block(&client, block)?;
+    let params = client.resolve_tx_params(call, &block)?;
+    trace_at_block(&client, &params, &block, latest_block_env, options)
+}
+
+/// Replays a mined transaction locally, as a library replacement for
+/// `debug_traceTransaction`.
+///
+/// The transaction and its block are fetched from the node, the prestate is obtained
+/// from `source`, and the transaction is traced with the environment of its block.
+///
+/// # Arguments
+///
+/// * `rpc_url` - JSON-RPC endpoint; it must serve `debug_traceTransaction` when `source`
+///   is `PrestateSource::RemoteTracer`
+/// * `tx_hash` - Hash of the mined transaction
+/// * `source` - Where the prestate comes from
+/// * `options` - State overrides, call tracer options and ABI registry
+///
+/// # Errors
+///
+/// Returns `TraceError::InvalidParameter` for pending transactions and contract
+/// creations, `TraceError::Rpc` if a request to the node fails, or any other
+/// `TraceError` if the transaction cannot be executed
+pub fn trace_transaction_by_hash(
+    rpc_url: &str,
+    tx_hash: B256,
+    source: PrestateSource,
+    options: TraceTransactionOptions,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let (params, block_env, block_number) = mined_transaction(&client, tx_hash)?;
+
+    let prestate = match source {
+        PrestateSource::RemoteTracer => client.transaction_prestate(tx_hash)?,
+        PrestateSource::LazyDatabase => {
+            let parent = block_number.saturating_sub(U256::from(1));
+            let rpc_db = RpcDatabase::with_client(client, format!("{:#x}", parent));
+            trace_prestate(&params, block_env.clone(), rpc_db)?
+        }
+        PrestateSource::ReplayBlock => {
+            let mut state = replay_block_until(client, tx_hash, block_number, &block_env, params.spec)?;
+            trace_prestate(&params, block_env.clone(), &mut state)?
+        }
+    };
+    trace_transaction(&params, block_env, prestate, options)
+}
+
+/// Fetches a mined transaction and returns it as `TxParams` with the environment and
+/// number of its block. The hardfork is set from the block for chains `spec_for_block`
+/// knows.
+pub(crate) fn mined_transaction(
+    client: &TraceRpcClient,
+    tx_hash: B256,
+) -> Result<(TxParams, BlockEnv, U256), TraceError> {
+    let transaction = client.transaction_by_hash(tx_hash)?;
+    let block_number = transaction.block_number
+        .ok_or_else(|| TraceError::InvalidParameter(format!("transaction {} is pending", tx_hash)))?;
+    let block_env = create_block_env_from_block_details(client.block_details(&format!("{:#x}", block_number))?)?;
+    let chain_id = client.chain_id()?;
+    let mut params = transaction.to_tx_params(chain_id)?;
+    params.spec = Some(spec_for_block(
+        chain_id,
+        block_env.number.saturating_to(),
+        block_env.timestamp.saturating_to(),
+    ));
+    Ok((params, block_env, block_number))
+}
+
+/// Executes the start-of-block system calls and the transactions of block
+/// `block_number` that precede `tx_hash` on top of the state of the previous block,
+/// returning the state `tx_hash` executed on
+pub(crate) fn replay_block_until(
+    client: TraceRpcClient,
+    tx_hash: B256,
+    block_number: U256,
+    block_env: &BlockEnv,
+    spec: Option<SpecId>,
+) -> Result<State<RpcDatabase>, TraceError> {
+    let block = client.block_with_transactions(&format!("{:#x}", block_number))?;
+    let parent = block_number.saturating_sub(U256::from(1));
+    let mut state = State::builder()
+        .with_database(RpcDatabase::with_client(client, format!("{:#x}", parent)))
+        .build();
+    apply_block_system_calls(
+        &mut state,
+        block_env,
+        spec.unwrap_or_default(),
+        block.parent_hash,
+        block.parent_beacon_block_root,
+    )?;
+    for transaction in block.transactions.iter().take_while(|transaction| transaction.hash != tx_hash) {
+        execute_and_commit(transaction.to_tx_env()?, block_env, &mut state, spec)?;
+    }
+    Ok(state)
+}
+
+/// Fetches `block` and returns its hex-encoded number along with its environment, so
+/// later requests read the same block even if a tag like `"latest"` moves on
+fn pinned_block(client: &TraceRpcClient, block: &str) -> Result<(String, BlockEnv), TraceError> {
+    let block_details = client.block_details(block)?;
+    let block = format!("{:#x}", block_details.number);
+    Ok((block, create_block_env_from_block_details(block_details)?))
+}
+
+fn trace_at_block(
+    client: &TraceRpcClient,
+    params: &TxParams,
+    block: &str,
+    latest_block_env: BlockEnv,
+    options: TraceTransactionOptions,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let prestate = client.prestate(params, block)?;
+    trace_transaction(params, latest_block_env, prestate, options)
+}
+
+fn request_body(method: &str, params: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+        "id": 1
+    })
+}
+
+fn parse_response<T: DeserializeOwned>(method: &str, mut response: Value) -> Result<T, RpcClientError> {
+    if let Some(error) = response.get("error") {
+        return Err(RpcClientError::Rpc {
+            code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+            message: error.get("message").and_then(Value::as_str).unwrap_or_default().to_string(),
+        });
+    }
+    let result = response.get_mut("result").map(Value::take).unwrap_or_default();
+    serde_json::from_value(result)
+        .map_err(|e| RpcClientError::Decode(format!("{} result: {}", method, e)))
+}
+
+/// Converts a quantity of a fetched transaction into the integer type of its `TxParams` field
+fn field<T: TryFrom<U256>>(value: U256, name: &str) -> Result<T, TraceError> {
+    value.try_into()
+        .map_err(|_| TraceError::InvalidParameter(format!("{} {} is out of range", name, value)))
+}
+
+fn to_u64(value: U256, what: &str) -> Result<u64, RpcClientError> {
+    value.try_into()
+        .map_err(|_| RpcClientError::Decode(format!("{} {} does not fit in u64", what, value)))
+}
+
+/// Parameters of a `debug_traceCall` request running `params` under the prestate tracer
+fn prestate_params(params: &TxParams, block: &str) -> Value {
+    let mut call = json!({
+        "from": params.from,
+        "to": params.to,
+        "value": params.value,
+        "data": params.data,
+        "gas": U256::from(params.gas_limit),
+        "nonce": U256::from(params.from_nonce),
+    });
+    match params.tx_spec {
+        TxSpec::Legacy => {
+            call["gasPrice"] = json!(U256::from(params.gas_price));
+        }
+        TxSpec::Eip1559 => {
+            call["maxFeePerGas"] = json!(U256::from(params.max_fee_per_gas()));
+            call["maxPriorityFeePerGas"] = json!(U256::from(params.max_priority_fee_per_gas()));
+        }
+    }
+    if let Some(access_list) = &params.access_list {
+        call["accessList"] = json!(access_list);
+    }
+    if !params.authorization_list.is_empty() {
+        call["authorizationList"] = json!(params.authorization_list);
+    }
+    json!([call, block, { "tracer": "prestateTracer" }])
+}
+
+#[cfg(test)]
+mod tests {
+    use revm::primitives::address;
+    use revm::Database;
+
+    use super::*;
+    use crate::trace::database::create_in_memory_database_from_prestate_trace;
+    use crate::trace::system::{seed_system_contracts, HISTORY_STORAGE_ADDRESS};
+
+    const SENDER: Address = address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    const AUTHORITY: Address = address!("19e7e376e7c213b7e7e7e46cc70a5dd086daff2a");
+    const DELEGATE: Address = address!("1000000000000000000000000000000000000001");
+
+    fn set_code_transaction() -> RpcTransaction {
+        serde_json::from_str(include_str!("../../tests/fixtures/rpc/eip7702_transaction.json")).unwrap()
+    }
+
+    fn sender_prestate() -> HashMap<Address, AccountDetails> {
+        let mut prestate = HashMap::default();
+        prestate.insert(SENDER, AccountDetails {
+            balance: Some(U256::from(10).pow(U256::from(18))),
+            nonce: Some(5),
+            code: None,
+            storage: None,
+        });
+        prestate
+    }
+
+    fn delegation_code(delegate: Address) -> Bytes {
+        [&[0xef, 0x01, 0x00], delegate.as_slice()].concat().into()
+    }
+
+    #[test]
+    fn set_code_transaction_keeps_its_authorizations() {
+        let mut params = set_code_transaction().to_tx_params(1).unwrap();
+        assert_eq!(params.tx_spec, TxSpec::Eip1559);
+        assert_eq!(params.authorization_list.len(), 1);
+        params.spec = Some(SpecId::PRAGUE);
+
+        let block_env = BlockEnv { basefee: 1_000_000_000, ..BlockEnv::default() };
+        let result = trace_transaction(&params, block_env, sender_prestate(), TraceTransactionOptions::default()).unwrap();
+
+        assert!(result.execution_result.is_success());
+        let authority = &result.state_diff[&AUTHORITY];
+        assert_eq!(authority.info.nonce, 1);
+        assert_eq!(authority.info.code.as_ref().unwrap().original_bytes(), delegation_code(DELEGATE));
+    }
+
+    #[test]
+    fn replayed_set_code_transaction_delegates_the_authority() {
+        let tx = set_code_transaction().to_tx_env().unwrap();
+        assert_eq!(tx.tx_type, 4);
+        assert_eq!(tx.authorization_list.len(), 1);
+
+        let mut db = create_in_memory_database_from_prestate_trace(sender_prestate());
+        let block_env = BlockEnv { basefee: 1_000_000_000, ..BlockEnv::default() };
+        let result = execute_and_commit(tx, &block_env, &mut db, Some(SpecId::PRAGUE)).unwrap();
+
+        assert!(result.is_success());
+        let authority = db.basic(AUTHORITY).unwrap().unwrap();
+        assert_eq!(authority.nonce, 1);
+        assert_eq!(db.code_by_hash(authority.code_hash).unwrap().original_bytes(), delegation_code(DELEGATE));
+    }
+
+    #[test]
+    fn block_system_calls_record_the_parent_roots() {
+        let mut prestate = HashMap::default();
+        seed_system_contracts(&mut prestate, SpecId::PRAGUE);
+        let mut db = create_in_memory_database_from_prestate_trace(prestate);
+        let block_env = BlockEnv {
+            number: U256::from(22_500_000),
+            timestamp: U256::from(1_750_000_000),
+            ..BlockEnv::default()
+        };
+        let parent_hash = B256::repeat_byte(0x22);
+        let beacon_root = B256::repeat_byte(0x44);
+
+        apply_block_system_calls(&mut db, &block_env, SpecId::PRAGUE, parent_hash, Some(beacon_root)).unwrap();
+
+        let (timestamp_slot, root_slot) = beacon_root_slots(1_750_000_000);
+        assert_eq!(db.storage(BEACON_ROOTS_ADDRESS, timestamp_slot).unwrap(), U256::from(1_750_000_000));
+        assert_eq!(db.storage(BEACON_ROOTS_ADDRESS, root_slot).unwrap(), U256::from_be_bytes(beacon_root.0));
+        let parent_slot = history_storage_slot(22_500_000 - 1);
+        assert_eq!(db.storage(HISTORY_STORAGE_ADDRESS, parent_slot).unwrap(), U256::from_be_bytes(parent_hash.0));
+    }
+}
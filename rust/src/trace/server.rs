@@ -0,0 +1,331 @@
+//! JSON-RPC server serving `debug_traceCall` and `debug_traceTransaction`
+//!
+//! State is read from an upstream node over plain `eth_*` calls, so the server can sit
+//! next to a node that lacks the `debug` namespace and answer tracing requests for it.
+//! The default struct logger, `callTracer` and `prestateTracer` (including `diffMode`)
+//! are supported.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use revm::context::BlockEnv;
+use revm::context_interface::transaction::AccessList;
+use revm::primitives::{Address, Bytes, B256, U256};
+use revm::Database;
+use tokio::net::TcpListener;
+
+use crate::trace::block::{create_block_env_from_block_details, spec_for_block};
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, PrestateKeys, RpcDatabase};
+use crate::trace::error::TraceError;
+use crate::trace::geth::GethCallFrame;
+use crate::trace::rpc::{mined_transaction, replay_block_until, TraceRpcClient};
+use crate::trace::inspector::{CallTracerConfig, OpcodeTracerConfig};
+use crate::trace::trace::{
+    trace_prestate,
+    trace_transaction_with_database,
+    trace_transaction_with_opcodes,
+    TxParams,
+    TxSpec,
+};
+
+/// JSON-RPC error codes used in responses
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+struct ServerState {
+    upstream_url: String,
+}
+
+/// Builds a router answering JSON-RPC requests, single or batched, on `/`
+///
+/// # Arguments
+///
+/// * `upstream_url` - JSON-RPC endpoint state and blocks are read from
+pub fn router(upstream_url: impl Into<String>) -> Router {
+    let state = Arc::new(ServerState { upstream_url: upstream_url.into() });
+    Router::new()
+        .route("/", post(handle))
+        .with_state(state)
+}
+
+/// Serves tracing requests on `listener` until the server fails
+///
+/// # Errors
+///
+/// Returns the I/O error that stopped the server
+pub async fn serve(listener: TcpListener, upstream_url: impl Into<String>) -> std::io::Result<()> {
+    axum::serve(listener, router(upstream_url)).await
+}
+
+/// Error object of a JSON-RPC response
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+impl From<TraceError> for RpcError {
+    fn from(error: TraceError) -> Self {
+        let code = match &error {
+            TraceError::InvalidParameter(_) | TraceError::InvalidAddress(_) | TraceError::InvalidHexData(_) => INVALID_PARAMS,
+            _ => SERVER_ERROR,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+/// Call object of `debug_traceCall`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct RpcCall {
+    from: Option<Address>,
+    to: Option<Address>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+    max_fee_per_gas: Option<U256>,
+    max_priority_fee_per_gas: Option<U256>,
+    value: Option<U256>,
+    #[serde(alias = "data")]
+    input: Option<Bytes>,
+    nonce: Option<U256>,
+    access_list: Option<AccessList>,
+}
+
+/// Tracer options of `debug_traceCall` and `debug_traceTransaction`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TraceOptions {
+    tracer: Option<String>,
+    tracer_config: Value,
+    // Struct logger options
+    enable_memory: bool,
+    disable_stack: bool,
+    disable_storage: bool,
+}
+
+/// `tracerConfig` of the prestate tracer
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PrestateTracerConfig {
+    diff_mode: bool,
+}
+
+impl RpcCall {
+    /// Converts the call into `TxParams`, filling in what the caller left out like
+    /// `eth_call` does: the sender's current nonce, the block gas limit and the base fee.
+    /// The hardfork is the one active at the block.
+    fn into_tx_params(
+        self,
+        chain_id: u64,
+        rpc_db: &mut RpcDatabase,
+        block_env: &BlockEnv,
+    ) -> Result<TxParams, TraceError> {
+        let to = self.to.ok_or_else(|| TraceError::InvalidParameter(
+            "contract creation is not supported".to_string()
+        ))?;
+        let from = self.from.unwrap_or_default();
+        let from_nonce = match self.nonce {
+            Some(nonce) => to_u64(nonce, "nonce")?,
+            None => rpc_db.basic(from)?.map(|account| account.nonce).unwrap_or_default(),
+        };
+        let gas_limit = match self.gas {
+            Some(gas) => to_u64(gas, "gas")?,
+            None => block_env.gas_limit,
+        };
+
+        let (tx_spec, gas_price, gas_priority_fee) = match self.max_fee_per_gas {
+            Some(max_fee_per_gas) => (
+                TxSpec::Eip1559,
+                to_u128(max_fee_per_gas, "maxFeePerGas")?,
+                to_u128(self.max_priority_fee_per_gas.unwrap_or_default(), "maxPriorityFeePerGas")?,
+            ),
+            None => (
+                TxSpec::Legacy,
+                match self.gas_price {
+                    Some(gas_price) => to_u128(gas_price, "gasPrice")?,
+                    None => u128::from(block_env.basefee),
+                },
+                0,
+            ),
+        };
+
+        Ok(TxParams {
+            chain_id,
+            from,
+            from_nonce,
+            to,
+            value: self.value.unwrap_or_default(),
+            data: self.input.unwrap_or_default(),
+            gas_limit,
+            gas_price,
+            gas_priority_fee,
+            tx_spec,
+            access_list: self.access_list,
+            blob_versioned_hashes: Vec::new(),
+            max_fee_per_blob_gas: 0,
+            authorization_list: Vec::new(),
+            deposit: None,
+            spec: Some(spec_for_block(chain_id, block_env.number.saturating_to(), block_env.timestamp.saturating_to())),
+        })
+    }
+}
+
+fn to_u64(value: U256, field: &str) -> Result<u64, TraceError> {
+    value.try_into()
+        .map_err(|_| TraceError::InvalidParameter(format!("{} does not fit in u64", field)))
+}
+
+fn to_u128(value: U256, field: &str) -> Result<u128, TraceError> {
+    value.try_into()
+        .map_err(|_| TraceError::InvalidParameter(format!("{} does not fit in u128", field)))
+}
+
+async fn handle(State(state): State<Arc<ServerState>>, Json(body): Json<Value>) -> Json<Value> {
+    match body {
+        Value::Array(requests) => {
+            let mut responses = Vec::with_capacity(requests.len());
+            for request in requests {
+                responses.push(handle_request(&state, request).await);
+            }
+            Json(Value::Array(responses))
+        }
+        request => Json(handle_request(&state, request).await),
+    }
+}
+
+async fn handle_request(state: &ServerState, request: Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some(method) => {
+            let method = method.to_string();
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            let upstream_url = state.upstream_url.clone();
+            // The upstream database is blocking, so it runs off the async workers
+            tokio::task::spawn_blocking(move || dispatch(&upstream_url, &method, params))
+                .await
+                .unwrap_or_else(|e| Err(RpcError::new(SERVER_ERROR, e.to_string())))
+        }
+        None => Err(RpcError::new(INVALID_REQUEST, "missing method")),
+    };
+
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message }
+        }),
+    }
+}
+
+fn dispatch(upstream_url: &str, method: &str, params: Value) -> Result<Value, RpcError> {
+    let params = match params {
+        Value::Array(params) => params,
+        Value::Null => Vec::new(),
+        _ => return Err(RpcError::new(INVALID_PARAMS, "params must be an array")),
+    };
+
+    match method {
+        "debug_traceCall" => {
+            let call: RpcCall = param(&params, 0)?
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing call object"))?;
+            let block: String = param(&params, 1)?.unwrap_or_else(|| "latest".to_string());
+            let options: TraceOptions = param(&params, 2)?.unwrap_or_default();
+            Ok(trace_call(upstream_url, call, &block, options)?)
+        }
+        "debug_traceTransaction" => {
+            let hash: B256 = param(&params, 0)?
+                .ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing transaction hash"))?;
+            let options: TraceOptions = param(&params, 1)?.unwrap_or_default();
+            Ok(trace_transaction_by_hash(upstream_url, hash, options)?)
+        }
+        _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("method {} not supported", method))),
+    }
+}
+
+/// Reads positional parameter `index`, treating a missing or `null` one as absent
+fn param<T: DeserializeOwned>(params: &[Value], index: usize) -> Result<Option<T>, RpcError> {
+    match params.get(index) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("invalid parameter {}: {}", index, e))),
+    }
+}
+
+fn trace_call(upstream_url: &str, call: RpcCall, block: &str, options: TraceOptions) -> Result<Value, TraceError> {
+    let mut rpc_db = RpcDatabase::new(upstream_url, block)?;
+    let block_env = create_block_env_from_block_details(rpc_db.block_details()?)?;
+    let chain_id = rpc_db.chain_id()?;
+    let params = call.into_tx_params(chain_id, &mut rpc_db, &block_env)?;
+    trace_with_options(&params, block_env, rpc_db, options)
+}
+
+/// Traces a mined transaction at its position in its block, after replaying the
+/// transactions preceding it
+fn trace_transaction_by_hash(upstream_url: &str, hash: B256, options: TraceOptions) -> Result<Value, TraceError> {
+    let client = TraceRpcClient::new(upstream_url)?;
+    let (params, block_env, block_number) = mined_transaction(&client, hash)?;
+    let mut state = replay_block_until(client, hash, block_number, &block_env, params.spec)?;
+    trace_with_options(&params, block_env, &mut state, options)
+}
+
+fn trace_with_options<DB: Database>(
+    params: &TxParams,
+    block_env: BlockEnv,
+    db: DB,
+    options: TraceOptions,
+) -> Result<Value, TraceError> {
+    // Discover what the transaction touches, then trace it against exactly that state
+    let prestate = trace_prestate(params, block_env.clone(), db)?;
+
+    match options.tracer.as_deref() {
+        None => {
+            let config = OpcodeTracerConfig {
+                enable_memory: options.enable_memory,
+                disable_stack: options.disable_stack,
+                disable_storage: options.disable_storage,
+            };
+            let result = trace_transaction_with_opcodes(params, block_env, prestate, config)?;
+            Ok(serde_json::to_value(result)?)
+        }
+        Some("callTracer") => {
+            let config: CallTracerConfig = tracer_config(options.tracer_config)?;
+            let prestate_keys = PrestateKeys::from_prestate(&prestate);
+            let db = create_in_memory_database_from_prestate_trace(prestate);
+            let result = trace_transaction_with_database(params, block_env, db, &prestate_keys, &config)?;
+            Ok(serde_json::to_value(GethCallFrame::for_transaction(params, &result))?)
+        }
+        Some("prestateTracer") => {
+            let config: PrestateTracerConfig = tracer_config(options.tracer_config)?;
+            if !config.diff_mode {
+                return Ok(serde_json::to_value(prestate)?);
+            }
+            let prestate_keys = PrestateKeys::from_prestate(&prestate);
+            let db = create_in_memory_database_from_prestate_trace(prestate);
+            let result = trace_transaction_with_database(params, block_env, db, &prestate_keys, &CallTracerConfig::default())?;
+            Ok(serde_json::to_value(result.diff_mode)?)
+        }
+        Some(tracer) => Err(TraceError::InvalidParameter(format!("unsupported tracer: {}", tracer))),
+    }
+}
+
+fn tracer_config<T: DeserializeOwned + Default>(config: Value) -> Result<T, TraceError> {
+    if config.is_null() {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_value(config)?)
+}
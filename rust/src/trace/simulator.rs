@@ -0,0 +1,177 @@
+//! Reusable simulation state for tracing many transactions against the same prestate
+//!
+//! `trace_transaction` parses the prestate and builds a database on every call. A
+//! `Simulator` builds it once as a `SharedPrestate` and hands each trace a copy of only
+//! the committed changes on top of it, which is what bundlers simulating hundreds of user
+//! operations against one block need.
+
+use revm::context::result::HaltReason;
+use revm::context::BlockEnv;
+use revm::primitives::{Address, Bytes, HashMap};
+use revm::state::EvmState;
+use revm::{Database, DatabaseCommit};
+
+use crate::trace::database::{apply_state_diff, set_account_code, AccountDetails, OverlayDB, PrestateKeys, SharedPrestate};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::CallTracerConfig;
+use crate::trace::parallel::parallel_map;
+use crate::trace::trace::{trace_transaction_with_database, TraceTransactionResult, TxParams};
+
+/// A prestate-backed database and block environment that transactions are traced against
+///
+/// Traces do not change the simulator's state until `commit` is called, so independent
+/// candidates can be traced one after another against the same state. `snapshot` and
+/// `revert_to` save and restore the state around what-if explorations, and `reset` goes
+/// back to the prestate the simulator was created with.
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    /// State built from the prestate, restored by `reset`
+    base: SharedPrestate,
+    /// Current state: every committed transaction on top of the prestate
+    db: OverlayDB,
+    prestate_keys: PrestateKeys,
+    block_env: BlockEnv,
+    tracer_config: CallTracerConfig,
+    /// State changes of the latest trace, for `commit`
+    last_state: Option<EvmState>,
+}
+
+impl Simulator {
+    /// Creates a simulator executing in `block_env` on top of `prestate_tracer_result`
+    pub fn new(block_env: BlockEnv, prestate_tracer_result: HashMap<Address, AccountDetails>) -> Self {
+        let base = SharedPrestate::new(prestate_tracer_result);
+        Self {
+            db: base.overlay(),
+            prestate_keys: base.prestate_keys().clone(),
+            base,
+            block_env,
+            tracer_config: CallTracerConfig::default(),
+            last_state: None,
+        }
+    }
+
+    /// Sets the call tracer options used by every trace
+    pub fn with_tracer_config(mut self, tracer_config: CallTracerConfig) -> Self {
+        self.tracer_config = tracer_config;
+        self
+    }
+
+    pub fn block_env(&self) -> &BlockEnv {
+        &self.block_env
+    }
+
+    /// Replaces the block environment, e.g. to simulate the next block
+    pub fn set_block_env(&mut self, block_env: BlockEnv) {
+        self.block_env = block_env;
+    }
+
+    /// The current state, committed transactions included
+    pub fn database(&self) -> &OverlayDB {
+        &self.db
+    }
+
+    /// Replaces the code at `address` for every later trace, keeping its balance, nonce
+    /// and storage, e.g. to swap a proxy's implementation for instrumented bytecode or mock
+    /// an oracle with `mock_return_code`. Empty `code` removes it.
+    ///
+    /// The change is part of the current state: `reset` and `revert_to` an earlier
+    /// snapshot undo it.
+    pub fn set_code(&mut self, address: Address, code: Bytes) {
+        let mut info = self.db.basic(address).ok().flatten().unwrap_or_default();
+        set_account_code(&mut info, code);
+        self.db.insert_account_info(address, info);
+        self.prestate_keys.insert_account(address);
+        self.last_state = None;
+    }
+
+    /// Traces `tx` against the current state without changing it
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the transaction cannot be built or executed
+    pub fn trace(&mut self, tx: &TxParams) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+        self.last_state = None;
+        let result = trace_transaction_with_database(
+            tx,
+            self.block_env.clone(),
+            self.db.clone(),
+            &self.prestate_keys,
+            &self.tracer_config,
+        )?;
+        self.last_state = Some(result.state_diff.clone());
+        Ok(result)
+    }
+
+    /// Traces each of `txs` independently against the current state
+    ///
+    /// Use `trace` and `commit` in turn instead for transactions that build on each other.
+    pub fn trace_many(&mut self, txs: &[TxParams]) -> Vec<Result<TraceTransactionResult<HaltReason>, TraceError>> {
+        let results = txs.iter().map(|tx| self.trace(tx)).collect();
+        self.last_state = None;
+        results
+    }
+
+    /// Traces each of `txs` independently against the current state, spreading them over
+    /// all cores. Results are in the order of `txs`.
+    ///
+    /// Every trace runs on its own copy of the state, so this gives the same results as
+    /// `trace_many` and leaves nothing to `commit`.
+    pub fn trace_parallel(&self, txs: Vec<TxParams>) -> Vec<Result<TraceTransactionResult<HaltReason>, TraceError>> {
+        parallel_map(txs, |tx| trace_transaction_with_database(
+            &tx,
+            self.block_env.clone(),
+            self.db.clone(),
+            &self.prestate_keys,
+            &self.tracer_config,
+        ))
+    }
+
+    /// Applies the state changes of the latest successful `trace` to the current state,
+    /// so later traces run on top of it. Returns false if there is nothing to commit.
+    pub fn commit(&mut self) -> bool {
+        let Some(state) = self.last_state.take() else {
+            return false;
+        };
+        self.prestate_keys.extend_from_state(&state);
+        self.db.commit(state);
+        true
+    }
+
+    /// Applies the state changes of a trace to the current state, like `commit` but for
+    /// any result, e.g. one kept from an earlier `trace_many`
+    pub fn commit_state(&mut self, state_diff: &EvmState) {
+        self.last_state = None;
+        self.prestate_keys.extend_from_state(state_diff);
+        apply_state_diff(&mut self.db, state_diff);
+    }
+
+    /// Saves the current state, to be restored with `revert_to`
+    pub fn snapshot(&self) -> SimulatorSnapshot {
+        SimulatorSnapshot {
+            db: self.db.clone(),
+            prestate_keys: self.prestate_keys.clone(),
+        }
+    }
+
+    /// Restores a state saved by `snapshot`, discarding everything committed since. A
+    /// snapshot can be restored any number of times.
+    pub fn revert_to(&mut self, snapshot: &SimulatorSnapshot) {
+        self.db = snapshot.db.clone();
+        self.prestate_keys = snapshot.prestate_keys.clone();
+        self.last_state = None;
+    }
+
+    /// Discards every committed transaction, going back to the initial prestate
+    pub fn reset(&mut self) {
+        self.db = self.base.overlay();
+        self.prestate_keys = self.base.prestate_keys().clone();
+        self.last_state = None;
+    }
+}
+
+/// State of a `Simulator` saved by `Simulator::snapshot`
+#[derive(Debug, Clone)]
+pub struct SimulatorSnapshot {
+    db: OverlayDB,
+    prestate_keys: PrestateKeys,
+}
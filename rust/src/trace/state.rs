@@ -0,0 +1,247 @@
+//! Stable, serialization-friendly reports derived from a trace's raw state diff
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use revm::context_interface::transaction::{AccessList, AccessListItem};
+use revm::primitives::{Address, Bytes, HashMap, StorageKey, StorageValue, B256, I256, KECCAK_EMPTY, U256};
+use revm::state::{AccountInfo, EvmState};
+use revm::DatabaseRef;
+
+use crate::trace::database::AccountDetails;
+use crate::trace::precompiles::precompile_name;
+
+/// Options controlling what `build_state_report` includes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateReportOptions {
+    /// Also report slots that were read but not written, with the value read.
+    /// Useful for risk engines that need to know what a decision depended on.
+    pub include_read_slots: bool,
+}
+
+/// Value of a storage slot before and after execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotChange {
+    pub original_value: StorageValue,
+    pub present_value: StorageValue,
+}
+
+/// Storage accessed on one account
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStateReport {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed_slots: BTreeMap<StorageKey, SlotChange>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub read_slots: BTreeMap<StorageKey, StorageValue>,
+}
+
+impl AccountStateReport {
+    fn is_empty(&self) -> bool {
+        self.changed_slots.is_empty() && self.read_slots.is_empty()
+    }
+}
+
+/// Builds a per-account storage report from `state_diff`, sorted by address and slot.
+///
+/// Accounts with nothing to report are omitted.
+pub fn build_state_report(
+    state_diff: &EvmState,
+    options: &StateReportOptions,
+) -> BTreeMap<Address, AccountStateReport> {
+    state_diff.iter()
+        .filter_map(|(address, account)| {
+            let mut report = AccountStateReport::default();
+            for (slot, value) in account.storage.iter() {
+                if value.is_changed() {
+                    report.changed_slots.insert(*slot, SlotChange {
+                        original_value: value.original_value,
+                        present_value: value.present_value,
+                    });
+                } else if options.include_read_slots {
+                    report.read_slots.insert(*slot, value.present_value);
+                }
+            }
+            (!report.is_empty()).then_some((*address, report))
+        })
+        .collect()
+}
+
+/// ETH balance of an account before and after execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceChange {
+    pub before: U256,
+    pub after: U256,
+    pub delta: I256,
+}
+
+/// Computes the balance change of every account in `state_diff` whose balance changed.
+///
+/// `db` must be the database the transaction executed against; it still holds the
+/// balances from before execution since changes are only kept in the journal.
+pub fn balance_changes<DB: DatabaseRef>(
+    db: &DB,
+    state_diff: &EvmState,
+) -> HashMap<Address, BalanceChange> {
+    state_diff.iter()
+        .filter_map(|(address, account)| {
+            let before = db.basic_ref(*address).ok()?
+                .map(|info| info.balance)
+                .unwrap_or_default();
+            let after = account.info.balance;
+            (before != after).then(|| (*address, BalanceChange {
+                before,
+                after,
+                delta: I256::from_raw(after.wrapping_sub(before)),
+            }))
+        })
+        .collect()
+}
+
+/// State changes in the format of geth's `prestateTracer` with `diffMode: true`.
+///
+/// `pre` holds the modified accounts as they were before execution with only their
+/// modified slots; `post` holds only the fields and slots that changed. Accounts
+/// created during execution are missing from `pre`, destroyed ones from `post`. As in
+/// geth, zero-valued slots are left out of both.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffModeState {
+    pub pre: BTreeMap<Address, AccountDetails>,
+    pub post: BTreeMap<Address, AccountDetails>,
+}
+
+/// Builds the geth `diffMode` view of `state_diff`.
+///
+/// `db` must be the database the transaction executed against, see `balance_changes`.
+pub fn build_diff_mode<DB: DatabaseRef>(db: &DB, state_diff: &EvmState) -> DiffModeState {
+    let mut diff = DiffModeState::default();
+
+    for (address, account) in state_diff.iter() {
+        if !account.is_touched() {
+            continue;
+        }
+        let Ok(before) = db.basic_ref(*address) else {
+            continue;
+        };
+        let existed = before.as_ref().is_some_and(|info| !info.is_empty());
+        let before = before.unwrap_or_default();
+        let after = &account.info;
+        let destroyed = account.is_selfdestructed();
+
+        let changed_slots: Vec<_> = account.storage.iter()
+            .filter(|(_, slot)| slot.is_changed())
+            .collect();
+        let balance_changed = before.balance != after.balance;
+        let nonce_changed = before.nonce != after.nonce;
+        let code_changed = before.code_hash != after.code_hash;
+        if !(balance_changed || nonce_changed || code_changed || destroyed || !changed_slots.is_empty()) {
+            continue;
+        }
+
+        if existed {
+            let storage: HashMap<StorageKey, StorageValue> = changed_slots.iter()
+                .filter(|(_, slot)| !slot.original_value.is_zero())
+                .map(|(key, slot)| (**key, slot.original_value))
+                .collect();
+            diff.pre.insert(*address, AccountDetails {
+                balance: Some(before.balance),
+                nonce: Some(before.nonce),
+                code: code_of(db, &before),
+                storage: (!storage.is_empty()).then_some(storage),
+            });
+        }
+
+        if !destroyed {
+            let storage: HashMap<StorageKey, StorageValue> = changed_slots.iter()
+                .filter(|(_, slot)| !slot.present_value.is_zero())
+                .map(|(key, slot)| (**key, slot.present_value))
+                .collect();
+            let post = AccountDetails {
+                balance: balance_changed.then_some(after.balance),
+                nonce: nonce_changed.then_some(after.nonce),
+                code: if code_changed { code_of(db, after) } else { None },
+                storage: (!storage.is_empty()).then_some(storage),
+            };
+            let is_empty = post.balance.is_none() && post.nonce.is_none()
+                && post.code.is_none() && post.storage.is_none();
+            if !is_empty {
+                diff.post.insert(*address, post);
+            }
+        }
+    }
+
+    diff
+}
+
+/// Bytecode of an account, if it has any
+fn code_of<DB: DatabaseRef>(db: &DB, info: &AccountInfo) -> Option<Bytes> {
+    if info.code_hash == KECCAK_EMPTY {
+        return None;
+    }
+    match &info.code {
+        Some(code) => Some(code.original_bytes()),
+        None => db.code_by_hash_ref(info.code_hash).ok().map(|code| code.original_bytes()),
+    }
+}
+
+/// Accounts and storage slots read during execution, sorted by address and slot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessedState {
+    pub accounts: BTreeMap<Address, AccessedAccount>,
+}
+
+/// What execution read of a single account
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessedAccount {
+    /// Code hash before execution; the empty hash for accounts without code
+    pub code_hash: B256,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub storage_slots: BTreeSet<StorageKey>,
+}
+
+impl AccessedState {
+    /// Builds an EIP-2930 access list covering the accessed state.
+    ///
+    /// Precompiles and `excluded` addresses are left out; the sender and recipient are
+    /// warm anyway, so listing them only adds gas.
+    pub fn to_access_list(&self, excluded: &[Address]) -> AccessList {
+        let items = self.accounts.iter()
+            .filter(|(address, _)| !excluded.contains(address) && precompile_name(**address).is_none())
+            .map(|(address, account)| AccessListItem {
+                address: *address,
+                storage_keys: account.storage_slots.iter()
+                    .map(|slot| B256::from(*slot))
+                    .collect(),
+            })
+            .collect();
+        AccessList(items)
+    }
+}
+
+/// Lists the accounts, code hashes and slots in `state_diff` that were read from `db`.
+///
+/// `db` must be the database the transaction executed against, see `balance_changes`.
+/// Accounts created during execution had no prior state to read and are left out. The
+/// result pairs with `extract_used_prestate` to shrink a prestate for re-simulation.
+pub fn accessed_state<DB: DatabaseRef>(db: &DB, state_diff: &EvmState) -> AccessedState {
+    let accounts = state_diff.iter()
+        .filter(|(_, account)| !account.is_created())
+        .map(|(address, account)| {
+            let code_hash = db.basic_ref(*address).ok()
+                .flatten()
+                .map(|info| info.code_hash)
+                .unwrap_or(KECCAK_EMPTY);
+            (*address, AccessedAccount {
+                code_hash,
+                storage_slots: account.storage.keys().copied().collect(),
+            })
+        })
+        .collect();
+    AccessedState { accounts }
+}
@@ -0,0 +1,210 @@
+//! Re-run a transaction across a range of conditions and report where its outcome changes
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::ExecutionResult;
+use revm::context::BlockEnv;
+use revm::primitives::{Address, Bytes, HashMap, U256};
+
+use crate::trace::database::{AccountDetails, SharedPrestate};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::CallTracerConfig;
+use crate::trace::trace::{trace_transaction_with_database, TxParams};
+
+/// Coarse outcome of a single execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExecutionStatus {
+    Success,
+    Revert,
+    Halt,
+}
+
+/// Outcome of one execution within a sweep
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepPoint<T> {
+    /// The swept input value used for this run
+    pub input: T,
+    pub status: ExecutionStatus,
+    pub gas_used: u64,
+    /// Return data on success, revert data on revert, empty on halt
+    pub output: Bytes,
+}
+
+impl ExecutionStatus {
+    pub fn of<H>(execution_result: &ExecutionResult<H>) -> Self {
+        match execution_result {
+            ExecutionResult::Success { .. } => ExecutionStatus::Success,
+            ExecutionResult::Revert { .. } => ExecutionStatus::Revert,
+            ExecutionResult::Halt { .. } => ExecutionStatus::Halt,
+        }
+    }
+}
+
+impl<T> SweepPoint<T> {
+    fn from_execution_result<H>(input: T, execution_result: &ExecutionResult<H>) -> Self {
+        let (status, output) = match execution_result {
+            ExecutionResult::Success { output, .. } => (ExecutionStatus::Success, output.data().clone()),
+            ExecutionResult::Revert { output, .. } => (ExecutionStatus::Revert, output.clone()),
+            ExecutionResult::Halt { .. } => (ExecutionStatus::Halt, Bytes::new()),
+        };
+        Self {
+            input,
+            status,
+            gas_used: execution_result.gas_used(),
+            output,
+        }
+    }
+
+    /// Two points have the same outcome if they share a status and, when reverting,
+    /// the same revert data. Success return data is ignored since it often legitimately
+    /// varies with the swept input.
+    fn same_outcome(&self, other: &Self) -> bool {
+        self.status == other.status
+            && (self.status != ExecutionStatus::Revert || self.output == other.output)
+    }
+}
+
+/// Boundary between two consecutive sweep points with different outcomes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutcomeTransition<T> {
+    /// Index of the last point before the change
+    pub before_index: usize,
+    /// Index of the first point after the change
+    pub after_index: usize,
+    pub before: T,
+    pub after: T,
+}
+
+/// All points of a sweep plus the places where the outcome changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepResult<T> {
+    pub points: Vec<SweepPoint<T>>,
+    pub transitions: Vec<OutcomeTransition<T>>,
+}
+
+impl<T: Clone> SweepResult<T> {
+    fn from_points(points: Vec<SweepPoint<T>>) -> Self {
+        let transitions = points.windows(2)
+            .enumerate()
+            .filter(|(_, pair)| !pair[0].same_outcome(&pair[1]))
+            .map(|(index, pair)| OutcomeTransition {
+                before_index: index,
+                after_index: index + 1,
+                before: pair[0].input.clone(),
+                after: pair[1].input.clone(),
+            })
+            .collect();
+        Self { points, transitions }
+    }
+}
+
+/// The transaction input varied by `sweep_parameter`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SweepParameter {
+    /// The ETH value sent with the transaction
+    Value,
+    /// The 32-byte ABI word at this index, counted after the 4-byte selector
+    CalldataWord(usize),
+    /// The gas price (max fee per gas for EIP-1559 transactions)
+    GasPrice,
+}
+
+impl SweepParameter {
+    /// Returns a copy of `params` with this parameter set to `value`
+    fn apply(&self, params: &TxParams, value: U256) -> Result<TxParams, TraceError> {
+        let mut params = params.clone();
+        match self {
+            SweepParameter::Value => params.value = value,
+            SweepParameter::CalldataWord(index) => {
+                let offset = 4 + index * 32;
+                if params.data.len() < offset + 32 {
+                    return Err(TraceError::InvalidParameter(format!(
+                        "calldata has no word at index {}", index
+                    )));
+                }
+                let mut data = params.data.to_vec();
+                data[offset..offset + 32].copy_from_slice(&value.to_be_bytes::<32>());
+                params.data = Bytes::from(data);
+            }
+            SweepParameter::GasPrice => {
+                params.gas_price = value.try_into().map_err(|_| TraceError::InvalidParameter(
+                    format!("gas price {} does not fit in u128", value)
+                ))?;
+            }
+        }
+        Ok(params)
+    }
+}
+
+/// Re-runs a transaction at each of the given block timestamps and reports where the
+/// outcome changes, e.g. to locate vesting unlocks, auction endings or deadline reverts.
+///
+/// The prestate is built once as a `SharedPrestate` and every run executes on an empty
+/// overlay of it, so each execution starts from the same state.
+///
+/// # Errors
+///
+/// Returns the first `TraceError` encountered; a revert is an outcome, not an error.
+pub fn sweep_timestamps(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    timestamps: impl IntoIterator<Item = U256>,
+) -> Result<SweepResult<U256>, TraceError> {
+    run_sweep(prestate_tracer_result, timestamps, |timestamp| {
+        let block_env = BlockEnv {
+            timestamp: *timestamp,
+            ..latest_block_env.clone()
+        };
+        Ok((params.clone(), block_env))
+    })
+}
+
+/// Re-runs a transaction with one input set to each of `values` and reports outcome and
+/// gas per point, e.g. to discover slippage limits or the threshold at which a call fails.
+///
+/// Like `sweep_timestamps`, every run starts from an empty overlay of the same prestate.
+///
+/// # Errors
+///
+/// Returns `TraceError::InvalidParameter` if a value cannot be applied (calldata too
+/// short, gas price overflow), or the first tracing error encountered.
+pub fn sweep_parameter(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    parameter: SweepParameter,
+    values: impl IntoIterator<Item = U256>,
+) -> Result<SweepResult<U256>, TraceError> {
+    run_sweep(prestate_tracer_result, values, |value| {
+        Ok((parameter.apply(params, *value)?, latest_block_env.clone()))
+    })
+}
+
+/// Executes one run per input against overlays of a single shared prestate database
+fn run_sweep<T: Clone>(
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    inputs: impl IntoIterator<Item = T>,
+    configure: impl Fn(&T) -> Result<(TxParams, BlockEnv), TraceError>,
+) -> Result<SweepResult<T>, TraceError> {
+    let prestate = SharedPrestate::new(prestate_tracer_result);
+
+    let mut points = Vec::new();
+    for input in inputs {
+        let (params, block_env) = configure(&input)?;
+        let result = trace_transaction_with_database(
+            &params,
+            block_env,
+            prestate.overlay(),
+            prestate.prestate_keys(),
+            &CallTracerConfig::default(),
+        )?;
+        points.push(SweepPoint::from_execution_result(input, &result.execution_result));
+    }
+
+    Ok(SweepResult::from_points(points))
+}
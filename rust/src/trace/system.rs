@@ -0,0 +1,132 @@
+//! Prestate helpers for system contracts whose storage is written by the protocol
+//!
+//! The EIP-4788 beacon roots and EIP-2935 history storage contracts are filled by
+//! system calls at the start of each block, which a prestate tracer never records. A
+//! simulation reading them against a bare prestate sees empty code or empty storage;
+//! these helpers seed their code and the ring buffer entries a transaction will read,
+//! or run the system calls themselves when replaying a block against full state.
+
+use revm::context::{BlockEnv, CfgEnv};
+use revm::handler::SystemCallCommitEvm;
+use revm::primitives::{address, bytes, Address, Bytes, HashMap, B256, U256};
+use revm::primitives::hardfork::SpecId;
+use revm::{Context, Database, DatabaseCommit, MainBuilder, MainContext};
+
+use crate::trace::database::AccountDetails;
+use crate::trace::error::{ExecutionError, TraceError};
+
+/// EIP-4788 beacon roots contract
+pub const BEACON_ROOTS_ADDRESS: Address = address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// Runtime code of the beacon roots contract
+pub const BEACON_ROOTS_CODE: Bytes = bytes!("3373fffffffffffffffffffffffffffffffffffffffe14604d57602036146024575f5ffd5b5f35801560495762001fff810690815414603c575f5ffd5b62001fff01545f5260205ff35b5f5ffd5b62001fff42064281555f359062001fff015500");
+
+/// Number of timestamps the beacon roots contract keeps
+pub const BEACON_ROOTS_HISTORY_LENGTH: u64 = 8191;
+
+/// EIP-2935 history storage contract
+pub const HISTORY_STORAGE_ADDRESS: Address = address!("0000F90827F1C53a10cb7A02335B175320002935");
+
+/// Runtime code of the history storage contract
+pub const HISTORY_STORAGE_CODE: Bytes = bytes!("3373fffffffffffffffffffffffffffffffffffffffe14604657602036036042575f35600143038111604257611fff81430311604257611fff9006545f5260205ff35b5f5ffd5b5f35611fff60014303065500");
+
+/// Number of block hashes the history storage contract keeps
+pub const HISTORY_SERVE_WINDOW: u64 = 8191;
+
+/// System contracts deployed under `spec`, with their runtime code
+pub fn system_contracts(spec: SpecId) -> Vec<(Address, Bytes)> {
+    let mut contracts = Vec::new();
+    if spec.is_enabled_in(SpecId::CANCUN) {
+        contracts.push((BEACON_ROOTS_ADDRESS, BEACON_ROOTS_CODE));
+    }
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        contracts.push((HISTORY_STORAGE_ADDRESS, HISTORY_STORAGE_CODE));
+    }
+    contracts
+}
+
+/// Adds the code of the system contracts deployed under `spec` to a prestate.
+///
+/// Accounts the prestate already contains keep their code, so a prestate fetched from a
+/// node is never overwritten.
+pub fn seed_system_contracts(prestate_tracer_result: &mut HashMap<Address, AccountDetails>, spec: SpecId) {
+    for (address, code) in system_contracts(spec) {
+        let details = system_account(prestate_tracer_result, address);
+        if details.code.is_none() {
+            details.code = Some(code);
+        }
+    }
+}
+
+/// Storage slots holding the beacon root of the block at `timestamp`: the timestamp slot
+/// and the root slot
+pub fn beacon_root_slots(timestamp: u64) -> (U256, U256) {
+    let timestamp_index = timestamp % BEACON_ROOTS_HISTORY_LENGTH;
+    (U256::from(timestamp_index), U256::from(timestamp_index + BEACON_ROOTS_HISTORY_LENGTH))
+}
+
+/// Records `root` as the parent beacon block root of the block at `timestamp`, as the
+/// system call at the start of that block does
+pub fn set_beacon_root(prestate_tracer_result: &mut HashMap<Address, AccountDetails>, timestamp: u64, root: B256) {
+    let (timestamp_slot, root_slot) = beacon_root_slots(timestamp);
+    let storage = system_account(prestate_tracer_result, BEACON_ROOTS_ADDRESS)
+        .storage.get_or_insert_with(HashMap::default);
+    storage.insert(timestamp_slot, U256::from(timestamp));
+    storage.insert(root_slot, U256::from_be_bytes(root.0));
+}
+
+/// Storage slot holding the hash of block `number`
+pub fn history_storage_slot(number: u64) -> U256 {
+    U256::from(number % HISTORY_SERVE_WINDOW)
+}
+
+/// Records `hash` as the hash of block `number`, as the system call of block `number + 1` does
+pub fn set_history_block_hash(prestate_tracer_result: &mut HashMap<Address, AccountDetails>, number: u64, hash: B256) {
+    system_account(prestate_tracer_result, HISTORY_STORAGE_ADDRESS)
+        .storage.get_or_insert_with(HashMap::default)
+        .insert(history_storage_slot(number), U256::from_be_bytes(hash.0));
+}
+
+/// Runs the system calls made at the start of a block under `spec` and commits them to
+/// `db`: the EIP-4788 beacon root from Cancun and the EIP-2935 parent hash from Prague.
+///
+/// # Errors
+///
+/// Returns `TraceError::Execution` if a system call cannot be executed
+pub fn apply_block_system_calls<DB: Database + DatabaseCommit>(
+    db: &mut DB,
+    block_env: &BlockEnv,
+    spec: SpecId,
+    parent_hash: B256,
+    parent_beacon_block_root: Option<B256>,
+) -> Result<(), TraceError> {
+    let mut cfg_env = CfgEnv::new();
+    cfg_env.spec = spec;
+    let mut evm = Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg_env)
+        .with_block(block_env.clone())
+        .build_mainnet();
+
+    if let Some(root) = parent_beacon_block_root.filter(|_| spec.is_enabled_in(SpecId::CANCUN)) {
+        evm.system_call_commit(BEACON_ROOTS_ADDRESS, root.into())
+            .map_err(ExecutionError::from)?;
+    }
+    if spec.is_enabled_in(SpecId::PRAGUE) {
+        evm.system_call_commit(HISTORY_STORAGE_ADDRESS, parent_hash.into())
+            .map_err(ExecutionError::from)?;
+    }
+    Ok(())
+}
+
+fn system_account(
+    prestate_tracer_result: &mut HashMap<Address, AccountDetails>,
+    address: Address,
+) -> &mut AccountDetails {
+    prestate_tracer_result.entry(address).or_insert(AccountDetails {
+        balance: None,
+        nonce: None,
+        code: None,
+        storage: None,
+    })
+}
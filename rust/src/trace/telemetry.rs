@@ -0,0 +1,52 @@
+//! Telemetry through the `tracing` crate, enabled with the `tracing` feature
+//!
+//! `trace_transaction_with_database` and its OP Stack counterpart run in a
+//! `trace_transaction` span. When the trace is done, the span records `setup_us` and
+//! `execution_us`, the time spent building the EVM and executing the transaction;
+//! `frames` and `logs`, how many the transaction produced, recorded or not; `db_misses`,
+//! the accounts and slots it read that the prestate did not contain; and `gas_used`.
+//! Building a database from a prestate runs in a `create_database` span, `RpcDatabase`
+//! emits a debug event for every account and slot it fetches from its node, and the call
+//! tracer emits one when a limit or cancellation stops execution.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use tracing::Span;
+
+/// Measures consecutive phases of a trace into fields of the current span. Durations are
+/// not measured in WebAssembly, which has no clock.
+pub(crate) struct PhaseTimer {
+    #[cfg(not(target_arch = "wasm32"))]
+    last: Instant,
+}
+
+impl PhaseTimer {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            last: Instant::now(),
+        }
+    }
+
+    /// Records the microseconds since the previous phase ended into `field`
+    pub(crate) fn lap(&mut self, field: &str) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let now = Instant::now();
+            Span::current().record(field, now.duration_since(self.last).as_micros() as u64);
+            self.last = now;
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = field;
+    }
+}
+
+/// Records what a finished trace produced into the current span
+pub(crate) fn record_trace(frames: u64, logs: u64, db_misses: usize, gas_used: u64) {
+    let span = Span::current();
+    span.record("frames", frames);
+    span.record("logs", logs);
+    span.record("db_misses", db_misses as u64);
+    span.record("gas_used", gas_used);
+}
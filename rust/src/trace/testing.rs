@@ -0,0 +1,151 @@
+//! Canned state for unit tests of code that processes traces (`testing` feature)
+//!
+//! These helpers build prestates without a node: funded accounts, a minimal ERC-20
+//! token, and the storage of a Uniswap V2 pair or an ERC-4337 EntryPoint v0.7 on top of
+//! their deployed code. Contracts compiled by the caller can be deployed into a database
+//! with `deploy_bytecode`.
+
+use revm::context::result::{ExecutionResult, Output};
+use revm::context::{BlockEnv, TxEnv};
+use revm::database::InMemoryDB;
+use revm::primitives::{address, bytes, keccak256, Address, Bytes, HashMap, StorageKey, StorageValue, TxKind, U256};
+use revm::DatabaseRef;
+
+use crate::trace::block::BlockEnvBuilder;
+use crate::trace::database::AccountDetails;
+use crate::trace::error::TraceError;
+use crate::trace::trace::execute_and_commit;
+
+/// Sender of the creation transactions of `deploy_bytecode`
+pub const TEST_DEPLOYER: Address = address!("00000000000000000000000000000000000d3910");
+
+/// Canonical address of the ERC-4337 EntryPoint v0.7
+pub const ENTRY_POINT_V07_ADDRESS: Address = address!("0000000071727De22E5E9d8BAf0edAc6f37da032");
+
+/// Runtime code of a minimal ERC-20 token with 18 decimals: `totalSupply`, `balanceOf`,
+/// `transfer`, `approve`, `allowance` and `transferFrom`, emitting `Transfer` and
+/// `Approval`. Storage follows OpenZeppelin's `ERC20`: balances in slot 0, allowances
+/// in slot 1 and the total supply in slot 2. An allowance of `type(uint256).max` is
+/// never spent. Failures revert without data.
+pub const ERC20_CODE: Bytes = bytes!("60003560e01c8063a9059cbb1461016f57806370a082311461006157806323b872dd14610191578063095ea7b3146100ed578063dd62ed3e1461009c57806318160ddd1461008d578063313ce56714610095575b600080fd5b60005260206000f35b60043573ffffffffffffffffffffffffffffffffffffffff166000526000602052604060002054610058565b600254610058565b6012610058565b60043573ffffffffffffffffffffffffffffffffffffffff1660243573ffffffffffffffffffffffffffffffffffffffff169060005260016020526040600020602052600052604060002054610058565b602435803360043573ffffffffffffffffffffffffffffffffffffffff16906000526001602052604060002060205260005260406000205560005260043573ffffffffffffffffffffffffffffffffffffffff16337f8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b92560206000a36001610058565b3360043573ffffffffffffffffffffffffffffffffffffffff1660243561021a565b60043573ffffffffffffffffffffffffffffffffffffffff16339060005260016020526040600020602052600052604060002080546044358119156101e05780821061005357900390556101e4565b5050505b60043573ffffffffffffffffffffffffffffffffffffffff1660243573ffffffffffffffffffffffffffffffffffffffff166044355b826000526000602052604060002080548083116100535782900390558160005260006020526040600020805482019055600052907fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef60206000a3600161005856");
+
+/// Block number of `test_block_env`
+pub const TEST_BLOCK_NUMBER: u64 = 20_000_000;
+
+/// Mainnet block environment after Prague with a zero base fee, so transactions with a
+/// zero gas price are valid
+pub fn test_block_env() -> BlockEnv {
+    BlockEnvBuilder::new(1)
+        .number(TEST_BLOCK_NUMBER)
+        .timestamp(1_750_000_000)
+        .basefee(0)
+        .build()
+        .expect("static test block is valid")
+}
+
+/// An externally owned account holding `balance`
+pub fn funded_account(balance: U256) -> AccountDetails {
+    AccountDetails {
+        balance: Some(balance),
+        nonce: Some(0),
+        code: None,
+        storage: None,
+    }
+}
+
+/// Storage slot of `key` in a Solidity `mapping(address => ...)` declared at `slot`
+pub fn mapping_slot(key: Address, slot: U256) -> StorageKey {
+    let mut preimage = [0u8; 64];
+    preimage[12..32].copy_from_slice(key.as_slice());
+    preimage[32..].copy_from_slice(&slot.to_be_bytes::<32>());
+    U256::from_be_bytes(keccak256(preimage).0)
+}
+
+/// Prestate with `ERC20_CODE` deployed at `token`, holding `balances`; the total
+/// supply is their sum
+pub fn erc20_prestate(token: Address, balances: &[(Address, U256)]) -> HashMap<Address, AccountDetails> {
+    let mut storage: HashMap<StorageKey, StorageValue> = balances.iter()
+        .map(|(holder, balance)| (mapping_slot(*holder, U256::ZERO), *balance))
+        .collect();
+    let total_supply = balances.iter().fold(U256::ZERO, |total, (_, balance)| total.saturating_add(*balance));
+    storage.insert(U256::from(2), total_supply);
+
+    let mut prestate = HashMap::default();
+    prestate.insert(token, AccountDetails {
+        balance: Some(U256::ZERO),
+        nonce: Some(1),
+        code: Some(ERC20_CODE),
+        storage: Some(storage),
+    });
+    prestate
+}
+
+/// A Uniswap V2 pair running `code`, the runtime code of `UniswapV2Pair` (e.g. read
+/// once with `eth_getCode`), trading `token0` for `token1` with the given reserves.
+/// The reentrancy lock is released; LP token balances and price accumulators are empty.
+/// The pair's token balances must be set in the tokens' own storage to match.
+pub fn uniswap_v2_pair_account(
+    code: Bytes,
+    token0: Address,
+    token1: Address,
+    reserve0: u128,
+    reserve1: u128,
+) -> AccountDetails {
+    const RESERVE_MASK: u128 = (1 << 112) - 1;
+    let reserves = U256::from(reserve0 & RESERVE_MASK) | (U256::from(reserve1 & RESERVE_MASK) << 112);
+
+    let mut storage = HashMap::default();
+    // token0, token1, reserves and blockTimestampLast, unlocked
+    storage.insert(U256::from(6), U256::from_be_slice(token0.as_slice()));
+    storage.insert(U256::from(7), U256::from_be_slice(token1.as_slice()));
+    storage.insert(U256::from(8), reserves);
+    storage.insert(U256::from(12), U256::from(1));
+
+    AccountDetails {
+        balance: Some(U256::ZERO),
+        nonce: Some(1),
+        code: Some(code),
+        storage: Some(storage),
+    }
+}
+
+/// An EntryPoint v0.7 running `code`, its runtime code, with `deposits` credited to
+/// accounts and paymasters. Its balance is the sum of the deposits.
+pub fn entry_point_account(code: Bytes, deposits: &[(Address, U256)]) -> AccountDetails {
+    // `deposit` is the first field of `DepositInfo` in `StakeManager.deposits`, slot 0
+    let storage = deposits.iter()
+        .map(|(account, deposit)| (mapping_slot(*account, U256::ZERO), *deposit))
+        .collect();
+    let balance = deposits.iter().fold(U256::ZERO, |total, (_, deposit)| total.saturating_add(*deposit));
+
+    AccountDetails {
+        balance: Some(balance),
+        nonce: Some(1),
+        code: Some(code),
+        storage: Some(storage),
+    }
+}
+
+/// Deploys a contract by executing `init_code` in a creation transaction from
+/// `TEST_DEPLOYER` on `test_block_env`, and returns its address
+///
+/// # Errors
+///
+/// Returns `TraceError::InvalidParameter` if the creation reverts or halts, or any other
+/// `TraceError` if the transaction cannot be executed
+pub fn deploy_bytecode(db: &mut InMemoryDB, init_code: Bytes) -> Result<Address, TraceError> {
+    let nonce = db.basic_ref(TEST_DEPLOYER).ok().flatten().map_or(0, |info| info.nonce);
+    let tx = TxEnv::builder()
+        .caller(TEST_DEPLOYER)
+        .kind(TxKind::Create)
+        .nonce(nonce)
+        .gas_limit(test_block_env().gas_limit)
+        .gas_price(0)
+        .data(init_code)
+        .build()?;
+
+    match execute_and_commit(tx, &test_block_env(), db, None)? {
+        ExecutionResult::Success { output: Output::Create(_, Some(address)), .. } => Ok(address),
+        result => Err(TraceError::InvalidParameter(format!("deployment failed: {:?}", result))),
+    }
+}
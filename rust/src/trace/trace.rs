@@ -2,23 +2,33 @@ use revm::context::result::{ExecutionResult, HaltReason};
 use revm::context::BlockEnv;
 use revm::context::CfgEnv;
 use revm::context::JournalTr;
+use revm::context_interface::transaction::{AccessList, SignedAuthorization, TransactionType};
 use revm::handler::instructions::EthInstructions;
 use revm::handler::EthPrecompiles;
+use revm::interpreter::gas::calculate_initial_tx_gas;
+use revm::primitives::eip4844::GAS_PER_BLOB;
+use revm::primitives::hardfork::SpecId;
 use revm::primitives::HashMap;
 use revm::primitives::TxKind;
-use revm::{ExecuteEvm, MainnetEvm};
+use revm::primitives::keccak256;
+use revm::state::EvmState;
+use revm::{Database, DatabaseCommit, DatabaseRef, ExecuteEvm, MainBuilder, MainnetEvm};
 use revm::InspectEvm;
 
 use serde::{Serialize, Deserialize};
 
 use revm::{
-    context::TxEnv,
+    context::{tx::TxEnvBuilder, TxEnv},
+    database::{CacheDB, InMemoryDB},
     primitives::{Address, Bytes, B256, U256},
     Context,
     MainContext,
 };
 
 // Optimism-specific imports
+#[cfg(feature = "op")]
+use op_revm::transaction::deposit::DEPOSIT_TRANSACTION_TYPE;
+#[cfg(feature = "op")]
 use op_revm::{
     L1BlockInfo,
     OpContext,
@@ -27,13 +37,28 @@ use op_revm::{
     OpTransaction,
     OpHaltReason,
 };
+#[cfg(feature = "op")]
 use revm::context::LocalContext;
+#[cfg(feature = "op")]
 use revm::Journal;
 
+use crate::trace::abi::{AbiRegistry, DecodedParam};
+use crate::trace::block::{create_block_env_from_block_details, BlockDetails};
 use crate::trace::database::create_in_memory_database_from_prestate_trace;
-use crate::trace::database::AccountDetails;
-use crate::trace::inspector::{CallFrame, CallTracer};
-use crate::trace::error::TraceError;
+use crate::trace::database::{apply_state_diff, apply_state_overrides, AccountDetails, AccountOverride, PrestateKeys, PrestateWarning, SharedPrestate};
+#[cfg(feature = "rpc")]
+use crate::trace::database::RpcDatabase;
+use crate::trace::labels::LabeledStorageChange;
+use crate::trace::transfers::ValueTransfer;
+use crate::trace::flat::FlatFrame;
+use crate::trace::quantity;
+use crate::trace::inspector::{CallFrame, CallTracer, CallTracerConfig, MultiInspector, OpcodeTracer, OpcodeTracerConfig, PrestateTracer, StructLog, TraceMemoryStats, TruncationReason};
+use crate::trace::error::{ExecutionError, FeeValidationError, TraceError};
+use crate::trace::arbitrum::ArbitrumDetails;
+use crate::trace::revert::{decode_revert_reason, DecodedRevert};
+#[cfg(feature = "tracing")]
+use crate::trace::telemetry::{record_trace, PhaseTimer};
+use crate::trace::state::{accessed_state, balance_changes, build_diff_mode, AccessedState, BalanceChange, DiffModeState};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -41,22 +66,494 @@ pub struct TraceTransactionResult<T> {
     pub execution_result: ExecutionResult<T>,
     pub state_diff: HashMap<Address, revm::state::Account>,
     pub calls: CallFrame,
+    /// Accounts/slots that were accessed but missing from the prestate
+    #[serde(default)]
+    pub warnings: Vec<PrestateWarning>,
+    /// Blob gas consumed by an EIP-4844 transaction, zero otherwise
+    #[serde(default, with = "quantity")]
+    pub blob_gas_used: u64,
+    /// How the gas used splits up and what it cost
+    #[serde(default)]
+    pub gas: GasBreakdown,
+    /// Decoded revert data of the transaction, if it reverted with any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert: Option<DecodedRevert>,
+    /// ETH balance changes of every account whose balance changed, fees included
+    #[serde(default)]
+    pub balance_changes: HashMap<Address, BalanceChange>,
+    /// Changed state as `{pre, post}`, compatible with geth's prestateTracer `diffMode`
+    #[serde(default)]
+    pub diff_mode: DiffModeState,
+    /// Accounts, code hashes and storage slots read during execution
+    #[serde(default)]
+    pub accessed_state: AccessedState,
+    /// Full cost of an OP Stack transaction, L1 data and operator fees included; `None`
+    /// for Ethereum traces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub op_fees: Option<OpFees>,
+    /// L1 poster gas and ArbOS precompile calls of an Arbitrum trace; `None` elsewhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arbitrum: Option<ArbitrumDetails>,
+    /// Whether execution was stopped by `CallTracerConfig::limits`; the trace then only
+    /// covers execution up to that point
+    #[serde(default)]
+    pub truncated: bool,
+    /// The limit that stopped execution, if `truncated`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncation_reason: Option<TruncationReason>,
+    /// Whether `apply_output_limits` cut data or logs from the call tree
+    #[serde(default)]
+    pub output_truncated: bool,
+    /// Changed storage slots named after their variables, if
+    /// `CallTracerConfig::storage_layout` or `CallTracerConfig::slot_labels` was given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub storage_changes: Vec<LabeledStorageChange>,
+    /// Every ETH transfer of the call tree, if `CallTracerConfig::with_value_transfers`
+    /// was set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub value_transfers: Vec<ValueTransfer>,
+    /// Call frames as a flat list, if a `CallLayout` other than `Tree` was applied
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flat_calls: Vec<FlatFrame>,
+    /// Memory the call tracer held for the trace, see `TraceMemoryStats`
+    #[serde(default)]
+    pub memory: TraceMemoryStats,
+    /// Return values of the top-level call decoded with an `AbiRegistry`, i.e. what an
+    /// `eth_call` of the transaction returns; only set if the call succeeded and the
+    /// registry knows its function
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded_output: Option<Vec<DecodedParam>>,
+}
+
+impl<T> TraceTransactionResult<T> {
+    /// The `n` frames whose own code spent the most gas, most expensive first, for
+    /// finding hot spots; see `CallFrame::self_gas_used`
+    pub fn top_gas_consumers(&self, n: usize) -> Vec<GasConsumer<'_>> {
+        let mut consumers: Vec<GasConsumer<'_>> = self.frames()
+            .map(|entry| GasConsumer { frame_path: entry.frame_path, frame: entry.frame })
+            .collect();
+        // Stable, so frames spending the same gas stay in execution order
+        consumers.sort_by_key(|consumer| std::cmp::Reverse(consumer.frame.self_gas_used));
+        consumers.truncate(n);
+        consumers
+    }
+}
+
+/// A frame of the call tree and where it sits, as returned by `top_gas_consumers`
+#[derive(Debug, Clone)]
+pub struct GasConsumer<'a> {
+    /// Index of each subcall from the top-level frame down, empty for the top-level frame
+    pub frame_path: Vec<usize>,
+    pub frame: &'a CallFrame,
+}
+
+/// Gas accounting of an executed transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasBreakdown {
+    /// Gas charged before execution: base cost, calldata, access list and authorizations
+    #[serde(with = "quantity")]
+    pub intrinsic: u64,
+    /// Gas spent executing, before refunds
+    #[serde(with = "quantity")]
+    pub execution: u64,
+    /// Gas refunded at the end of the transaction, e.g. for clearing storage; zero when
+    /// the floor is charged
+    #[serde(with = "quantity")]
+    pub refund: u64,
+    /// EIP-7623 calldata floor, zero before Prague
+    #[serde(with = "quantity")]
+    pub floor: u64,
+    /// Gas actually charged, i.e. `intrinsic + execution - refund` or `floor` if that
+    /// is higher
+    #[serde(with = "quantity")]
+    pub total_used: u64,
+    /// Price per gas paid by the sender
+    #[serde(with = "quantity")]
+    pub effective_gas_price: u128,
+    /// `total_used * effective_gas_price`; excludes blob fees and the OP Stack L1 fee
+    #[serde(with = "quantity")]
+    pub fee_paid: U256,
+}
+
+/// Fees charged to the sender of an OP Stack transaction, in wei. Deposits pay none.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpFees {
+    /// Cost of posting the transaction data to L1, from the L1Block predeploy values
+    pub l1_data_fee: U256,
+    /// `gas.fee_paid`: gas used times the effective gas price
+    pub l2_execution_fee: U256,
+    /// Isthmus operator fee, `gas_used * operator_fee_scalar / 1e6 + operator_fee_constant`
+    pub operator_fee: U256,
+    pub total: U256,
+}
+
+/// Traces of every transaction in a block plus the block's combined state changes
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockTraceResult {
+    pub transactions: Vec<TraceTransactionResult<HaltReason>>,
+    /// State changes of the whole block; `original_value` of each slot is its value
+    /// before the first transaction, `present_value` its value after the last
+    pub state_diff: EvmState,
+}
+
+/// Opcode-level trace in the format of geth's default `debug_traceTransaction` tracer
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpcodeTraceResult {
+    pub gas: u64,
+    pub failed: bool,
+    pub return_value: Bytes,
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// Tracers to run in a single execution with `trace_transaction_multi`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TracerSelection {
+    /// Run the call tracer with these options
+    pub call_tracer: Option<CallTracerConfig>,
+    /// Run the prestate tracer
+    pub prestate_tracer: bool,
+    /// Run the opcode logger with these options
+    pub opcode_tracer: Option<OpcodeTracerConfig>,
+}
+
+/// Outputs of the tracers of a `TracerSelection`, all from the same execution
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiTraceResult {
+    pub execution_result: ExecutionResult<HaltReason>,
+    pub state_diff: HashMap<Address, revm::state::Account>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub calls: Option<CallFrame>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prestate: Option<HashMap<Address, AccountDetails>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub struct_logs: Option<Vec<StructLog>>,
+    /// Whether the call tracer's limits stopped execution
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Parameters describing a single transaction to trace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxParams {
+    pub chain_id: u64,
+    pub from: Address,
+    pub from_nonce: u64,
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub gas_limit: u64,
+    /// Gas price, i.e. `maxFeePerGas` for EIP-1559 transactions. Either name is
+    /// accepted, giving both is rejected as a duplicate field.
+    #[serde(alias = "maxFeePerGas")]
+    pub gas_price: u128,
+    /// Priority fee, i.e. `maxPriorityFeePerGas` for EIP-1559 transactions, under
+    /// either name but not both
+    #[serde(alias = "maxPriorityFeePerGas")]
+    pub gas_priority_fee: u128,
+    /// Fee semantics of the transaction; defaults to EIP-1559
+    #[serde(default)]
+    pub tx_spec: TxSpec,
+    /// EIP-2930 access list; addresses and slots listed here start warm
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    /// EIP-4844 versioned hashes; a non-empty list makes this a blob transaction (type 3)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blob_versioned_hashes: Vec<B256>,
+    /// `maxFeePerBlobGas` of a blob transaction
+    #[serde(default)]
+    pub max_fee_per_blob_gas: u128,
+    /// EIP-7702 authorizations; a non-empty list makes this a set-code transaction (type 4)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authorization_list: Vec<SignedAuthorization>,
+    /// Deposit fields; when set the transaction is executed as an OP Stack deposit (type 0x7E)
+    /// by `trace_transaction_op`. Deposits pay no L2 fees and their nonce is not checked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deposit: Option<DepositParams>,
+    /// Hardfork to execute under; `None` uses revm's default spec. See `spec_for_block`
+    /// for historical blocks. Ignored by the Optimism tracer, which takes an `OpSpecId`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<SpecId>,
+}
+
+/// Transaction type, selecting how gas price fields are interpreted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TxSpec {
+    /// Type 0: `gas_price` is paid in full and `gas_priority_fee` is ignored
+    Legacy,
+    /// Type 2: `gas_price` is `maxFeePerGas` and `gas_priority_fee` is `maxPriorityFeePerGas`
+    #[default]
+    Eip1559,
+}
+
+/// Options of `trace_transaction` and `trace_transaction_op` besides the transaction itself
+#[derive(Debug, Clone, Default)]
+pub struct TraceTransactionOptions<'a> {
+    /// Account overrides merged over the prestate, as in `debug_traceCall`
+    pub state_overrides: HashMap<Address, AccountOverride>,
+    /// Call tracer options (subcalls, logs, depth and frame limits)
+    pub tracer_config: CallTracerConfig,
+    /// ABIs used to decode call inputs, outputs and logs
+    pub abi_registry: Option<&'a AbiRegistry>,
+}
+
+/// OP Stack deposit transaction fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepositParams {
+    /// Identifier of the L1 event that triggered the deposit
+    pub source_hash: B256,
+    /// ETH minted on L2 before execution
+    pub mint: u128,
+    pub is_system_transaction: bool,
+}
+
+impl TxParams {
+    /// `maxFeePerGas` of the transaction (alias of `gas_price`)
+    pub fn max_fee_per_gas(&self) -> u128 {
+        self.gas_price
+    }
+
+    /// `maxPriorityFeePerGas` of the transaction (alias of `gas_priority_fee`)
+    pub fn max_priority_fee_per_gas(&self) -> u128 {
+        self.gas_priority_fee
+    }
+
+    /// Whether this is an EIP-4844 blob transaction
+    pub fn is_blob_transaction(&self) -> bool {
+        !self.blob_versioned_hashes.is_empty()
+    }
+
+    /// Blob gas consumed by the transaction's blobs
+    pub fn blob_gas_used(&self) -> u64 {
+        self.blob_versioned_hashes.len() as u64 * GAS_PER_BLOB
+    }
+
+    /// Price per gas paid by the sender at the given block base fee
+    pub fn effective_gas_price(&self, basefee: u64) -> u128 {
+        // Deposits are paid for on L1
+        if self.deposit.is_some() {
+            return 0;
+        }
+        match self.tx_spec {
+            TxSpec::Legacy if !self.is_blob_transaction() => self.gas_price,
+            _ => self.max_fee_per_gas()
+                .min(u128::from(basefee).saturating_add(self.max_priority_fee_per_gas())),
+        }
+    }
+
+    /// Destination of the transaction; `TxParams` always describe a call
+    pub fn kind(&self) -> TxKind {
+        TxKind::Call(self.to)
+    }
+
+    /// Splits the gas used by `execution_result` into intrinsic, execution and refund.
+    ///
+    /// `execution` is the gas spent by the top-level frame: once the EIP-7623 floor is
+    /// charged the result no longer tells it apart from the intrinsic cost.
+    pub fn gas_breakdown<H>(
+        &self,
+        execution_result: &ExecutionResult<H>,
+        execution: u64,
+        spec: SpecId,
+        basefee: u64,
+    ) -> GasBreakdown {
+        let (access_list_accounts, access_list_storages) = self.access_list.as_ref()
+            .map(|access_list| (
+                access_list.0.len() as u64,
+                access_list.0.iter().map(|item| item.storage_keys.len() as u64).sum(),
+            ))
+            .unwrap_or_default();
+        let initial_gas = calculate_initial_tx_gas(
+            spec,
+            &self.data,
+            self.kind().is_create(),
+            access_list_accounts,
+            access_list_storages,
+            self.authorization_list.len() as u64,
+        );
+
+        let refund = match execution_result {
+            ExecutionResult::Success { gas_refunded, .. } => *gas_refunded,
+            _ => 0,
+        };
+        let total_used = execution_result.gas_used();
+        let effective_gas_price = self.effective_gas_price(basefee);
+
+        GasBreakdown {
+            intrinsic: initial_gas.initial_gas,
+            execution,
+            refund,
+            floor: initial_gas.floor_gas,
+            total_used,
+            effective_gas_price,
+            fee_paid: U256::from(total_used) * U256::from(effective_gas_price),
+        }
+    }
+
+    /// Checks the EIP-1559 fee caps against each other and the block base fee, so
+    /// violations surface as typed errors instead of opaque EVM failures.
+    /// Legacy transactions only need their gas price to cover the base fee.
+    pub fn validate_fee_caps(&self, basefee: u64) -> Result<(), FeeValidationError> {
+        if self.tx_spec == TxSpec::Eip1559 && self.max_priority_fee_per_gas() > self.max_fee_per_gas() {
+            return Err(FeeValidationError::PriorityFeeAboveMaxFee {
+                max_fee_per_gas: self.max_fee_per_gas(),
+                max_priority_fee_per_gas: self.max_priority_fee_per_gas(),
+            });
+        }
+        if self.max_fee_per_gas() < u128::from(basefee) {
+            return Err(FeeValidationError::MaxFeeBelowBaseFee {
+                max_fee_per_gas: self.max_fee_per_gas(),
+                basefee,
+            });
+        }
+        Ok(())
+    }
+
+    /// Builds the base transaction environment shared by the Ethereum and Optimism tracers
+    fn tx_env_builder(&self) -> TxEnvBuilder {
+        let builder = TxEnv::builder()
+            .chain_id(Some(self.chain_id))
+            .caller(self.from)
+            .kind(self.kind())
+            .value(self.value)
+            .nonce(self.from_nonce)
+            .gas_limit(self.gas_limit)
+            .gas_price(self.gas_price)
+            .data(self.data.clone());
+        let builder = match &self.access_list {
+            Some(access_list) => builder.access_list(access_list.clone()),
+            None => builder,
+        };
+
+        if self.is_blob_transaction() {
+            return builder
+                .tx_type(Some(TransactionType::Eip4844 as u8))
+                .gas_priority_fee(Some(self.gas_priority_fee))
+                .blob_hashes(self.blob_versioned_hashes.clone())
+                .max_fee_per_blob_gas(self.max_fee_per_blob_gas);
+        }
+        if !self.authorization_list.is_empty() {
+            return builder
+                .tx_type(Some(TransactionType::Eip7702 as u8))
+                .gas_priority_fee(Some(self.gas_priority_fee))
+                .authorization_list_signed(self.authorization_list.clone());
+        }
+
+        match self.tx_spec {
+            // A legacy-priced transaction carrying an access list is type 1
+            TxSpec::Legacy if self.access_list.is_some() => builder
+                .tx_type(Some(TransactionType::Eip2930 as u8))
+                .gas_priority_fee(None),
+            TxSpec::Legacy => builder
+                .tx_type(Some(TransactionType::Legacy as u8))
+                .gas_priority_fee(None),
+            TxSpec::Eip1559 => builder
+                .tx_type(Some(TransactionType::Eip1559 as u8))
+                .gas_priority_fee(Some(self.gas_priority_fee)),
+        }
+    }
+
+    /// Encodes the transaction as it would be posted to L1, with a placeholder signature
+    /// of realistic size and entropy, for L1 data fees of rollups.
+    pub(crate) fn enveloped_tx(&self) -> Bytes {
+        let signature_r = keccak256(b"signature r");
+        let signature_s = keccak256(b"signature s");
+        let mut fields = Vec::new();
+        let typed = self.tx_spec == TxSpec::Eip1559 || self.access_list.is_some();
+        if typed {
+            rlp_uint(&mut fields, U256::from(self.chain_id));
+        }
+        rlp_uint(&mut fields, U256::from(self.from_nonce));
+        match self.tx_spec {
+            TxSpec::Eip1559 => {
+                rlp_uint(&mut fields, U256::from(self.max_priority_fee_per_gas()));
+                rlp_uint(&mut fields, U256::from(self.max_fee_per_gas()));
+            }
+            TxSpec::Legacy => rlp_uint(&mut fields, U256::from(self.gas_price)),
+        }
+        rlp_uint(&mut fields, U256::from(self.gas_limit));
+        rlp_bytes(&mut fields, self.to.as_slice());
+        rlp_uint(&mut fields, self.value);
+        rlp_bytes(&mut fields, &self.data);
+        if typed {
+            let mut items = Vec::new();
+            for item in self.access_list.iter().flat_map(|access_list| access_list.0.iter()) {
+                let mut item_fields = Vec::new();
+                rlp_bytes(&mut item_fields, item.address.as_slice());
+                let mut keys = Vec::new();
+                for key in &item.storage_keys {
+                    rlp_bytes(&mut keys, key.as_slice());
+                }
+                rlp_list(&mut item_fields, &keys);
+                rlp_list(&mut items, &item_fields);
+            }
+            rlp_list(&mut fields, &items);
+            rlp_uint(&mut fields, U256::from(1));
+        } else {
+            // EIP-155 `v`
+            rlp_uint(&mut fields, U256::from(self.chain_id) * U256::from(2) + U256::from(35));
+        }
+        rlp_bytes(&mut fields, signature_r.as_slice());
+        rlp_bytes(&mut fields, signature_s.as_slice());
+
+        let mut envelope = Vec::new();
+        if typed {
+            envelope.push(match self.tx_spec {
+                TxSpec::Eip1559 => TransactionType::Eip1559 as u8,
+                TxSpec::Legacy => TransactionType::Eip2930 as u8,
+            });
+        }
+        rlp_list(&mut envelope, &fields);
+        envelope.into()
+    }
+}
+
+fn rlp_header(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let len_bytes = &len_bytes[len_bytes.iter().take_while(|byte| **byte == 0).count()..];
+        out.push(offset + 55 + len_bytes.len() as u8);
+        out.extend_from_slice(len_bytes);
+    }
+}
+
+fn rlp_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    if let [byte @ 0..=0x7f] = data {
+        out.push(*byte);
+    } else {
+        rlp_header(out, 0x80, data.len());
+        out.extend_from_slice(data);
+    }
+}
+
+fn rlp_uint(out: &mut Vec<u8>, value: U256) {
+    rlp_bytes(out, &value.to_be_bytes_trimmed_vec());
+}
+
+fn rlp_list(out: &mut Vec<u8>, payload: &[u8]) {
+    rlp_header(out, 0xc0, payload.len());
+    out.extend_from_slice(payload);
 }
 
 /// Trace a transaction execution with detailed call information
 ///
 /// # Arguments
 ///
-/// * `chain_id` - The chain ID for the transaction
-/// * `from` - The sender address
-/// * `from_nonce` - The sender's nonce
-/// * `to` - The recipient address
-/// * `data` - The transaction calldata
-/// * `gas_limit` - Maximum gas allowed for execution
-/// * `gas_price` - Gas price in wei
-/// * `gas_priority_fee` - Priority fee in wei
+/// * `params` - The transaction, including its value, blob fields and the hardfork to
+///   execute under (`TxParams::spec`, e.g. from `spec_for_block`)
 /// * `latest_block_env` - Block environment for execution
 /// * `prestate_tracer_result` - Account states before execution
+/// * `options` - State overrides, call tracer options and ABI registry
 ///
 /// # Returns
 ///
@@ -69,37 +566,164 @@ pub struct TraceTransactionResult<T> {
 /// - Transaction execution fails
 /// - No trace result is available from the inspector
 pub fn trace_transaction(
-    chain_id: u64,
-    from: Address,
-    from_nonce: u64,
-    to: Address,
-    data: Bytes,
-    gas_limit: u64,
-    gas_price: u128,
-    gas_priority_fee: u128,
+    params: &TxParams,
     latest_block_env: BlockEnv,
-    prestate_tracer_result: HashMap<Address, AccountDetails>
+    mut prestate_tracer_result: HashMap<Address, AccountDetails>,
+    options: TraceTransactionOptions,
 ) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
-    // Build transaction environment - errors are automatically converted via From trait
-    let tx = TxEnv::builder()
-        .chain_id(Some(chain_id))
-        .caller(from)
-        .kind(TxKind::Call(to))
-        .nonce(from_nonce)
-        .gas_limit(gas_limit)
-        .gas_price(gas_price)
-        .gas_priority_fee(Some(gas_priority_fee))
-        .data(data)
-        .build()?;
-
-    let inspector = CallTracer::new();
-
-    // Create in-memory database from prestate
+    // Create in-memory database from the overridden prestate
+    apply_state_overrides(&mut prestate_tracer_result, options.state_overrides)?;
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
     let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
 
+    let mut result = trace_transaction_with_database(params, latest_block_env, db, &prestate_keys, &options.tracer_config)?;
+    if let Some(abi_registry) = options.abi_registry {
+        abi_registry.decode_result(&mut result);
+    }
+    Ok(result)
+}
+
+/// Decodes the revert data of a reverted execution
+fn revert_of<H>(execution_result: &ExecutionResult<H>) -> Option<DecodedRevert> {
+    match execution_result {
+        ExecutionResult::Revert { output, .. } => decode_revert_reason(output),
+        _ => None,
+    }
+}
+
+/// Validates `params` and builds the transaction and EVM configuration for mainnet execution
+pub(crate) fn prepare_mainnet_execution(
+    params: &TxParams,
+    latest_block_env: &BlockEnv,
+) -> Result<(TxEnv, CfgEnv), TraceError> {
+    if params.deposit.is_some() {
+        return Err(TraceError::InvalidParameter(
+            "deposit transactions are only supported on OP Stack chains".to_string()
+        ));
+    }
+    if params.is_blob_transaction() && params.tx_spec == TxSpec::Legacy {
+        return Err(TraceError::InvalidParameter(
+            "blob transactions require EIP-1559 fee semantics".to_string()
+        ));
+    }
+    if !params.authorization_list.is_empty() && params.tx_spec == TxSpec::Legacy {
+        return Err(TraceError::InvalidParameter(
+            "set-code transactions require EIP-1559 fee semantics".to_string()
+        ));
+    }
+    params.validate_fee_caps(latest_block_env.basefee)?;
+
+    // Build transaction environment - errors are automatically converted via From trait
+    let tx = params.tx_env_builder().build()?;
+
     // Configure EVM with chain settings
-    let mut cfg_env = CfgEnv::new().with_chain_id(chain_id);
+    let mut cfg_env = CfgEnv::new().with_chain_id(params.chain_id);
     cfg_env.disable_eip3607 = true;
+    if let Some(spec) = params.spec {
+        cfg_env.spec = spec;
+    }
+
+    Ok((tx, cfg_env))
+}
+
+/// Execute a transaction against an already constructed database without any inspector.
+///
+/// Returns the execution result and the resulting state, exactly as a node would
+/// compute them. Used to cross-check that tracing does not alter execution.
+pub fn execute_transaction_with_database(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    db: InMemoryDB,
+) -> Result<(ExecutionResult<HaltReason>, EvmState), TraceError> {
+    let (tx, cfg_env) = prepare_mainnet_execution(params, &latest_block_env)?;
+
+    let mut my_evm = Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env)
+        .build_mainnet();
+
+    let execution_result = my_evm.transact_one(tx)
+        .map_err(ExecutionError::from)?;
+    let state = my_evm.finalize();
+
+    Ok((execution_result, state))
+}
+
+/// Execute a transaction without any inspector and commit its state changes to `db`.
+///
+/// Used to bring a database to the state a later transaction sees, e.g. by replaying
+/// the transactions preceding it in its block. `spec` selects the hardfork as
+/// `TxParams::spec` does.
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction is invalid or a database read fails
+pub fn execute_and_commit<DB: Database + DatabaseCommit>(
+    tx: TxEnv,
+    latest_block_env: &BlockEnv,
+    db: &mut DB,
+    spec: Option<SpecId>,
+) -> Result<ExecutionResult<HaltReason>, TraceError> {
+    let mut cfg_env = CfgEnv::new();
+    if let Some(chain_id) = tx.chain_id {
+        cfg_env = cfg_env.with_chain_id(chain_id);
+    }
+    if let Some(spec) = spec {
+        cfg_env.spec = spec;
+    }
+
+    let mut my_evm = Context::mainnet()
+        .with_db(&mut *db)
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env.clone())
+        .build_mainnet();
+
+    let execution_result = my_evm.transact_one(tx)
+        .map_err(ExecutionError::from)?;
+    let state = my_evm.finalize();
+    drop(my_evm);
+
+    db.commit(state);
+    Ok(execution_result)
+}
+
+/// Trace a transaction against an already constructed database.
+///
+/// This lets callers that execute the same transaction many times (e.g. sweeps) build
+/// the database from the prestate once and hand each run a cheap clone, or an overlay of
+/// a `SharedPrestate`.
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    name = "trace_transaction",
+    skip_all,
+    fields(
+        chain_id = params.chain_id,
+        from = %params.from,
+        to = %params.to,
+        gas_limit = params.gas_limit,
+        setup_us = tracing::field::Empty,
+        execution_us = tracing::field::Empty,
+        frames = tracing::field::Empty,
+        logs = tracing::field::Empty,
+        db_misses = tracing::field::Empty,
+        gas_used = tracing::field::Empty,
+    ),
+))]
+pub fn trace_transaction_with_database<DB: Database + DatabaseRef>(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    db: DB,
+    prestate_keys: &PrestateKeys,
+    tracer_config: &CallTracerConfig,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    #[cfg(feature = "tracing")]
+    let mut timer = PhaseTimer::start();
+    let (tx, cfg_env) = prepare_mainnet_execution(params, &latest_block_env)?;
+    let spec = cfg_env.spec;
+    let basefee = latest_block_env.basefee;
+    let beneficiary = latest_block_env.beneficiary;
+
+    let inspector = CallTracer::new_with_config(tracer_config.clone());
 
     // Setup execution context
     let context = Context::mainnet()
@@ -113,25 +737,341 @@ pub fn trace_transaction(
         EthInstructions::new_mainnet(),
         EthPrecompiles::default()
     );
+    #[cfg(feature = "tracing")]
+    timer.lap("setup_us");
 
     // Execute transaction and collect trace
     let execution_result = my_evm.inspect_one_tx(tx)
-        .map_err(|e| TraceError::Execution(e.to_string()))?;
+        .map_err(ExecutionError::from)?;
+    #[cfg(feature = "tracing")]
+    timer.lap("execution_us");
 
     // Get state changes from the EVM context
     let state_diff = my_evm.ctx.journaled_state.state.clone();
+    let balance_changes = balance_changes(&my_evm.ctx.journaled_state.database, &state_diff);
+    let diff_mode = build_diff_mode(&my_evm.ctx.journaled_state.database, &state_diff);
+    let accessed_state = accessed_state(&my_evm.ctx.journaled_state.database, &state_diff);
 
-    let inspector = my_evm.inspector;
-    let calls = inspector.into_result()
+    let mut inspector = my_evm.inspector;
+    if inspector.is_cancelled() {
+        return Err(TraceError::Cancelled(inspector.into_result().map(Box::new)));
+    }
+    let truncation_reason = inspector.truncation_reason();
+    let memory = inspector.memory_stats();
+    #[cfg(feature = "tracing")]
+    let (frames, logs) = (inspector.frames_entered(), inspector.logs_emitted());
+    let storage_labeler = inspector.take_storage_labeler();
+    let value_transfers = inspector.take_value_transfers();
+    let mut calls = inspector.into_result()
         .ok_or(TraceError::NoTraceResult)?;
+    let storage_changes = storage_labeler.map(|labeler| {
+        labeler.label_frames(&mut calls);
+        labeler.label_changes(&state_diff)
+    }).unwrap_or_default();
+
+    let warnings = prestate_keys.warnings(&state_diff, beneficiary);
+    if tracer_config.strict_prestate && !warnings.is_empty() {
+        return Err(TraceError::IncompletePrestate(warnings));
+    }
+    #[cfg(feature = "tracing")]
+    record_trace(frames, logs, warnings.len(), execution_result.gas_used());
+    let blob_gas_used = params.blob_gas_used();
+    let revert = revert_of(&execution_result);
+    let gas = params.gas_breakdown(&execution_result, calls.gas_used.saturating_to(), spec, basefee);
 
     Ok(TraceTransactionResult {
         execution_result,
         state_diff,
-        calls
+        calls,
+        warnings,
+        blob_gas_used,
+        gas,
+        revert,
+        balance_changes,
+        diff_mode,
+        accessed_state,
+        op_fees: None,
+        arbitrum: None,
+        truncated: truncation_reason.is_some(),
+        truncation_reason,
+        output_truncated: false,
+        storage_changes,
+        value_transfers,
+        flat_calls: Vec::new(),
+        memory,
+        decoded_output: None,
+    })
+}
+
+/// Produce the prestate of a transaction, i.e. every account and storage slot it touches
+/// with its value before execution, in the same format as geth's `prestateTracer`.
+///
+/// `db` can be any `Database`, typically a cache in front of a remote node. The result
+/// can be passed straight to `trace_transaction` as `prestate_tracer_result`.
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be executed or a database read fails
+pub fn trace_prestate<DB: Database>(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    db: DB,
+) -> Result<HashMap<Address, AccountDetails>, TraceError> {
+    let (tx, cfg_env) = prepare_mainnet_execution(params, &latest_block_env)?;
+
+    let context = Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env);
+
+    let mut my_evm = MainnetEvm::new_with_inspector(
+        context,
+        PrestateTracer::new(),
+        EthInstructions::new_mainnet(),
+        EthPrecompiles::default()
+    );
+
+    my_evm.inspect_one_tx(tx)
+        .map_err(ExecutionError::from)?;
+
+    my_evm.inspector.into_result()
+}
+
+/// Trace a transaction against a live node without a prestate.
+///
+/// State is fetched lazily over JSON-RPC at `block` (a tag such as `"latest"` or a
+/// hex-encoded number), and the transaction is executed on top of it with that block's
+/// environment, as `eth_call` would. Only the accounts and slots the transaction touches
+/// are requested.
+///
+/// # Arguments
+///
+/// * `rpc_url` - JSON-RPC endpoint serving `eth_getBalance`, `eth_getCode`,
+///   `eth_getStorageAt`, `eth_getTransactionCount` and `eth_getBlockByNumber`
+/// * `block` - Block whose state and environment are used
+/// * `params` - The transaction to trace
+/// * `options` - State overrides, call tracer options and ABI registry
+///
+/// # Errors
+///
+/// Returns `TraceError` if a request to the node fails or the transaction cannot be
+/// built or executed
+#[cfg(feature = "rpc")]
+pub fn trace_transaction_rpc(
+    rpc_url: &str,
+    block: &str,
+    params: &TxParams,
+    options: TraceTransactionOptions,
+) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+    let rpc_db = RpcDatabase::new(rpc_url, block)?;
+    let latest_block_env = create_block_env_from_block_details(rpc_db.block_details()?)?;
+
+    // Discover what the transaction touches, then trace it against exactly that state
+    let prestate = trace_prestate(params, latest_block_env.clone(), rpc_db)?;
+    trace_transaction(params, latest_block_env, prestate, options)
+}
+
+/// Trace a transaction opcode by opcode, returning geth-style `structLogs`
+///
+/// # Arguments
+///
+/// * `params` - The transaction to trace
+/// * `latest_block_env` - Block environment for execution
+/// * `prestate_tracer_result` - Account states before execution
+/// * `config` - Which stack, memory and storage snapshots to capture per step
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be built or executed
+pub fn trace_transaction_with_opcodes(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    config: OpcodeTracerConfig,
+) -> Result<OpcodeTraceResult, TraceError> {
+    let (tx, cfg_env) = prepare_mainnet_execution(params, &latest_block_env)?;
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+
+    let context = Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env);
+
+    let mut my_evm = MainnetEvm::new_with_inspector(
+        context,
+        OpcodeTracer::new(config),
+        EthInstructions::new_mainnet(),
+        EthPrecompiles::default()
+    );
+
+    let execution_result = my_evm.inspect_one_tx(tx)
+        .map_err(ExecutionError::from)?;
+
+    Ok(OpcodeTraceResult {
+        gas: execution_result.gas_used(),
+        failed: !execution_result.is_success(),
+        return_value: execution_result.output().cloned().unwrap_or_default(),
+        struct_logs: my_evm.inspector.into_result(),
+    })
+}
+
+/// Run several tracers over one execution of a transaction
+///
+/// Produces the same call tree, prestate and struct logs as `trace_transaction`,
+/// `trace_prestate` and `trace_transaction_with_opcodes`, without executing the
+/// transaction once per tracer.
+///
+/// # Arguments
+///
+/// * `params` - The transaction to trace
+/// * `latest_block_env` - Block environment for execution
+/// * `prestate_tracer_result` - Account states before execution
+/// * `selection` - Which tracers to run and their options
+///
+/// # Errors
+///
+/// Returns `TraceError` if the transaction cannot be built or executed, if a prestate
+/// read fails, or `TraceError::Cancelled` if the call tracer was cancelled
+pub fn trace_transaction_multi(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    selection: &TracerSelection,
+) -> Result<MultiTraceResult, TraceError> {
+    let (tx, cfg_env) = prepare_mainnet_execution(params, &latest_block_env)?;
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+
+    let context = Context::mainnet()
+        .with_db(db)
+        .with_cfg(cfg_env)
+        .with_block(latest_block_env);
+
+    let inspector = MultiInspector {
+        call_tracer: selection.call_tracer.clone().map(CallTracer::new_with_config),
+        prestate_tracer: selection.prestate_tracer.then(PrestateTracer::new),
+        opcode_tracer: selection.opcode_tracer.clone().map(OpcodeTracer::new),
+    };
+    let mut my_evm = MainnetEvm::new_with_inspector(
+        context,
+        inspector,
+        EthInstructions::new_mainnet(),
+        EthPrecompiles::default()
+    );
+
+    let execution_result = my_evm.inspect_one_tx(tx)
+        .map_err(ExecutionError::from)?;
+    let state_diff = my_evm.ctx.journaled_state.state.clone();
+
+    let MultiInspector { call_tracer, prestate_tracer, opcode_tracer } = my_evm.inspector;
+    let truncated = call_tracer.as_ref().is_some_and(|call_tracer| call_tracer.truncation_reason().is_some());
+    let calls = match call_tracer {
+        Some(call_tracer) if call_tracer.is_cancelled() => {
+            return Err(TraceError::Cancelled(call_tracer.into_result().map(Box::new)));
+        }
+        Some(call_tracer) => Some(call_tracer.into_result().ok_or(TraceError::NoTraceResult)?),
+        None => None,
+    };
+
+    Ok(MultiTraceResult {
+        execution_result,
+        state_diff,
+        calls,
+        prestate: prestate_tracer.map(PrestateTracer::into_result).transpose()?,
+        struct_logs: opcode_tracer.map(OpcodeTracer::into_result),
+        truncated,
     })
 }
 
+/// Trace a sequence of transactions against a shared, evolving state.
+///
+/// Each transaction runs on the state left by the previous ones, so a later transaction
+/// sees e.g. an approval granted earlier in the bundle. Every result's `state_diff` holds
+/// only the changes made by that transaction.
+///
+/// # Arguments
+///
+/// * `transactions` - Transactions to execute, in order
+/// * `latest_block_env` - Block environment shared by all transactions
+/// * `prestate_tracer_result` - Account states before the first transaction
+///
+/// # Errors
+///
+/// Returns the first `TraceError` encountered; reverted transactions are traced
+/// normally and do not stop the batch.
+pub fn trace_transactions(
+    transactions: Vec<TxParams>,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+) -> Result<Vec<TraceTransactionResult<HaltReason>>, TraceError> {
+    let prestate = SharedPrestate::new(prestate_tracer_result);
+    let mut prestate_keys = prestate.prestate_keys().clone();
+    // Committed transactions accumulate in the overlay, and each trace runs on an empty
+    // layer on top of it rather than on a copy of the state
+    let mut db = prestate.overlay();
+
+    transactions.iter()
+        .map(|params| {
+            let result = trace_transaction_with_database(params, latest_block_env.clone(), CacheDB::new(&db), &prestate_keys, &CallTracerConfig::default())?;
+            prestate_keys.extend_from_state(&result.state_diff);
+            apply_state_diff(&mut db, &result.state_diff);
+            Ok(result)
+        })
+        .collect()
+}
+
+/// Trace every transaction of a block in order, analogous to geth's `debug_traceBlockByNumber`
+///
+/// # Arguments
+///
+/// * `block_details` - The block as returned by `eth_getBlockByNumber`
+/// * `transactions` - The block's transactions, in order
+/// * `prestate_tracer_result` - Account states before the first transaction
+///
+/// # Returns
+///
+/// Returns a `BlockTraceResult` with per-transaction traces and the cumulative state diff
+///
+/// # Errors
+///
+/// Returns `TraceError` if the block environment cannot be built or any transaction
+/// fails to execute
+pub fn trace_block(
+    block_details: BlockDetails,
+    transactions: Vec<TxParams>,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+) -> Result<BlockTraceResult, TraceError> {
+    let block_env = create_block_env_from_block_details(block_details)?;
+    let transactions = trace_transactions(transactions, block_env, prestate_tracer_result)?;
+
+    let mut state_diff = EvmState::default();
+    for result in transactions.iter() {
+        merge_state_diff(&mut state_diff, &result.state_diff);
+    }
+
+    Ok(BlockTraceResult { transactions, state_diff })
+}
+
+/// Folds the state changes of a later transaction into `cumulative`, keeping the
+/// earliest original storage values
+fn merge_state_diff(cumulative: &mut EvmState, state: &EvmState) {
+    for (address, account) in state.iter() {
+        let Some(merged) = cumulative.get_mut(address) else {
+            cumulative.insert(*address, account.clone());
+            continue;
+        };
+        merged.info = account.info.clone();
+        merged.status |= account.status;
+        for (slot, value) in account.storage.iter() {
+            match merged.storage.get_mut(slot) {
+                Some(merged_slot) => merged_slot.present_value = value.present_value,
+                None => {
+                    merged.storage.insert(*slot, value.clone());
+                }
+            }
+        }
+    }
+}
+
 /// Trace an Optimism transaction execution with detailed call information
 ///
 /// This function is specifically for Optimism (OP Stack) chains and uses op-revm.
@@ -140,18 +1080,15 @@ pub fn trace_transaction(
 ///
 /// # Arguments
 ///
-/// * `chain_id` - The chain ID (e.g., 10 for OP Mainnet, 420 for OP Goerli)
-/// * `from` - The sender address
-/// * `from_nonce` - The sender's nonce
-/// * `to` - The recipient address
-/// * `data` - The transaction calldata
-/// * `gas_limit` - Maximum gas allowed for execution
-/// * `gas_price` - Gas price in wei
-/// * `gas_priority_fee` - Priority fee in wei
+/// * `params` - The transaction; `TxParams::spec` is ignored in favor of `op_spec`, and
+///   `TxParams::deposit` traces it as a deposit
 /// * `latest_block_env` - Block environment for execution
 /// * `prestate_tracer_result` - Account states before execution
-/// * `op_spec` - Optimism specification version (e.g., Bedrock, Canyon, Delta)
-/// * `l1_block_info` - Optional L1 block information for L1 fee calculation
+/// * `options` - State overrides, call tracer options and ABI registry
+/// * `op_spec` - Optimism specification version (e.g., Bedrock, Canyon, Ecotone); see
+///   `op_spec_for_block`
+/// * `l1_block_info` - Optional L1 block information for L1 fee calculation; when `None`
+///   it is read from the L1Block predeploy storage in the prestate
 ///
 /// # Returns
 ///
@@ -169,72 +1106,136 @@ pub fn trace_transaction(
 /// ```ignore
 /// use op_revm::OpSpecId;
 /// let result = trace_transaction_op(
-///     10,  // OP Mainnet
-///     from_address,
-///     nonce,
-///     to_address,
-///     calldata,
-///     gas_limit,
-///     gas_price,
-///     priority_fee,
+///     &params,
 ///     block_env,
 ///     prestate,
+///     TraceTransactionOptions::default(),
 ///     OpSpecId::CANYON,
-///     None,  // No custom L1 block info
+///     None,  // Read L1 block info from the prestate
 /// )?;
 /// ```
+#[cfg(feature = "op")]
 pub fn trace_transaction_op(
-    chain_id: u64,
-    from: Address,
-    from_nonce: u64,
-    to: Address,
-    data: Bytes,
-    gas_limit: u64,
-    gas_price: u128,
-    gas_priority_fee: u128,
+    params: &TxParams,
     latest_block_env: BlockEnv,
-    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    mut prestate_tracer_result: HashMap<Address, AccountDetails>,
+    options: TraceTransactionOptions,
+    op_spec: OpSpecId,
+    l1_block_info: Option<L1BlockInfo>,
 ) -> Result<TraceTransactionResult<OpHaltReason>, TraceError> {
-    // Build base transaction environment
-    let base_tx = TxEnv::builder()
-        .chain_id(Some(chain_id))
-        .caller(from)
-        .kind(TxKind::Call(to))
-        .nonce(from_nonce)
-        .gas_limit(gas_limit)
-        .gas_price(gas_price)
-        .gas_priority_fee(Some(gas_priority_fee))
-        .data(data);
-
-    // Build Optimism-specific transaction
-    // mint: 0 for regular transactions (L1->L2 deposit amount)
-    // source_hash: Identifier for the L1 transaction that triggered this (dummy for user transactions)
-    let op_tx = OpTransaction::builder()
-        .base(base_tx)
-        .enveloped_tx(None)
-        .not_system_transaction()
-        .mint(0u128)
-        .source_hash(B256::from([1u8; 32]))
-        .build()?;
-
-    let inspector = CallTracer::new();
-
-    // Create in-memory database from prestate
+    // Create in-memory database from the overridden prestate
+    apply_state_overrides(&mut prestate_tracer_result, options.state_overrides)?;
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
     let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
 
+    let mut result = trace_transaction_op_with_database(params, latest_block_env, db, &prestate_keys, &options.tracer_config, op_spec, l1_block_info)?;
+    if let Some(abi_registry) = options.abi_registry {
+        abi_registry.decode_result(&mut result);
+    }
+    Ok(result)
+}
+
+/// Trace an Optimism transaction against an already constructed database.
+///
+/// The Optimism counterpart of `trace_transaction_with_database`. L1 fees are computed
+/// from `l1_block_info`, or from the L1Block predeploy storage in `db` when it is `None`.
+#[cfg(feature = "op")]
+#[cfg_attr(feature = "tracing", tracing::instrument(
+    name = "trace_transaction",
+    skip_all,
+    fields(
+        chain_id = params.chain_id,
+        from = %params.from,
+        to = %params.to,
+        gas_limit = params.gas_limit,
+        setup_us = tracing::field::Empty,
+        execution_us = tracing::field::Empty,
+        frames = tracing::field::Empty,
+        logs = tracing::field::Empty,
+        db_misses = tracing::field::Empty,
+        gas_used = tracing::field::Empty,
+    ),
+))]
+pub fn trace_transaction_op_with_database<DB: Database + DatabaseRef>(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    mut db: DB,
+    prestate_keys: &PrestateKeys,
+    tracer_config: &CallTracerConfig,
+    op_spec: OpSpecId,
+    l1_block_info: Option<L1BlockInfo>,
+) -> Result<TraceTransactionResult<OpHaltReason>, TraceError> {
+    #[cfg(feature = "tracing")]
+    let mut timer = PhaseTimer::start();
+    if params.is_blob_transaction() {
+        return Err(TraceError::InvalidParameter(
+            "blob transactions are not supported on OP Stack chains".to_string()
+        ));
+    }
+    if !params.authorization_list.is_empty() && params.tx_spec == TxSpec::Legacy {
+        return Err(TraceError::InvalidParameter(
+            "set-code transactions require EIP-1559 fee semantics".to_string()
+        ));
+    }
+
+    // Build base transaction environment
+    let base_tx = params.tx_env_builder();
+
+    let op_tx = match &params.deposit {
+        Some(deposit) => {
+            // Deposits pay no L2 fees, so the fee caps are not validated
+            let builder = OpTransaction::builder()
+                .base(base_tx.tx_type(Some(DEPOSIT_TRANSACTION_TYPE)))
+                .mint(deposit.mint)
+                .source_hash(deposit.source_hash);
+            if deposit.is_system_transaction {
+                builder.is_system_transaction().build()?
+            } else {
+                builder.not_system_transaction().build()?
+            }
+        }
+        None => {
+            params.validate_fee_caps(latest_block_env.basefee)?;
+
+            // Build Optimism-specific transaction
+            // mint: 0 for regular transactions (L1->L2 deposit amount)
+            // source_hash: Identifier for the L1 transaction that triggered this (dummy for user transactions)
+            OpTransaction::builder()
+                .base(base_tx)
+                .enveloped_tx(Some(params.enveloped_tx()))
+                .not_system_transaction()
+                .mint(0u128)
+                .source_hash(B256::from([1u8; 32]))
+                .build()?
+        }
+    };
+
+    let inspector = CallTracer::new_with_config(tracer_config.clone());
+
     // Configure EVM with chain settings
-    let cfg_env = CfgEnv::new().with_chain_id(chain_id);
-    let spec_id = cfg_env.spec;
+    let cfg_env = CfgEnv::new().with_chain_id(params.chain_id);
+    let spec_id = op_spec.into_eth_spec();
 
-    // Setup Optimism-specific configuration
-    let op_spec = OpSpecId::default();
-    let mut chain = L1BlockInfo::default();
+    // Setup Optimism-specific configuration. Pinning `l2_block` to the traced block
+    // keeps op-revm from reloading the L1 block info from the database.
+    let mut chain = match l1_block_info {
+        Some(l1_block_info) => l1_block_info,
+        None => L1BlockInfo::try_fetch(&mut db, latest_block_env.number, op_spec)
+            .map_err(|e| TraceError::Database(e.to_string()))?,
+    };
+    chain.l2_block = latest_block_env.number;
 
     // Isthmus upgrade requires operator fee parameters
-    if op_spec == OpSpecId::ISTHMUS {
-        chain.operator_fee_constant = Some(U256::from(0));
-        chain.operator_fee_scalar = Some(U256::from(0));
+    if op_spec.is_enabled_in(OpSpecId::ISTHMUS) {
+        chain.operator_fee_constant.get_or_insert(U256::ZERO);
+        chain.operator_fee_scalar.get_or_insert(U256::ZERO);
     }
+    let l1_data_fee = match params.deposit {
+        Some(_) => U256::ZERO,
+        None => chain.clone().calculate_tx_l1_cost(&params.enveloped_tx(), op_spec),
+    };
+    let operator_fee_params = (params.deposit.is_none() && op_spec.is_enabled_in(OpSpecId::ISTHMUS))
+        .then(|| (chain.operator_fee_scalar.unwrap_or_default(), chain.operator_fee_constant.unwrap_or_default()));
 
     let op_cfg = cfg_env.with_spec(op_spec);
 
@@ -253,23 +1254,271 @@ pub fn trace_transaction_op(
         error: Ok(()),
     };
 
+    let basefee = op_context.block.basefee;
+    let beneficiary = op_context.block.beneficiary;
     let mut my_evm = OpEvm::new(op_context, inspector);
+    #[cfg(feature = "tracing")]
+    timer.lap("setup_us");
 
     // Execute transaction and collect trace
     let execution_result = my_evm.inspect_one_tx(op_tx)
-        .map_err(|e| TraceError::Execution(e.to_string()))?;
+        .map_err(ExecutionError::from)?;
+    #[cfg(feature = "tracing")]
+    timer.lap("execution_us");
 
     // Finalize to get state changes
     let state_diff = my_evm.finalize();
+    let balance_changes = balance_changes(&my_evm.0.ctx.journaled_state.database, &state_diff);
+    let diff_mode = build_diff_mode(&my_evm.0.ctx.journaled_state.database, &state_diff);
+    let accessed_state = accessed_state(&my_evm.0.ctx.journaled_state.database, &state_diff);
 
     // Extract call trace from inspector
-    let inspector = my_evm.into_inspector();
-    let calls = inspector.into_result()
+    let mut inspector = my_evm.into_inspector();
+    if inspector.is_cancelled() {
+        return Err(TraceError::Cancelled(inspector.into_result().map(Box::new)));
+    }
+    let truncation_reason = inspector.truncation_reason();
+    let memory = inspector.memory_stats();
+    #[cfg(feature = "tracing")]
+    let (frames, logs) = (inspector.frames_entered(), inspector.logs_emitted());
+    let storage_labeler = inspector.take_storage_labeler();
+    let value_transfers = inspector.take_value_transfers();
+    let mut calls = inspector.into_result()
         .ok_or(TraceError::NoTraceResult)?;
+    let storage_changes = storage_labeler.map(|labeler| {
+        labeler.label_frames(&mut calls);
+        labeler.label_changes(&state_diff)
+    }).unwrap_or_default();
+
+    let warnings = prestate_keys.warnings(&state_diff, beneficiary);
+    if tracer_config.strict_prestate && !warnings.is_empty() {
+        return Err(TraceError::IncompletePrestate(warnings));
+    }
+    #[cfg(feature = "tracing")]
+    record_trace(frames, logs, warnings.len(), execution_result.gas_used());
+    let revert = revert_of(&execution_result);
+    let gas = params.gas_breakdown(&execution_result, calls.gas_used.saturating_to(), op_spec.into_eth_spec(), basefee);
+    let operator_fee = operator_fee_params
+        .map(|(scalar, constant)| U256::from(gas.total_used) * scalar / U256::from(1_000_000) + constant)
+        .unwrap_or_default();
+    let op_fees = OpFees {
+        l1_data_fee,
+        l2_execution_fee: gas.fee_paid,
+        operator_fee,
+        total: l1_data_fee + gas.fee_paid + operator_fee,
+    };
 
     Ok(TraceTransactionResult {
         execution_result,
         state_diff,
-        calls
+        calls,
+        warnings,
+        blob_gas_used: 0,
+        gas,
+        revert,
+        balance_changes,
+        diff_mode,
+        accessed_state,
+        op_fees: Some(op_fees),
+        arbitrum: None,
+        truncated: truncation_reason.is_some(),
+        truncation_reason,
+        output_truncated: false,
+        storage_changes,
+        value_transfers,
+        flat_calls: Vec::new(),
+        memory,
+        decoded_output: None,
     })
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use revm::primitives::address;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::trace::labels::SlotLabels;
+
+    const SENDER: Address = address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    const RECIPIENT: Address = address!("bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+
+    fn params(data: Bytes) -> TxParams {
+        TxParams {
+            chain_id: 1,
+            from: SENDER,
+            from_nonce: 0,
+            to: RECIPIENT,
+            value: U256::ZERO,
+            data,
+            gas_limit: 200_000,
+            gas_price: 2_000_000_000,
+            gas_priority_fee: 1_000_000_000,
+            tx_spec: TxSpec::Eip1559,
+            access_list: None,
+            blob_versioned_hashes: Vec::new(),
+            max_fee_per_blob_gas: 0,
+            authorization_list: Vec::new(),
+            deposit: None,
+            spec: Some(SpecId::PRAGUE),
+        }
+    }
+
+    fn trace_gas(params: &TxParams, recipient_code: Option<Bytes>) -> GasBreakdown {
+        let mut prestate = HashMap::default();
+        prestate.insert(SENDER, AccountDetails {
+            balance: Some(U256::from(10).pow(U256::from(18))),
+            nonce: Some(0),
+            code: None,
+            storage: None,
+        });
+        prestate.insert(RECIPIENT, AccountDetails {
+            balance: None,
+            nonce: None,
+            code: recipient_code,
+            storage: None,
+        });
+        let block_env = BlockEnv { basefee: 1_000_000_000, ..BlockEnv::default() };
+        trace_transaction(params, block_env, prestate, TraceTransactionOptions::default()).unwrap().gas
+    }
+
+    #[test]
+    fn trace_transactions_build_on_earlier_ones() {
+        let mut first = params(Bytes::new());
+        first.value = U256::from(1);
+        let second = TxParams { from_nonce: 1, ..first.clone() };
+        let mut prestate = HashMap::default();
+        prestate.insert(SENDER, AccountDetails {
+            balance: Some(U256::from(10).pow(U256::from(18))),
+            nonce: Some(0),
+            code: None,
+            storage: None,
+        });
+        let block_env = BlockEnv { basefee: 1_000_000_000, ..BlockEnv::default() };
+
+        let results = trace_transactions(vec![first, second], block_env, prestate).unwrap();
+
+        assert!(results.iter().all(|result| result.execution_result.is_success()));
+        assert_eq!(results[1].state_diff[&SENDER].info.nonce, 2);
+        assert_eq!(results[1].state_diff[&RECIPIENT].info.balance, U256::from(2));
+        assert!(results[1].warnings.is_empty());
+    }
+
+    #[test]
+    fn slot_labels_name_the_storage_changes() {
+        let mut slot_labels = SlotLabels::new();
+        slot_labels.insert(RECIPIENT, U256::ZERO, "owner");
+        let options = TraceTransactionOptions {
+            tracer_config: CallTracerConfig {
+                with_storage: true,
+                slot_labels: Some(slot_labels),
+                ..CallTracerConfig::default()
+            },
+            ..TraceTransactionOptions::default()
+        };
+        let mut prestate = HashMap::default();
+        prestate.insert(SENDER, AccountDetails {
+            balance: Some(U256::from(10).pow(U256::from(18))),
+            nonce: Some(0),
+            code: None,
+            storage: None,
+        });
+        prestate.insert(RECIPIENT, AccountDetails {
+            balance: None,
+            nonce: None,
+            code: Some(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55, 0x00])),
+            storage: None,
+        });
+        let block_env = BlockEnv { basefee: 1_000_000_000, ..BlockEnv::default() };
+
+        let result = trace_transaction(&params(Bytes::new()), block_env, prestate, options).unwrap();
+
+        assert_eq!(result.storage_changes.len(), 1);
+        assert_eq!(result.storage_changes[0].label.as_deref(), Some("owner"));
+        assert_eq!(result.calls.storage_writes[0].label.as_deref(), Some("owner"));
+    }
+
+    #[test]
+    fn gas_breakdown_of_a_contract_call() {
+        // PUSH1 1 PUSH1 0 SSTORE STOP: a cold zero-to-nonzero store
+        let gas = trace_gas(&params(Bytes::new()), Some(Bytes::from_static(&[0x60, 0x01, 0x60, 0x00, 0x55, 0x00])));
+
+        assert_eq!(gas.intrinsic, 21_000);
+        assert_eq!(gas.execution, 3 + 3 + 22_100);
+        assert_eq!(gas.refund, 0);
+        assert_eq!(gas.floor, 21_000);
+        assert_eq!(gas.total_used, 21_000 + 22_106);
+        assert_eq!(gas.effective_gas_price, 2_000_000_000);
+    }
+
+    #[test]
+    fn gas_breakdown_reports_the_calldata_floor() {
+        let gas = trace_gas(&params(Bytes::from(vec![0xff; 1000])), None);
+
+        assert_eq!(gas.intrinsic, 21_000 + 16 * 1000);
+        assert_eq!(gas.execution, 0);
+        assert_eq!(gas.floor, 21_000 + 10 * 4 * 1000);
+        assert_eq!(gas.total_used, gas.floor);
+    }
+
+    #[test]
+    fn gas_breakdown_counts_authorizations() {
+        let transaction: Value = serde_json::from_str(include_str!("../../tests/fixtures/rpc/eip7702_transaction.json")).unwrap();
+        let mut params = params(Bytes::new());
+        params.authorization_list = serde_json::from_value(transaction["authorizationList"].clone()).unwrap();
+
+        let gas = trace_gas(&params, None);
+
+        assert_eq!(gas.intrinsic, 21_000 + 25_000);
+        assert_eq!(gas.total_used, 21_000 + 25_000);
+    }
+
+    #[test]
+    fn fee_caps_reject_a_priority_fee_above_the_max_fee() {
+        let params = TxParams { gas_priority_fee: 3_000_000_000, ..params(Bytes::new()) };
+
+        assert_eq!(
+            params.validate_fee_caps(1_000_000_000),
+            Err(FeeValidationError::PriorityFeeAboveMaxFee {
+                max_fee_per_gas: 2_000_000_000,
+                max_priority_fee_per_gas: 3_000_000_000,
+            })
+        );
+        // A legacy gas price is paid whole, whatever the priority fee says
+        let legacy = TxParams { tx_spec: TxSpec::Legacy, ..params };
+        assert_eq!(legacy.validate_fee_caps(1_000_000_000), Ok(()));
+    }
+
+    #[test]
+    fn fee_caps_reject_a_max_fee_below_the_base_fee() {
+        let params = params(Bytes::new());
+
+        assert_eq!(params.validate_fee_caps(2_000_000_000), Ok(()));
+        assert_eq!(
+            params.validate_fee_caps(2_000_000_001),
+            Err(FeeValidationError::MaxFeeBelowBaseFee {
+                max_fee_per_gas: 2_000_000_000,
+                basefee: 2_000_000_001,
+            })
+        );
+        let block_env = BlockEnv { basefee: 2_000_000_001, ..BlockEnv::default() };
+        let error = trace_transaction(&params, block_env, HashMap::default(), TraceTransactionOptions::default()).unwrap_err();
+        assert!(matches!(error, TraceError::FeeValidation(FeeValidationError::MaxFeeBelowBaseFee { .. })));
+    }
+
+    #[test]
+    fn tx_params_accept_either_name_of_a_fee_but_not_both() {
+        let mut json = serde_json::to_value(params(Bytes::new())).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("gasPrice");
+        fields.remove("gasPriorityFee");
+        fields.insert("maxFeePerGas".into(), 5.into());
+        fields.insert("maxPriorityFeePerGas".into(), 4.into());
+
+        let parsed: TxParams = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!((parsed.max_fee_per_gas(), parsed.max_priority_fee_per_gas()), (5, 4));
+
+        json["gasPrice"] = 5.into();
+        let error = serde_json::from_value::<TxParams>(json).unwrap_err();
+        assert!(error.to_string().contains("duplicate field `gasPrice`"), "{}", error);
+    }
+}
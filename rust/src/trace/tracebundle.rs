@@ -0,0 +1,160 @@
+//! Self-contained `.tracebundle` files for replaying a simulation without RPC access
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use revm::context::result::HaltReason;
+use revm::context::BlockEnv;
+use revm::primitives::{keccak256, Address, HashMap, B256};
+#[cfg(feature = "op")]
+use op_revm::OpHaltReason;
+
+#[cfg(feature = "op")]
+use crate::trace::block::op_spec_for_block;
+use crate::trace::database::{
+    create_in_memory_database_from_prestate_trace,
+    extract_used_prestate,
+    AccountDetails,
+    PrestateKeys,
+};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::CallTracerConfig;
+#[cfg(feature = "op")]
+use crate::trace::trace::trace_transaction_op_with_database;
+use crate::trace::trace::{
+    trace_transaction_with_database,
+    TraceTransactionResult,
+    TxParams,
+};
+
+/// Current version of the `.tracebundle` format
+pub const TRACE_BUNDLE_VERSION: u32 = 1;
+
+/// Options that affect how a bundled transaction is executed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceBundleOptions {
+    /// Replay with the Optimism tracer instead of the Ethereum one
+    pub is_op_stack: bool,
+}
+
+/// A replayed trace, typed by the chain it was executed for
+#[derive(Debug)]
+pub enum ReplayedTrace {
+    Ethereum(TraceTransactionResult<HaltReason>),
+    #[cfg(feature = "op")]
+    Optimism(TraceTransactionResult<OpHaltReason>),
+}
+
+/// Everything needed to reproduce a trace deterministically: the transaction, block
+/// environment, the minimal prestate it touched, execution options, and a hash of
+/// the original result to check the replay against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceBundleFile {
+    pub version: u32,
+    pub tx: TxParams,
+    pub block_env: BlockEnv,
+    pub prestate: HashMap<Address, AccountDetails>,
+    pub options: TraceBundleOptions,
+    pub result_hash: B256,
+}
+
+impl TraceBundleFile {
+    /// Traces the transaction and captures it as a bundle.
+    ///
+    /// The stored prestate is reduced to what execution touched, so the bundle stays
+    /// small while still replaying bit-for-bit. Returns the bundle along with the trace.
+    pub fn record(
+        tx: TxParams,
+        block_env: BlockEnv,
+        prestate_tracer_result: HashMap<Address, AccountDetails>,
+        options: TraceBundleOptions,
+    ) -> Result<(Self, ReplayedTrace), TraceError> {
+        let trace = run(&tx, block_env.clone(), prestate_tracer_result.clone(), &options)?;
+        let state_diff = match &trace {
+            ReplayedTrace::Ethereum(result) => &result.state_diff,
+            #[cfg(feature = "op")]
+            ReplayedTrace::Optimism(result) => &result.state_diff,
+        };
+        let prestate = extract_used_prestate(&prestate_tracer_result, state_diff);
+
+        let bundle = Self {
+            version: TRACE_BUNDLE_VERSION,
+            tx,
+            block_env,
+            prestate,
+            options,
+            result_hash: trace.result_hash()?,
+        };
+        Ok((bundle, trace))
+    }
+
+    /// Writes the bundle as JSON, conventionally to a `.tracebundle` file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TraceError> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Reads a bundle written by `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TraceError> {
+        let bundle: Self = serde_json::from_slice(&fs::read(path)?)?;
+        if bundle.version > TRACE_BUNDLE_VERSION {
+            return Err(TraceError::InvalidParameter(format!(
+                "unsupported trace bundle version {}", bundle.version
+            )));
+        }
+        Ok(bundle)
+    }
+
+    /// Re-executes the bundled transaction against the bundled prestate
+    pub fn replay(&self) -> Result<ReplayedTrace, TraceError> {
+        run(&self.tx, self.block_env.clone(), self.prestate.clone(), &self.options)
+    }
+
+    /// Replays the bundle and checks that the result hash matches the recorded one
+    pub fn verify(&self) -> Result<bool, TraceError> {
+        Ok(self.replay()?.result_hash()? == self.result_hash)
+    }
+}
+
+impl ReplayedTrace {
+    /// Keccak hash of the result's canonical JSON.
+    ///
+    /// Converting through `serde_json::Value` sorts object keys, so hash map iteration
+    /// order does not affect the hash.
+    pub fn result_hash(&self) -> Result<B256, TraceError> {
+        let value = match self {
+            ReplayedTrace::Ethereum(result) => serde_json::to_value(result)?,
+            #[cfg(feature = "op")]
+            ReplayedTrace::Optimism(result) => serde_json::to_value(result)?,
+        };
+        Ok(keccak256(serde_json::to_vec(&value)?))
+    }
+}
+
+fn run(
+    tx: &TxParams,
+    block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+    options: &TraceBundleOptions,
+) -> Result<ReplayedTrace, TraceError> {
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+
+    if options.is_op_stack {
+        #[cfg(feature = "op")]
+        {
+            let op_spec = op_spec_for_block(tx.chain_id, block_env.timestamp.saturating_to());
+            return trace_transaction_op_with_database(tx, block_env, db, &prestate_keys, &CallTracerConfig::default(), op_spec, None)
+                .map(ReplayedTrace::Optimism);
+        }
+        #[cfg(not(feature = "op"))]
+        return Err(TraceError::InvalidParameter(
+            "OP Stack bundles require the `op` feature".to_string()
+        ));
+    }
+    trace_transaction_with_database(tx, block_env, db, &prestate_keys, &CallTracerConfig::default())
+        .map(ReplayedTrace::Ethereum)
+}
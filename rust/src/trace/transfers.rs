@@ -0,0 +1,96 @@
+//! ETH value transfers made anywhere in the call tree, collected during inspection
+
+use serde::{Deserialize, Serialize};
+use revm::primitives::{Address, U256};
+
+/// How a value transfer was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValueTransferKind {
+    Call,
+    Create,
+    /// Balance swept to the beneficiary of a SELFDESTRUCT
+    Selfdestruct,
+}
+
+/// ETH moved from one account to another by a frame that did not revert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueTransfer {
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+    pub kind: ValueTransferKind,
+    /// Position of the frame in the call tree: the index of each subcall from the
+    /// top-level frame down, empty for the top-level frame itself. It matches `calls`
+    /// unless frames were left out by `max_depth` or `max_frames`.
+    pub frame_path: Vec<usize>,
+}
+
+/// Follows the frames of an execution and collects the transfers of those that succeed
+#[derive(Debug, Default)]
+pub(crate) struct ValueTransferTracker {
+    transfers: Vec<ValueTransfer>,
+    /// Path of the executing frame
+    path: Vec<usize>,
+    /// Subcalls entered so far by each executing frame
+    children: Vec<usize>,
+    /// Length of `transfers` when each executing frame was entered
+    checkpoints: Vec<usize>,
+}
+
+impl ValueTransferTracker {
+    /// Starts a frame moving `amount` from `from` to `to`. The recipient of a creation is
+    /// only known once it ends, so `to` is filled in by `exit`.
+    pub(crate) fn enter(&mut self, from: Address, to: Address, amount: U256, kind: ValueTransferKind) {
+        if let Some(children) = self.children.last_mut() {
+            self.path.push(*children);
+            *children += 1;
+        }
+        self.children.push(0);
+        self.checkpoints.push(self.transfers.len());
+        // CALLCODE moves value from the caller to itself
+        if !amount.is_zero() && (from != to || kind == ValueTransferKind::Create) {
+            self.transfers.push(ValueTransfer { from, to, amount, kind, frame_path: self.path.clone() });
+        }
+    }
+
+    /// Ends the executing frame, dropping every transfer it made if it failed
+    pub(crate) fn exit(&mut self, is_success: bool, created: Option<Address>) {
+        let Some(checkpoint) = self.checkpoints.pop() else {
+            return;
+        };
+        self.children.pop();
+        if !is_success {
+            self.transfers.truncate(checkpoint);
+        } else if let Some(created) = created {
+            if let Some(transfer) = self.transfers.get_mut(checkpoint)
+                .filter(|transfer| transfer.kind == ValueTransferKind::Create && transfer.frame_path == self.path)
+            {
+                transfer.to = created;
+            }
+        }
+        // The top-level frame has no index of its own
+        if !self.checkpoints.is_empty() {
+            self.path.pop();
+        }
+    }
+
+    /// Records the balance of `contract` being swept to `target` by the executing frame
+    pub(crate) fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if value.is_zero() || contract == target {
+            return;
+        }
+        self.transfers.push(ValueTransfer {
+            from: contract,
+            to: target,
+            amount: value,
+            kind: ValueTransferKind::Selfdestruct,
+            frame_path: self.path.clone(),
+        });
+    }
+
+    pub(crate) fn into_transfers(self) -> Vec<ValueTransfer> {
+        self.transfers
+    }
+}
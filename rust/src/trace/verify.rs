@@ -0,0 +1,231 @@
+//! Verification that attaching the tracer does not change execution, and that local
+//! traces match the traces of a node
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "rpc")]
+use serde_json::{json, Value};
+use revm::context::result::ExecutionResult;
+use revm::context::BlockEnv;
+use revm::primitives::{Address, HashMap, StorageKey};
+#[cfg(feature = "rpc")]
+use revm::primitives::B256;
+use revm::state::EvmState;
+
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, AccountDetails, PrestateKeys};
+use crate::trace::error::TraceError;
+#[cfg(feature = "rpc")]
+use crate::trace::geth::GethCallFrame;
+use crate::trace::inspector::CallTracerConfig;
+#[cfg(feature = "rpc")]
+use crate::trace::rpc::{mined_transaction, TraceRpcClient};
+use crate::trace::sweep::ExecutionStatus;
+use crate::trace::trace::{
+    execute_transaction_with_database,
+    trace_transaction_with_database,
+    TraceTransactionResult,
+    TxParams,
+};
+#[cfg(feature = "rpc")]
+use crate::trace::trace::{trace_transaction, TraceTransactionOptions};
+
+/// A difference between the traced and the untraced execution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Divergence {
+    GasUsed { traced: u64, untraced: u64 },
+    Status { traced: ExecutionStatus, untraced: ExecutionStatus },
+    Output,
+    /// Account present in only one of the two states, or with different info
+    Account { address: Address },
+    Storage { address: Address, slot: StorageKey },
+}
+
+/// Outcome of `verify_inspector_consistency`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsistencyReport {
+    pub consistent: bool,
+    pub divergences: Vec<Divergence>,
+    /// The traced result, so the verification run doubles as a normal trace
+    pub trace: TraceTransactionResult<revm::context::result::HaltReason>,
+}
+
+/// Executes the transaction twice, with and without the call tracer attached, and
+/// reports any difference in gas used, status, output or resulting state.
+///
+/// Inspector hooks should be purely observational; a divergence means tracing is
+/// perturbing execution (e.g. by warming accounts) and the trace cannot be trusted.
+pub fn verify_inspector_consistency(
+    params: &TxParams,
+    latest_block_env: BlockEnv,
+    prestate_tracer_result: HashMap<Address, AccountDetails>,
+) -> Result<ConsistencyReport, TraceError> {
+    let prestate_keys = PrestateKeys::from_prestate(&prestate_tracer_result);
+    let db = create_in_memory_database_from_prestate_trace(prestate_tracer_result);
+
+    let trace = trace_transaction_with_database(params, latest_block_env.clone(), db.clone(), &prestate_keys, &CallTracerConfig::default())?;
+    let (untraced_result, untraced_state) = execute_transaction_with_database(params, latest_block_env, db)?;
+
+    let mut divergences = compare_results(&trace.execution_result, &untraced_result);
+    divergences.extend(compare_states(&trace.state_diff, &untraced_state));
+
+    Ok(ConsistencyReport {
+        consistent: divergences.is_empty(),
+        divergences,
+        trace,
+    })
+}
+
+fn compare_results<H>(traced: &ExecutionResult<H>, untraced: &ExecutionResult<H>) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    if traced.gas_used() != untraced.gas_used() {
+        divergences.push(Divergence::GasUsed {
+            traced: traced.gas_used(),
+            untraced: untraced.gas_used(),
+        });
+    }
+    let (traced_status, untraced_status) = (ExecutionStatus::of(traced), ExecutionStatus::of(untraced));
+    if traced_status != untraced_status {
+        divergences.push(Divergence::Status {
+            traced: traced_status,
+            untraced: untraced_status,
+        });
+    } else if traced.output() != untraced.output() {
+        divergences.push(Divergence::Output);
+    }
+    divergences
+}
+
+fn compare_states(traced: &EvmState, untraced: &EvmState) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    let addresses = traced.keys().chain(untraced.keys().filter(|address| !traced.contains_key(*address)));
+
+    for address in addresses {
+        let (Some(traced_account), Some(untraced_account)) = (traced.get(address), untraced.get(address)) else {
+            divergences.push(Divergence::Account { address: *address });
+            continue;
+        };
+        let (traced_info, untraced_info) = (&traced_account.info, &untraced_account.info);
+        if traced_info.balance != untraced_info.balance
+            || traced_info.nonce != untraced_info.nonce
+            || traced_info.code_hash != untraced_info.code_hash
+        {
+            divergences.push(Divergence::Account { address: *address });
+        }
+
+        let slots = traced_account.storage.keys()
+            .chain(untraced_account.storage.keys().filter(|slot| !traced_account.storage.contains_key(*slot)));
+        for slot in slots {
+            let traced_value = traced_account.storage.get(slot).map(|value| value.present_value);
+            let untraced_value = untraced_account.storage.get(slot).map(|value| value.present_value);
+            if traced_value != untraced_value {
+                divergences.push(Divergence::Storage { address: *address, slot: *slot });
+            }
+        }
+    }
+
+    divergences.sort_by_key(|divergence| match divergence {
+        Divergence::Account { address } => (Some(*address), None),
+        Divergence::Storage { address, slot } => (Some(*address), Some(*slot)),
+        _ => (None, None),
+    });
+    divergences
+}
+
+/// A field whose value differs between the local trace and the node's `callTracer`
+#[cfg(feature = "rpc")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldMismatch {
+    /// Location of the field in the call tree, e.g. `calls[0].calls[2].gasUsed`
+    pub path: String,
+    /// Local value; `null` if the local trace does not have the field
+    pub local: Value,
+    /// Value reported by the node; `null` if the node does not report the field
+    pub remote: Value,
+}
+
+/// Outcome of `verify_against_node`
+#[cfg(feature = "rpc")]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeVerificationReport {
+    pub matches: bool,
+    pub mismatches: Vec<FieldMismatch>,
+    /// The local trace
+    pub trace: TraceTransactionResult<revm::context::result::HaltReason>,
+}
+
+/// Replays a mined transaction locally and compares its call tree, field by field, with
+/// the node's `debug_traceTransaction` `callTracer` output.
+///
+/// The local trace runs on the prestate reported by the node's `prestateTracer`, so a
+/// mismatch points at the local EVM configuration (hardfork, fee handling, chain
+/// settings) rather than at the state. The local tree is compared in geth's format, see
+/// `GethCallFrame`; hex strings are compared case-insensitively.
+///
+/// The node is asked for the same `onlyTopCall` and `withLog` as `options.tracer_config`.
+///
+/// # Errors
+///
+/// Returns `TraceError::Rpc` if a request to the node fails, or any other `TraceError`
+/// if the transaction cannot be replayed
+#[cfg(feature = "rpc")]
+pub fn verify_against_node(
+    rpc_url: &str,
+    tx_hash: B256,
+    options: TraceTransactionOptions,
+) -> Result<NodeVerificationReport, TraceError> {
+    let client = TraceRpcClient::new(rpc_url)?;
+    let (params, block_env, _) = mined_transaction(&client, tx_hash)?;
+    let prestate = client.transaction_prestate(tx_hash)?;
+    let tracer_config = json!({
+        "onlyTopCall": options.tracer_config.only_top_call,
+        "withLog": options.tracer_config.with_logs,
+    });
+    let trace = trace_transaction(&params, block_env, prestate, options)?;
+
+    let remote: Value = client.request(
+        "debug_traceTransaction",
+        json!([tx_hash, { "tracer": "callTracer", "tracerConfig": tracer_config }]),
+    )?;
+    let local = serde_json::to_value(GethCallFrame::for_transaction(&params, &trace))?;
+
+    let mut mismatches = Vec::new();
+    compare_values(String::new(), &local, &remote, &mut mismatches);
+    Ok(NodeVerificationReport {
+        matches: mismatches.is_empty(),
+        mismatches,
+        trace,
+    })
+}
+
+#[cfg(feature = "rpc")]
+fn compare_values(path: String, local: &Value, remote: &Value, mismatches: &mut Vec<FieldMismatch>) {
+    match (local, remote) {
+        (Value::Object(local), Value::Object(remote)) => {
+            let keys = local.keys().chain(remote.keys().filter(|key| !local.contains_key(*key)));
+            for key in keys {
+                let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                compare_values(
+                    path,
+                    local.get(key).unwrap_or(&Value::Null),
+                    remote.get(key).unwrap_or(&Value::Null),
+                    mismatches,
+                );
+            }
+        }
+        (Value::Array(local), Value::Array(remote)) if local.len() == remote.len() => {
+            for (index, (local, remote)) in local.iter().zip(remote).enumerate() {
+                compare_values(format!("{}[{}]", path, index), local, remote, mismatches);
+            }
+        }
+        (Value::String(local), Value::String(remote)) if local.eq_ignore_ascii_case(remote) => {}
+        (local, remote) if local == remote => {}
+        (local, remote) => mismatches.push(FieldMismatch {
+            path,
+            local: local.clone(),
+            remote: remote.clone(),
+        }),
+    }
+}
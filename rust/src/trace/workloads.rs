@@ -0,0 +1,199 @@
+//! Representative transactions for benchmarking the tracer (`bench` feature)
+//!
+//! Each `Workload` is a transaction with the prestate and block it runs on, built from the
+//! canned state of `testing`, so it traces without a node. The suite in `benches/` runs
+//! them to catch performance regressions, e.g. across revm upgrades; services can reuse
+//! them to measure the tracer on their own hardware.
+
+use revm::context::result::HaltReason;
+use revm::context::BlockEnv;
+use revm::primitives::{address, bytes, Address, Bytes, HashMap, B256, U256};
+
+use crate::trace::database::{create_in_memory_database_from_prestate_trace, AccountDetails, PrestateKeys};
+use crate::trace::erc4337::{handle_ops_params, PackedUserOperation};
+use crate::trace::error::TraceError;
+use crate::trace::inspector::CallTracerConfig;
+use crate::trace::testing::{
+    entry_point_account, erc20_prestate, funded_account, test_block_env, ENTRY_POINT_V07_ADDRESS,
+};
+use crate::trace::trace::{trace_transaction_with_database, TraceTransactionResult, TxParams, TxSpec};
+
+/// Sender of every workload transaction
+pub const WORKLOAD_SENDER: Address = address!("00000000000000000000000000000000000b3e01");
+
+/// Recipient of transfers
+pub const WORKLOAD_RECIPIENT: Address = address!("00000000000000000000000000000000000b3e02");
+
+/// Address of the ERC-20 token of `erc20_transfer` and `nested_calls`
+pub const WORKLOAD_TOKEN: Address = address!("00000000000000000000000000000000000b3e20");
+
+/// Runtime code of a forwarder: calls the address in its slot 0 with its own calldata
+/// and all remaining gas, then returns or reverts with the callee's return data
+const FORWARDER_CODE: Bytes = bytes!("365f5f375f5f365f5f5f545af13d5f5f3e6017573d5ffd5b3d5ff3");
+
+/// Runtime code of an account accepting every user operation: returns a zero word, i.e.
+/// valid `validationData`, for any call
+const ACCEPTING_ACCOUNT_CODE: Bytes = bytes!("60205ff3");
+
+/// A transaction ready to be traced, with the state and block it runs on
+#[derive(Debug, Clone)]
+pub struct Workload {
+    pub name: &'static str,
+    pub params: TxParams,
+    pub block_env: BlockEnv,
+    pub prestate: HashMap<Address, AccountDetails>,
+}
+
+impl Workload {
+    /// Traces the workload with the default call tracer, the way `trace_transaction` would
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the transaction cannot be executed
+    pub fn trace(&self) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+        self.trace_with_config(&CallTracerConfig::default())
+    }
+
+    /// Traces the workload with `tracer_config`, e.g. to measure the cost of storage or
+    /// opcode-level options
+    ///
+    /// # Errors
+    ///
+    /// Returns `TraceError` if the transaction cannot be executed
+    pub fn trace_with_config(
+        &self,
+        tracer_config: &CallTracerConfig,
+    ) -> Result<TraceTransactionResult<HaltReason>, TraceError> {
+        let prestate_keys = PrestateKeys::from_prestate(&self.prestate);
+        let db = create_in_memory_database_from_prestate_trace(self.prestate.clone());
+        trace_transaction_with_database(&self.params, self.block_env.clone(), db, &prestate_keys, tracer_config)
+    }
+}
+
+/// A plain ETH transfer between externally owned accounts
+pub fn simple_transfer() -> Workload {
+    let mut prestate = HashMap::default();
+    prestate.insert(WORKLOAD_SENDER, funded_account(ether(100)));
+    Workload {
+        name: "simple_transfer",
+        params: params(WORKLOAD_RECIPIENT, ether(1), Bytes::new()),
+        block_env: test_block_env(),
+        prestate,
+    }
+}
+
+/// `transfer` of `ERC20_CODE` tokens, emitting a `Transfer` log
+pub fn erc20_transfer() -> Workload {
+    let mut prestate = erc20_prestate(WORKLOAD_TOKEN, &[(WORKLOAD_SENDER, ether(1_000))]);
+    prestate.insert(WORKLOAD_SENDER, funded_account(ether(100)));
+    Workload {
+        name: "erc20_transfer",
+        params: params(WORKLOAD_TOKEN, U256::ZERO, transfer_calldata(WORKLOAD_RECIPIENT, ether(1))),
+        block_env: test_block_env(),
+        prestate,
+    }
+}
+
+/// A token transfer made through `depth` forwarding contracts, like a router calling
+/// into pools and tokens; the tracer handles a frame and a storage read per level
+pub fn nested_calls(depth: usize) -> Workload {
+    let forwarders: Vec<Address> = (1..=depth as u64)
+        .map(|i| Address::left_padding_from(&(0xf0_0000 + i).to_be_bytes()))
+        .collect();
+    // The innermost forwarder calls the token and holds the balance
+    let holder = forwarders.last().copied().unwrap_or(WORKLOAD_SENDER);
+    let mut prestate = erc20_prestate(WORKLOAD_TOKEN, &[(holder, ether(1_000))]);
+    prestate.insert(WORKLOAD_SENDER, funded_account(ether(100)));
+    for (i, forwarder) in forwarders.iter().enumerate() {
+        let next = forwarders.get(i + 1).copied().unwrap_or(WORKLOAD_TOKEN);
+        let mut storage = HashMap::default();
+        storage.insert(U256::ZERO, U256::from_be_slice(next.as_slice()));
+        prestate.insert(*forwarder, AccountDetails {
+            balance: Some(U256::ZERO),
+            nonce: Some(1),
+            code: Some(FORWARDER_CODE),
+            storage: Some(storage),
+        });
+    }
+
+    let to = forwarders.first().copied().unwrap_or(WORKLOAD_TOKEN);
+    Workload {
+        name: "nested_calls",
+        params: params(to, U256::ZERO, transfer_calldata(WORKLOAD_RECIPIENT, ether(1))),
+        block_env: test_block_env(),
+        prestate,
+    }
+}
+
+/// `handleOps` with a single user operation from an account that accepts any
+/// signature and prepays from its deposit, run on `entry_point_code`, the runtime code
+/// of EntryPoint v0.7 (e.g. read once with `eth_getCode`)
+pub fn handle_ops(entry_point_code: Bytes) -> Workload {
+    let account = address!("00000000000000000000000000000000000b3e37");
+    let user_op = PackedUserOperation {
+        sender: account,
+        nonce: U256::ZERO,
+        init_code: Bytes::new(),
+        call_data: Bytes::new(),
+        account_gas_limits: gas_pair(100_000, 100_000),
+        pre_verification_gas: U256::from(50_000),
+        gas_fees: gas_pair(1_000_000_000, 1_000_000_000),
+        paymaster_and_data: Bytes::new(),
+        signature: Bytes::from(vec![1u8; 65]),
+    };
+
+    let mut prestate = HashMap::default();
+    prestate.insert(ENTRY_POINT_V07_ADDRESS, entry_point_account(entry_point_code, &[(account, ether(1))]));
+    prestate.insert(account, AccountDetails {
+        balance: Some(U256::ZERO),
+        nonce: Some(1),
+        code: Some(ACCEPTING_ACCOUNT_CODE),
+        storage: None,
+    });
+    let block_env = test_block_env();
+    let params = handle_ops_params(1, ENTRY_POINT_V07_ADDRESS, WORKLOAD_SENDER, &user_op, &mut prestate, &block_env);
+    Workload { name: "handle_ops", params, block_env, prestate }
+}
+
+fn params(to: Address, value: U256, data: Bytes) -> TxParams {
+    TxParams {
+        chain_id: 1,
+        from: WORKLOAD_SENDER,
+        from_nonce: 0,
+        to,
+        value,
+        data,
+        gas_limit: 1_000_000,
+        gas_price: 0,
+        gas_priority_fee: 0,
+        tx_spec: TxSpec::Eip1559,
+        access_list: None,
+        blob_versioned_hashes: Vec::new(),
+        max_fee_per_blob_gas: 0,
+        authorization_list: Vec::new(),
+        deposit: None,
+        spec: None,
+    }
+}
+
+/// Calldata of `transfer(to, amount)`
+fn transfer_calldata(to: Address, amount: U256) -> Bytes {
+    let mut calldata = Vec::with_capacity(4 + 2 * 32);
+    calldata.extend_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+    calldata.extend_from_slice(&[0u8; 12]);
+    calldata.extend_from_slice(to.as_slice());
+    calldata.extend_from_slice(&amount.to_be_bytes::<32>());
+    Bytes::from(calldata)
+}
+
+/// Two `u128`s packed into a word, high then low, as in `PackedUserOperation`
+fn gas_pair(high: u128, low: u128) -> B256 {
+    let mut word = [0u8; 32];
+    word[..16].copy_from_slice(&high.to_be_bytes());
+    word[16..].copy_from_slice(&low.to_be_bytes());
+    B256::from(word)
+}
+
+fn ether(amount: u64) -> U256 {
+    U256::from(amount) * U256::from(10u64).pow(U256::from(18))
+}
@@ -0,0 +1,28 @@
+//! WebAssembly bindings, so web wallets can simulate transactions client-side
+//!
+//! Built with `--no-default-features --features wasm` for `wasm32-unknown-unknown`. Only
+//! the Ethereum tracer is exported; the OP Stack tracer and the Flutter bindings are not
+//! part of this build.
+
+use wasm_bindgen::prelude::*;
+
+use crate::trace::chain::{trace, ChainKind, TraceRequest};
+use crate::trace::error::TraceError;
+use crate::trace::quantity::with_quantity_encoding;
+
+/// Traces a transaction described by a JSON request and returns the result as JSON
+///
+/// The request is a `TraceRequest`: the transaction (`tx`, in the `TxParams` format), the
+/// `latestBlockEnv`, the `prestateTracerResult` and optionally `stateOverrides` and a
+/// call `tracerConfig`. Errors are thrown as JavaScript `Error`s.
+#[wasm_bindgen(js_name = traceTransaction)]
+pub fn trace_transaction_json(request: &str) -> Result<String, JsError> {
+    trace_request(request).map_err(|e| JsError::new(&e.to_string()))
+}
+
+fn trace_request(request: &str) -> Result<String, TraceError> {
+    let request: TraceRequest = serde_json::from_str(request)?;
+    let quantity_encoding = request.quantity_encoding;
+    let result = trace(&ChainKind::Ethereum, request)?;
+    Ok(with_quantity_encoding(quantity_encoding, || serde_json::to_string(&result))?)
+}